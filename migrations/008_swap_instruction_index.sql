@@ -0,0 +1,15 @@
+-- A single transaction can carry several swaps (multi-hop routes, repeated
+-- Raydium legs), so swap tables key on the producing instruction instead of
+-- the bare signature.
+
+ALTER TABLE raydium_swaps ADD COLUMN instruction_index INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE raydium_swaps DROP CONSTRAINT raydium_swaps_pkey;
+ALTER TABLE raydium_swaps ADD PRIMARY KEY (signature, instruction_index);
+
+ALTER TABLE jupiter_swaps ADD COLUMN instruction_index INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE jupiter_swaps DROP CONSTRAINT jupiter_swaps_pkey;
+ALTER TABLE jupiter_swaps ADD PRIMARY KEY (signature, instruction_index);
+
+ALTER TABLE pump_fun_trades ADD COLUMN instruction_index INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE pump_fun_trades DROP CONSTRAINT pump_fun_trades_pkey;
+ALTER TABLE pump_fun_trades ADD PRIMARY KEY (signature, instruction_index);
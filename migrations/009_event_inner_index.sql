@@ -0,0 +1,15 @@
+-- Record where each event came from inside its transaction: the top-level
+-- instruction index, plus the position within that instruction's CPI group
+-- for events decoded from inner instructions (NULL for top-level events).
+
+ALTER TABLE raydium_swaps   ADD COLUMN inner_index INTEGER;
+ALTER TABLE jupiter_swaps   ADD COLUMN inner_index INTEGER;
+ALTER TABLE pump_fun_trades ADD COLUMN inner_index INTEGER;
+
+-- token_transfers previously deduplicated on (signature, sender, receiver, mint),
+-- which collapsed identical transfers made by different instructions.
+ALTER TABLE token_transfers ADD COLUMN instruction_index INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE token_transfers ADD COLUMN inner_index INTEGER;
+ALTER TABLE token_transfers DROP CONSTRAINT token_transfers_pkey;
+CREATE UNIQUE INDEX idx_tt_instruction
+    ON token_transfers (signature, instruction_index, COALESCE(inner_index, -1));
@@ -0,0 +1,9 @@
+-- block_time is the on-chain block timestamp, not ingestion time. It is NULL
+-- when the source could not resolve it (e.g. a gRPC transaction that arrived
+-- before its slot's BlockMeta).
+
+ALTER TABLE token_transfers ADD COLUMN block_time TIMESTAMPTZ;
+ALTER TABLE raydium_swaps   ADD COLUMN block_time TIMESTAMPTZ;
+
+ALTER TABLE jupiter_swaps   ALTER COLUMN block_time DROP NOT NULL;
+ALTER TABLE pump_fun_trades ALTER COLUMN block_time DROP NOT NULL;
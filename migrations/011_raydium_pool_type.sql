@@ -0,0 +1,4 @@
+-- Raydium swaps now come from both the AMM v4 and CLMM programs.
+ALTER TABLE raydium_swaps ADD COLUMN pool_type TEXT NOT NULL DEFAULT 'amm_v4';
+
+CREATE INDEX idx_raydium_pool_type ON raydium_swaps(pool_type);
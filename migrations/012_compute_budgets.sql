@@ -0,0 +1,13 @@
+-- One row per transaction that set a compute-unit limit or priority fee.
+-- Either value is NULL when the transaction did not set it.
+CREATE TABLE compute_budgets (
+    signature                 TEXT PRIMARY KEY,
+    slot                      BIGINT NOT NULL,
+    block_time                TIMESTAMPTZ,
+    instruction_index         INT NOT NULL,
+    unit_limit                BIGINT,
+    unit_price_micro_lamports NUMERIC,
+    created_at                TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_cb_slot ON compute_budgets(slot);
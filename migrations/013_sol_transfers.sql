@@ -0,0 +1,17 @@
+-- Native SOL moved by the System program, top-level or via CPI.
+CREATE TABLE sol_transfers (
+    signature         TEXT NOT NULL,
+    slot              BIGINT NOT NULL,
+    block_time        TIMESTAMPTZ,
+    sender            TEXT NOT NULL,
+    receiver          TEXT NOT NULL,
+    lamports          NUMERIC NOT NULL,
+    instruction_index INTEGER NOT NULL,
+    inner_index       INTEGER,
+    created_at        TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE UNIQUE INDEX idx_st_instruction
+    ON sol_transfers (signature, instruction_index, COALESCE(inner_index, -1));
+CREATE INDEX idx_st_sender ON sol_transfers(sender);
+CREATE INDEX idx_st_receiver ON sol_transfers(receiver);
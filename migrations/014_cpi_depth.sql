@@ -0,0 +1,11 @@
+-- How deep in the CPI stack each swap ran: 0 for a top-level instruction,
+-- 1+ when another program (an aggregator, a bot) invoked the DEX.
+ALTER TABLE raydium_swaps   ADD COLUMN cpi_depth INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE jupiter_swaps   ADD COLUMN cpi_depth INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE pump_fun_trades ADD COLUMN cpi_depth INTEGER NOT NULL DEFAULT 0;
+
+-- Raydium swaps are now also decoded from inner instructions, so several can
+-- share one top-level instruction (e.g. the legs of a routed swap).
+ALTER TABLE raydium_swaps DROP CONSTRAINT raydium_swaps_pkey;
+CREATE UNIQUE INDEX idx_raydium_instruction
+    ON raydium_swaps (signature, instruction_index, COALESCE(inner_index, -1));
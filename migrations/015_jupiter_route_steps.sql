@@ -0,0 +1,15 @@
+-- One row per hop of a Jupiter route, so multi-hop swaps can be queried without
+-- unpacking jupiter_swaps.route_plan. instruction_index is part of the key
+-- because a transaction can carry more than one Jupiter swap.
+CREATE TABLE jupiter_route_steps (
+    signature         TEXT NOT NULL,
+    instruction_index INTEGER NOT NULL,
+    step_index        INTEGER NOT NULL,
+    swap_label        TEXT NOT NULL,
+    percent           SMALLINT NOT NULL,
+    input_index       SMALLINT NOT NULL,
+    output_index      SMALLINT NOT NULL,
+    PRIMARY KEY (signature, instruction_index, step_index)
+);
+
+CREATE INDEX idx_jrs_label ON jupiter_route_steps(swap_label);
@@ -0,0 +1,6 @@
+-- Slot-ordered access on every event table: reorg rollbacks, range backfills and
+-- future slot-range partitioning all filter on slot, and not every table had it indexed.
+-- Signer lookups on raydium_swaps are covered by idx_raydium_sender (004).
+CREATE INDEX IF NOT EXISTS idx_raydium_slot ON raydium_swaps(slot);
+CREATE INDEX IF NOT EXISTS idx_pf_slot      ON pump_fun_trades(slot);
+CREATE INDEX IF NOT EXISTS idx_st_slot      ON sol_transfers(slot);
@@ -0,0 +1,26 @@
+-- Swaps on the PumpSwap AMM, where Pump.fun tokens trade after their bonding
+-- curve completes. Reserves are the pool vault balances after the swap.
+CREATE TABLE pump_amm_swaps (
+    signature          TEXT NOT NULL,
+    slot               BIGINT NOT NULL,
+    block_time         TIMESTAMPTZ,
+    pool               TEXT NOT NULL,
+    user_address       TEXT NOT NULL,
+    base_mint          TEXT NOT NULL,
+    quote_mint         TEXT NOT NULL,
+    is_buy             BOOLEAN NOT NULL,
+    base_amount        NUMERIC NOT NULL,
+    quote_amount       NUMERIC NOT NULL,
+    pool_base_reserve  NUMERIC,
+    pool_quote_reserve NUMERIC,
+    instruction_index  INTEGER NOT NULL,
+    inner_index        INTEGER,
+    cpi_depth          INTEGER NOT NULL DEFAULT 0,
+    created_at         TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE UNIQUE INDEX idx_pamm_instruction
+    ON pump_amm_swaps (signature, instruction_index, COALESCE(inner_index, -1));
+CREATE INDEX idx_pamm_slot      ON pump_amm_swaps(slot);
+CREATE INDEX idx_pamm_pool      ON pump_amm_swaps(pool);
+CREATE INDEX idx_pamm_base_mint ON pump_amm_swaps(base_mint);
@@ -0,0 +1,35 @@
+-- One row per transaction that produced at least one indexed event, so every
+-- event table joins on signature to one place. event_kinds lists the event types
+-- the transaction produced (TransactionEvent::kind), merged across batches.
+-- Fee payer and success are not carried on parsed events, so they are not stored.
+CREATE TABLE transactions (
+    signature   TEXT PRIMARY KEY,
+    slot        BIGINT NOT NULL,
+    block_time  TIMESTAMPTZ,
+    event_kinds TEXT[] NOT NULL DEFAULT '{}',
+    created_at  TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_tx_slot ON transactions(slot);
+
+-- Parent rows for everything indexed before this table existed
+INSERT INTO transactions (signature, slot, block_time, event_kinds)
+SELECT signature, MIN(slot), MAX(block_time), array_agg(DISTINCT kind)
+FROM (
+    SELECT signature, slot, block_time, 'token_transfer' AS kind FROM token_transfers
+    UNION ALL SELECT signature, slot, block_time, 'raydium_swap'   FROM raydium_swaps
+    UNION ALL SELECT signature, slot, block_time, 'jupiter_swap'   FROM jupiter_swaps
+    UNION ALL SELECT signature, slot, block_time, 'pump_fun_trade' FROM pump_fun_trades
+    UNION ALL SELECT signature, slot, block_time, 'pump_amm_swap'  FROM pump_amm_swaps
+    UNION ALL SELECT signature, slot, block_time, 'compute_budget' FROM compute_budgets
+    UNION ALL SELECT signature, slot, block_time, 'sol_transfer'   FROM sol_transfers
+) events
+GROUP BY signature;
+
+ALTER TABLE token_transfers ADD CONSTRAINT fk_tt_transaction FOREIGN KEY (signature) REFERENCES transactions(signature);
+ALTER TABLE raydium_swaps   ADD CONSTRAINT fk_raydium_transaction FOREIGN KEY (signature) REFERENCES transactions(signature);
+ALTER TABLE jupiter_swaps   ADD CONSTRAINT fk_jupiter_transaction FOREIGN KEY (signature) REFERENCES transactions(signature);
+ALTER TABLE pump_fun_trades ADD CONSTRAINT fk_pf_transaction FOREIGN KEY (signature) REFERENCES transactions(signature);
+ALTER TABLE pump_amm_swaps  ADD CONSTRAINT fk_pamm_transaction FOREIGN KEY (signature) REFERENCES transactions(signature);
+ALTER TABLE compute_budgets ADD CONSTRAINT fk_cb_transaction FOREIGN KEY (signature) REFERENCES transactions(signature);
+ALTER TABLE sol_transfers   ADD CONSTRAINT fk_st_transaction FOREIGN KEY (signature) REFERENCES transactions(signature);
@@ -0,0 +1,8 @@
+-- Realized slippage headroom: how far amount_received landed above min_amount_out,
+-- in bps of min_amount_out. Matches RaydiumSwapEvent::realized_slippage_bps, and
+-- stays NULL when either amount is 0 (no floor, or the received amount is unknown).
+ALTER TABLE raydium_swaps ADD COLUMN realized_slippage_bps INTEGER GENERATED ALWAYS AS (
+    CASE WHEN min_amount_out > 0 AND amount_received > 0
+         THEN LEAST(GREATEST(TRUNC((amount_received - min_amount_out) * 10000 / min_amount_out), -2147483648), 2147483647)::INTEGER
+    END
+) STORED;
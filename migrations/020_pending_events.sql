@@ -0,0 +1,13 @@
+-- Two-phase persistence: events indexed below finalized commitment wait here
+-- until their slot finalizes, then move to the event tables. Events of a dead
+-- slot are deleted by the rollback for that slot.
+CREATE TABLE pending_events (
+    id         BIGSERIAL PRIMARY KEY,
+    signature  TEXT NOT NULL,
+    slot       BIGINT NOT NULL,
+    kind       TEXT NOT NULL,
+    event      JSONB NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_pending_slot ON pending_events(slot);
@@ -0,0 +1,10 @@
+-- Progress of the mint backfill job, one row per table it repairs, so an
+-- interrupted run resumes after the last row it looked at. A table's row is
+-- removed once the job has walked all of it.
+CREATE TABLE mint_backfill_cursors (
+    table_name        TEXT PRIMARY KEY,
+    signature         TEXT NOT NULL,
+    instruction_index INTEGER NOT NULL,
+    inner_index       INTEGER NOT NULL,
+    updated_at        TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
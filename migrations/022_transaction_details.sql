@@ -0,0 +1,9 @@
+-- Per-transaction details the events do not carry: who paid the fee, whether the
+-- transaction succeeded on-chain, and which parsers' events were kept. NULL for
+-- rows written before this migration or replayed from the WAL.
+ALTER TABLE transactions
+    ADD COLUMN fee_payer TEXT,
+    ADD COLUMN success   BOOLEAN,
+    ADD COLUMN parsers   TEXT[] NOT NULL DEFAULT '{}';
+
+CREATE INDEX idx_tx_fee_payer ON transactions(fee_payer);
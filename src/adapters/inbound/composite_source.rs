@@ -0,0 +1,121 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::sync::{Mutex, mpsc};
+
+use crate::{
+    application::{AppResult, TransactionSource},
+    domain::ChainEvent,
+};
+
+/// Fans several sources into one. Each source is drained by its own task, so a
+/// source that is slow or idle never holds up events from the others.
+/// Returns `Ok(None)` once every source is exhausted.
+pub struct CompositeSource {
+    rx: mpsc::Receiver<AppResult<ChainEvent>>,
+}
+
+impl CompositeSource {
+    pub fn new(sources: Vec<Arc<Mutex<dyn TransactionSource>>>) -> Self {
+        let (tx, rx) = mpsc::channel(1024);
+
+        for (idx, source) in sources.into_iter().enumerate() {
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                loop {
+                    let event = source.lock().await.next_event().await;
+                    let forwarded = match event {
+                        Ok(Some(ev)) => tx.send(Ok(ev)).await,
+                        Ok(None) => {
+                            tracing::info!("Composite source #{} exhausted", idx);
+                            break;
+                        }
+                        Err(e) if e.is_fatal() => {
+                            tracing::error!("Composite source #{} failed permanently: {}", idx, e);
+                            let _ = tx.send(Err(e)).await;
+                            break;
+                        }
+                        Err(e) => tx.send(Err(e)).await,
+                    };
+                    if forwarded.is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+
+        Self { rx }
+    }
+}
+
+#[async_trait]
+impl TransactionSource for CompositeSource {
+    async fn next_event(&mut self) -> AppResult<Option<ChainEvent>> {
+        // The channel closes when the last forwarding task drops its sender
+        match self.rx.recv().await {
+            Some(Ok(ev)) => Ok(Some(ev)),
+            Some(Err(e)) => Err(e),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, time::Duration};
+
+    use super::*;
+    use crate::adapters::FileSourceAdaptor;
+
+    /// Never yields an event.
+    struct StalledSource;
+
+    #[async_trait]
+    impl TransactionSource for StalledSource {
+        async fn next_event(&mut self) -> AppResult<Option<ChainEvent>> {
+            std::future::pending().await
+        }
+    }
+
+    fn signature(event: ChainEvent) -> String {
+        match event {
+            ChainEvent::Transaction(txn) => txn.signature,
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn delivers_every_event_of_every_source_then_ends() {
+        let short: Arc<Mutex<dyn TransactionSource>> = Arc::new(Mutex::new(FileSourceAdaptor::new(3)));
+        let long: Arc<Mutex<dyn TransactionSource>> = Arc::new(Mutex::new(FileSourceAdaptor::new(7)));
+        let mut source = CompositeSource::new(vec![short, long]);
+
+        let mut per_signature = HashMap::new();
+        while let Some(event) = source.next_event().await.unwrap() {
+            *per_signature.entry(signature(event)).or_insert(0) += 1;
+        }
+
+        // Both sources count from sim_sig_1, so the first three arrive twice
+        assert_eq!(per_signature.values().sum::<usize>(), 10);
+        for n in 1..=7 {
+            let expected = if n <= 3 { 2 } else { 1 };
+            assert_eq!(per_signature[&format!("sim_sig_{}", n)], expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn stalled_source_does_not_hold_up_the_others() {
+        let stalled: Arc<Mutex<dyn TransactionSource>> = Arc::new(Mutex::new(StalledSource));
+        let live: Arc<Mutex<dyn TransactionSource>> = Arc::new(Mutex::new(FileSourceAdaptor::new(5)));
+        let mut source = CompositeSource::new(vec![stalled, live]);
+
+        for n in 1..=5 {
+            let event = tokio::time::timeout(Duration::from_secs(1), source.next_event())
+                .await
+                .expect("the live source's events keep coming")
+                .unwrap()
+                .unwrap();
+            assert_eq!(signature(event), format!("sim_sig_{}", n));
+        }
+    }
+}
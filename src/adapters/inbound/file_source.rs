@@ -1,22 +1,28 @@
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 
 use async_trait::async_trait;
-use chrono::Utc;
 use tokio::time::sleep;
 
 use crate::{
-    application::{AppResult, TransactionSource},
+    application::{AppResult, Clock, SystemClock, TransactionSource},
     domain::{ChainEvent, SolanaTransaction, TxData},
 };
 
 pub struct FileSourceAdaptor {
     current_count: u64,
     max_count: u64,
+    clock: Arc<dyn Clock>,
 }
 
 impl FileSourceAdaptor {
     pub fn new(max_count: u64) -> Self {
-        Self { current_count: 0, max_count }
+        Self { current_count: 0, max_count, clock: Arc::new(SystemClock) }
+    }
+
+    /// Stamp simulated transactions with `clock` instead of the system time.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
     }
 }
 
@@ -36,7 +42,59 @@ impl TransactionSource for FileSourceAdaptor {
             slot: 1000 + self.current_count,
             data: TxData::Grpc(Vec::new()),
             signature: format!("sim_sig_{}", self.current_count),
-            block_time: Utc::now().timestamp(),
+            block_time: Some(self.clock.now()),
+            fee: Some(5_000),
+            compute_units_consumed: None,
+            fee_payer: String::new(),
+            recent_blockhash: String::new(),
         })))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+
+    use super::*;
+    use crate::application::{FixedClock, TransactionSourceExt};
+
+    #[tokio::test]
+    async fn stream_yields_every_simulated_transaction() {
+        let events: Vec<_> = FileSourceAdaptor::new(25).into_stream().collect().await;
+
+        assert_eq!(events.len(), 25);
+        assert!(events.iter().all(|ev| matches!(ev, Ok(ChainEvent::Transaction(_)))));
+    }
+
+    #[tokio::test]
+    async fn stream_composes_with_combinators() {
+        let slots: Vec<u64> = FileSourceAdaptor::new(10)
+            .into_stream()
+            .filter_map(|ev| async move {
+                match ev {
+                    Ok(ChainEvent::Transaction(txn)) => Some(txn.slot),
+                    _ => None,
+                }
+            })
+            .filter(|slot| futures::future::ready(slot % 2 == 0))
+            .take(3)
+            .collect()
+            .await;
+
+        assert_eq!(slots, vec![1002, 1004, 1006]);
+    }
+
+    #[tokio::test]
+    async fn transactions_are_stamped_by_the_injected_clock() {
+        let events: Vec<_> = FileSourceAdaptor::new(3)
+            .with_clock(Arc::new(FixedClock(1_700_000_000)))
+            .into_stream()
+            .collect()
+            .await;
+
+        for event in events {
+            let Ok(ChainEvent::Transaction(txn)) = event else { panic!("the file source only yields transactions") };
+            assert_eq!(txn.block_time, Some(1_700_000_000));
+        }
+    }
+}
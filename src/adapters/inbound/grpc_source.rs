@@ -1,39 +1,249 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    time::Duration,
+};
 
 use anyhow::Result;
 use async_trait::async_trait;
-use chrono::Utc;
+use futures::{StreamExt, stream::BoxStream};
 use prost::Message;
-use tonic::transport::{Channel, ClientTlsConfig};
+use tonic::{Code, Status, transport::{Channel, ClientTlsConfig, Endpoint}};
 use yellowstone_grpc_proto::geyser::{
-    SubscribeRequest, SubscribeRequestFilterBlocksMeta, SubscribeRequestFilterTransactions,
-    SubscribeUpdate, geyser_client::GeyserClient,
+    CommitmentLevel, SlotStatus, SubscribeRequest, SubscribeRequestFilterBlocksMeta, SubscribeRequestFilterSlots,
+    SubscribeRequestFilterTransactions, SubscribeUpdate, geyser_client::GeyserClient, subscribe_update::UpdateOneof,
 };
 
 use crate::{
     application::{AppError, AppResult, TransactionSource},
-    domain::{ChainEvent, SolanaTransaction, TxData},
+    domain::{self, ChainEvent, SolanaTransaction, TxData},
 };
 
+/// Account filters for the transaction subscription, passed through to Geyser.
+/// A transaction matches if it touches any `account_include` key, all
+/// `account_required` keys, and none of the `account_exclude` keys.
+/// All-empty subscribes to every non-vote transaction.
+#[derive(Debug, Clone, Default)]
+pub struct TransactionFilter {
+    pub account_include: Vec<String>,
+    pub account_required: Vec<String>,
+    pub account_exclude: Vec<String>,
+    /// Also stream transactions that failed on-chain. Off asks Geyser to leave
+    /// them out, so the pipeline's `skip_failed` check never sees one.
+    pub include_failed: bool,
+}
+
+impl TransactionFilter {
+    /// Only transactions touching the DEX programs this indexer has parsers for.
+    pub fn dex_programs() -> Self {
+        Self {
+            account_include: vec![
+                domain::JUPITER_V6_PROGRAM_ID.to_string(),
+                domain::RAYDIUM_V4_PROGRAM_ID.to_string(),
+                domain::PUMP_FUN_PROGRAM_ID.to_string(),
+                domain::PUMP_AMM_PROGRAM_ID.to_string(),
+            ],
+            ..Default::default()
+        }
+    }
+}
+
+/// Transport settings for the Geyser channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChannelOptions {
+    /// Largest update the client decodes. Transactions with many lookup tables or
+    /// large inner-instruction sets can exceed tonic's 4 MiB default.
+    pub max_decoding_message_size: usize,
+    /// HTTP/2 PING interval, also while idle, so proxies do not drop a quiet stream.
+    pub keepalive_interval: Duration,
+    /// How long a PING may go unanswered before the connection counts as dead.
+    pub keepalive_timeout: Duration,
+    pub connect_timeout: Duration,
+}
+
+impl Default for ChannelOptions {
+    fn default() -> Self {
+        Self {
+            max_decoding_message_size: 64 * 1024 * 1024,
+            keepalive_interval: Duration::from_secs(30),
+            keepalive_timeout: Duration::from_secs(10),
+            connect_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Signatures recently emitted by a multi-subscription source. Overlapping
+/// filters deliver the same transaction once per subscription that matches it.
+struct RecentSignatures {
+    capacity: usize,
+    seen: HashSet<Vec<u8>>,
+    order: VecDeque<Vec<u8>>,
+}
+
+impl RecentSignatures {
+    fn new(capacity: usize) -> Self {
+        Self { capacity, seen: HashSet::with_capacity(capacity), order: VecDeque::with_capacity(capacity) }
+    }
+
+    /// Records the signature and returns true if it had not been seen recently.
+    fn insert(&mut self, signature: &[u8]) -> bool {
+        if self.seen.contains(signature) {
+            return false;
+        }
+        if self.order.len() >= self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.seen.remove(&oldest);
+            }
+        }
+        self.seen.insert(signature.to_vec());
+        self.order.push_back(signature.to_vec());
+        true
+    }
+}
+
+/// How many signatures a multi-subscription source remembers for dedup. The
+/// same transaction reaches every subscription within a slot or two.
+const MERGE_DEDUP_CAPACITY: usize = 50_000;
+
+/// Everything needed to open the subscriptions again after a stall.
+struct Subscription {
+    endpoint: String,
+    x_token: Option<String>,
+    commitment: CommitmentLevel,
+    filters: Vec<TransactionFilter>,
+    channel: ChannelOptions,
+    /// Ask the server to replay from this slot instead of starting at the tip.
+    from_slot: Option<u64>,
+}
+
+/// Updates of all subscriptions, each tagged with the index of the one it came on.
+type MergedStream = BoxStream<'static, (usize, Result<SubscribeUpdate, Status>)>;
+
+/// Inclusive slot window of a bounded backfill, with how far each subscription
+/// has got.
+struct SlotRange {
+    start: u64,
+    end: u64,
+    reached: Vec<u64>,
+    finished: Vec<bool>,
+}
+
+impl SlotRange {
+    fn new(start: u64, end: u64, subscriptions: usize) -> Self {
+        Self { start, end, reached: vec![start; subscriptions], finished: vec![false; subscriptions] }
+    }
+
+    fn contains(&self, slot: u64) -> bool {
+        (self.start..=self.end).contains(&slot)
+    }
+
+    /// Record that subscription `idx` reached `slot`. Each stream delivers slots
+    /// in commitment order, so once one is past `end` it has sent all it will for
+    /// the window. Returns true when that holds for every subscription.
+    fn advance(&mut self, idx: usize, slot: u64) -> bool {
+        if let Some(reached) = self.reached.get_mut(idx) {
+            *reached = (*reached).max(slot);
+        }
+        if slot > self.end {
+            if let Some(finished) = self.finished.get_mut(idx) {
+                *finished = true;
+            }
+        }
+        self.finished.iter().all(|f| *f)
+    }
+
+    fn is_finished(&self, idx: usize) -> bool {
+        self.finished.get(idx).copied().unwrap_or(false)
+    }
+
+    /// Where a re-subscription picks up: the slot the slowest subscription had
+    /// reached. Anything delivered twice is absorbed by dedup and ON CONFLICT.
+    fn resume_slot(&self) -> u64 {
+        self.reached.iter().copied().min().unwrap_or(self.start)
+    }
+}
+
 pub struct GrpcSourceAdaptor {
-    stream: tonic::codec::Streaming<SubscribeUpdate>,
+    subscription: Subscription,
+    // All subscriptions merged; updates interleave in arrival order
+    stream: MergedStream,
+    // Only set with more than one subscription
+    recent_signatures: Option<RecentSignatures>,
     // slot → block_time cache to assign accurate timestamps to transactions
     block_time_cache: HashMap<u64, i64>,
+    // Window of a bounded backfill
+    slot_range: Option<SlotRange>,
+}
+
+/// Parse a commitment name as used in config (`processed`, `confirmed`, `finalized`).
+pub fn parse_commitment(value: &str) -> Option<CommitmentLevel> {
+    match value.to_ascii_lowercase().as_str() {
+        "processed" => Some(CommitmentLevel::Processed),
+        "confirmed" => Some(CommitmentLevel::Confirmed),
+        "finalized" => Some(CommitmentLevel::Finalized),
+        _ => None,
+    }
 }
 
 impl GrpcSourceAdaptor {
-    pub async fn connect(endpoint: String, x_token: Option<String>) -> Result<Self> {
-        tracing::info!("Connecting to gRPC endpoint: {}", endpoint);
+    /// `commitment` controls how settled a transaction must be before it is streamed.
+    /// `Processed` is lowest latency but can deliver transactions from forks that are
+    /// later rolled back; `Confirmed` (the usual choice) makes that rare, and
+    /// `Finalized` rules it out at the cost of ~30s extra delay.
+    pub async fn connect(
+        endpoint: String,
+        x_token: Option<String>,
+        commitment: CommitmentLevel,
+        filter: TransactionFilter,
+    ) -> Result<Self> {
+        Self::connect_many(endpoint, x_token, commitment, vec![filter]).await
+    }
 
-        let mut channel_builder = Channel::from_shared(endpoint.clone())?;
+    /// One subscribe stream per filter over a shared channel, merged into a single
+    /// event stream. A transaction matched by several filters is emitted once.
+    /// Block meta and slot updates are requested on the first stream only.
+    pub async fn connect_many(
+        endpoint: String,
+        x_token: Option<String>,
+        commitment: CommitmentLevel,
+        filters: Vec<TransactionFilter>,
+    ) -> Result<Self> {
+        Self::connect_with_options(endpoint, x_token, commitment, filters, ChannelOptions::default()).await
+    }
 
-        if endpoint.starts_with("https://") {
-            let tls = ClientTlsConfig::new().with_webpki_roots();
-            channel_builder = channel_builder.tls_config(tls)?;
+    /// `connect_many` with explicit transport settings; reconnects reuse them.
+    pub async fn connect_with_options(
+        endpoint: String,
+        x_token: Option<String>,
+        commitment: CommitmentLevel,
+        filters: Vec<TransactionFilter>,
+        channel: ChannelOptions,
+    ) -> Result<Self> {
+        if filters.is_empty() {
+            anyhow::bail!("at least one transaction filter is required");
         }
+        let subscription = Subscription { endpoint, x_token, commitment, filters, channel, from_slot: None };
+        let stream = Self::subscribe(&subscription).await?;
+        Ok(Self::from_stream(subscription, stream))
+    }
 
-        let channel = channel_builder.connect().await?;
+    fn from_stream(subscription: Subscription, stream: MergedStream) -> Self {
+        let merged = subscription.filters.len() > 1;
+        Self {
+            subscription,
+            stream,
+            recent_signatures: merged.then(|| RecentSignatures::new(MERGE_DEDUP_CAPACITY)),
+            block_time_cache: HashMap::new(),
+            slot_range: None,
+        }
+    }
 
+    async fn subscribe(subscription: &Subscription) -> Result<MergedStream> {
+        let endpoint = &subscription.endpoint;
+        tracing::info!("Connecting to gRPC endpoint: {}", endpoint);
+
+        let channel = Self::endpoint(endpoint, &subscription.channel)?.connect().await?;
+
+        let x_token = subscription.x_token.clone();
         let mut client = GeyserClient::with_interceptor(channel, move |mut req: tonic::Request<()>| {
             if let Some(token) = &x_token {
                 if let Ok(val) = token.parse() {
@@ -41,38 +251,125 @@ impl GrpcSourceAdaptor {
                 }
             }
             Ok(req)
-        });
+        })
+        .max_decoding_message_size(subscription.channel.max_decoding_message_size);
+
+        let mut streams = Vec::with_capacity(subscription.filters.len());
+        for idx in 0..subscription.filters.len() {
+            let request = Self::build_request(subscription, idx);
+            // Geyser uses bidirectional streaming — we send one request then only read
+            let stream = client
+                .subscribe(tokio_stream::iter(vec![request]))
+                .await?
+                .into_inner();
+            streams.push(stream.map(move |update| (idx, update)));
+        }
+        if streams.len() > 1 {
+            tracing::info!("Merging {} gRPC subscriptions", streams.len());
+        }
+
+        Ok(futures::stream::select_all(streams).boxed())
+    }
+
+    /// Channel endpoint with TLS for `https://` URLs and the transport settings applied.
+    fn endpoint(url: &str, options: &ChannelOptions) -> Result<Endpoint> {
+        let mut endpoint = Channel::from_shared(url.to_string())?
+            .connect_timeout(options.connect_timeout)
+            .http2_keep_alive_interval(options.keepalive_interval)
+            .keep_alive_timeout(options.keepalive_timeout)
+            .keep_alive_while_idle(true);
+
+        if url.starts_with("https://") {
+            endpoint = endpoint.tls_config(ClientTlsConfig::new().with_webpki_roots())?;
+        }
+        Ok(endpoint)
+    }
+
+    /// Bounded backfill: replay from `start` (as far back as the server keeps
+    /// slots), emit only updates for `start..=end`, and end the stream once every
+    /// subscription has delivered a slot past `end`. Re-subscribes to send `from_slot`.
+    pub async fn with_slot_range(mut self, start: u64, end: u64) -> Result<Self> {
+        self.subscription.from_slot = Some(start);
+        self.slot_range = Some(SlotRange::new(start, end, self.subscription.filters.len()));
+        self.stream = Self::subscribe(&self.subscription).await?;
+        Ok(self)
+    }
+
+    /// The slot an update is about, whatever its kind.
+    fn update_slot(update: &SubscribeUpdate) -> Option<u64> {
+        match update.update_oneof.as_ref()? {
+            UpdateOneof::Transaction(tx) => Some(tx.slot),
+            UpdateOneof::BlockMeta(meta) => Some(meta.slot),
+            UpdateOneof::Slot(slot) => Some(slot.slot),
+            _ => None,
+        }
+    }
+
+    /// The slot an update shows its stream has reached at the subscription's
+    /// commitment. Inter-slot updates (shreds, bank creation) for a later slot can
+    /// arrive while an earlier one is still being confirmed, so they do not count.
+    fn progress_slot(update: &SubscribeUpdate, commitment: CommitmentLevel) -> Option<u64> {
+        let reached = match commitment {
+            CommitmentLevel::Processed => SlotStatus::SlotProcessed,
+            CommitmentLevel::Confirmed => SlotStatus::SlotConfirmed,
+            CommitmentLevel::Finalized => SlotStatus::SlotFinalized,
+        };
+        match update.update_oneof.as_ref()? {
+            UpdateOneof::Slot(slot) if slot.status != reached as i32 => None,
+            _ => Self::update_slot(update),
+        }
+    }
+
+    /// Request for subscription `idx`. Block meta and dead-slot notices ride on the first only.
+    fn build_request(subscription: &Subscription, idx: usize) -> SubscribeRequest {
+        let commitment = subscription.commitment;
+        let filter = subscription.filters[idx].clone();
+        let with_blocks = idx == 0;
 
         let mut transactions = HashMap::new();
         transactions.insert(
             "all_txs".to_string(),
             SubscribeRequestFilterTransactions {
                 vote: Some(false),
-                failed: Some(false),
+                // `None` streams both outcomes; `Some(false)` only successful ones
+                failed: (!filter.include_failed).then_some(false),
                 signature: None,
-                account_exclude: vec![],
-                account_include: vec![],
-                account_required: vec![],
+                account_exclude: filter.account_exclude,
+                account_include: filter.account_include,
+                account_required: filter.account_required,
             },
         );
 
         let mut blocks_meta = HashMap::new();
-        blocks_meta.insert("all-blocks".to_string(), SubscribeRequestFilterBlocksMeta {});
+        if with_blocks {
+            blocks_meta.insert("all-blocks".to_string(), SubscribeRequestFilterBlocksMeta {});
+        }
+
+        // Dead-slot notices only come with inter-slot updates; finalized data never rolls back.
+        // The same updates carry the finalized notices two-phase persistence waits for
+        let mut slots = HashMap::new();
+        if with_blocks && commitment != CommitmentLevel::Finalized {
+            slots.insert(
+                "dead-slots".to_string(),
+                SubscribeRequestFilterSlots { filter_by_commitment: Some(false), interslot_updates: Some(true) },
+            );
+        }
+        // A backfill needs every stream to report slot progress, to know when the window is done
+        if subscription.from_slot.is_some() {
+            slots.insert(
+                "progress".to_string(),
+                SubscribeRequestFilterSlots { filter_by_commitment: Some(true), interslot_updates: Some(false) },
+            );
+        }
 
-        let request = SubscribeRequest {
+        SubscribeRequest {
             transactions,
             blocks_meta,
-            commitment: None,
+            slots,
+            commitment: Some(commitment as i32),
+            from_slot: subscription.from_slot,
             ..Default::default()
-        };
-
-        // Geyser uses bidirectional streaming — we send one request then only read
-        let stream = client
-            .subscribe(tokio_stream::iter(vec![request]))
-            .await?
-            .into_inner();
-
-        Ok(Self { stream, block_time_cache: HashMap::new() })
+        }
     }
 }
 
@@ -80,32 +377,71 @@ impl GrpcSourceAdaptor {
 impl TransactionSource for GrpcSourceAdaptor {
     async fn next_event(&mut self) -> AppResult<Option<ChainEvent>> {
         loop {
-            match self.stream.message().await {
-                Ok(Some(update)) => {
+            let Some((stream_idx, update)) = self.stream.next().await else { return Ok(None) };
+            match update {
+                Ok(update) => {
+                    if let Some(range) = self.slot_range.as_mut() {
+                        if let Some(slot) = Self::progress_slot(&update, self.subscription.commitment) {
+                            if range.advance(stream_idx, slot) {
+                                tracing::info!("Every subscription is past slot {}, backfill complete", range.end);
+                                return Ok(None);
+                            }
+                        }
+                        // A finished subscription may keep sending; nothing it sends is in the window
+                        if range.is_finished(stream_idx) || Self::update_slot(&update).is_some_and(|slot| !range.contains(slot)) {
+                            continue;
+                        }
+                    }
                     match update.update_oneof {
-                        Some(yellowstone_grpc_proto::geyser::subscribe_update::UpdateOneof::Transaction(ref tx_info)) => {
-                            let tx = tx_info.transaction.as_ref().unwrap();
+                        Some(UpdateOneof::Transaction(ref tx_info)) => {
+                            let Some(tx) = tx_info.transaction.as_ref() else {
+                                tracing::warn!("Skipping transaction update with no body at slot {}", tx_info.slot);
+                                continue;
+                            };
+                            // A malformed signature would become a bogus dedup / primary key
+                            if tx.signature.len() != domain::SIGNATURE_LEN {
+                                tracing::warn!("Skipping transaction with {}-byte signature at slot {}", tx.signature.len(), tx_info.slot);
+                                continue;
+                            }
+                            if let Some(recent) = self.recent_signatures.as_mut() {
+                                if !recent.insert(&tx.signature) { continue; }
+                            }
                             let signature = bs58::encode(&tx.signature).into_string();
+                            let success = tx.meta.as_ref().map_or(true, |m| m.err.is_none());
+                            let fee = tx.meta.as_ref().map(|m| m.fee);
+                            let compute_units_consumed = tx.meta.as_ref().and_then(|m| m.compute_units_consumed);
+                            let message = tx.transaction.as_ref().and_then(|t| t.message.as_ref());
+                            let fee_payer = message
+                                .and_then(|m| m.account_keys.first())
+                                .map(|k| bs58::encode(k).into_string())
+                                .unwrap_or_default();
+                            let recent_blockhash = message
+                                .map(|m| bs58::encode(&m.recent_blockhash).into_string())
+                                .unwrap_or_default();
 
-                            // Use cached block_time if available, fall back to wall-clock
-                            let block_time = *self.block_time_cache
-                                .get(&tx_info.slot)
-                                .unwrap_or(&Utc::now().timestamp());
+                            // Transaction updates carry no block time of their own; use the
+                            // slot's BlockMeta if it has already arrived, otherwise leave it unset
+                            let block_time = self.block_time_cache.get(&tx_info.slot).copied();
 
                             let raw_bytes = update.encode_to_vec();
 
                             return Ok(Some(ChainEvent::Transaction(SolanaTransaction {
                                 signature,
-                                success: true,
+                                success,
                                 data: TxData::Grpc(raw_bytes),
                                 slot: tx_info.slot,
                                 block_time,
+                                fee,
+                                compute_units_consumed,
+                                fee_payer,
+                                recent_blockhash,
                             })));
                         }
 
-                        Some(yellowstone_grpc_proto::geyser::subscribe_update::UpdateOneof::BlockMeta(meta)) => {
-                            if let Some(time) = meta.block_time {
-                                self.block_time_cache.insert(meta.slot, time.timestamp);
+                        Some(UpdateOneof::BlockMeta(meta)) => {
+                            let block_time = meta.block_time.as_ref().map(|t| t.timestamp);
+                            if let Some(time) = block_time {
+                                self.block_time_cache.insert(meta.slot, time);
                                 // Evict old entries — keep only the last 1000 slots
                                 self.block_time_cache.retain(|&k, _| k > meta.slot.saturating_sub(1000));
                             }
@@ -114,15 +450,389 @@ impl TransactionSource for GrpcSourceAdaptor {
                                 slot: meta.slot,
                                 block_hash: meta.blockhash,
                                 parent_block_hash: meta.parent_blockhash,
+                                block_time,
                             }));
                         }
 
+                        Some(UpdateOneof::Slot(update)) if update.status == SlotStatus::SlotDead as i32 => {
+                            tracing::warn!("Slot {} is dead: {}", update.slot, update.dead_error.as_deref().unwrap_or("no reason given"));
+                            self.block_time_cache.remove(&update.slot);
+                            return Ok(Some(ChainEvent::Rollback { slot: update.slot }));
+                        }
+
+                        Some(UpdateOneof::Slot(update)) if update.status == SlotStatus::SlotFinalized as i32 => {
+                            return Ok(Some(ChainEvent::Finalized { slot: update.slot }));
+                        }
+
                         _ => continue,
                     }
                 }
-                Ok(None) => return Ok(None),
-                Err(_) => return Err(AppError::GrpcStreamingError),
+                Err(status) => return Err(match status.code() {
+                    Code::Unauthenticated | Code::PermissionDenied | Code::InvalidArgument => {
+                        AppError::ConfigError(status.message().to_string())
+                    }
+                    _ => AppError::ConnectionError(status.to_string()),
+                }),
+            }
+        }
+    }
+
+    /// Re-subscribes with the original filters. Updates already delivered are not
+    /// replayed, so events sent during the gap are only recovered by a backfill.
+    /// A backfill itself resumes from where its slowest subscription had got.
+    async fn reconnect(&mut self) -> AppResult<()> {
+        if let Some(range) = &self.slot_range {
+            self.subscription.from_slot = Some(range.resume_slot());
+        }
+        self.stream = Self::subscribe(&self.subscription)
+            .await
+            .map_err(|e| AppError::ConnectionError(e.to_string()))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use yellowstone_grpc_proto::{
+        geyser::{SubscribeUpdateBlockMeta, SubscribeUpdateTransaction, SubscribeUpdateTransactionInfo, subscribe_update::UpdateOneof},
+        prelude::{Message as GrpcMessage, Transaction, TransactionError, TransactionStatusMeta, UnixTimestamp},
+    };
+
+    use super::*;
+
+    fn subscription(filters: Vec<TransactionFilter>) -> Subscription {
+        Subscription {
+            endpoint: "http://127.0.0.1:10000".to_string(),
+            x_token: None,
+            commitment: CommitmentLevel::Confirmed,
+            filters,
+            channel: ChannelOptions::default(),
+            from_slot: None,
+        }
+    }
+
+    /// A source over canned updates, each tagged with the subscription it arrives on.
+    fn merged_source(filters: usize, updates: Vec<(usize, SubscribeUpdate)>) -> GrpcSourceAdaptor {
+        let stream = futures::stream::iter(updates.into_iter().map(|(idx, update)| (idx, Ok(update)))).boxed();
+        GrpcSourceAdaptor::from_stream(subscription(vec![TransactionFilter::default(); filters]), stream)
+    }
+
+    fn source(filters: usize, updates: Vec<SubscribeUpdate>) -> GrpcSourceAdaptor {
+        merged_source(filters, updates.into_iter().map(|update| (0, update)).collect())
+    }
+
+    fn slots(events: &[ChainEvent]) -> Vec<u64> {
+        events
+            .iter()
+            .map(|ev| match ev {
+                ChainEvent::Transaction(txn) => txn.slot,
+                other => panic!("unexpected event {:?}", other),
+            })
+            .collect()
+    }
+
+    fn transaction(slot: u64, seed: u8, failed: bool) -> SubscribeUpdate {
+        SubscribeUpdate {
+            update_oneof: Some(UpdateOneof::Transaction(SubscribeUpdateTransaction {
+                slot,
+                transaction: Some(SubscribeUpdateTransactionInfo {
+                    signature: vec![seed; domain::SIGNATURE_LEN],
+                    meta: Some(TransactionStatusMeta {
+                        err: failed.then(|| TransactionError { err: vec![1] }),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+            })),
+            ..Default::default()
+        }
+    }
+
+    async fn drain(source: &mut GrpcSourceAdaptor) -> Vec<ChainEvent> {
+        let mut events = Vec::new();
+        while let Some(event) = source.next_event().await.unwrap() {
+            events.push(event);
+        }
+        events
+    }
+
+    #[tokio::test]
+    async fn meta_error_marks_the_transaction_failed() {
+        let mut source = source(1, vec![transaction(1, 1, true), transaction(1, 2, false)]);
+
+        let success: Vec<bool> = drain(&mut source)
+            .await
+            .into_iter()
+            .map(|ev| match ev {
+                ChainEvent::Transaction(txn) => txn.success,
+                other => panic!("unexpected event {:?}", other),
+            })
+            .collect();
+
+        assert_eq!(success, vec![false, true]);
+    }
+
+    #[test]
+    fn failed_transactions_are_filtered_server_side_only_when_not_wanted() {
+        let failed = |include_failed| {
+            let filter = TransactionFilter { include_failed, ..Default::default() };
+            GrpcSourceAdaptor::build_request(&subscription(vec![filter]), 0).transactions["all_txs"].failed
+        };
+
+        assert_eq!(failed(false), Some(false));
+        assert_eq!(failed(true), None);
+    }
+
+    #[tokio::test]
+    async fn slot_range_yields_only_the_window_then_ends() {
+        let updates = (90..=110).map(|slot| transaction(slot, slot as u8, false)).collect();
+        let mut source = source(1, updates);
+        source.slot_range = Some(SlotRange::new(95, 105, 1));
+
+        let events = drain(&mut source).await;
+
+        assert_eq!(slots(&events), (95..=105).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn slot_range_waits_for_every_subscription_to_pass_the_end() {
+        let mut source = merged_source(2, vec![
+            (0, transaction(104, 1, false)),
+            (0, transaction(106, 2, false)),
+            // Still part of the window although subscription 0 has moved on
+            (1, transaction(105, 3, false)),
+            (0, transaction(107, 4, false)),
+            (1, transaction(106, 5, false)),
+            (1, transaction(105, 6, false)),
+        ]);
+        source.slot_range = Some(SlotRange::new(100, 105, 2));
+
+        let events = drain(&mut source).await;
+
+        assert_eq!(slots(&events), vec![104, 105]);
+    }
+
+    #[test]
+    fn backfill_requests_replay_and_progress_on_every_subscription() {
+        let mut subscription = subscription(vec![TransactionFilter::default(); 2]);
+        subscription.from_slot = Some(95);
+
+        for idx in 0..2 {
+            let request = GrpcSourceAdaptor::build_request(&subscription, idx);
+            assert_eq!(request.from_slot, Some(95));
+            assert!(request.slots.contains_key("progress"));
+        }
+    }
+
+    fn signatures(events: &[ChainEvent]) -> Vec<String> {
+        events
+            .iter()
+            .map(|ev| match ev {
+                ChainEvent::Transaction(txn) => txn.signature.clone(),
+                other => panic!("unexpected event {:?}", other),
+            })
+            .collect()
+    }
+
+    fn signature(seed: u8) -> String {
+        bs58::encode([seed; domain::SIGNATURE_LEN]).into_string()
+    }
+
+    #[tokio::test]
+    async fn overlapping_subscriptions_emit_each_transaction_once() {
+        let mut source = merged_source(2, vec![
+            (0, transaction(10, 1, false)),
+            (1, transaction(10, 1, false)),
+            (1, transaction(10, 2, false)),
+            (0, transaction(11, 3, false)),
+            (0, transaction(10, 2, false)),
+            (1, transaction(11, 3, false)),
+        ]);
+
+        let events = drain(&mut source).await;
+
+        assert_eq!(signatures(&events), vec![signature(1), signature(2), signature(3)]);
+    }
+
+    #[tokio::test]
+    async fn dedup_forgets_signatures_older_than_its_window() {
+        let mut source = merged_source(2, vec![
+            (0, transaction(10, 1, false)),
+            (0, transaction(10, 2, false)),
+            (1, transaction(10, 2, false)),
+            (0, transaction(11, 3, false)),
+            // 1 has left the two-signature window, 3 has not
+            (1, transaction(10, 1, false)),
+            (1, transaction(11, 3, false)),
+        ]);
+        source.recent_signatures = Some(RecentSignatures::new(2));
+
+        let events = drain(&mut source).await;
+
+        assert_eq!(signatures(&events), vec![signature(1), signature(2), signature(3), signature(1)]);
+    }
+
+    #[tokio::test]
+    async fn single_subscription_is_not_deduplicated() {
+        let mut source = source(1, vec![transaction(10, 1, false), transaction(10, 1, false)]);
+
+        assert!(source.recent_signatures.is_none());
+        assert_eq!(drain(&mut source).await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn transactions_take_their_block_time_from_the_block_meta() {
+        let meta = SubscribeUpdate {
+            update_oneof: Some(UpdateOneof::BlockMeta(SubscribeUpdateBlockMeta {
+                slot: 10,
+                block_time: Some(UnixTimestamp { timestamp: 1_600_000_000 }),
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        let mut source = source(1, vec![meta, transaction(10, 1, false), transaction(11, 2, false)]);
+
+        let times: Vec<_> = drain(&mut source)
+            .await
+            .into_iter()
+            .filter_map(|ev| match ev {
+                ChainEvent::Transaction(txn) => Some(txn.block_time),
+                _ => None,
+            })
+            .collect();
+
+        // Slot 11 has no BlockMeta yet: unset, never the wall clock
+        assert_eq!(times, vec![Some(1_600_000_000), None]);
+    }
+
+    #[test]
+    fn request_carries_the_configured_commitment() {
+        for commitment in [CommitmentLevel::Processed, CommitmentLevel::Confirmed, CommitmentLevel::Finalized] {
+            let mut subscription = subscription(vec![TransactionFilter::default()]);
+            subscription.commitment = commitment;
+
+            let request = GrpcSourceAdaptor::build_request(&subscription, 0);
+
+            assert_eq!(request.commitment, Some(commitment as i32));
+            // Finalized slots never roll back, so there is nothing to watch for
+            assert_eq!(request.slots.contains_key("dead-slots"), commitment != CommitmentLevel::Finalized);
+        }
+    }
+
+    #[test]
+    fn account_filters_are_passed_through_to_the_request() {
+        let filter = TransactionFilter {
+            account_include: vec!["Include1".to_string(), "Include2".to_string()],
+            account_required: vec!["Required".to_string()],
+            account_exclude: vec!["Exclude".to_string()],
+            include_failed: false,
+        };
+
+        let request = GrpcSourceAdaptor::build_request(&subscription(vec![filter]), 0);
+
+        let transactions = &request.transactions["all_txs"];
+        assert_eq!(transactions.account_include, vec!["Include1", "Include2"]);
+        assert_eq!(transactions.account_required, vec!["Required"]);
+        assert_eq!(transactions.account_exclude, vec!["Exclude"]);
+        assert_eq!(transactions.vote, Some(false));
+    }
+
+    #[test]
+    fn dex_filter_watches_every_parsed_program() {
+        let request = GrpcSourceAdaptor::build_request(&subscription(vec![TransactionFilter::dex_programs()]), 0);
+
+        let include = &request.transactions["all_txs"].account_include;
+        for program in [
+            domain::JUPITER_V6_PROGRAM_ID,
+            domain::RAYDIUM_V4_PROGRAM_ID,
+            domain::PUMP_FUN_PROGRAM_ID,
+            domain::PUMP_AMM_PROGRAM_ID,
+        ] {
+            assert!(include.iter().any(|key| key == program), "{} is not watched", program);
+        }
+    }
+
+    #[tokio::test]
+    async fn fee_and_compute_units_come_from_the_meta() {
+        let mut update = transaction(10, 1, false);
+        if let Some(UpdateOneof::Transaction(tx)) = update.update_oneof.as_mut() {
+            let meta = tx.transaction.as_mut().unwrap().meta.as_mut().unwrap();
+            meta.fee = 5_000;
+            meta.compute_units_consumed = Some(42_000);
+        }
+        let mut source = source(1, vec![update]);
+
+        let events = drain(&mut source).await;
+
+        let [ChainEvent::Transaction(txn)] = events.as_slice() else { panic!("unexpected events {:?}", events) };
+        assert_eq!(txn.fee, Some(5_000));
+        assert_eq!(txn.compute_units_consumed, Some(42_000));
+    }
+
+    #[tokio::test]
+    async fn transaction_with_a_malformed_signature_is_skipped() {
+        let mut empty = transaction(10, 1, false);
+        let mut short = transaction(11, 2, false);
+        for (update, len) in [(&mut empty, 0), (&mut short, 32)] {
+            if let Some(UpdateOneof::Transaction(tx)) = update.update_oneof.as_mut() {
+                tx.transaction.as_mut().unwrap().signature.truncate(len);
             }
         }
+        let mut source = source(1, vec![empty, short, transaction(12, 3, false)]);
+
+        let events = drain(&mut source).await;
+
+        assert_eq!(signatures(&events), vec![signature(3)]);
+    }
+
+    #[tokio::test]
+    async fn fee_payer_and_blockhash_come_from_the_message() {
+        let mut with_message = transaction(10, 1, false);
+        if let Some(UpdateOneof::Transaction(tx)) = with_message.update_oneof.as_mut() {
+            tx.transaction.as_mut().unwrap().transaction = Some(Transaction {
+                signatures: vec![vec![1; domain::SIGNATURE_LEN]],
+                message: Some(GrpcMessage {
+                    account_keys: vec![vec![9; 32], vec![8; 32]],
+                    recent_blockhash: vec![4; 32],
+                    ..Default::default()
+                }),
+            });
+        }
+        let mut source = source(1, vec![with_message, transaction(11, 2, false)]);
+
+        let events = drain(&mut source).await;
+
+        let [ChainEvent::Transaction(decoded), ChainEvent::Transaction(bare)] = events.as_slice() else {
+            panic!("unexpected events {:?}", events)
+        };
+        assert_eq!(decoded.fee_payer, bs58::encode([9u8; 32]).into_string());
+        assert_eq!(decoded.recent_blockhash, bs58::encode([4u8; 32]).into_string());
+        // No message to read them from
+        assert_eq!((bare.fee_payer.as_str(), bare.recent_blockhash.as_str()), ("", ""));
+    }
+
+    #[test]
+    fn default_message_limit_exceeds_tonics_four_mib() {
+        assert!(ChannelOptions::default().max_decoding_message_size > 4 * 1024 * 1024);
+    }
+
+    #[test]
+    fn endpoint_accepts_plain_and_tls_urls_and_rejects_garbage() {
+        let options = ChannelOptions::default();
+
+        assert!(GrpcSourceAdaptor::endpoint("http://127.0.0.1:10000", &options).is_ok());
+        assert!(GrpcSourceAdaptor::endpoint("https://geyser.example.com", &options).is_ok());
+        assert!(GrpcSourceAdaptor::endpoint("not a url", &options).is_err());
+    }
+
+    #[tokio::test]
+    async fn connect_gives_up_after_the_configured_timeout() {
+        // Nothing answers on a non-routable address, so only the timeout ends the attempt
+        let options = ChannelOptions { connect_timeout: Duration::from_millis(200), ..Default::default() };
+        let endpoint = GrpcSourceAdaptor::endpoint("http://10.255.255.1:10000", &options).unwrap();
+
+        let attempt = tokio::time::timeout(Duration::from_secs(5), endpoint.connect()).await;
+
+        assert!(matches!(attempt, Ok(Err(_))), "connect did not fail within the timeout");
     }
 }
@@ -1,7 +1,13 @@
+mod composite_source;
 mod file_source;
 mod grpc_source;
 mod rpc_source;
+#[cfg(feature = "synthetic")]
+mod synthetic_source;
 
+pub use composite_source::*;
 pub use file_source::*;
 pub use grpc_source::*;
 pub use rpc_source::*;
+#[cfg(feature = "synthetic")]
+pub use synthetic_source::*;
@@ -1,90 +1,210 @@
-use std::sync::Arc;
+use std::collections::VecDeque;
 
-use futures::{StreamExt, stream};
-use solana_client::rpc_client::RpcClient;
+use async_trait::async_trait;
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    nonblocking::rpc_client::RpcClient,
+    rpc_config::RpcBlockConfig,
+    rpc_custom_error::{JSON_RPC_SERVER_ERROR_LONG_TERM_STORAGE_SLOT_SKIPPED, JSON_RPC_SERVER_ERROR_SLOT_SKIPPED},
+    rpc_request::RpcError,
+};
 use solana_sdk::commitment_config::CommitmentConfig;
-use solana_transaction_status::EncodedTransactionWithStatusMeta;
+use solana_transaction_status::{
+    EncodedTransactionWithStatusMeta, TransactionDetails, UiTransactionEncoding, option_serializer::OptionSerializer,
+};
 
 use crate::{
-    application::EventBuffer,
+    application::{AppError, AppResult, TransactionSource},
     domain::{ChainEvent, SolanaTransaction, TxData},
 };
 
-/// Fetches historical blocks from an RPC node and pushes them into the shared buffer.
-/// Designed to run as a one-shot tokio task — drops the buffer handle when done,
-/// which automatically signals the downstream pipeline to stop if no other producers exist.
-pub async fn run_backfill_producer(
-    rpc_url: String,
-    buffer: Arc<dyn EventBuffer>,
-    start_slot: u64,
-    end_slot: u64,
-) {
-    tracing::info!("Backfill producer started: slots {} → {}", start_slot, end_slot);
-
-    let client = Arc::new(RpcClient::new_with_commitment(
-        rpc_url,
-        CommitmentConfig::confirmed(),
-    ));
-
-    let slot_range = stream::iter(start_slot..=end_slot);
-
-    let mut fetch_stream = slot_range
-        .map(|slot| {
-            let client = client.clone();
-            async move {
-                match client.get_block(slot) {
-                    Ok(block) => Some((slot, block)),
-                    Err(e) => {
-                        tracing::warn!("Skipped slot {}: {}", slot, e);
-                        None
-                    }
-                }
-            }
-        })
-        .buffered(10);
+/// Loads the events of one block: its `BlockMeta` followed by its transactions.
+/// `Ok(None)` means the slot was skipped and has no block.
+#[async_trait]
+pub trait BlockFetcher: Send + Sync {
+    async fn fetch_block(&self, slot: u64) -> AppResult<Option<Vec<ChainEvent>>>;
+}
 
-    while let Some(result) = fetch_stream.next().await {
-        if let Some((slot, block)) = result {
-            let block_time = block.block_time.unwrap_or(0);
+/// `getBlock` over JSON-RPC, at confirmed commitment.
+pub struct RpcBlockFetcher {
+    client: RpcClient,
+}
 
-            let meta_event = ChainEvent::BlockMeta {
-                slot,
-                block_hash: block.blockhash.clone(),
-                parent_block_hash: block.previous_blockhash.clone(),
-            };
+impl RpcBlockFetcher {
+    pub fn new(rpc_url: String) -> Self {
+        Self { client: RpcClient::new_with_commitment(rpc_url, CommitmentConfig::confirmed()) }
+    }
+}
 
-            if buffer.produce(meta_event).await.is_err() {
-                tracing::error!("Pipeline closed during backfill");
-                break;
-            }
+/// Leader skipped the slot, or the node's long-term storage has no block for it.
+fn is_skipped_slot(e: &ClientError) -> bool {
+    matches!(
+        e.kind(),
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. })
+            if *code == JSON_RPC_SERVER_ERROR_SLOT_SKIPPED || *code == JSON_RPC_SERVER_ERROR_LONG_TERM_STORAGE_SLOT_SKIPPED
+    )
+}
+
+#[async_trait]
+impl BlockFetcher for RpcBlockFetcher {
+    async fn fetch_block(&self, slot: u64) -> AppResult<Option<Vec<ChainEvent>>> {
+        let config = RpcBlockConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            transaction_details: Some(TransactionDetails::Full),
+            rewards: Some(false),
+            commitment: Some(CommitmentConfig::confirmed()),
+            max_supported_transaction_version: Some(0),
+        };
+        let block = match self.client.get_block_with_config(slot, config).await {
+            Ok(block) => block,
+            Err(e) if is_skipped_slot(&e) => return Ok(None),
+            Err(e) => return Err(AppError::ConnectionError(format!("getBlock {}: {}", slot, e))),
+        };
 
-            for tx_with_meta in block.transactions {
-                if let Some(sol_tx) = decode_rpc_transaction(tx_with_meta, slot, block_time) {
-                    if buffer.produce(ChainEvent::Transaction(sol_tx)).await.is_err() {
-                        break;
-                    }
-                }
+        let block_time = block.block_time;
+        let mut events = vec![ChainEvent::BlockMeta {
+            slot,
+            block_hash: block.blockhash,
+            parent_block_hash: block.previous_blockhash,
+            block_time,
+        }];
+        events.extend(
+            block
+                .transactions
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|tx| decode_rpc_transaction(tx, slot, block_time))
+                .map(ChainEvent::Transaction),
+        );
+        Ok(Some(events))
+    }
+}
+
+/// Historical blocks for an inclusive slot range, one `getBlock` at a time, in
+/// slot order. Skipped slots are passed over; the stream ends after `end`. A
+/// failed fetch is returned as a transient error and retried on the next call,
+/// so the fetcher's backoff applies.
+pub struct RpcBlockSource {
+    fetcher: Box<dyn BlockFetcher>,
+    next_slot: u64,
+    end: u64,
+    pending: VecDeque<ChainEvent>,
+}
+
+impl RpcBlockSource {
+    pub fn new(rpc_url: String, start: u64, end: u64) -> Self {
+        Self::with_fetcher(Box::new(RpcBlockFetcher::new(rpc_url)), start, end)
+    }
+
+    pub fn with_fetcher(fetcher: Box<dyn BlockFetcher>, start: u64, end: u64) -> Self {
+        Self { fetcher, next_slot: start, end, pending: VecDeque::new() }
+    }
+}
+
+#[async_trait]
+impl TransactionSource for RpcBlockSource {
+    async fn next_event(&mut self) -> AppResult<Option<ChainEvent>> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Ok(Some(event));
+            }
+            if self.next_slot > self.end {
+                return Ok(None);
             }
+            let slot = self.next_slot;
+            match self.fetcher.fetch_block(slot).await? {
+                Some(events) => self.pending.extend(events),
+                None => tracing::debug!("Slot {} was skipped", slot),
+            }
+            self.next_slot += 1;
         }
     }
-
-    tracing::info!("Backfill producer finished");
 }
 
 fn decode_rpc_transaction(
     tx: EncodedTransactionWithStatusMeta,
     slot: u64,
-    block_time: i64,
+    block_time: Option<i64>,
 ) -> Option<SolanaTransaction> {
     let decoded = tx.transaction.decode()?;
-    let signature = decoded.signatures[0].to_string();
+    let Some(signature) = decoded.signatures.first().map(|s| s.to_string()) else {
+        tracing::warn!("Skipping unsigned transaction at slot {}", slot);
+        return None;
+    };
+    let fee_payer = decoded.message.static_account_keys().first().map(|k| k.to_string()).unwrap_or_default();
+    let recent_blockhash = decoded.message.recent_blockhash().to_string();
     let meta = tx.meta?;
+    let success = meta.err.is_none();
+    let fee = Some(meta.fee);
+    let compute_units_consumed = match meta.compute_units_consumed {
+        OptionSerializer::Some(units) => Some(units),
+        _ => None,
+    };
 
     Some(SolanaTransaction {
         signature,
-        success: true,
+        success,
         data: TxData::Rpc { tx: decoded, meta },
         slot,
         block_time,
+        fee,
+        compute_units_consumed,
+        fee_payer,
+        recent_blockhash,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    /// Blocks for every slot in `available` except the skipped ones.
+    struct FakeChain {
+        available: std::ops::RangeInclusive<u64>,
+        skipped: HashSet<u64>,
+    }
+
+    #[async_trait]
+    impl BlockFetcher for FakeChain {
+        async fn fetch_block(&self, slot: u64) -> AppResult<Option<Vec<ChainEvent>>> {
+            if !self.available.contains(&slot) || self.skipped.contains(&slot) {
+                return Ok(None);
+            }
+            Ok(Some(vec![ChainEvent::BlockMeta {
+                slot,
+                block_hash: format!("hash{}", slot),
+                parent_block_hash: format!("hash{}", slot - 1),
+                block_time: None,
+            }]))
+        }
+    }
+
+    async fn block_slots(source: &mut RpcBlockSource) -> Vec<u64> {
+        let mut slots = Vec::new();
+        while let Some(event) = source.next_event().await.unwrap() {
+            match event {
+                ChainEvent::BlockMeta { slot, .. } => slots.push(slot),
+                other => panic!("unexpected event {:?}", other),
+            }
+        }
+        slots
+    }
+
+    #[tokio::test]
+    async fn yields_only_the_requested_range_then_ends() {
+        let chain = FakeChain { available: 90..=110, skipped: HashSet::new() };
+        let mut source = RpcBlockSource::with_fetcher(Box::new(chain), 95, 105);
+
+        assert_eq!(block_slots(&mut source).await, (95..=105).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn passes_over_skipped_slots() {
+        let chain = FakeChain { available: 90..=110, skipped: HashSet::from([97, 98]) };
+        let mut source = RpcBlockSource::with_fetcher(Box::new(chain), 95, 100);
+
+        assert_eq!(block_slots(&mut source).await, vec![95, 96, 99, 100]);
+    }
+}
@@ -0,0 +1,378 @@
+use std::{str::FromStr, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use prost::Message as _;
+use solana_sdk::pubkey::Pubkey;
+use tokio::time::{Interval, MissedTickBehavior};
+use yellowstone_grpc_proto::prelude::{
+    CompiledInstruction, InnerInstruction, InnerInstructions, Message, SubscribeUpdate, SubscribeUpdateTransaction,
+    SubscribeUpdateTransactionInfo, TokenBalance, Transaction, TransactionStatusMeta, subscribe_update::UpdateOneof,
+};
+
+use crate::{
+    application::{AppResult, Clock, SystemClock, TransactionSource},
+    domain::{self, ChainEvent, SolanaTransaction, TxData},
+};
+
+/// Programs the synthetic source knows how to build instructions for. Jupiter and
+/// Pump.fun are decoded through Vixen's IDL parsers and are not generated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyntheticProgram {
+    SplToken,
+    RaydiumAmm,
+    SystemTransfer,
+    ComputeBudget,
+}
+
+impl FromStr for SyntheticProgram {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "spl_token" => Ok(Self::SplToken),
+            "raydium_amm" => Ok(Self::RaydiumAmm),
+            "system_transfer" => Ok(Self::SystemTransfer),
+            "compute_budget" => Ok(Self::ComputeBudget),
+            _ => Err(format!("unknown synthetic program `{}`", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SyntheticConfig {
+    /// Transactions per second; 0 emits as fast as the consumer reads.
+    pub tps: u32,
+    /// Relative weights; each transaction targets one program.
+    pub mix: Vec<(SyntheticProgram, u32)>,
+    /// Stop after this many transactions; `None` runs forever.
+    pub max_count: Option<u64>,
+    pub txs_per_slot: u64,
+    pub start_slot: u64,
+}
+
+impl Default for SyntheticConfig {
+    fn default() -> Self {
+        Self {
+            tps: 1_000,
+            mix: vec![
+                (SyntheticProgram::SplToken, 1),
+                (SyntheticProgram::RaydiumAmm, 1),
+                (SyntheticProgram::SystemTransfer, 1),
+                (SyntheticProgram::ComputeBudget, 1),
+            ],
+            max_count: None,
+            txs_per_slot: 50,
+            start_slot: 1_000,
+        }
+    }
+}
+
+/// Load-test source that emits real, decodable gRPC `SubscribeUpdate` bytes, so the
+/// parsers run their full decode path. Programs are picked by weighted round robin,
+/// which keeps runs reproducible.
+pub struct SyntheticSource {
+    config: SyntheticConfig,
+    schedule: Vec<SyntheticProgram>,
+    ticker: Option<Interval>,
+    count: u64,
+    current_slot: Option<u64>,
+    keys: Vec<Pubkey>,
+    clock: Arc<dyn Clock>,
+}
+
+// Fixed layout of `keys`: 18 generic accounts (Raydium AMM uses all of them),
+// followed by the program ids
+const ACCOUNTS: usize = 18;
+const PROGRAMS: [&str; 4] = [
+    domain::TOKEN_PROGRAM_ID,
+    domain::RAYDIUM_V4_PROGRAM_ID,
+    domain::SYSTEM_PROGRAM,
+    domain::COMPUTE_BUDGET_PROGRAM_ID,
+];
+
+impl SyntheticSource {
+    pub fn new(config: SyntheticConfig) -> Self {
+        let schedule: Vec<SyntheticProgram> = config.mix.iter()
+            .flat_map(|&(program, weight)| std::iter::repeat_n(program, weight as usize))
+            .collect();
+        let ticker = (config.tps > 0).then(|| {
+            let mut ticker = tokio::time::interval(Duration::from_secs(1) / config.tps);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Burst);
+            ticker
+        });
+        let keys = (0..ACCOUNTS).map(|_| Pubkey::new_unique())
+            .chain(PROGRAMS.iter().map(|p| p.parse().expect("valid program id")))
+            .collect();
+
+        Self { config, schedule, ticker, count: 0, current_slot: None, keys, clock: Arc::new(SystemClock) }
+    }
+
+    /// Stamp blocks and transactions with `clock` instead of the system time.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    fn program_index(id: &str) -> u32 {
+        (ACCOUNTS + PROGRAMS.iter().position(|p| *p == id).expect("known program")) as u32
+    }
+
+    fn signature(n: u64) -> Vec<u8> {
+        let mut sig = vec![0x5a; domain::SIGNATURE_LEN];
+        sig[..8].copy_from_slice(&n.to_le_bytes());
+        sig
+    }
+
+    /// One stand-in blockhash per slot.
+    fn blockhash(slot: u64) -> [u8; 32] {
+        let mut hash = [0xb1; 32];
+        hash[..8].copy_from_slice(&slot.to_le_bytes());
+        hash
+    }
+
+    fn token_balance(&self, account_index: u32, mint: &str) -> TokenBalance {
+        TokenBalance {
+            account_index,
+            mint: mint.to_string(),
+            program_id: domain::TOKEN_PROGRAM_ID.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Instruction, inner instructions and token balances for one transaction.
+    fn build(&self, program: SyntheticProgram, n: u64) -> (Vec<CompiledInstruction>, Vec<InnerInstructions>, Vec<TokenBalance>) {
+        let amount = 1_000 + n % 1_000_000;
+        match program {
+            SyntheticProgram::SplToken => {
+                let mut data = vec![3];
+                data.extend_from_slice(&amount.to_le_bytes());
+                let ix = CompiledInstruction {
+                    program_id_index: Self::program_index(domain::TOKEN_PROGRAM_ID),
+                    accounts: vec![1, 2, 0],
+                    data,
+                };
+                (vec![ix], vec![], vec![])
+            }
+            SyntheticProgram::RaydiumAmm => {
+                // SwapBaseIn: amount_in, min_amount_out
+                let mut data = vec![9];
+                data.extend_from_slice(&amount.to_le_bytes());
+                data.extend_from_slice(&(amount / 2).to_le_bytes());
+                let ix = CompiledInstruction {
+                    program_id_index: Self::program_index(domain::RAYDIUM_V4_PROGRAM_ID),
+                    accounts: (0..ACCOUNTS as u8).collect(),
+                    data,
+                };
+
+                // The pool paying out to the user's destination account (index 16)
+                let mut payout = vec![3];
+                payout.extend_from_slice(&(amount / 2 + 1).to_le_bytes());
+                let inner = InnerInstructions {
+                    index: 0,
+                    instructions: vec![InnerInstruction {
+                        program_id_index: Self::program_index(domain::TOKEN_PROGRAM_ID),
+                        accounts: vec![6, 16, 1],
+                        data: payout,
+                        stack_height: Some(2),
+                    }],
+                };
+                let balances = vec![
+                    self.token_balance(15, domain::WSOL_MINT),
+                    self.token_balance(16, domain::USDC_MINT),
+                ];
+                (vec![ix], vec![inner], balances)
+            }
+            SyntheticProgram::SystemTransfer => {
+                let mut data = 2u32.to_le_bytes().to_vec();
+                data.extend_from_slice(&amount.to_le_bytes());
+                let ix = CompiledInstruction {
+                    program_id_index: Self::program_index(domain::SYSTEM_PROGRAM),
+                    accounts: vec![0, 3],
+                    data,
+                };
+                (vec![ix], vec![], vec![])
+            }
+            SyntheticProgram::ComputeBudget => {
+                let pgm = Self::program_index(domain::COMPUTE_BUDGET_PROGRAM_ID);
+                let mut limit = vec![2];
+                limit.extend_from_slice(&200_000u32.to_le_bytes());
+                let mut price = vec![3];
+                price.extend_from_slice(&amount.to_le_bytes());
+                let ixs = vec![
+                    CompiledInstruction { program_id_index: pgm, accounts: vec![], data: limit },
+                    CompiledInstruction { program_id_index: pgm, accounts: vec![], data: price },
+                ];
+                (ixs, vec![], vec![])
+            }
+        }
+    }
+
+    fn transaction(&self, program: SyntheticProgram, n: u64, slot: u64) -> SolanaTransaction {
+        let signature = Self::signature(n);
+        let (instructions, inner_instructions, token_balances) = self.build(program, n);
+
+        let update = SubscribeUpdate {
+            update_oneof: Some(UpdateOneof::Transaction(SubscribeUpdateTransaction {
+                slot,
+                transaction: Some(SubscribeUpdateTransactionInfo {
+                    signature: signature.clone(),
+                    transaction: Some(Transaction {
+                        signatures: vec![signature.clone()],
+                        message: Some(Message {
+                            account_keys: self.keys.iter().map(|k| k.to_bytes().to_vec()).collect(),
+                            recent_blockhash: Self::blockhash(slot).to_vec(),
+                            instructions,
+                            ..Default::default()
+                        }),
+                    }),
+                    meta: Some(TransactionStatusMeta {
+                        fee: 5_000,
+                        inner_instructions,
+                        pre_token_balances: token_balances.clone(),
+                        post_token_balances: token_balances,
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+            })),
+            ..Default::default()
+        };
+
+        SolanaTransaction {
+            signature: bs58::encode(&signature).into_string(),
+            success: true,
+            data: TxData::Grpc(update.encode_to_vec()),
+            slot,
+            block_time: Some(self.clock.now()),
+            fee: Some(5_000),
+            compute_units_consumed: None,
+            fee_payer: self.keys[0].to_string(),
+            recent_blockhash: bs58::encode(Self::blockhash(slot)).into_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl TransactionSource for SyntheticSource {
+    async fn next_event(&mut self) -> AppResult<Option<ChainEvent>> {
+        if self.schedule.is_empty() || self.config.max_count.is_some_and(|max| self.count >= max) {
+            return Ok(None);
+        }
+
+        let slot = self.config.start_slot + self.count / self.config.txs_per_slot.max(1);
+        if self.current_slot != Some(slot) {
+            self.current_slot = Some(slot);
+            return Ok(Some(ChainEvent::BlockMeta {
+                slot,
+                block_hash: format!("synthetic_{}", slot),
+                parent_block_hash: format!("synthetic_{}", slot.saturating_sub(1)),
+                block_time: Some(self.clock.now()),
+            }));
+        }
+
+        if let Some(ticker) = self.ticker.as_mut() {
+            ticker.tick().await;
+        }
+
+        let n = self.count;
+        self.count += 1;
+        let program = self.schedule[(n % self.schedule.len() as u64) as usize];
+        Ok(Some(ChainEvent::Transaction(self.transaction(program, n, slot))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prost::Message as _;
+
+    use super::*;
+    use crate::{
+        adapters::{ComputeBudgetParser, RaydiumAmmParser, SplTokenTransfer, SystemTransferParser},
+        application::{FixedClock, TransactionParser},
+    };
+
+    fn source(mix: Vec<(SyntheticProgram, u32)>, max_count: u64) -> SyntheticSource {
+        SyntheticSource::new(SyntheticConfig { tps: 0, mix, max_count: Some(max_count), ..Default::default() })
+    }
+
+    async fn transactions(source: &mut SyntheticSource) -> Vec<SolanaTransaction> {
+        let mut out = Vec::new();
+        while let Some(event) = source.next_event().await.unwrap() {
+            if let ChainEvent::Transaction(txn) = event {
+                out.push(txn);
+            }
+        }
+        out
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn every_program_decodes_to_its_event_kind() {
+        let cases: [(SyntheticProgram, Box<dyn TransactionParser>, &str); 4] = [
+            (SyntheticProgram::SplToken, Box::new(SplTokenTransfer::new()), "token_transfer"),
+            (SyntheticProgram::RaydiumAmm, Box::new(RaydiumAmmParser::new()), "raydium_swap"),
+            (SyntheticProgram::SystemTransfer, Box::new(SystemTransferParser::new()), "sol_transfer"),
+            (SyntheticProgram::ComputeBudget, Box::new(ComputeBudgetParser::new()), "compute_budget"),
+        ];
+        for (program, parser, kind) in cases {
+            let txns = transactions(&mut source(vec![(program, 1)], 3)).await;
+            assert_eq!(txns.len(), 3);
+
+            for txn in txns {
+                let TxData::Grpc(bytes) = &txn.data else { panic!("synthetic transactions are gRPC frames") };
+                assert!(SubscribeUpdate::decode(bytes.as_slice()).is_ok());
+
+                let events = parser.parse(txn).unwrap().unwrap_or_default();
+                assert!(!events.is_empty(), "{:?} produced no events", program);
+                assert!(events.iter().all(|ev| ev.kind() == kind), "{:?} produced {:?}", program, events);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn mix_is_followed_and_slots_advance() {
+        let mut source = SyntheticSource::new(SyntheticConfig {
+            tps: 0,
+            mix: vec![(SyntheticProgram::SplToken, 2), (SyntheticProgram::ComputeBudget, 1)],
+            max_count: Some(6),
+            txs_per_slot: 3,
+            start_slot: 100,
+        });
+        let mut blocks = Vec::new();
+        let mut programs = Vec::new();
+        while let Some(event) = source.next_event().await.unwrap() {
+            match event {
+                ChainEvent::BlockMeta { slot, .. } => blocks.push(slot),
+                ChainEvent::Transaction(txn) => {
+                    let TxData::Grpc(bytes) = &txn.data else { unreachable!() };
+                    let update = SubscribeUpdate::decode(bytes.as_slice()).unwrap();
+                    let Some(UpdateOneof::Transaction(tx)) = update.update_oneof else { unreachable!() };
+                    let message = tx.transaction.unwrap().transaction.unwrap().message.unwrap();
+                    programs.push(message.instructions[0].program_id_index);
+                }
+                other => panic!("unexpected event {:?}", other),
+            }
+        }
+
+        assert_eq!(blocks, vec![100, 101]);
+        let token = SyntheticSource::program_index(domain::TOKEN_PROGRAM_ID);
+        let budget = SyntheticSource::program_index(domain::COMPUTE_BUDGET_PROGRAM_ID);
+        assert_eq!(programs, vec![token, token, budget, token, token, budget]);
+    }
+
+    #[tokio::test]
+    async fn blocks_and_transactions_share_the_injected_time() {
+        let mut source = source(vec![(SyntheticProgram::SystemTransfer, 1)], 4).with_clock(Arc::new(FixedClock(1_700_000_000)));
+
+        let mut times = Vec::new();
+        while let Some(event) = source.next_event().await.unwrap() {
+            match event {
+                ChainEvent::BlockMeta { block_time, .. } => times.push(block_time),
+                ChainEvent::Transaction(txn) => times.push(txn.block_time),
+                other => panic!("unexpected event {:?}", other),
+            }
+        }
+
+        assert!(times.len() > 4);
+        assert!(times.iter().all(|&time| time == Some(1_700_000_000)));
+    }
+}
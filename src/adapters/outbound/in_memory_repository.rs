@@ -0,0 +1,190 @@
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicU64, Ordering},
+};
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::{
+    application::TransactionRepository,
+    domain::{IndexedTransaction, IndexerState, SolanaTransaction, TransactionEvent},
+};
+
+/// Repository that keeps every saved event in memory, for tests and examples.
+/// Clones share the same storage, so a clone handed to the pipeline can be
+/// inspected from the test afterwards.
+#[derive(Clone, Default)]
+pub struct InMemoryRepository {
+    events: Arc<Mutex<Vec<TransactionEvent>>>,
+    dlq: Arc<Mutex<Vec<(String, String, String)>>>,
+    transactions: Arc<Mutex<Vec<IndexedTransaction>>>,
+    last_slot: Arc<AtomicU64>,
+}
+
+impl InMemoryRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Snapshot of every event saved so far, in write order.
+    pub fn events(&self) -> Vec<TransactionEvent> {
+        self.events.lock().unwrap().clone()
+    }
+
+    /// (signature, parser_name, error) for every DLQ write.
+    pub fn dlq_entries(&self) -> Vec<(String, String, String)> {
+        self.dlq.lock().unwrap().clone()
+    }
+
+    /// Every transaction record saved so far, in write order.
+    pub fn transactions(&self) -> Vec<IndexedTransaction> {
+        self.transactions.lock().unwrap().clone()
+    }
+
+    pub fn clear(&self) {
+        self.events.lock().unwrap().clear();
+        self.dlq.lock().unwrap().clear();
+        self.transactions.lock().unwrap().clear();
+    }
+}
+
+#[async_trait]
+impl TransactionRepository for InMemoryRepository {
+    async fn get_state(&self) -> Result<IndexerState> {
+        Ok(IndexerState {
+            last_slot: self.last_slot.load(Ordering::Relaxed),
+            last_block_hash: String::new(),
+        })
+    }
+
+    async fn get_last_slot(&self) -> Result<u64> {
+        Ok(self.last_slot.load(Ordering::Relaxed))
+    }
+
+    async fn save_batch(&self, events: &[TransactionEvent], current_slot: u64) -> Result<()> {
+        self.events.lock().unwrap().extend_from_slice(events);
+        self.last_slot.fetch_max(current_slot, Ordering::Relaxed);
+        Ok(())
+    }
+
+    async fn save_dlq(&self, txn: &SolanaTransaction, parser_name: &str, error: &str) -> Result<()> {
+        self.dlq.lock().unwrap().push((txn.signature.clone(), parser_name.to_string(), error.to_string()));
+        Ok(())
+    }
+
+    async fn save_event_dlq(&self, event: &TransactionEvent, error: &str) -> Result<()> {
+        self.dlq.lock().unwrap().push((event.signature().to_string(), format!("persist:{}", event.kind()), error.to_string()));
+        Ok(())
+    }
+
+    async fn save_transactions(&self, transactions: &[IndexedTransaction]) -> Result<()> {
+        self.transactions.lock().unwrap().extend_from_slice(transactions);
+        Ok(())
+    }
+
+    async fn delete_events_from_slot(&self, slot: u64) -> Result<u64> {
+        self.transactions.lock().unwrap().retain(|t| t.slot < slot);
+        let mut events = self.events.lock().unwrap();
+        let before = events.len();
+        events.retain(|ev| ev.slot() < slot);
+        self.last_slot.fetch_min(slot.saturating_sub(1), Ordering::Relaxed);
+        Ok((before - events.len()) as u64)
+    }
+
+    async fn ping(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{
+        JupiterSwapEvent, Pubkey58, PumpFunTrade, RaydiumSwapEvent, Signature58, SwapDirection, TokenTransfer, fixtures,
+    };
+
+    fn token_transfer() -> TransactionEvent {
+        TransactionEvent::TokenTransfer(TokenTransfer {
+            from: Pubkey58::from([1u8; 32]),
+            to: Pubkey58::from([2u8; 32]),
+            slot: 10,
+            block_time: None,
+            amount: 5_000,
+            signature: Signature58::from([1u8; 64]),
+            mint: Some("Mint".to_string()),
+            instruction_index: 0,
+            inner_index: None,
+        })
+    }
+
+    fn raydium_swap() -> TransactionEvent {
+        TransactionEvent::RaydiumSwap(RaydiumSwapEvent { slot: 11, min_amount_out: 900, ..fixtures::raydium_swap(2, 1_000, 950) })
+    }
+
+    fn jupiter_swap() -> TransactionEvent {
+        TransactionEvent::JupiterSwap(JupiterSwapEvent {
+            signature: Signature58::from([3u8; 64]),
+            instruction_index: 0,
+            inner_index: None,
+            cpi_depth: 0,
+            slot: 12,
+            block_time: None,
+            signer: "signer".to_string(),
+            amm_pool: "pool".to_string(),
+            mint_in: "MintA".to_string(),
+            mint_out: "MintB".to_string(),
+            amount_in: 2_000,
+            amount_out: 1_900,
+            slippage_bps: 50,
+            platform_fee_bps: 0,
+            route_plan: Vec::new(),
+        })
+    }
+
+    fn pump_fun_trade() -> TransactionEvent {
+        TransactionEvent::PumpFunTrade(PumpFunTrade {
+            signature: Signature58::from([4u8; 64]),
+            instruction_index: 0,
+            inner_index: None,
+            cpi_depth: 0,
+            slot: 13,
+            mint: "Mint".to_string(),
+            direction: SwapDirection::Buy,
+            user: "user".to_string(),
+            timestamp: None,
+            token_amount: 3_000,
+            sol_amount: 40,
+            block_time: None,
+        })
+    }
+
+    fn as_json(events: &[TransactionEvent]) -> Vec<String> {
+        events.iter().map(|ev| serde_json::to_string(ev).unwrap()).collect()
+    }
+
+    #[tokio::test]
+    async fn every_variant_is_returned_as_saved() {
+        let repo = InMemoryRepository::new();
+        let saved = vec![token_transfer(), raydium_swap(), jupiter_swap(), pump_fun_trade()];
+
+        repo.save_batch(&saved[..2], 11).await.unwrap();
+        repo.save_batch(&saved[2..], 13).await.unwrap();
+
+        assert_eq!(as_json(&repo.events()), as_json(&saved));
+        assert_eq!(repo.get_last_slot().await.unwrap(), 13);
+    }
+
+    #[tokio::test]
+    async fn clones_share_storage_and_rollback_drops_later_slots() {
+        let repo = InMemoryRepository::new();
+        let handed_off = repo.clone();
+
+        handed_off.save_batch(&[token_transfer(), raydium_swap(), jupiter_swap()], 12).await.unwrap();
+        assert_eq!(repo.events().len(), 3);
+
+        assert_eq!(repo.delete_events_from_slot(11).await.unwrap(), 2);
+        assert_eq!(as_json(&handed_off.events()), as_json(&[token_transfer()]));
+        assert_eq!(handed_off.get_last_slot().await.unwrap(), 10);
+    }
+}
@@ -0,0 +1,112 @@
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use tokio::{io::AsyncWriteExt, sync::Mutex};
+
+use crate::{
+    adapters::outbound::{Compression, SerializationFormat, serialize_event},
+    application::{AppError, AppResult, EventSink},
+    domain::TransactionEvent,
+};
+
+/// Appends every batch to a file as JSON lines, one event per line. With
+/// compression each batch is written as its own gzip member or zstd frame; both
+/// codecs read concatenated members back as one stream, so the file is complete
+/// after every batch.
+pub struct JsonlFileSink {
+    path: PathBuf,
+    compression: Compression,
+    file: Mutex<tokio::fs::File>,
+}
+
+impl JsonlFileSink {
+    /// Opens `path` for appending, creating it if needed.
+    pub fn open(path: impl Into<PathBuf>, compression: Compression) -> AppResult<Self> {
+        let path = path.into();
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| Self::error(&path, e))?;
+        Ok(Self { path, compression, file: Mutex::new(tokio::fs::File::from_std(file)) })
+    }
+
+    /// Every event in a file this sink wrote with `compression`, in write order.
+    pub async fn read_events(path: impl AsRef<Path>, compression: Compression) -> AppResult<Vec<TransactionEvent>> {
+        let path = path.as_ref();
+        let bytes = tokio::fs::read(path).await.map_err(|e| Self::error(path, e))?;
+        let plain = compression.decompress(&bytes)?;
+        plain.split(|&b| b == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_slice(line).map_err(|e| Self::error(path, e)))
+            .collect()
+    }
+
+    fn error(path: &Path, reason: impl ToString) -> AppError {
+        AppError::SinkError { sink: format!("jsonl {}", path.display()), reason: reason.to_string() }
+    }
+}
+
+#[async_trait]
+impl EventSink for JsonlFileSink {
+    async fn emit(&self, events: &[TransactionEvent]) -> AppResult<()> {
+        if events.is_empty() {
+            return Ok(());
+        }
+        let mut lines = Vec::new();
+        for event in events {
+            lines.extend(serialize_event(event, SerializationFormat::Json)?);
+            lines.push(b'\n');
+        }
+        let body = self.compression.compress(&lines)?;
+
+        let mut file = self.file.lock().await;
+        file.write_all(&body).await.map_err(|e| Self::error(&self.path, e))?;
+        file.flush().await.map_err(|e| Self::error(&self.path, e))
+    }
+
+    fn name(&self) -> &str { "jsonl" }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::fixtures::transfer;
+
+    /// Two batches through a fresh sink, read back from the file.
+    async fn round_trip(compression: Compression) -> (Vec<TransactionEvent>, Vec<TransactionEvent>) {
+        let path = std::env::temp_dir().join(format!("indexer-jsonl-{}-{:?}.jsonl", std::process::id(), compression));
+        let _ = std::fs::remove_file(&path);
+        let written = vec![transfer(1), transfer(2), transfer(3)];
+
+        let sink = JsonlFileSink::open(&path, compression).unwrap();
+        sink.emit(&written[..2]).await.unwrap();
+        sink.emit(&written[2..]).await.unwrap();
+        let read = JsonlFileSink::read_events(&path, compression).await.unwrap();
+
+        std::fs::remove_file(&path).unwrap();
+        (written, read)
+    }
+
+    fn as_json(events: &[TransactionEvent]) -> Vec<String> {
+        events.iter().map(|ev| serde_json::to_string(ev).unwrap()).collect()
+    }
+
+    #[tokio::test]
+    async fn gzip_jsonl_round_trips() {
+        let (written, read) = round_trip(Compression::Gzip).await;
+        assert_eq!(as_json(&read), as_json(&written));
+    }
+
+    #[tokio::test]
+    async fn zstd_jsonl_round_trips() {
+        let (written, read) = round_trip(Compression::Zstd).await;
+        assert_eq!(as_json(&read), as_json(&written));
+    }
+
+    #[tokio::test]
+    async fn plain_jsonl_round_trips() {
+        let (written, read) = round_trip(Compression::None).await;
+        assert_eq!(as_json(&read), as_json(&written));
+    }
+}
@@ -1,5 +1,25 @@
+mod compression;
+mod in_memory_repository;
+mod jsonl_file;
+mod multi_sink;
+mod null_repository;
 mod postgres_repository;
+mod repository_factory;
+mod rpc_mint_resolver;
+mod serialization;
+mod stdout;
 mod telegram;
+mod webhook;
 
+pub use compression::*;
+pub use in_memory_repository::*;
+pub use jsonl_file::*;
+pub use multi_sink::*;
+pub use null_repository::*;
 pub use postgres_repository::*;
+pub use repository_factory::*;
+pub use rpc_mint_resolver::*;
+pub use serialization::*;
+pub use stdout::*;
 pub use telegram::*;
+pub use webhook::*;
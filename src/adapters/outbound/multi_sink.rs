@@ -0,0 +1,122 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures::future::join_all;
+
+use crate::{
+    application::{AppError, AppResult, EventSink},
+    domain::TransactionEvent,
+};
+
+/// When a `MultiSink` emit counts as successful.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryPolicy {
+    /// Every sink must accept the batch.
+    All,
+    /// At least one sink must accept the batch; the rest are logged.
+    Any,
+}
+
+/// Forwards each batch to several sinks concurrently.
+pub struct MultiSink {
+    sinks: Vec<Arc<dyn EventSink>>,
+    policy: DeliveryPolicy,
+}
+
+impl MultiSink {
+    pub fn new(sinks: Vec<Arc<dyn EventSink>>, policy: DeliveryPolicy) -> Self {
+        Self { sinks, policy }
+    }
+}
+
+#[async_trait]
+impl EventSink for MultiSink {
+    async fn emit(&self, events: &[TransactionEvent]) -> AppResult<()> {
+        let results = join_all(self.sinks.iter().map(|s| s.emit(events))).await;
+
+        let failures: Vec<String> = self.sinks.iter()
+            .zip(&results)
+            .filter_map(|(sink, r)| r.as_ref().err().map(|e| format!("{}: {}", sink.name(), e)))
+            .collect();
+
+        if failures.is_empty() {
+            return Ok(());
+        }
+
+        let delivered = results.len() - failures.len();
+        match self.policy {
+            DeliveryPolicy::Any if delivered > 0 => {
+                for f in &failures {
+                    tracing::warn!("Sink delivery failed ({}/{} succeeded): {}", delivered, results.len(), f);
+                }
+                Ok(())
+            }
+            _ => Err(AppError::SinkError { sink: "multi".to_string(), reason: failures.join("; ") }),
+        }
+    }
+
+    fn name(&self) -> &str { "multi" }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::domain::fixtures::transfer;
+
+    /// Keeps every event it is given, or fails every emit.
+    struct CapturingSink {
+        name: &'static str,
+        fails: bool,
+        events: Mutex<Vec<TransactionEvent>>,
+    }
+
+    impl CapturingSink {
+        fn new(name: &'static str, fails: bool) -> Arc<Self> {
+            Arc::new(Self { name, fails, events: Mutex::new(Vec::new()) })
+        }
+
+        fn signatures(&self) -> Vec<String> {
+            self.events.lock().unwrap().iter().map(|ev| ev.signature().to_string()).collect()
+        }
+    }
+
+    #[async_trait]
+    impl EventSink for CapturingSink {
+        async fn emit(&self, events: &[TransactionEvent]) -> AppResult<()> {
+            if self.fails {
+                return Err(AppError::SinkError { sink: self.name.to_string(), reason: "unavailable".to_string() });
+            }
+            self.events.lock().unwrap().extend_from_slice(events);
+            Ok(())
+        }
+
+        fn name(&self) -> &str { self.name }
+    }
+
+    #[tokio::test]
+    async fn every_sink_receives_every_event() {
+        let (first, second) = (CapturingSink::new("first", false), CapturingSink::new("second", false));
+        let sink = MultiSink::new(vec![first.clone(), second.clone()], DeliveryPolicy::All);
+
+        sink.emit(&[transfer(1), transfer(2)]).await.unwrap();
+        sink.emit(&[transfer(3)]).await.unwrap();
+
+        let expected: Vec<_> = (1..=3).map(|seed| transfer(seed).signature().to_string()).collect();
+        assert_eq!(first.signatures(), expected);
+        assert_eq!(second.signatures(), expected);
+    }
+
+    #[tokio::test]
+    async fn policy_decides_whether_a_partial_delivery_fails() {
+        let sinks = || -> Vec<Arc<dyn EventSink>> { vec![CapturingSink::new("up", false), CapturingSink::new("down", true)] };
+
+        assert!(MultiSink::new(sinks(), DeliveryPolicy::Any).emit(&[transfer(1)]).await.is_ok());
+        let err = MultiSink::new(sinks(), DeliveryPolicy::All).emit(&[transfer(1)]).await.unwrap_err();
+        assert!(matches!(err, AppError::SinkError { ref reason, .. } if reason.starts_with("down:")));
+
+        let all_down: Vec<Arc<dyn EventSink>> = vec![CapturingSink::new("down", true)];
+        assert!(MultiSink::new(all_down, DeliveryPolicy::Any).emit(&[transfer(1)]).await.is_err());
+    }
+}
@@ -0,0 +1,110 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::{
+    application::TransactionRepository,
+    domain::{IndexerState, SolanaTransaction, TransactionEvent},
+};
+
+/// Repository that counts events instead of persisting them.
+/// Used when no database is configured so the batching path still runs end to end.
+pub struct NullRepository {
+    events_saved: AtomicU64,
+    batches_saved: AtomicU64,
+    dlq_entries: AtomicU64,
+    last_slot: AtomicU64,
+    // Log a summary every N batches; 0 disables logging
+    log_every: u64,
+}
+
+impl NullRepository {
+    pub fn new(log_every: u64) -> Self {
+        Self {
+            events_saved: AtomicU64::new(0),
+            batches_saved: AtomicU64::new(0),
+            dlq_entries: AtomicU64::new(0),
+            last_slot: AtomicU64::new(0),
+            log_every,
+        }
+    }
+
+    pub fn events_saved(&self) -> u64 {
+        self.events_saved.load(Ordering::Relaxed)
+    }
+
+    pub fn batches_saved(&self) -> u64 {
+        self.batches_saved.load(Ordering::Relaxed)
+    }
+
+    pub fn dlq_entries(&self) -> u64 {
+        self.dlq_entries.load(Ordering::Relaxed)
+    }
+}
+
+#[async_trait]
+impl TransactionRepository for NullRepository {
+    async fn get_state(&self) -> Result<IndexerState> {
+        Ok(IndexerState {
+            last_slot: self.last_slot.load(Ordering::Relaxed),
+            last_block_hash: String::new(),
+        })
+    }
+
+    async fn get_last_slot(&self) -> Result<u64> {
+        Ok(self.last_slot.load(Ordering::Relaxed))
+    }
+
+    async fn save_batch(&self, events: &[TransactionEvent], current_slot: u64) -> Result<()> {
+        let total = self.events_saved.fetch_add(events.len() as u64, Ordering::Relaxed) + events.len() as u64;
+        let batches = self.batches_saved.fetch_add(1, Ordering::Relaxed) + 1;
+        self.last_slot.fetch_max(current_slot, Ordering::Relaxed);
+
+        if self.log_every > 0 && batches % self.log_every == 0 {
+            tracing::info!("[no-db] {} batches, {} events discarded (slot {})", batches, total, current_slot);
+        }
+
+        Ok(())
+    }
+
+    async fn save_dlq(&self, txn: &SolanaTransaction, parser_name: &str, error: &str) -> Result<()> {
+        self.dlq_entries.fetch_add(1, Ordering::Relaxed);
+        tracing::debug!("[no-db] DLQ {} ({}): {}", txn.signature, parser_name, error);
+        Ok(())
+    }
+
+    async fn save_event_dlq(&self, event: &TransactionEvent, error: &str) -> Result<()> {
+        self.dlq_entries.fetch_add(1, Ordering::Relaxed);
+        tracing::debug!("[no-db] DLQ event {} ({}): {}", event.signature(), event.kind(), error);
+        Ok(())
+    }
+
+    async fn delete_events_from_slot(&self, slot: u64) -> Result<u64> {
+        self.last_slot.fetch_min(slot.saturating_sub(1), Ordering::Relaxed);
+        tracing::debug!("[no-db] rollback from slot {}", slot);
+        Ok(0)
+    }
+
+    async fn ping(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::fixtures::transfer;
+
+    #[tokio::test]
+    async fn save_batch_counts_every_event_it_is_given() {
+        let repo = NullRepository::new(0);
+
+        repo.save_batch(&[transfer(1), transfer(2), transfer(3)], 10).await.unwrap();
+        repo.save_batch(&[transfer(4)], 12).await.unwrap();
+
+        assert_eq!(repo.events_saved(), 4);
+        assert_eq!(repo.batches_saved(), 2);
+        assert_eq!(repo.get_last_slot().await.unwrap(), 12);
+    }
+}
@@ -1,201 +1,1302 @@
+use std::{collections::{BTreeMap, BTreeSet}, str::FromStr, time::Duration};
+
 use anyhow::{Ok, Result};
 use async_trait::async_trait;
-use bigdecimal::BigDecimal;
-use sqlx::{PgPool, Row, postgres::PgPoolOptions};
+use bigdecimal::{BigDecimal, ToPrimitive};
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, PgPool, Row, postgres::{PgPoolOptions, PgRow}};
 
 use crate::{
-    application::TransactionRepository,
-    domain::{IndexerState, SolanaTransaction, TransactionEvent},
+    application::{
+        AccountMints, AppError, AppResult, EventSink, FinalizeResult, MintResolver, PoolStats, RateLimiter, TransactionRepository,
+    },
+    domain::{
+        ComputeBudgetEvent, IndexedTransaction, IndexerState, JupiterSwapEvent, Pubkey58, PumpAmmSwapEvent, PumpFunTrade, RaydiumPoolType, RaydiumSwapEvent, RouteStep, Signature58, SolTransfer,
+        SolanaTransaction,
+        SwapEvent, TokenTransfer, TransactionEvent,
+    },
 };
 
+/// Batches at or above this many events are written with COPY instead of UNNEST inserts.
+pub const DEFAULT_COPY_THRESHOLD: usize = 5_000;
+pub const DEFAULT_MAX_CONNECTIONS: u32 = 5;
+/// How long a writer waits for a free connection before giving up with `PoolExhausted`.
+pub const DEFAULT_ACQUIRE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// What to do when an event's row already exists, e.g. on replay or when a later
+/// source delivers the same transaction with more detail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UpsertMode {
+    /// Keep the first row written.
+    #[default]
+    Ignore,
+    /// Overwrite the enrichable columns (mints, amounts, block time) with the new
+    /// values. NULLs and the parsers' stand-ins for an unknown value (`''` or
+    /// `'unknown'` mints, a 0 received amount) never overwrite a known value.
+    Update,
+}
+
+impl FromStr for UpsertMode {
+    type Err = String;
+
+    // `Ok` is anyhow's in this module, so build the result from an Option
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "ignore" => Some(Self::Ignore),
+            "update" => Some(Self::Update),
+            _ => None,
+        }
+        .ok_or_else(|| format!("unknown upsert mode `{}`", value))
+    }
+}
+
 pub struct PostgresRepository {
     pool: PgPool,
+    copy_threshold: usize,
+    upsert_mode: UpsertMode,
+    statements: InsertStatements,
+    /// Park batches in `pending_events` until `finalize_slot` promotes them.
+    two_phase: bool,
 }
 
 impl PostgresRepository {
     pub async fn new(url: &str) -> Result<Self> {
+        Self::connect(url, DEFAULT_MAX_CONNECTIONS, DEFAULT_ACQUIRE_TIMEOUT).await
+    }
+
+    pub async fn connect(url: &str, max_connections: u32, acquire_timeout: Duration) -> Result<Self> {
         let pool = PgPoolOptions::new()
-            .max_connections(5)
+            .max_connections(max_connections)
+            .acquire_timeout(acquire_timeout)
             .connect(url)
             .await?;
-        Ok(Self { pool })
+
+        // Schema lives in ./migrations and is embedded at compile time; already-applied
+        // versions are tracked in _sqlx_migrations so this is a no-op on a current database
+        sqlx::migrate!("./migrations").run(&pool).await?;
+        tracing::info!("Database schema up to date");
+
+        Ok(Self {
+            pool,
+            copy_threshold: DEFAULT_COPY_THRESHOLD,
+            upsert_mode: UpsertMode::default(),
+            statements: InsertStatements::new(UpsertMode::default()),
+            two_phase: false,
+        })
+    }
+
+    pub fn with_copy_threshold(mut self, copy_threshold: usize) -> Self {
+        self.copy_threshold = copy_threshold;
+        self
+    }
+
+    pub fn with_upsert_mode(mut self, upsert_mode: UpsertMode) -> Self {
+        self.upsert_mode = upsert_mode;
+        self.statements = InsertStatements::new(upsert_mode);
+        self
+    }
+
+    /// Two-phase persistence: `save_batch` writes to the pending table, and only a
+    /// finalized slot moves events into the event tables.
+    pub fn with_two_phase(mut self, two_phase: bool) -> Self {
+        self.two_phase = two_phase;
+        self
+    }
+
+    fn on_conflict(&self, key: &ConflictKey) -> String {
+        key.clause(self.upsert_mode)
+    }
+
+    /// Maximum number of connections in the pool, used to size the writer pool.
+    pub fn max_connections(&self) -> u32 {
+        self.pool.options().get_max_connections()
+    }
+
+    pub fn pool_stats(&self) -> PoolStats {
+        PoolStats { size: self.pool.size(), idle: self.pool.num_idle() }
     }
 }
 
-#[async_trait]
-impl TransactionRepository for PostgresRepository {
-    async fn get_state(&self) -> Result<IndexerState> {
-        let row = sqlx::query(
-            "SELECT last_slot, last_block_hash FROM indexer_state WHERE id = 'main_indexer'"
-        )
-        .fetch_one(&self.pool)
+/// Surface pool exhaustion as `AppError::PoolExhausted` so callers can tell a
+/// saturated pool from a failing query.
+fn pool_error(e: sqlx::Error) -> anyhow::Error {
+    match e {
+        sqlx::Error::PoolTimedOut => AppError::PoolExhausted.into(),
+        other => other.into(),
+    }
+}
+
+fn to_timestamptz(block_time: Option<i64>) -> Option<DateTime<Utc>> {
+    block_time.and_then(|t| DateTime::from_timestamp(t, 0))
+}
+
+// ─── Read API ───────────────────────────────────────────────────────────────
+
+const RAYDIUM_COLUMNS: &str = "signature, amm_pool, sender, amount_in, min_amount_out, amount_received, \
+    mint_source, mint_destination, slot, instruction_index, inner_index, block_time, pool_type, cpi_depth";
+const JUPITER_COLUMNS: &str = "signature, slot, block_time, signer, amm_pool, mint_in, mint_out, amount_in, \
+    amount_out, slippage_bps, platform_fee_bps, route_plan, instruction_index, inner_index, cpi_depth";
+const PUMP_FUN_COLUMNS: &str = "signature, slot, block_time, mint, is_buy, user_address, token_amount, \
+    sol_amount, instruction_index, inner_index, cpi_depth";
+const PUMP_AMM_COLUMNS: &str = "signature, slot, block_time, pool, user_address, base_mint, quote_mint, is_buy, \
+    base_amount, quote_amount, pool_base_reserve, pool_quote_reserve, instruction_index, inner_index, cpi_depth";
+const TRANSFER_COLUMNS: &str = "signature, sender, receiver, mint, amount, slot, instruction_index, \
+    inner_index, block_time";
+
+fn get_u64(row: &PgRow, col: &str) -> Result<u64> {
+    Ok(row.try_get::<BigDecimal, _>(col)?.to_u64().unwrap_or(0))
+}
+
+fn get_block_time(row: &PgRow) -> Result<Option<i64>> {
+    Ok(row.try_get::<Option<DateTime<Utc>>, _>("block_time")?.map(|t| t.timestamp()))
+}
+
+fn get_inner_index(row: &PgRow) -> Result<Option<u32>> {
+    Ok(row.try_get::<Option<i32>, _>("inner_index")?.map(|i| i as u32))
+}
+
+fn get_signature(row: &PgRow) -> Result<Signature58> {
+    Ok(Signature58::try_from(row.try_get::<String, _>("signature")?)?)
+}
+
+fn get_address(row: &PgRow, column: &str) -> Result<Pubkey58> {
+    Ok(Pubkey58::try_from(row.try_get::<String, _>(column)?)?)
+}
+
+fn get_cpi_depth(row: &PgRow) -> Result<u32> {
+    Ok(row.try_get::<i32, _>("cpi_depth")? as u32)
+}
+
+fn raydium_from_row(row: &PgRow) -> Result<RaydiumSwapEvent> {
+    let pool_type: String = row.try_get("pool_type")?;
+    Ok(RaydiumSwapEvent {
+        pool_type: pool_type.parse().unwrap_or(RaydiumPoolType::AmmV4),
+        amm_pool: row.try_get("amm_pool")?,
+        signer: row.try_get("sender")?,
+        amount_in: get_u64(row, "amount_in")?,
+        min_amount_out: get_u64(row, "min_amount_out")?,
+        amount_received: get_u64(row, "amount_received")?,
+        mint_source: row.try_get("mint_source")?,
+        mint_destination: row.try_get("mint_destination")?,
+        slot: row.try_get::<i64, _>("slot")? as u64,
+        block_time: get_block_time(row)?,
+        signature: get_signature(row)?,
+        instruction_index: row.try_get::<i32, _>("instruction_index")? as u32,
+        inner_index: get_inner_index(row)?,
+        cpi_depth: get_cpi_depth(row)?,
+    })
+}
+
+fn jupiter_from_row(row: &PgRow) -> Result<JupiterSwapEvent> {
+    let route_plan: Option<serde_json::Value> = row.try_get("route_plan")?;
+    let route_plan: Vec<RouteStep> = match route_plan {
+        Some(v) => serde_json::from_value(v)?,
+        None => Vec::new(),
+    };
+
+    Ok(JupiterSwapEvent {
+        signature: get_signature(row)?,
+        instruction_index: row.try_get::<i32, _>("instruction_index")? as u32,
+        inner_index: get_inner_index(row)?,
+        cpi_depth: get_cpi_depth(row)?,
+        slot: row.try_get::<i64, _>("slot")? as u64,
+        block_time: get_block_time(row)?,
+        signer: row.try_get("signer")?,
+        amm_pool: row.try_get::<Option<String>, _>("amm_pool")?.unwrap_or_default(),
+        mint_in: row.try_get("mint_in")?,
+        mint_out: row.try_get("mint_out")?,
+        amount_in: get_u64(row, "amount_in")?,
+        amount_out: get_u64(row, "amount_out")?,
+        slippage_bps: row.try_get::<Option<i32>, _>("slippage_bps")?.unwrap_or(0) as u16,
+        platform_fee_bps: row.try_get::<Option<i32>, _>("platform_fee_bps")?.unwrap_or(0) as u8,
+        route_plan,
+    })
+}
+
+fn pump_fun_from_row(row: &PgRow) -> Result<PumpFunTrade> {
+    let block_time = get_block_time(row)?;
+    Ok(PumpFunTrade {
+        signature: get_signature(row)?,
+        instruction_index: row.try_get::<i32, _>("instruction_index")? as u32,
+        inner_index: get_inner_index(row)?,
+        cpi_depth: get_cpi_depth(row)?,
+        slot: row.try_get::<i64, _>("slot")? as u64,
+        mint: row.try_get("mint")?,
+        direction: row.try_get::<bool, _>("is_buy")?.into(),
+        user: row.try_get("user_address")?,
+        timestamp: block_time,
+        token_amount: get_u64(row, "token_amount")?,
+        sol_amount: get_u64(row, "sol_amount")?,
+        block_time,
+    })
+}
+
+fn pump_amm_from_row(row: &PgRow) -> Result<PumpAmmSwapEvent> {
+    let reserve = |col: &str| -> Result<Option<u64>> {
+        Ok(row.try_get::<Option<BigDecimal>, _>(col)?.and_then(|v| v.to_u64()))
+    };
+    Ok(PumpAmmSwapEvent {
+        signature: get_signature(row)?,
+        instruction_index: row.try_get::<i32, _>("instruction_index")? as u32,
+        inner_index: get_inner_index(row)?,
+        cpi_depth: get_cpi_depth(row)?,
+        slot: row.try_get::<i64, _>("slot")? as u64,
+        block_time: get_block_time(row)?,
+        pool: row.try_get("pool")?,
+        user: row.try_get("user_address")?,
+        base_mint: row.try_get("base_mint")?,
+        quote_mint: row.try_get("quote_mint")?,
+        direction: row.try_get::<bool, _>("is_buy")?.into(),
+        base_amount: get_u64(row, "base_amount")?,
+        quote_amount: get_u64(row, "quote_amount")?,
+        pool_base_reserve: reserve("pool_base_reserve")?,
+        pool_quote_reserve: reserve("pool_quote_reserve")?,
+    })
+}
+
+fn transfer_from_row(row: &PgRow) -> Result<TokenTransfer> {
+    let mint: String = row.try_get("mint")?;
+    Ok(TokenTransfer {
+        from: get_address(row, "sender")?,
+        to: get_address(row, "receiver")?,
+        slot: row.try_get::<i64, _>("slot")? as u64,
+        block_time: get_block_time(row)?,
+        amount: get_u64(row, "amount")?,
+        signature: get_signature(row)?,
+        // Transfers without a known mint are stored as ''
+        mint: (!mint.is_empty()).then_some(mint),
+        instruction_index: row.try_get::<i32, _>("instruction_index")? as u32,
+        inner_index: get_inner_index(row)?,
+    })
+}
+
+impl PostgresRepository {
+    /// Most recent swaps across all protocols, newest slot first.
+    pub async fn recent_swaps(&self, limit: i64) -> Result<Vec<SwapEvent>> {
+        let mut swaps = self
+            .fetch_swaps("ORDER BY slot DESC, instruction_index DESC LIMIT $1", &[limit])
+            .await?;
+        swaps.sort_by(|a, b| b.slot().cmp(&a.slot()));
+        swaps.truncate(limit.max(0) as usize);
+        Ok(swaps)
+    }
+
+    /// Swaps across all protocols with `start <= slot <= end`, oldest slot first.
+    pub async fn swaps_in_slot_range(&self, start: u64, end: u64) -> Result<Vec<SwapEvent>> {
+        let mut swaps = self
+            .fetch_swaps("WHERE slot BETWEEN $1 AND $2", &[start as i64, end as i64])
+            .await?;
+        swaps.sort_by_key(|s| s.slot());
+        Ok(swaps)
+    }
+
+    /// Transfers where `address` is the sender or receiver, newest slot first.
+    pub async fn transfers_for_address(&self, address: &str, limit: i64) -> Result<Vec<TokenTransfer>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM token_transfers WHERE sender = $1 OR receiver = $1 ORDER BY slot DESC LIMIT $2",
+            TRANSFER_COLUMNS
+        ))
+        .bind(address)
+        .bind(limit)
+        .fetch_all(&self.pool)
         .await?;
 
-        Ok(IndexerState {
-            last_slot: row.try_get::<i64, _>("last_slot")? as u64,
-            last_block_hash: row.try_get("last_block_hash")?,
-        })
+        rows.iter().map(transfer_from_row).collect()
     }
 
-    async fn get_last_slot(&self) -> Result<u64> {
-        let row = sqlx::query(
-            "SELECT last_slot FROM indexer_state WHERE id = 'main_indexer'"
-        )
-        .fetch_one(&self.pool)
+    /// PumpSwap AMM swaps on `pool`, newest slot first.
+    pub async fn pump_amm_swaps_for_pool(&self, pool: &str, limit: i64) -> Result<Vec<PumpAmmSwapEvent>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM pump_amm_swaps WHERE pool = $1 ORDER BY slot DESC LIMIT $2",
+            PUMP_AMM_COLUMNS
+        ))
+        .bind(pool)
+        .bind(limit)
+        .fetch_all(&self.pool)
         .await?;
 
-        Ok(row.try_get::<i64, _>("last_slot")? as u64)
+        rows.iter().map(pump_amm_from_row).collect()
     }
 
-    async fn save_batch(&self, events: &[TransactionEvent], current_slot: u64) -> Result<()> {
-        let mut txn = self.pool.begin().await?;
+    /// Run the same filter against every swap table; `binds` fill $1, $2, ... in order.
+    async fn fetch_swaps(&self, filter: &str, binds: &[i64]) -> Result<Vec<SwapEvent>> {
+        let mut swaps = Vec::new();
 
-        let mut transfers = Vec::new();
-        let mut raydium_swaps = Vec::new();
-        let mut jupiter_swaps = Vec::new();
-        let mut pump_trades = Vec::new();
+        for (table, columns) in [
+            ("raydium_swaps", RAYDIUM_COLUMNS),
+            ("jupiter_swaps", JUPITER_COLUMNS),
+            ("pump_fun_trades", PUMP_FUN_COLUMNS),
+            ("pump_amm_swaps", PUMP_AMM_COLUMNS),
+        ] {
+            let sql = format!("SELECT {} FROM {} {}", columns, table, filter);
+            let mut query = sqlx::query(&sql);
+            for b in binds {
+                query = query.bind(*b);
+            }
+            let rows = query.fetch_all(&self.pool).await?;
 
-        for ev in events {
-            match ev {
-                TransactionEvent::TokenTransfer(t) => transfers.push(t),
-                TransactionEvent::RaydiumSwap(s) => raydium_swaps.push(s),
-                TransactionEvent::JupiterSwap(s) => jupiter_swaps.push(s),
-                TransactionEvent::PumpFunTrade(t) => pump_trades.push(t),
+            for row in &rows {
+                swaps.push(match table {
+                    "raydium_swaps" => SwapEvent::Raydium(raydium_from_row(row)?),
+                    "jupiter_swaps" => SwapEvent::Jupiter(jupiter_from_row(row)?),
+                    "pump_amm_swaps" => SwapEvent::PumpAmm(pump_amm_from_row(row)?),
+                    _ => SwapEvent::PumpFun(pump_fun_from_row(row)?),
+                });
             }
         }
 
-        if !transfers.is_empty() {
-            let slots: Vec<i64>          = transfers.iter().map(|t| t.slot as i64).collect();
-            let amounts: Vec<BigDecimal> = transfers.iter().map(|t| BigDecimal::from(t.amount)).collect();
-            let sigs: Vec<String>        = transfers.iter().map(|t| t.signature.clone()).collect();
-            let senders: Vec<String>     = transfers.iter().map(|t| t.from.clone()).collect();
-            let receivers: Vec<String>   = transfers.iter().map(|t| t.to.clone()).collect();
-            let mints: Vec<String>       = transfers.iter().map(|t| t.mint.as_deref().unwrap_or("").to_string()).collect();
+        Ok(swaps)
+    }
+}
 
-            sqlx::query(
-                r#"INSERT INTO token_transfers (signature, sender, receiver, mint, amount, slot)
-                   SELECT * FROM UNNEST($1::text[], $2::text[], $3::text[], $4::text[], $5::numeric[], $6::bigint[])
-                   ON CONFLICT DO NOTHING"#,
-            )
-            .bind(&sigs)
-            .bind(&senders)
-            .bind(&receivers)
-            .bind(&mints)
-            .bind(&amounts)
-            .bind(&slots)
-            .execute(&mut *txn)
-            .await?;
+// ─── Mint backfill ──────────────────────────────────────────────────────────
+
+/// Settings for [`PostgresRepository::backfill_mints`].
+#[derive(Debug, Clone, Copy)]
+pub struct MintBackfillConfig {
+    /// Rows read and repaired per round, each round making one resolver call.
+    pub batch_size: usize,
+    /// Cap on resolver calls per second.
+    pub requests_per_sec: u32,
+}
+
+impl Default for MintBackfillConfig {
+    fn default() -> Self {
+        Self { batch_size: 100, requests_per_sec: 5 }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MintBackfillReport {
+    pub scanned: u64,
+    pub updated: u64,
+}
+
+/// Position in a table keyed on `(signature, instruction_index, COALESCE(inner_index, -1))`.
+type RowKey = (String, i32, i32);
+
+/// Sorts before every real row.
+fn first_row_key() -> RowKey {
+    (String::new(), i32::MIN, i32::MIN)
+}
+
+fn row_key(row: &PgRow) -> Result<RowKey> {
+    Ok((row.try_get("signature")?, row.try_get("instruction_index")?, row.try_get("inner_key")?))
+}
+
+impl PostgresRepository {
+    /// Repair rows written before their mint was known: token transfers with no
+    /// mint and Raydium swaps with an `"unknown"` side. Each table is walked in
+    /// key order, one batch per resolver call at a capped rate, and the position
+    /// is saved with every batch so an interrupted run resumes where it stopped.
+    /// Rows whose accounts no longer exist, or swaps with both sides unknown,
+    /// are left as they are.
+    pub async fn backfill_mints(&self, resolver: &dyn MintResolver, config: MintBackfillConfig) -> Result<MintBackfillReport> {
+        let mut limiter = RateLimiter::new(config.requests_per_sec);
+        let mut report = MintBackfillReport::default();
+        self.backfill_transfer_mints(resolver, config.batch_size, &mut limiter, &mut report).await?;
+        self.backfill_raydium_mints(resolver, config.batch_size, &mut limiter, &mut report).await?;
+        tracing::info!("Mint backfill done: {} rows scanned, {} updated", report.scanned, report.updated);
+        Ok(report)
+    }
+
+    async fn backfill_cursor(&self, table: &str) -> Result<RowKey> {
+        let row = sqlx::query("SELECT signature, instruction_index, inner_index AS inner_key FROM mint_backfill_cursors WHERE table_name = $1")
+            .bind(table)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(pool_error)?;
+        match row {
+            Some(row) => {
+                let key = row_key(&row)?;
+                tracing::info!("Resuming {} mint backfill after {}", table, key.0);
+                Ok(key)
+            }
+            None => Ok(first_row_key()),
         }
+    }
 
-        if !raydium_swaps.is_empty() {
-            let sigs:      Vec<String>     = raydium_swaps.iter().map(|s| s.signature.clone()).collect();
-            let pools:     Vec<String>     = raydium_swaps.iter().map(|s| s.amm_pool.clone()).collect();
-            let users:     Vec<String>     = raydium_swaps.iter().map(|s| s.signer.clone()).collect();
-            let amts_in:   Vec<BigDecimal> = raydium_swaps.iter().map(|s| BigDecimal::from(s.amount_in)).collect();
-            let min_outs:  Vec<BigDecimal> = raydium_swaps.iter().map(|s| BigDecimal::from(s.min_amount_out)).collect();
-            let received:  Vec<BigDecimal> = raydium_swaps.iter().map(|s| BigDecimal::from(s.amount_received)).collect();
-            let mints_src: Vec<String>     = raydium_swaps.iter().map(|s| s.mint_source.clone()).collect();
-            let mints_dst: Vec<String>     = raydium_swaps.iter().map(|s| s.mint_destination.clone()).collect();
-            let slots:     Vec<i64>        = raydium_swaps.iter().map(|s| s.slot as i64).collect();
+    async fn save_backfill_cursor(conn: &mut PgConnection, table: &str, key: &RowKey) -> Result<()> {
+        sqlx::query(
+            r#"INSERT INTO mint_backfill_cursors (table_name, signature, instruction_index, inner_index)
+               VALUES ($1, $2, $3, $4)
+               ON CONFLICT (table_name) DO UPDATE SET
+                   signature = EXCLUDED.signature, instruction_index = EXCLUDED.instruction_index,
+                   inner_index = EXCLUDED.inner_index, updated_at = CURRENT_TIMESTAMP"#,
+        )
+        .bind(table)
+        .bind(&key.0)
+        .bind(key.1)
+        .bind(key.2)
+        .execute(conn)
+        .await?;
+        Ok(())
+    }
 
-            sqlx::query(
-                r#"INSERT INTO raydium_swaps
-                   (signature, amm_pool, sender, amount_in, min_amount_out, amount_received, mint_source, mint_destination, slot)
-                   SELECT * FROM UNNEST($1::text[], $2::text[], $3::text[], $4::numeric[], $5::numeric[], $6::numeric[], $7::text[], $8::text[], $9::bigint[])
-                   ON CONFLICT (signature, amm_pool) DO NOTHING"#,
-            )
-            .bind(&sigs)
-            .bind(&pools)
-            .bind(&users)
-            .bind(&amts_in)
-            .bind(&min_outs)
-            .bind(&received)
-            .bind(&mints_src)
-            .bind(&mints_dst)
-            .bind(&slots)
-            .execute(&mut *txn)
+    async fn clear_backfill_cursor(&self, table: &str) -> Result<()> {
+        sqlx::query("DELETE FROM mint_backfill_cursors WHERE table_name = $1")
+            .bind(table)
+            .execute(&self.pool)
             .await?;
+        Ok(())
+    }
 
-            tracing::info!("Saved {} Raydium swaps", raydium_swaps.len());
-        }
-
-        if !jupiter_swaps.is_empty() {
-            let sigs:      Vec<String>     = jupiter_swaps.iter().map(|e| e.signature.clone()).collect();
-            let slots_:    Vec<i64>        = jupiter_swaps.iter().map(|e| e.slot as i64).collect();
-            let times:     Vec<chrono::NaiveDateTime> = jupiter_swaps.iter()
-                .map(|e| chrono::DateTime::from_timestamp(e.block_time, 0).unwrap().naive_utc())
-                .collect();
-            let signers:   Vec<String>     = jupiter_swaps.iter().map(|e| e.signer.clone()).collect();
-            let pools:     Vec<String>     = jupiter_swaps.iter().map(|e| e.amm_pool.clone()).collect();
-            let mints_in:  Vec<String>     = jupiter_swaps.iter().map(|e| e.mint_in.clone()).collect();
-            let mints_out: Vec<String>     = jupiter_swaps.iter().map(|e| e.mint_out.clone()).collect();
-            let amts_in:   Vec<BigDecimal> = jupiter_swaps.iter().map(|e| BigDecimal::from(e.amount_in)).collect();
-            let amts_out:  Vec<BigDecimal> = jupiter_swaps.iter().map(|e| BigDecimal::from(e.amount_out)).collect();
-            let slippages: Vec<i32>        = jupiter_swaps.iter().map(|e| e.slippage_bps as i32).collect();
-            let fees:      Vec<i32>        = jupiter_swaps.iter().map(|e| e.platform_fee_bps as i32).collect();
-            let routes:    Vec<serde_json::Value> = jupiter_swaps.iter()
-                .map(|e| serde_json::to_value(&e.route_plan).unwrap())
-                .collect();
+    /// Rows of `table` matching `filter` after `cursor`, in key order.
+    async fn backfill_rows(&self, table: &str, columns: &str, filter: &str, cursor: &RowKey, limit: usize) -> Result<Vec<PgRow>> {
+        let sql = format!(
+            "SELECT signature, instruction_index, COALESCE(inner_index, -1) AS inner_key, {columns} FROM {table} \
+             WHERE {filter} AND (signature, instruction_index, COALESCE(inner_index, -1)) > ($1, $2, $3) \
+             ORDER BY signature, instruction_index, COALESCE(inner_index, -1) LIMIT $4"
+        );
+        Ok(sqlx::query(&sql)
+            .bind(&cursor.0)
+            .bind(cursor.1)
+            .bind(cursor.2)
+            .bind(limit as i64)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(pool_error)?)
+    }
 
-            sqlx::query(
+    async fn backfill_transfer_mints(
+        &self,
+        resolver: &dyn MintResolver,
+        batch_size: usize,
+        limiter: &mut RateLimiter,
+        report: &mut MintBackfillReport,
+    ) -> Result<()> {
+        const TABLE: &str = "token_transfers";
+        let mut cursor = self.backfill_cursor(TABLE).await?;
+        loop {
+            let rows = self.backfill_rows(TABLE, "sender, receiver", "mint IN ('', 'unknown')", &cursor, batch_size).await?;
+            let Some(last) = rows.last() else { break };
+            let next = row_key(last)?;
+
+            // Both ends of a transfer are token accounts of the same mint
+            let mut accounts = BTreeSet::new();
+            for row in &rows {
+                accounts.insert(row.try_get::<String, _>("sender")?);
+                accounts.insert(row.try_get::<String, _>("receiver")?);
+            }
+            limiter.acquire().await;
+            let mints = resolver.account_mints(&accounts.into_iter().collect::<Vec<_>>()).await?;
+
+            let mut txn = self.pool.begin().await.map_err(pool_error)?;
+            for row in &rows {
+                let (sender, receiver): (String, String) = (row.try_get("sender")?, row.try_get("receiver")?);
+                let mint = [sender, receiver].iter().find_map(|account| match mints.get(account) {
+                    Some(AccountMints::TokenAccount(mint)) => Some(mint.clone()),
+                    _ => None,
+                });
+                let Some(mint) = mint else { continue };
+                let (signature, ix, inner) = row_key(row)?;
+                report.updated += sqlx::query(
+                    "UPDATE token_transfers SET mint = $1 WHERE signature = $2 AND instruction_index = $3 AND COALESCE(inner_index, -1) = $4",
+                )
+                .bind(mint)
+                .bind(signature)
+                .bind(ix)
+                .bind(inner)
+                .execute(&mut *txn)
+                .await?
+                .rows_affected();
+            }
+            Self::save_backfill_cursor(&mut *txn, TABLE, &next).await?;
+            txn.commit().await?;
+
+            report.scanned += rows.len() as u64;
+            cursor = next;
+        }
+        self.clear_backfill_cursor(TABLE).await
+    }
+
+    async fn backfill_raydium_mints(
+        &self,
+        resolver: &dyn MintResolver,
+        batch_size: usize,
+        limiter: &mut RateLimiter,
+        report: &mut MintBackfillReport,
+    ) -> Result<()> {
+        const TABLE: &str = "raydium_swaps";
+        let mut cursor = self.backfill_cursor(TABLE).await?;
+        loop {
+            let rows = self.backfill_rows(
+                TABLE,
+                "amm_pool, mint_source, mint_destination",
+                "'unknown' IN (mint_source, mint_destination)",
+                &cursor,
+                batch_size,
+            ).await?;
+            let Some(last) = rows.last() else { break };
+            let next = row_key(last)?;
+
+            let pools: BTreeSet<String> = rows.iter().map(|row| row.try_get("amm_pool")).collect::<Result<_, _>>()?;
+            limiter.acquire().await;
+            let mints = resolver.account_mints(&pools.into_iter().collect::<Vec<_>>()).await?;
+
+            let mut txn = self.pool.begin().await.map_err(pool_error)?;
+            for row in &rows {
+                let pool: String = row.try_get("amm_pool")?;
+                let (source, destination): (String, String) = (row.try_get("mint_source")?, row.try_get("mint_destination")?);
+                let Some(pool_mints) = mints.get(&pool) else { continue };
+                // The known side tells which of the pool's mints the unknown one is
+                let repaired = match (source.as_str(), destination.as_str()) {
+                    ("unknown", "unknown") => None,
+                    ("unknown", known) => pool_mints.counterpart(known).map(|other| (other.to_string(), known.to_string())),
+                    (known, _) => pool_mints.counterpart(known).map(|other| (known.to_string(), other.to_string())),
+                };
+                let Some((source, destination)) = repaired else { continue };
+                let (signature, ix, inner) = row_key(row)?;
+                report.updated += sqlx::query(
+                    "UPDATE raydium_swaps SET mint_source = $1, mint_destination = $2 \
+                     WHERE signature = $3 AND instruction_index = $4 AND COALESCE(inner_index, -1) = $5",
+                )
+                .bind(source)
+                .bind(destination)
+                .bind(signature)
+                .bind(ix)
+                .bind(inner)
+                .execute(&mut *txn)
+                .await?
+                .rows_affected();
+            }
+            Self::save_backfill_cursor(&mut *txn, TABLE, &next).await?;
+            txn.commit().await?;
+
+            report.scanned += rows.len() as u64;
+            cursor = next;
+        }
+        self.clear_backfill_cursor(TABLE).await
+    }
+}
+
+// ─── Conflict handling ──────────────────────────────────────────────────────
+
+/// A table's unique key and the columns a later write is allowed to fill in.
+struct ConflictKey {
+    table: &'static str,
+    target: &'static str,
+    enrichable: &'static [&'static str],
+}
+
+impl ConflictKey {
+    fn clause(&self, mode: UpsertMode) -> String {
+        match mode {
+            UpsertMode::Ignore => format!("{} DO NOTHING", self.target),
+            UpsertMode::Update => {
+                let sets: Vec<String> = self.enrichable.iter()
+                    .map(|c| match unknown_values(c) {
+                        Some(unknown) => format!(
+                            "{c} = CASE WHEN EXCLUDED.{c} IS NULL OR EXCLUDED.{c} IN {unknown} THEN {t}.{c} ELSE EXCLUDED.{c} END",
+                            t = self.table
+                        ),
+                        None => format!("{c} = COALESCE(EXCLUDED.{c}, {}.{c})", self.table),
+                    })
+                    .collect();
+                format!("{} DO UPDATE SET {}", self.target, sets.join(", "))
+            }
+        }
+    }
+}
+
+/// What parsers write into a NOT NULL column when they don't know its value.
+fn unknown_values(column: &str) -> Option<&'static str> {
+    match column {
+        "mint" | "mint_source" | "mint_destination" | "mint_in" | "mint_out" => Some("('', 'unknown')"),
+        // Raydium's output transfer was not found among the inner instructions
+        "amount_received" => Some("(0)"),
+        _ => None,
+    }
+}
+
+const TRANSFERS_KEY: ConflictKey = ConflictKey {
+    table: "token_transfers",
+    target: "(signature, instruction_index, COALESCE(inner_index, -1))",
+    enrichable: &["mint", "amount", "block_time"],
+};
+const RAYDIUM_KEY: ConflictKey = ConflictKey {
+    table: "raydium_swaps",
+    target: "(signature, instruction_index, COALESCE(inner_index, -1))",
+    enrichable: &["amount_in", "amount_received", "mint_source", "mint_destination", "block_time"],
+};
+const JUPITER_KEY: ConflictKey = ConflictKey {
+    table: "jupiter_swaps",
+    target: "(signature, instruction_index)",
+    enrichable: &["amm_pool", "mint_in", "mint_out", "amount_in", "amount_out", "route_plan", "block_time"],
+};
+const PUMP_FUN_KEY: ConflictKey = ConflictKey {
+    table: "pump_fun_trades",
+    target: "(signature, instruction_index)",
+    enrichable: &["sol_amount", "block_time"],
+};
+const PUMP_AMM_KEY: ConflictKey = ConflictKey {
+    table: "pump_amm_swaps",
+    target: "(signature, instruction_index, COALESCE(inner_index, -1))",
+    enrichable: &["quote_amount", "pool_base_reserve", "pool_quote_reserve", "block_time"],
+};
+const COMPUTE_BUDGET_KEY: ConflictKey = ConflictKey {
+    table: "compute_budgets",
+    target: "(signature)",
+    enrichable: &["unit_limit", "unit_price_micro_lamports", "block_time"],
+};
+const SOL_TRANSFERS_KEY: ConflictKey = ConflictKey {
+    table: "sol_transfers",
+    target: "(signature, instruction_index, COALESCE(inner_index, -1))",
+    enrichable: &["block_time"],
+};
+
+/// Parent rows: one per transaction in the batch, with the event kinds it produced
+/// merged into whatever earlier batches recorded. Always upserts, whatever the
+/// upsert mode, since a later batch can add kinds.
+const UPSERT_TRANSACTIONS: &str = r#"INSERT INTO transactions (signature, slot, block_time, event_kinds)
+    SELECT signature, slot, block_time, string_to_array(kinds, ',')
+    FROM UNNEST($1::text[], $2::bigint[], $3::timestamptz[], $4::text[]) AS t(signature, slot, block_time, kinds)
+    ON CONFLICT (signature) DO UPDATE SET
+        block_time = COALESCE(transactions.block_time, EXCLUDED.block_time),
+        event_kinds = ARRAY(SELECT DISTINCT k FROM unnest(transactions.event_kinds || EXCLUDED.event_kinds) AS k ORDER BY k)"#;
+
+/// Fee payer, success and matching parsers of transactions already stored. Creates
+/// the parent row when it is not there yet, as under two-phase persistence, where
+/// a rollback clears it with the pending events.
+const UPSERT_TRANSACTION_DETAILS: &str = r#"INSERT INTO transactions (signature, slot, block_time, fee_payer, success, parsers)
+    SELECT signature, slot, block_time, NULLIF(fee_payer, ''), success, string_to_array(parsers, ',')
+    FROM UNNEST($1::text[], $2::bigint[], $3::timestamptz[], $4::text[], $5::bool[], $6::text[])
+        AS t(signature, slot, block_time, fee_payer, success, parsers)
+    ON CONFLICT (signature) DO UPDATE SET
+        block_time = COALESCE(transactions.block_time, EXCLUDED.block_time),
+        fee_payer = COALESCE(EXCLUDED.fee_payer, transactions.fee_payer),
+        success = EXCLUDED.success,
+        parsers = ARRAY(SELECT DISTINCT p FROM unnest(transactions.parsers || EXCLUDED.parsers) AS p ORDER BY p)"#;
+
+/// Slot, block time and event kinds of each transaction in `events`, by signature.
+fn transaction_rows(events: &[TransactionEvent]) -> BTreeMap<&str, (u64, Option<i64>, BTreeSet<&'static str>)> {
+    let mut rows: BTreeMap<&str, (u64, Option<i64>, BTreeSet<&'static str>)> = BTreeMap::new();
+    for ev in events {
+        let row = rows.entry(ev.signature()).or_insert_with(|| (ev.slot(), None, BTreeSet::new()));
+        row.1 = row.1.or(ev.block_time());
+        row.2.insert(ev.kind());
+    }
+    rows
+}
+
+/// Tables holding one row per parsed event, each with a `slot` column. Each
+/// references its transaction in `transactions`, which is cleared after them.
+const EVENT_TABLES: [&str; 7] = [
+    "token_transfers",
+    "raydium_swaps",
+    "jupiter_swaps",
+    "pump_fun_trades",
+    "pump_amm_swaps",
+    "compute_budgets",
+    "sol_transfers",
+];
+
+/// UNNEST insert statements, rendered once for the repository's upsert mode.
+/// sqlx prepares each distinct SQL string once per connection and caches it, so
+/// keeping the text stable means every batch after the first skips parse/plan.
+struct InsertStatements {
+    transfers: String,
+    raydium: String,
+    jupiter: String,
+    pump_fun: String,
+    pump_amm: String,
+    compute_budgets: String,
+    sol_transfers: String,
+}
+
+impl InsertStatements {
+    fn new(mode: UpsertMode) -> Self {
+        Self {
+            transfers: format!(
+                r#"INSERT INTO token_transfers (signature, sender, receiver, mint, amount, slot, instruction_index, inner_index, block_time)
+                   SELECT * FROM UNNEST($1::text[], $2::text[], $3::text[], $4::text[], $5::numeric[], $6::bigint[], $7::int[], $8::int[], $9::timestamptz[])
+                   ON CONFLICT {}"#,
+                TRANSFERS_KEY.clause(mode),
+            ),
+            raydium: format!(
+                r#"INSERT INTO raydium_swaps
+                   (signature, amm_pool, sender, amount_in, min_amount_out, amount_received, mint_source, mint_destination, slot, instruction_index, inner_index, block_time, pool_type, cpi_depth)
+                   SELECT * FROM UNNEST($1::text[], $2::text[], $3::text[], $4::numeric[], $5::numeric[], $6::numeric[], $7::text[], $8::text[], $9::bigint[], $10::int[], $11::int[], $12::timestamptz[], $13::text[], $14::int[])
+                   ON CONFLICT {}"#,
+                RAYDIUM_KEY.clause(mode),
+            ),
+            jupiter: format!(
                 r#"INSERT INTO jupiter_swaps
                    (signature, slot, block_time, signer, amm_pool, mint_in, mint_out,
-                    amount_in, amount_out, slippage_bps, platform_fee_bps, route_plan)
+                    amount_in, amount_out, slippage_bps, platform_fee_bps, route_plan, instruction_index, inner_index, cpi_depth)
                    SELECT * FROM UNNEST(
-                       $1::text[], $2::bigint[], $3::timestamp[], $4::text[], $5::text[],
+                       $1::text[], $2::bigint[], $3::timestamptz[], $4::text[], $5::text[],
                        $6::text[], $7::text[], $8::numeric[], $9::numeric[],
-                       $10::int[], $11::int[], $12::jsonb[]
+                       $10::int[], $11::int[], $12::jsonb[], $13::int[], $14::int[], $15::int[]
                    )
-                   ON CONFLICT (signature) DO NOTHING"#,
-            )
+                   ON CONFLICT {}"#,
+                JUPITER_KEY.clause(mode),
+            ),
+            pump_fun: format!(
+                r#"INSERT INTO pump_fun_trades
+                   (signature, slot, block_time, mint, is_buy, user_address, token_amount, sol_amount, instruction_index, inner_index, cpi_depth)
+                   SELECT * FROM UNNEST($1::text[], $2::bigint[], $3::timestamptz[], $4::text[], $5::boolean[], $6::text[], $7::numeric[], $8::numeric[], $9::int[], $10::int[], $11::int[])
+                   ON CONFLICT {}"#,
+                PUMP_FUN_KEY.clause(mode),
+            ),
+            pump_amm: format!(
+                r#"INSERT INTO pump_amm_swaps ({})
+                   SELECT * FROM UNNEST(
+                       $1::text[], $2::bigint[], $3::timestamptz[], $4::text[], $5::text[], $6::text[], $7::text[],
+                       $8::boolean[], $9::numeric[], $10::numeric[], $11::numeric[], $12::numeric[], $13::int[], $14::int[], $15::int[]
+                   )
+                   ON CONFLICT {}"#,
+                PUMP_AMM_COLUMNS,
+                PUMP_AMM_KEY.clause(mode),
+            ),
+            compute_budgets: format!(
+                r#"INSERT INTO compute_budgets
+                   (signature, slot, block_time, instruction_index, unit_limit, unit_price_micro_lamports)
+                   SELECT * FROM UNNEST($1::text[], $2::bigint[], $3::timestamptz[], $4::int[], $5::bigint[], $6::numeric[])
+                   ON CONFLICT {}"#,
+                COMPUTE_BUDGET_KEY.clause(mode),
+            ),
+            sol_transfers: format!(
+                r#"INSERT INTO sol_transfers
+                   (signature, slot, block_time, sender, receiver, lamports, instruction_index, inner_index)
+                   SELECT * FROM UNNEST($1::text[], $2::bigint[], $3::timestamptz[], $4::text[], $5::text[], $6::numeric[], $7::int[], $8::int[])
+                   ON CONFLICT {}"#,
+                SOL_TRANSFERS_KEY.clause(mode),
+            ),
+        }
+    }
+}
+
+// ─── COPY path ──────────────────────────────────────────────────────────────
+//
+// COPY cannot resolve conflicts, so rows are streamed into a transaction-scoped
+// temp table and then moved across with the same ON CONFLICT clause the UNNEST
+// path uses. In `UpsertMode::Ignore` duplicates inside the batch itself are
+// dropped too; `Update` expects the batch to be deduplicated already.
+
+type CsvRow = Vec<Option<String>>;
+
+/// Append one row in Postgres CSV format: unquoted empty field is NULL, everything else is quoted.
+fn push_csv_row(out: &mut String, row: &CsvRow) {
+    for (i, field) in row.iter().enumerate() {
+        if i > 0 { out.push(','); }
+        if let Some(v) = field {
+            out.push('"');
+            out.push_str(&v.replace('"', "\"\""));
+            out.push('"');
+        }
+    }
+    out.push('\n');
+}
+
+async fn copy_rows(
+    conn: &mut PgConnection,
+    table: &str,
+    columns: &str,
+    on_conflict: &str,
+    rows: Vec<CsvRow>,
+) -> Result<u64> {
+    if rows.is_empty() {
+        return Ok(0);
+    }
+
+    let staging = format!("{}_copy", table);
+    sqlx::query(&format!(
+        "CREATE TEMP TABLE {} (LIKE {} INCLUDING DEFAULTS) ON COMMIT DROP",
+        staging, table
+    ))
+    .execute(&mut *conn)
+    .await?;
+
+    let mut csv = String::new();
+    for row in &rows {
+        push_csv_row(&mut csv, row);
+    }
+
+    let mut copy = conn
+        .copy_in_raw(&format!("COPY {} ({}) FROM STDIN WITH (FORMAT csv)", staging, columns))
+        .await?;
+    copy.send(csv.into_bytes()).await?;
+    copy.finish().await?;
+
+    let inserted = sqlx::query(&format!(
+        "INSERT INTO {table} ({columns}) SELECT {columns} FROM {staging} ON CONFLICT {on_conflict}",
+    ))
+    .execute(&mut *conn)
+    .await?
+    .rows_affected();
+
+    Ok(inserted)
+}
+
+fn opt<T: ToString>(v: T) -> Option<String> {
+    Some(v.to_string())
+}
+
+async fn copy_transfers(conn: &mut PgConnection, transfers: &[&TokenTransfer], on_conflict: &str) -> Result<u64> {
+    let rows = transfers.iter().map(|t| vec![
+        opt(&t.signature), opt(&t.from), opt(&t.to), opt(t.mint.as_deref().unwrap_or("")),
+        opt(t.amount), opt(t.slot), opt(t.instruction_index), t.inner_index.map(|i| i.to_string()),
+        to_timestamptz(t.block_time).map(|d| d.to_rfc3339()),
+    ]).collect();
+
+    copy_rows(
+        conn,
+        "token_transfers",
+        "signature, sender, receiver, mint, amount, slot, instruction_index, inner_index, block_time",
+        on_conflict,
+        rows,
+    ).await
+}
+
+async fn copy_raydium_swaps(conn: &mut PgConnection, swaps: &[&RaydiumSwapEvent], on_conflict: &str) -> Result<u64> {
+    let rows = swaps.iter().map(|s| vec![
+        opt(&s.signature), opt(&s.amm_pool), opt(&s.signer), opt(s.amount_in), opt(s.min_amount_out),
+        opt(s.amount_received), opt(&s.mint_source), opt(&s.mint_destination), opt(s.slot),
+        opt(s.instruction_index), s.inner_index.map(|i| i.to_string()),
+        to_timestamptz(s.block_time).map(|d| d.to_rfc3339()), opt(s.pool_type.as_str()), opt(s.cpi_depth),
+    ]).collect();
+
+    copy_rows(
+        conn,
+        "raydium_swaps",
+        "signature, amm_pool, sender, amount_in, min_amount_out, amount_received, mint_source, mint_destination, slot, instruction_index, inner_index, block_time, pool_type, cpi_depth",
+        on_conflict,
+        rows,
+    ).await
+}
+
+async fn copy_jupiter_swaps(conn: &mut PgConnection, swaps: &[&JupiterSwapEvent], on_conflict: &str) -> Result<u64> {
+    let mut rows = Vec::with_capacity(swaps.len());
+    for e in swaps {
+        rows.push(vec![
+            opt(&e.signature), opt(e.slot), to_timestamptz(e.block_time).map(|d| d.to_rfc3339()),
+            opt(&e.signer), opt(&e.amm_pool), opt(&e.mint_in), opt(&e.mint_out),
+            opt(e.amount_in), opt(e.amount_out), opt(e.slippage_bps), opt(e.platform_fee_bps),
+            opt(serde_json::to_string(&e.route_plan)?), opt(e.instruction_index),
+            e.inner_index.map(|i| i.to_string()), opt(e.cpi_depth),
+        ]);
+    }
+
+    copy_rows(
+        conn,
+        "jupiter_swaps",
+        "signature, slot, block_time, signer, amm_pool, mint_in, mint_out, amount_in, amount_out, slippage_bps, platform_fee_bps, route_plan, instruction_index, inner_index, cpi_depth",
+        on_conflict,
+        rows,
+    ).await
+}
+
+/// Route steps of each swap, flattened as (swap, step_index, step).
+fn route_steps<'a>(swaps: &'a [&JupiterSwapEvent]) -> impl Iterator<Item = (&'a JupiterSwapEvent, usize, &'a RouteStep)> {
+    swaps.iter().flat_map(|s| s.route_plan.iter().enumerate().map(move |(i, step)| (*s, i, step)))
+}
+
+async fn copy_jupiter_route_steps(conn: &mut PgConnection, swaps: &[&JupiterSwapEvent]) -> Result<u64> {
+    let rows = route_steps(swaps).map(|(s, i, step)| vec![
+        opt(&s.signature), opt(s.instruction_index), opt(i), opt(&step.swap_label),
+        opt(step.percent), opt(step.input_index), opt(step.output_index),
+    ]).collect();
+
+    copy_rows(
+        conn,
+        "jupiter_route_steps",
+        "signature, instruction_index, step_index, swap_label, percent, input_index, output_index",
+        "DO NOTHING",
+        rows,
+    ).await
+}
+
+async fn copy_pump_trades(conn: &mut PgConnection, trades: &[&PumpFunTrade], on_conflict: &str) -> Result<u64> {
+    let rows = trades.iter().map(|t| vec![
+        opt(&t.signature), opt(t.slot), to_timestamptz(t.block_time).map(|d| d.to_rfc3339()),
+        opt(&t.mint), opt(t.direction.is_buy()), opt(&t.user), opt(t.token_amount), opt(t.sol_amount),
+        opt(t.instruction_index), t.inner_index.map(|i| i.to_string()), opt(t.cpi_depth),
+    ]).collect();
+
+    copy_rows(
+        conn,
+        "pump_fun_trades",
+        "signature, slot, block_time, mint, is_buy, user_address, token_amount, sol_amount, instruction_index, inner_index, cpi_depth",
+        on_conflict,
+        rows,
+    ).await
+}
+
+async fn copy_pump_amm_swaps(conn: &mut PgConnection, swaps: &[&PumpAmmSwapEvent], on_conflict: &str) -> Result<u64> {
+    let rows = swaps.iter().map(|s| vec![
+        opt(&s.signature), opt(s.slot), to_timestamptz(s.block_time).map(|d| d.to_rfc3339()),
+        opt(&s.pool), opt(&s.user), opt(&s.base_mint), opt(&s.quote_mint), opt(s.direction.is_buy()),
+        opt(s.base_amount), opt(s.quote_amount), s.pool_base_reserve.map(|v| v.to_string()),
+        s.pool_quote_reserve.map(|v| v.to_string()), opt(s.instruction_index),
+        s.inner_index.map(|i| i.to_string()), opt(s.cpi_depth),
+    ]).collect();
+
+    copy_rows(conn, "pump_amm_swaps", PUMP_AMM_COLUMNS, on_conflict, rows).await
+}
+
+async fn copy_compute_budgets(conn: &mut PgConnection, budgets: &[&ComputeBudgetEvent], on_conflict: &str) -> Result<u64> {
+    let rows = budgets.iter().map(|b| vec![
+        opt(&b.signature), opt(b.slot), to_timestamptz(b.block_time).map(|d| d.to_rfc3339()),
+        opt(b.instruction_index), b.unit_limit.map(|v| v.to_string()),
+        b.unit_price_micro_lamports.map(|v| v.to_string()),
+    ]).collect();
+
+    copy_rows(
+        conn,
+        "compute_budgets",
+        "signature, slot, block_time, instruction_index, unit_limit, unit_price_micro_lamports",
+        on_conflict,
+        rows,
+    ).await
+}
+
+async fn copy_sol_transfers(conn: &mut PgConnection, transfers: &[&SolTransfer], on_conflict: &str) -> Result<u64> {
+    let rows = transfers.iter().map(|t| vec![
+        opt(&t.signature), opt(t.slot), to_timestamptz(t.block_time).map(|d| d.to_rfc3339()),
+        opt(&t.from), opt(&t.to), opt(t.lamports), opt(t.instruction_index),
+        t.inner_index.map(|i| i.to_string()),
+    ]).collect();
+
+    copy_rows(
+        conn,
+        "sol_transfers",
+        "signature, slot, block_time, sender, receiver, lamports, instruction_index, inner_index",
+        on_conflict,
+        rows,
+    ).await
+}
+
+impl PostgresRepository {
+    /// Write events straight into the event tables and advance the slot cursor.
+    async fn write_events(&self, events: &[TransactionEvent], current_slot: u64) -> Result<()> {
+        let mut txn = self.pool.begin().await.map_err(pool_error)?;
+
+        // Parents first: every event table has a foreign key to transactions
+        let parents = transaction_rows(events);
+        if !parents.is_empty() {
+            let sigs:  Vec<&str> = parents.keys().copied().collect();
+            let slots: Vec<i64>  = parents.values().map(|(slot, _, _)| *slot as i64).collect();
+            let times: Vec<Option<DateTime<Utc>>> = parents.values().map(|(_, time, _)| to_timestamptz(*time)).collect();
+            let kinds: Vec<String> = parents.values().map(|(_, _, kinds)| kinds.iter().copied().collect::<Vec<_>>().join(",")).collect();
+
+            sqlx::query(UPSERT_TRANSACTIONS)
             .bind(&sigs)
-            .bind(&slots_)
+            .bind(&slots)
             .bind(&times)
-            .bind(&signers)
-            .bind(&pools)
-            .bind(&mints_in)
-            .bind(&mints_out)
-            .bind(&amts_in)
-            .bind(&amts_out)
-            .bind(&slippages)
-            .bind(&fees)
-            .bind(&routes)
+            .bind(&kinds)
             .execute(&mut *txn)
             .await?;
         }
 
-        if !pump_trades.is_empty() {
-            let sigs:    Vec<String>     = pump_trades.iter().map(|t| t.signature.clone()).collect();
-            let slots_:  Vec<i64>        = pump_trades.iter().map(|t| t.slot as i64).collect();
-            let times:   Vec<chrono::NaiveDateTime> = pump_trades.iter()
-                .map(|t| chrono::DateTime::from_timestamp(t.block_time, 0).unwrap().naive_utc())
-                .collect();
-            let mints:   Vec<String>     = pump_trades.iter().map(|t| t.mint.clone()).collect();
-            let is_buys: Vec<bool>       = pump_trades.iter().map(|t| t.is_buy).collect();
-            let users:   Vec<String>     = pump_trades.iter().map(|t| t.user.clone()).collect();
-            let tokens:  Vec<BigDecimal> = pump_trades.iter().map(|t| BigDecimal::from(t.token_amount)).collect();
-            let sols:    Vec<BigDecimal> = pump_trades.iter().map(|t| BigDecimal::from(t.sol_amount)).collect();
+        let mut transfers = Vec::new();
+        let mut raydium_swaps = Vec::new();
+        let mut jupiter_swaps = Vec::new();
+        let mut pump_trades = Vec::new();
+        let mut pump_amm_swaps = Vec::new();
+        let mut compute_budgets = Vec::new();
+        let mut sol_transfers = Vec::new();
+
+        for ev in events {
+            match ev {
+                TransactionEvent::TokenTransfer(t) => transfers.push(t),
+                TransactionEvent::RaydiumSwap(s) => raydium_swaps.push(s),
+                TransactionEvent::JupiterSwap(s) => jupiter_swaps.push(s),
+                TransactionEvent::PumpFunTrade(t) => pump_trades.push(t),
+                TransactionEvent::PumpAmmSwap(s) => pump_amm_swaps.push(s),
+                TransactionEvent::ComputeBudget(b) => compute_budgets.push(b),
+                TransactionEvent::SolTransfer(t) => sol_transfers.push(t),
+            }
+        }
+
+        if events.len() >= self.copy_threshold {
+            copy_transfers(&mut txn, &transfers, &self.on_conflict(&TRANSFERS_KEY)).await?;
+            copy_raydium_swaps(&mut txn, &raydium_swaps, &self.on_conflict(&RAYDIUM_KEY)).await?;
+            copy_jupiter_swaps(&mut txn, &jupiter_swaps, &self.on_conflict(&JUPITER_KEY)).await?;
+            copy_jupiter_route_steps(&mut txn, &jupiter_swaps).await?;
+            copy_pump_trades(&mut txn, &pump_trades, &self.on_conflict(&PUMP_FUN_KEY)).await?;
+            copy_pump_amm_swaps(&mut txn, &pump_amm_swaps, &self.on_conflict(&PUMP_AMM_KEY)).await?;
+            copy_compute_budgets(&mut txn, &compute_budgets, &self.on_conflict(&COMPUTE_BUDGET_KEY)).await?;
+            copy_sol_transfers(&mut txn, &sol_transfers, &self.on_conflict(&SOL_TRANSFERS_KEY)).await?;
+            tracing::info!("Batch of {} events written via COPY", events.len());
+        } else {
+            if !transfers.is_empty() {
+                let slots: Vec<i64>          = transfers.iter().map(|t| t.slot as i64).collect();
+                let amounts: Vec<BigDecimal> = transfers.iter().map(|t| BigDecimal::from(t.amount)).collect();
+                let sigs: Vec<String>        = transfers.iter().map(|t| t.signature.to_string()).collect();
+                let senders: Vec<String>     = transfers.iter().map(|t| t.from.to_string()).collect();
+                let receivers: Vec<String>   = transfers.iter().map(|t| t.to.to_string()).collect();
+                let mints: Vec<String>       = transfers.iter().map(|t| t.mint.as_deref().unwrap_or("").to_string()).collect();
+                let ix_idxs: Vec<i32>        = transfers.iter().map(|t| t.instruction_index as i32).collect();
+                let inner_idxs: Vec<Option<i32>> = transfers.iter().map(|t| t.inner_index.map(|i| i as i32)).collect();
+                let times: Vec<Option<DateTime<Utc>>> = transfers.iter().map(|t| to_timestamptz(t.block_time)).collect();
+
+                sqlx::query(&self.statements.transfers)
+                .bind(&sigs)
+                .bind(&senders)
+                .bind(&receivers)
+                .bind(&mints)
+                .bind(&amounts)
+                .bind(&slots)
+                .bind(&ix_idxs)
+                .bind(&inner_idxs)
+                .bind(&times)
+                .execute(&mut *txn)
+                .await?;
+            }
+
+            if !raydium_swaps.is_empty() {
+                let sigs:      Vec<String>     = raydium_swaps.iter().map(|s| s.signature.to_string()).collect();
+                let pools:     Vec<String>     = raydium_swaps.iter().map(|s| s.amm_pool.clone()).collect();
+                let users:     Vec<String>     = raydium_swaps.iter().map(|s| s.signer.clone()).collect();
+                let amts_in:   Vec<BigDecimal> = raydium_swaps.iter().map(|s| BigDecimal::from(s.amount_in)).collect();
+                let min_outs:  Vec<BigDecimal> = raydium_swaps.iter().map(|s| BigDecimal::from(s.min_amount_out)).collect();
+                let received:  Vec<BigDecimal> = raydium_swaps.iter().map(|s| BigDecimal::from(s.amount_received)).collect();
+                let mints_src: Vec<String>     = raydium_swaps.iter().map(|s| s.mint_source.clone()).collect();
+                let mints_dst: Vec<String>     = raydium_swaps.iter().map(|s| s.mint_destination.clone()).collect();
+                let slots:     Vec<i64>        = raydium_swaps.iter().map(|s| s.slot as i64).collect();
+                let ix_idxs:   Vec<i32>        = raydium_swaps.iter().map(|s| s.instruction_index as i32).collect();
+                let inner_idxs: Vec<Option<i32>> = raydium_swaps.iter().map(|s| s.inner_index.map(|i| i as i32)).collect();
+                let times: Vec<Option<DateTime<Utc>>> = raydium_swaps.iter().map(|s| to_timestamptz(s.block_time)).collect();
+                let pool_types: Vec<String>    = raydium_swaps.iter().map(|s| s.pool_type.as_str().to_string()).collect();
+                let depths:    Vec<i32>        = raydium_swaps.iter().map(|s| s.cpi_depth as i32).collect();
+
+                sqlx::query(&self.statements.raydium)
+                .bind(&sigs)
+                .bind(&pools)
+                .bind(&users)
+                .bind(&amts_in)
+                .bind(&min_outs)
+                .bind(&received)
+                .bind(&mints_src)
+                .bind(&mints_dst)
+                .bind(&slots)
+                .bind(&ix_idxs)
+                .bind(&inner_idxs)
+                .bind(&times)
+                .bind(&pool_types)
+                .bind(&depths)
+                .execute(&mut *txn)
+                .await?;
+
+                tracing::info!("Saved {} Raydium swaps", raydium_swaps.len());
+            }
+
+            if !jupiter_swaps.is_empty() {
+                let sigs:      Vec<String>     = jupiter_swaps.iter().map(|e| e.signature.to_string()).collect();
+                let slots_:    Vec<i64>        = jupiter_swaps.iter().map(|e| e.slot as i64).collect();
+                let times:     Vec<Option<DateTime<Utc>>> = jupiter_swaps.iter().map(|e| to_timestamptz(e.block_time)).collect();
+                let signers:   Vec<String>     = jupiter_swaps.iter().map(|e| e.signer.clone()).collect();
+                let pools:     Vec<String>     = jupiter_swaps.iter().map(|e| e.amm_pool.clone()).collect();
+                let mints_in:  Vec<String>     = jupiter_swaps.iter().map(|e| e.mint_in.clone()).collect();
+                let mints_out: Vec<String>     = jupiter_swaps.iter().map(|e| e.mint_out.clone()).collect();
+                let amts_in:   Vec<BigDecimal> = jupiter_swaps.iter().map(|e| BigDecimal::from(e.amount_in)).collect();
+                let amts_out:  Vec<BigDecimal> = jupiter_swaps.iter().map(|e| BigDecimal::from(e.amount_out)).collect();
+                let slippages: Vec<i32>        = jupiter_swaps.iter().map(|e| e.slippage_bps as i32).collect();
+                let fees:      Vec<i32>        = jupiter_swaps.iter().map(|e| e.platform_fee_bps as i32).collect();
+                let routes:    Vec<serde_json::Value> = jupiter_swaps.iter()
+                    .map(|e| serde_json::to_value(&e.route_plan).unwrap())
+                    .collect();
+                let ix_idxs:   Vec<i32>        = jupiter_swaps.iter().map(|e| e.instruction_index as i32).collect();
+                let inner_idxs: Vec<Option<i32>> = jupiter_swaps.iter().map(|e| e.inner_index.map(|i| i as i32)).collect();
+                let depths:    Vec<i32>        = jupiter_swaps.iter().map(|e| e.cpi_depth as i32).collect();
+
+                sqlx::query(&self.statements.jupiter)
+                .bind(&sigs)
+                .bind(&slots_)
+                .bind(&times)
+                .bind(&signers)
+                .bind(&pools)
+                .bind(&mints_in)
+                .bind(&mints_out)
+                .bind(&amts_in)
+                .bind(&amts_out)
+                .bind(&slippages)
+                .bind(&fees)
+                .bind(&routes)
+                .bind(&ix_idxs)
+                .bind(&inner_idxs)
+                .bind(&depths)
+                .execute(&mut *txn)
+                .await?;
+
+                let steps: Vec<_> = route_steps(&jupiter_swaps).collect();
+                if !steps.is_empty() {
+                    let step_sigs:    Vec<String> = steps.iter().map(|(s, _, _)| s.signature.to_string()).collect();
+                    let step_ixs:     Vec<i32>    = steps.iter().map(|(s, _, _)| s.instruction_index as i32).collect();
+                    let step_idxs:    Vec<i32>    = steps.iter().map(|(_, i, _)| *i as i32).collect();
+                    let labels:       Vec<String> = steps.iter().map(|(_, _, r)| r.swap_label.clone()).collect();
+                    let percents:     Vec<i16>    = steps.iter().map(|(_, _, r)| r.percent as i16).collect();
+                    let input_idxs:   Vec<i16>    = steps.iter().map(|(_, _, r)| r.input_index as i16).collect();
+                    let output_idxs:  Vec<i16>    = steps.iter().map(|(_, _, r)| r.output_index as i16).collect();
+
+                    sqlx::query(
+                        r#"INSERT INTO jupiter_route_steps
+                           (signature, instruction_index, step_index, swap_label, percent, input_index, output_index)
+                           SELECT * FROM UNNEST($1::text[], $2::int[], $3::int[], $4::text[], $5::smallint[], $6::smallint[], $7::smallint[])
+                           ON CONFLICT DO NOTHING"#,
+                    )
+                    .bind(&step_sigs)
+                    .bind(&step_ixs)
+                    .bind(&step_idxs)
+                    .bind(&labels)
+                    .bind(&percents)
+                    .bind(&input_idxs)
+                    .bind(&output_idxs)
+                    .execute(&mut *txn)
+                    .await?;
+                }
+            }
+
+            if !pump_trades.is_empty() {
+                let sigs:    Vec<String>     = pump_trades.iter().map(|t| t.signature.to_string()).collect();
+                let slots_:  Vec<i64>        = pump_trades.iter().map(|t| t.slot as i64).collect();
+                let times:   Vec<Option<DateTime<Utc>>> = pump_trades.iter().map(|t| to_timestamptz(t.block_time)).collect();
+                let mints:   Vec<String>     = pump_trades.iter().map(|t| t.mint.clone()).collect();
+                let is_buys: Vec<bool>       = pump_trades.iter().map(|t| t.direction.is_buy()).collect();
+                let users:   Vec<String>     = pump_trades.iter().map(|t| t.user.clone()).collect();
+                let tokens:  Vec<BigDecimal> = pump_trades.iter().map(|t| BigDecimal::from(t.token_amount)).collect();
+                let sols:    Vec<BigDecimal> = pump_trades.iter().map(|t| BigDecimal::from(t.sol_amount)).collect();
+                let ix_idxs: Vec<i32>        = pump_trades.iter().map(|t| t.instruction_index as i32).collect();
+                let inner_idxs: Vec<Option<i32>> = pump_trades.iter().map(|t| t.inner_index.map(|i| i as i32)).collect();
+                let depths:  Vec<i32>        = pump_trades.iter().map(|t| t.cpi_depth as i32).collect();
+
+                sqlx::query(&self.statements.pump_fun)
+                .bind(&sigs)
+                .bind(&slots_)
+                .bind(&times)
+                .bind(&mints)
+                .bind(&is_buys)
+                .bind(&users)
+                .bind(&tokens)
+                .bind(&sols)
+                .bind(&ix_idxs)
+                .bind(&inner_idxs)
+                .bind(&depths)
+                .execute(&mut *txn)
+                .await?;
+            }
+
+            if !pump_amm_swaps.is_empty() {
+                let sigs:      Vec<String>     = pump_amm_swaps.iter().map(|s| s.signature.to_string()).collect();
+                let slots_:    Vec<i64>        = pump_amm_swaps.iter().map(|s| s.slot as i64).collect();
+                let times:     Vec<Option<DateTime<Utc>>> = pump_amm_swaps.iter().map(|s| to_timestamptz(s.block_time)).collect();
+                let pools:     Vec<String>     = pump_amm_swaps.iter().map(|s| s.pool.clone()).collect();
+                let users:     Vec<String>     = pump_amm_swaps.iter().map(|s| s.user.clone()).collect();
+                let bases:     Vec<String>     = pump_amm_swaps.iter().map(|s| s.base_mint.clone()).collect();
+                let quotes:    Vec<String>     = pump_amm_swaps.iter().map(|s| s.quote_mint.clone()).collect();
+                let is_buys:   Vec<bool>       = pump_amm_swaps.iter().map(|s| s.direction.is_buy()).collect();
+                let base_amts: Vec<BigDecimal> = pump_amm_swaps.iter().map(|s| BigDecimal::from(s.base_amount)).collect();
+                let quote_amts: Vec<BigDecimal> = pump_amm_swaps.iter().map(|s| BigDecimal::from(s.quote_amount)).collect();
+                let base_res:  Vec<Option<BigDecimal>> = pump_amm_swaps.iter().map(|s| s.pool_base_reserve.map(BigDecimal::from)).collect();
+                let quote_res: Vec<Option<BigDecimal>> = pump_amm_swaps.iter().map(|s| s.pool_quote_reserve.map(BigDecimal::from)).collect();
+                let ix_idxs:   Vec<i32>        = pump_amm_swaps.iter().map(|s| s.instruction_index as i32).collect();
+                let inner_idxs: Vec<Option<i32>> = pump_amm_swaps.iter().map(|s| s.inner_index.map(|i| i as i32)).collect();
+                let depths:    Vec<i32>        = pump_amm_swaps.iter().map(|s| s.cpi_depth as i32).collect();
+
+                sqlx::query(&self.statements.pump_amm)
+                .bind(&sigs)
+                .bind(&slots_)
+                .bind(&times)
+                .bind(&pools)
+                .bind(&users)
+                .bind(&bases)
+                .bind(&quotes)
+                .bind(&is_buys)
+                .bind(&base_amts)
+                .bind(&quote_amts)
+                .bind(&base_res)
+                .bind(&quote_res)
+                .bind(&ix_idxs)
+                .bind(&inner_idxs)
+                .bind(&depths)
+                .execute(&mut *txn)
+                .await?;
+            }
+
+            if !compute_budgets.is_empty() {
+                let sigs:    Vec<String>     = compute_budgets.iter().map(|b| b.signature.to_string()).collect();
+                let slots_:  Vec<i64>        = compute_budgets.iter().map(|b| b.slot as i64).collect();
+                let times:   Vec<Option<DateTime<Utc>>> = compute_budgets.iter().map(|b| to_timestamptz(b.block_time)).collect();
+                let ix_idxs: Vec<i32>        = compute_budgets.iter().map(|b| b.instruction_index as i32).collect();
+                let limits:  Vec<Option<i64>> = compute_budgets.iter().map(|b| b.unit_limit.map(|v| v as i64)).collect();
+                let prices:  Vec<Option<BigDecimal>> = compute_budgets.iter().map(|b| b.unit_price_micro_lamports.map(BigDecimal::from)).collect();
+
+                sqlx::query(&self.statements.compute_budgets)
+                .bind(&sigs)
+                .bind(&slots_)
+                .bind(&times)
+                .bind(&ix_idxs)
+                .bind(&limits)
+                .bind(&prices)
+                .execute(&mut *txn)
+                .await?;
+            }
+
+            if !sol_transfers.is_empty() {
+                let sigs:      Vec<String>     = sol_transfers.iter().map(|t| t.signature.to_string()).collect();
+                let slots_:    Vec<i64>        = sol_transfers.iter().map(|t| t.slot as i64).collect();
+                let times:     Vec<Option<DateTime<Utc>>> = sol_transfers.iter().map(|t| to_timestamptz(t.block_time)).collect();
+                let senders:   Vec<String>     = sol_transfers.iter().map(|t| t.from.to_string()).collect();
+                let receivers: Vec<String>     = sol_transfers.iter().map(|t| t.to.to_string()).collect();
+                let lamports:  Vec<BigDecimal> = sol_transfers.iter().map(|t| BigDecimal::from(t.lamports)).collect();
+                let ix_idxs:   Vec<i32>        = sol_transfers.iter().map(|t| t.instruction_index as i32).collect();
+                let inner_idxs: Vec<Option<i32>> = sol_transfers.iter().map(|t| t.inner_index.map(|i| i as i32)).collect();
+
+                sqlx::query(&self.statements.sol_transfers)
+                .bind(&sigs)
+                .bind(&slots_)
+                .bind(&times)
+                .bind(&senders)
+                .bind(&receivers)
+                .bind(&lamports)
+                .bind(&ix_idxs)
+                .bind(&inner_idxs)
+                .execute(&mut *txn)
+                .await?;
+            }
+        }
+
+        sqlx::query(
+            "UPDATE indexer_state SET last_slot = GREATEST(last_slot, $1), last_block_hash = 'TODO' WHERE id = 'main_indexer'"
+        )
+        .bind(current_slot as i64)
+        .execute(&mut *txn)
+        .await?;
+
+        txn.commit().await?;
+
+        tracing::info!("Batch committed: {} transfers, {} raydium, {} jupiter, {} pump, {} pump amm, {} compute budgets, {} sol transfers",
+            transfers.len(), raydium_swaps.len(), jupiter_swaps.len(), pump_trades.len(), pump_amm_swaps.len(), compute_budgets.len(), sol_transfers.len());
+
+        Ok(())
+    }
+
+    /// Park events in `pending_events` until their slot finalizes. The slot cursor
+    /// still advances, so a restart resumes after them.
+    async fn save_pending(&self, events: &[TransactionEvent], current_slot: u64) -> Result<()> {
+        let mut txn = self.pool.begin().await.map_err(pool_error)?;
+
+        if !events.is_empty() {
+            let sigs:   Vec<&str> = events.iter().map(|ev| ev.signature()).collect();
+            let slots:  Vec<i64>  = events.iter().map(|ev| ev.slot() as i64).collect();
+            let kinds:  Vec<&str> = events.iter().map(|ev| ev.kind()).collect();
+            let bodies: Vec<serde_json::Value> = events.iter().map(serde_json::to_value).collect::<Result<_, _>>()?;
 
             sqlx::query(
-                r#"INSERT INTO pump_fun_trades
-                   (signature, slot, block_time, mint, is_buy, user_address, token_amount, sol_amount)
-                   SELECT * FROM UNNEST($1::text[], $2::bigint[], $3::timestamp[], $4::text[], $5::boolean[], $6::text[], $7::numeric[], $8::numeric[])
-                   ON CONFLICT (signature, mint) DO NOTHING"#,
+                r#"INSERT INTO pending_events (signature, slot, kind, event)
+                   SELECT * FROM UNNEST($1::text[], $2::bigint[], $3::text[], $4::jsonb[])"#,
             )
             .bind(&sigs)
-            .bind(&slots_)
-            .bind(&times)
-            .bind(&mints)
-            .bind(&is_buys)
-            .bind(&users)
-            .bind(&tokens)
-            .bind(&sols)
+            .bind(&slots)
+            .bind(&kinds)
+            .bind(&bodies)
             .execute(&mut *txn)
             .await?;
         }
 
         sqlx::query(
-            "UPDATE indexer_state SET last_slot = $1, last_block_hash = 'TODO' WHERE id = 'main_indexer'"
+            "UPDATE indexer_state SET last_slot = GREATEST(last_slot, $1) WHERE id = 'main_indexer'"
         )
         .bind(current_slot as i64)
         .execute(&mut *txn)
@@ -203,11 +1304,43 @@ impl TransactionRepository for PostgresRepository {
 
         txn.commit().await?;
 
-        tracing::info!("Batch committed: {} transfers, {} raydium, {} jupiter, {} pump",
-            transfers.len(), raydium_swaps.len(), jupiter_swaps.len(), pump_trades.len());
-
+        tracing::debug!("Parked {} events pending finalization", events.len());
         Ok(())
     }
+}
+
+#[async_trait]
+impl TransactionRepository for PostgresRepository {
+    async fn get_state(&self) -> Result<IndexerState> {
+        let row = sqlx::query(
+            "SELECT last_slot, last_block_hash FROM indexer_state WHERE id = 'main_indexer'"
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(IndexerState {
+            last_slot: row.try_get::<i64, _>("last_slot")? as u64,
+            last_block_hash: row.try_get("last_block_hash")?,
+        })
+    }
+
+    async fn get_last_slot(&self) -> Result<u64> {
+        let row = sqlx::query(
+            "SELECT last_slot FROM indexer_state WHERE id = 'main_indexer'"
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row.try_get::<i64, _>("last_slot")? as u64)
+    }
+
+    async fn save_batch(&self, events: &[TransactionEvent], current_slot: u64) -> Result<()> {
+        if self.two_phase {
+            self.save_pending(events, current_slot).await
+        } else {
+            self.write_events(events, current_slot).await
+        }
+    }
 
     async fn save_dlq(&self, txn: &SolanaTransaction, parser_name: &str, error: &str) -> Result<()> {
         let tx_json = serde_json::to_value(txn)?;
@@ -228,4 +1361,724 @@ impl TransactionRepository for PostgresRepository {
 
         Ok(())
     }
+
+    async fn save_event_dlq(&self, event: &TransactionEvent, error: &str) -> Result<()> {
+        sqlx::query(
+            r#"INSERT INTO transaction_dlq (signature, slot, parser_name, error_msg, tx_data)
+               VALUES ($1, $2, $3, $4, $5)
+               ON CONFLICT (signature) DO UPDATE
+               SET error_msg = $4, retry_count = transaction_dlq.retry_count + 1"#,
+        )
+        .bind(event.signature())
+        .bind(event.slot() as i64)
+        .bind(format!("persist:{}", event.kind()))
+        .bind(error)
+        .bind(serde_json::to_value(event)?)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn save_transactions(&self, transactions: &[IndexedTransaction]) -> Result<()> {
+        if transactions.is_empty() {
+            return Ok(());
+        }
+        let sigs: Vec<&str> = transactions.iter().map(|t| t.signature.as_str()).collect();
+        let slots: Vec<i64> = transactions.iter().map(|t| t.slot as i64).collect();
+        let times: Vec<Option<DateTime<Utc>>> = transactions.iter().map(|t| to_timestamptz(t.block_time)).collect();
+        let payers: Vec<&str> = transactions.iter().map(|t| t.fee_payer.as_str()).collect();
+        let successes: Vec<bool> = transactions.iter().map(|t| t.success).collect();
+        let parsers: Vec<String> = transactions.iter().map(|t| t.parsers.join(",")).collect();
+
+        sqlx::query(UPSERT_TRANSACTION_DETAILS)
+            .bind(&sigs)
+            .bind(&slots)
+            .bind(&times)
+            .bind(&payers)
+            .bind(&successes)
+            .bind(&parsers)
+            .execute(&self.pool)
+            .await
+            .map_err(pool_error)?;
+        Ok(())
+    }
+
+    async fn delete_events_from_slot(&self, slot: u64) -> Result<u64> {
+        let mut txn = self.pool.begin().await.map_err(pool_error)?;
+        let slot = slot as i64;
+
+        // Route steps carry no slot of their own; drop them with their parent swaps
+        sqlx::query(
+            r#"DELETE FROM jupiter_route_steps r USING jupiter_swaps j
+               WHERE r.signature = j.signature AND r.instruction_index = j.instruction_index AND j.slot >= $1"#,
+        )
+        .bind(slot)
+        .execute(&mut *txn)
+        .await?;
+
+        let mut deleted = 0;
+        for table in EVENT_TABLES {
+            deleted += sqlx::query(&format!("DELETE FROM {} WHERE slot >= $1", table))
+                .bind(slot)
+                .execute(&mut *txn)
+                .await?
+                .rows_affected();
+        }
+        sqlx::query("DELETE FROM transactions WHERE slot >= $1")
+            .bind(slot)
+            .execute(&mut *txn)
+            .await?;
+        deleted += sqlx::query("DELETE FROM pending_events WHERE slot >= $1")
+            .bind(slot)
+            .execute(&mut *txn)
+            .await?
+            .rows_affected();
+
+        sqlx::query(
+            "UPDATE indexer_state SET last_slot = LEAST(last_slot, $1) WHERE id = 'main_indexer'"
+        )
+        .bind((slot - 1).max(0))
+        .execute(&mut *txn)
+        .await?;
+
+        txn.commit().await?;
+
+        tracing::warn!("Rolled back {} events from slot {}", deleted, slot);
+        Ok(deleted)
+    }
+
+    async fn finalize_slot(&self, slot: u64) -> Result<FinalizeResult> {
+        if !self.two_phase {
+            return Ok(FinalizeResult::default());
+        }
+        // Rollback already removed the pending rows of dead slots, so anything left at
+        // or below a finalized slot is canonical. Taking all of it, not just this
+        // slot's, covers a finalized notice that was missed or arrived out of order.
+        let rows = sqlx::query("SELECT id, event FROM pending_events WHERE slot <= $1 ORDER BY id")
+            .bind(slot as i64)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(pool_error)?;
+
+        let mut ids = Vec::with_capacity(rows.len());
+        let mut events = Vec::with_capacity(rows.len());
+        let mut dropped = 0;
+        for row in &rows {
+            let id: i64 = row.try_get("id")?;
+            ids.push(id);
+            let decoded = serde_json::from_value::<TransactionEvent>(row.try_get("event")?)
+                .inspect_err(|e| tracing::error!("Dropping unreadable pending event {}: {}", id, e))
+                .ok();
+            match decoded {
+                Some(event) => events.push(event),
+                None => dropped += 1,
+            }
+        }
+
+        // Promote before deleting: a crash in between leaves rows that promote
+        // again harmlessly, since the event tables absorb duplicates
+        if !events.is_empty() {
+            self.write_events(&events, 0).await?;
+        }
+        // By id, so rows parked for these slots after the SELECT wait for the next finalize
+        if !ids.is_empty() {
+            sqlx::query("DELETE FROM pending_events WHERE id = ANY($1)")
+                .bind(&ids)
+                .execute(&self.pool)
+                .await?;
+        }
+
+        Ok(FinalizeResult { promoted: events.len(), dropped })
+    }
+
+    async fn ping(&self) -> Result<()> {
+        sqlx::query("SELECT 1").execute(&self.pool).await?;
+        Ok(())
+    }
+
+    fn pool_stats(&self) -> Option<PoolStats> {
+        Some(PostgresRepository::pool_stats(self))
+    }
+}
+
+#[async_trait]
+impl EventSink for PostgresRepository {
+    /// Stores the events without touching the slot cursor; GREATEST keeps it where it is.
+    async fn emit(&self, events: &[TransactionEvent]) -> AppResult<()> {
+        self.save_batch(events, 0).await.map_err(|e| AppError::SinkError {
+            sink: "postgres".to_string(),
+            reason: e.to_string(),
+        })
+    }
+
+    fn name(&self) -> &str { "postgres" }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::domain::WSOL_MINT;
+
+    /// The tests share one scratch schema, so they take turns; each also keeps to
+    /// its own slot band and clears it first.
+    static DB: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+    async fn repo() -> PostgresRepository {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must point at a scratch database");
+        PostgresRepository::new(&url).await.expect("connect to TEST_DATABASE_URL")
+    }
+
+    fn sol_transfer(seed: u8, slot: u64) -> TransactionEvent {
+        TransactionEvent::SolTransfer(SolTransfer {
+            from: Pubkey58::from([1u8; 32]),
+            to: Pubkey58::from([2u8; 32]),
+            slot,
+            block_time: None,
+            lamports: 1_000,
+            signature: Signature58::from([seed; 64]),
+            instruction_index: 0,
+            inner_index: None,
+        })
+    }
+
+    fn raydium_swap(seed: u8, slot: u64, mint_source: &str, amount_received: u64) -> TransactionEvent {
+        TransactionEvent::RaydiumSwap(RaydiumSwapEvent {
+            pool_type: RaydiumPoolType::AmmV4,
+            amm_pool: "pool".to_string(),
+            signer: "signer".to_string(),
+            amount_in: 1_000,
+            min_amount_out: 900,
+            amount_received,
+            mint_source: mint_source.to_string(),
+            mint_destination: "So11111111111111111111111111111111111111112".to_string(),
+            slot,
+            block_time: None,
+            signature: Signature58::from([seed; 64]),
+            instruction_index: 0,
+            inner_index: None,
+            cpi_depth: 0,
+        })
+    }
+
+    fn pump_amm_swap(seed: u8, slot: u64) -> TransactionEvent {
+        TransactionEvent::PumpAmmSwap(PumpAmmSwapEvent {
+            signature: Signature58::from([seed; 64]),
+            instruction_index: 0,
+            inner_index: None,
+            cpi_depth: 0,
+            slot,
+            block_time: None,
+            pool: "pool".to_string(),
+            user: "user".to_string(),
+            base_mint: "base".to_string(),
+            quote_mint: "So11111111111111111111111111111111111111112".to_string(),
+            direction: crate::domain::SwapDirection::Buy,
+            base_amount: 5_000,
+            quote_amount: 1_000,
+            pool_base_reserve: Some(1_000_000),
+            pool_quote_reserve: Some(200_000),
+        })
+    }
+
+    async fn count(repo: &PostgresRepository, table: &str, signature: &str) -> i64 {
+        sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {} WHERE signature = $1", table))
+            .bind(signature)
+            .fetch_one(&repo.pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a scratch Postgres in TEST_DATABASE_URL"]
+    async fn finalize_promotes_canonical_pending_rows_and_rollback_drops_dead_ones() {
+        let _db = DB.lock().await;
+        const BASE: u64 = 625_000_000;
+        let repo = repo().await.with_two_phase(true);
+        repo.delete_events_from_slot(BASE).await.unwrap();
+
+        let finalized = sol_transfer(61, BASE);
+        // Canonical, but its finalized notice never arrives
+        let missed_notice = sol_transfer(62, BASE + 1);
+        let dead = sol_transfer(63, BASE + 2);
+        repo.save_batch(&[finalized.clone(), missed_notice.clone(), dead.clone()], BASE + 2).await.unwrap();
+        assert_eq!(count(&repo, "sol_transfers", finalized.signature()).await, 0);
+        assert_eq!(count(&repo, "pending_events", finalized.signature()).await, 1);
+
+        repo.delete_events_from_slot(BASE + 2).await.unwrap();
+        let result = repo.finalize_slot(BASE + 3).await.unwrap();
+
+        assert_eq!(result, FinalizeResult { promoted: 2, dropped: 0 });
+        for ev in [&finalized, &missed_notice] {
+            assert_eq!(count(&repo, "sol_transfers", ev.signature()).await, 1);
+            assert_eq!(count(&repo, "pending_events", ev.signature()).await, 0);
+        }
+        assert_eq!(count(&repo, "sol_transfers", dead.signature()).await, 0);
+        assert_eq!(count(&repo, "pending_events", dead.signature()).await, 0);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a scratch Postgres in TEST_DATABASE_URL"]
+    async fn swap_queries_include_pump_amm_swaps() {
+        let _db = DB.lock().await;
+        const BASE: u64 = 619_000_000;
+        let repo = repo().await;
+        repo.delete_events_from_slot(BASE).await.unwrap();
+
+        let raydium = raydium_swap(191, BASE, "mint", 950);
+        let pump_amm = pump_amm_swap(192, BASE + 1);
+        repo.save_batch(&[raydium.clone(), pump_amm.clone()], BASE + 1).await.unwrap();
+
+        let in_range: Vec<_> = repo.swaps_in_slot_range(BASE, BASE + 1).await.unwrap().iter().map(|s| s.signature().to_string()).collect();
+        assert_eq!(in_range, vec![raydium.signature().to_string(), pump_amm.signature().to_string()]);
+        let recent = repo.recent_swaps(1).await.unwrap();
+        assert!(matches!(recent.as_slice(), [SwapEvent::PumpAmm(s)] if s.signature.as_str() == pump_amm.signature()));
+
+        repo.delete_events_from_slot(BASE).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a scratch Postgres in TEST_DATABASE_URL"]
+    async fn update_mode_replay_never_downgrades_known_values_to_placeholders() {
+        let _db = DB.lock().await;
+        const BASE: u64 = 585_000_000;
+        let repo = repo().await.with_upsert_mode(UpsertMode::Update);
+        repo.delete_events_from_slot(BASE).await.unwrap();
+        async fn row(repo: &PostgresRepository, signature: &str) -> (String, BigDecimal) {
+            sqlx::query_as("SELECT mint_source, amount_received FROM raydium_swaps WHERE signature = $1")
+                .bind(signature)
+                .fetch_one(&repo.pool)
+                .await
+                .unwrap()
+        }
+
+        let known = raydium_swap(58, BASE, "MintA", 950);
+        let signature = known.signature().to_string();
+        repo.save_batch(&[known], BASE).await.unwrap();
+        // A replay that could not resolve the mint or find the output transfer
+        repo.save_batch(&[raydium_swap(58, BASE, "unknown", 0)], BASE).await.unwrap();
+        repo.save_batch(&[raydium_swap(58, BASE, "", 0)], BASE).await.unwrap();
+        assert_eq!(row(&repo, &signature).await, ("MintA".to_string(), BigDecimal::from(950)));
+
+        // A later, better-informed write still fills in the values
+        repo.save_batch(&[raydium_swap(58, BASE, "MintB", 960)], BASE).await.unwrap();
+        assert_eq!(row(&repo, &signature).await, ("MintB".to_string(), BigDecimal::from(960)));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a scratch Postgres in TEST_DATABASE_URL"]
+    async fn parent_row_matches_its_child_rows_after_a_batch() {
+        let _db = DB.lock().await;
+        const BASE: u64 = 622_000_000;
+        let repo = repo().await;
+        repo.delete_events_from_slot(BASE).await.unwrap();
+
+        let transfer = sol_transfer(22, BASE);
+        let swap = raydium_swap(22, BASE, "MintA", 950);
+        let signature = transfer.signature().to_string();
+        repo.save_batch(&[transfer, swap], BASE).await.unwrap();
+        let record = IndexedTransaction {
+            signature: signature.clone(),
+            slot: BASE,
+            block_time: None,
+            fee_payer: "Payer1111111111111111111111111111111111111".to_string(),
+            success: true,
+            parsers: vec!["system".to_string(), "raydium_amm".to_string()],
+        };
+        repo.save_transactions(std::slice::from_ref(&record)).await.unwrap();
+
+        let (slot, fee_payer, success, parsers, kinds): (i64, Option<String>, Option<bool>, Vec<String>, Vec<String>) = sqlx::query_as(
+            "SELECT slot, fee_payer, success, parsers, event_kinds FROM transactions WHERE signature = $1",
+        )
+        .bind(&signature)
+        .fetch_one(&repo.pool)
+        .await
+        .unwrap();
+        assert_eq!(slot, BASE as i64);
+        assert_eq!(fee_payer.as_deref(), Some(record.fee_payer.as_str()));
+        assert_eq!(success, Some(true));
+        assert_eq!(parsers, vec!["raydium_amm", "system"]);
+        assert_eq!(kinds, vec!["raydium_swap", "sol_transfer"]);
+
+        // Every child row points at the parent, and both agree on the slot
+        let children: i64 = sqlx::query_scalar(
+            "SELECT (SELECT COUNT(*) FROM sol_transfers c JOIN transactions t USING (signature) WHERE c.signature = $1 AND c.slot = t.slot)
+                  + (SELECT COUNT(*) FROM raydium_swaps c JOIN transactions t USING (signature) WHERE c.signature = $1 AND c.slot = t.slot)",
+        )
+        .bind(&signature)
+        .fetch_one(&repo.pool)
+        .await
+        .unwrap();
+        assert_eq!(children, 2);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a scratch Postgres in TEST_DATABASE_URL"]
+    async fn two_swaps_in_one_transaction_are_both_stored() {
+        let _db = DB.lock().await;
+        const BASE: u64 = 539_000_000;
+        let repo = repo().await;
+        repo.delete_events_from_slot(BASE).await.unwrap();
+
+        // A two-hop route: the same signature, two swap instructions
+        let first = raydium_swap(39, BASE, "MintA", 950);
+        let mut second = raydium_swap(39, BASE, "MintB", 900);
+        if let TransactionEvent::RaydiumSwap(swap) = &mut second {
+            swap.instruction_index = 1;
+        }
+        repo.save_batch(&[first.clone(), second], BASE).await.unwrap();
+
+        assert_eq!(count(&repo, "raydium_swaps", first.signature()).await, 2);
+        let indexes: Vec<i32> = sqlx::query_scalar("SELECT instruction_index FROM raydium_swaps WHERE signature = $1 ORDER BY instruction_index")
+            .bind(first.signature())
+            .fetch_all(&repo.pool)
+            .await
+            .unwrap();
+        assert_eq!(indexes, vec![0, 1]);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a scratch Postgres in TEST_DATABASE_URL"]
+    async fn persisted_block_time_is_the_source_value() {
+        let _db = DB.lock().await;
+        const BASE: u64 = 541_000_000;
+        let repo = repo().await;
+        repo.delete_events_from_slot(BASE).await.unwrap();
+
+        let mut transfer = sol_transfer(41, BASE);
+        if let TransactionEvent::SolTransfer(t) = &mut transfer {
+            t.block_time = Some(1_600_000_000);
+        }
+        let untimed = sol_transfer(42, BASE);
+        repo.save_batch(&[transfer.clone(), untimed.clone()], BASE).await.unwrap();
+
+        async fn block_time(repo: &PostgresRepository, signature: &str) -> Option<DateTime<Utc>> {
+            sqlx::query_scalar("SELECT block_time FROM sol_transfers WHERE signature = $1")
+                .bind(signature)
+                .fetch_one(&repo.pool)
+                .await
+                .unwrap()
+        }
+        assert_eq!(block_time(&repo, transfer.signature()).await, DateTime::from_timestamp(1_600_000_000, 0));
+        assert_eq!(block_time(&repo, untimed.signature()).await, None);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a scratch Postgres in TEST_DATABASE_URL"]
+    async fn copy_path_drops_duplicate_signatures() {
+        let _db = DB.lock().await;
+        const BASE: u64 = 544_000_000;
+        let repo = repo().await.with_copy_threshold(10);
+        repo.delete_events_from_slot(BASE).await.unwrap();
+
+        // 100 distinct transfers, then the first 20 again in a later batch and
+        // the next 20 twice within one. Signatures stay clear of the other tests'.
+        let unique: Vec<_> = (0..100u8)
+            .map(|seed| {
+                let mut ev = sol_transfer(0, BASE);
+                if let TransactionEvent::SolTransfer(t) = &mut ev {
+                    let mut signature = [44u8; 64];
+                    signature[0] = seed;
+                    t.signature = Signature58::from(signature);
+                }
+                ev
+            })
+            .collect();
+        repo.save_batch(&unique, BASE).await.unwrap();
+        let replay: Vec<_> = unique[..40].iter().chain(&unique[20..40]).cloned().collect();
+        repo.save_batch(&replay, BASE).await.unwrap();
+
+        let rows: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sol_transfers WHERE slot = $1")
+            .bind(BASE as i64)
+            .fetch_one(&repo.pool)
+            .await
+            .unwrap();
+        assert_eq!(rows, 100);
+        for ev in &unique[..40] {
+            assert_eq!(count(&repo, "sol_transfers", ev.signature()).await, 1);
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a scratch Postgres in TEST_DATABASE_URL"]
+    async fn new_creates_the_schema_on_an_empty_database() {
+        let _db = DB.lock().await;
+        const SCHEMA: &str = "indexer_fresh_545";
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must point at a scratch database");
+        // An empty schema stands in for a fresh database: nothing created by hand
+        let admin = PgPool::connect(&url).await.unwrap();
+        sqlx::query(&format!("DROP SCHEMA IF EXISTS {} CASCADE", SCHEMA)).execute(&admin).await.unwrap();
+        sqlx::query(&format!("CREATE SCHEMA {}", SCHEMA)).execute(&admin).await.unwrap();
+        let separator = if url.contains('?') { '&' } else { '?' };
+        let fresh_url = format!("{}{}options=-c%20search_path%3D{}", url, separator, SCHEMA);
+
+        let repo = PostgresRepository::new(&fresh_url).await.unwrap();
+        let transfer = sol_transfer(45, 545_000_000);
+        repo.save_batch(std::slice::from_ref(&transfer), 545_000_000).await.unwrap();
+
+        assert_eq!(count(&repo, "sol_transfers", transfer.signature()).await, 1);
+        assert_eq!(repo.get_last_slot().await.unwrap(), 545_000_000);
+        repo.pool.close().await;
+        sqlx::query(&format!("DROP SCHEMA {} CASCADE", SCHEMA)).execute(&admin).await.unwrap();
+    }
+
+    fn token_transfer(seed: u8, slot: u64, from: [u8; 32], to: [u8; 32]) -> TransactionEvent {
+        TransactionEvent::TokenTransfer(TokenTransfer {
+            from: Pubkey58::from(from),
+            to: Pubkey58::from(to),
+            slot,
+            block_time: None,
+            amount: 500,
+            signature: Signature58::from([seed; 64]),
+            mint: Some("Mint".to_string()),
+            instruction_index: 0,
+            inner_index: None,
+        })
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a scratch Postgres in TEST_DATABASE_URL"]
+    async fn read_queries_filter_what_was_written() {
+        let _db = DB.lock().await;
+        const BASE: u64 = 548_000_000;
+        let repo = repo().await;
+        repo.delete_events_from_slot(BASE).await.unwrap();
+        let (alice, bob, carol) = ([148u8; 32], [149u8; 32], [150u8; 32]);
+
+        let sent = token_transfer(146, BASE, alice, bob);
+        let received = token_transfer(147, BASE + 1, carol, alice);
+        let unrelated = token_transfer(151, BASE + 1, bob, carol);
+        let early = raydium_swap(148, BASE, "MintA", 950);
+        let late = raydium_swap(149, BASE + 5, "MintB", 960);
+        repo.save_batch(&[sent.clone(), received.clone(), unrelated, early.clone(), late.clone()], BASE + 5)
+            .await
+            .unwrap();
+
+        let for_alice: Vec<_> = repo
+            .transfers_for_address(&Pubkey58::from(alice).to_string(), 10)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|t| t.signature.to_string())
+            .collect();
+        assert_eq!(for_alice, vec![received.signature().to_string(), sent.signature().to_string()]);
+        assert_eq!(repo.transfers_for_address(&Pubkey58::from(alice).to_string(), 1).await.unwrap().len(), 1);
+
+        let in_range: Vec<_> = repo.swaps_in_slot_range(BASE, BASE + 4).await.unwrap().iter().map(|s| s.signature().to_string()).collect();
+        assert_eq!(in_range, vec![early.signature().to_string()]);
+
+        let recent = repo.recent_swaps(2).await.unwrap();
+        assert_eq!(recent.len(), 2);
+        assert!(recent[0].slot() >= recent[1].slot());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a scratch Postgres in TEST_DATABASE_URL"]
+    async fn saturated_pool_times_out_with_pool_exhausted() {
+        let _db = DB.lock().await;
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must point at a scratch database");
+        let repo = PostgresRepository::connect(&url, 1, Duration::from_millis(200)).await.unwrap();
+        let _held = repo.pool.acquire().await.unwrap();
+
+        let result = tokio::time::timeout(Duration::from_secs(5), repo.save_batch(&[sol_transfer(152, 152_000_000)], 152_000_000))
+            .await
+            .expect("save_batch must give up instead of waiting for a connection");
+
+        let err = result.unwrap_err();
+        assert!(matches!(err.downcast_ref(), Some(AppError::PoolExhausted)), "got {}", err);
+    }
+
+    fn jupiter_swap(seed: u8, slot: u64, labels: &[&str]) -> TransactionEvent {
+        TransactionEvent::JupiterSwap(JupiterSwapEvent {
+            signature: Signature58::from([seed; 64]),
+            instruction_index: 0,
+            inner_index: None,
+            cpi_depth: 0,
+            slot,
+            block_time: None,
+            signer: "signer".to_string(),
+            amm_pool: "pool".to_string(),
+            mint_in: "MintA".to_string(),
+            mint_out: "MintB".to_string(),
+            amount_in: 1_000,
+            amount_out: 990,
+            slippage_bps: 50,
+            platform_fee_bps: 0,
+            route_plan: labels
+                .iter()
+                .enumerate()
+                .map(|(i, label)| RouteStep { swap_label: label.to_string(), percent: 100, input_index: i as u8, output_index: i as u8 + 1 })
+                .collect(),
+        })
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a scratch Postgres in TEST_DATABASE_URL"]
+    async fn three_hop_route_is_stored_as_one_parent_and_three_steps() {
+        let _db = DB.lock().await;
+        const BASE: u64 = 582_000_000;
+        let repo = repo().await;
+        repo.delete_events_from_slot(BASE).await.unwrap();
+
+        let swap = jupiter_swap(153, BASE, &["Raydium", "Whirlpool", "Meteora"]);
+        repo.save_batch(&[swap.clone()], BASE).await.unwrap();
+
+        assert_eq!(count(&repo, "jupiter_swaps", swap.signature()).await, 1);
+        let steps: Vec<(i32, String, i16, i16)> = sqlx::query_as(
+            "SELECT step_index, swap_label, input_index, output_index FROM jupiter_route_steps WHERE signature = $1 ORDER BY step_index",
+        )
+        .bind(swap.signature())
+        .fetch_all(&repo.pool)
+        .await
+        .unwrap();
+        assert_eq!(steps, vec![
+            (0, "Raydium".to_string(), 0, 1),
+            (1, "Whirlpool".to_string(), 1, 2),
+            (2, "Meteora".to_string(), 2, 3),
+        ]);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a scratch Postgres in TEST_DATABASE_URL"]
+    async fn repeated_inserts_reuse_one_prepared_statement() {
+        let _db = DB.lock().await;
+        const BASE: u64 = 586_000_000;
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must point at a scratch database");
+        // One connection, so the prepared statements below are all on the session we inspect
+        let repo = PostgresRepository::connect(&url, 1, DEFAULT_ACQUIRE_TIMEOUT).await.unwrap();
+        repo.delete_events_from_slot(BASE).await.unwrap();
+
+        repo.save_batch(&[sol_transfer(154, BASE)], BASE).await.unwrap();
+        repo.save_batch(&[sol_transfer(155, BASE + 1)], BASE + 1).await.unwrap();
+
+        let prepared: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM pg_prepared_statements WHERE statement LIKE 'INSERT INTO sol_transfers%'")
+            .fetch_one(&repo.pool)
+            .await
+            .unwrap();
+        assert_eq!(prepared, 1);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a scratch Postgres in TEST_DATABASE_URL"]
+    async fn slot_and_signer_indexes_exist_after_migration() {
+        let _db = DB.lock().await;
+        let repo = repo().await;
+
+        let indexes: Vec<String> = sqlx::query_scalar("SELECT indexname::text FROM pg_indexes WHERE schemaname = current_schema()")
+            .fetch_all(&repo.pool)
+            .await
+            .unwrap();
+        for name in ["idx_raydium_slot", "idx_pf_slot", "idx_st_slot", "idx_raydium_sender", "idx_jup_signer"] {
+            assert!(indexes.iter().any(|i| i == name), "missing index {}", name);
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a scratch Postgres in TEST_DATABASE_URL"]
+    async fn rollback_removes_only_rows_at_or_above_the_slot() {
+        let _db = DB.lock().await;
+        const BASE: u64 = 588_000_000;
+        let repo = repo().await;
+        repo.delete_events_from_slot(BASE).await.unwrap();
+
+        let canonical = [sol_transfer(156, BASE), raydium_swap(157, BASE + 1, "MintA", 950)];
+        let stale = [
+            sol_transfer(158, BASE + 2),
+            raydium_swap(159, BASE + 2, "MintA", 950),
+            jupiter_swap(160, BASE + 3, &["Raydium", "Whirlpool"]),
+        ];
+        let all: Vec<_> = canonical.iter().chain(&stale).cloned().collect();
+        repo.save_batch(&all, BASE + 3).await.unwrap();
+
+        let deleted = repo.delete_events_from_slot(BASE + 2).await.unwrap();
+
+        assert_eq!(deleted, 3);
+        assert_eq!(count(&repo, "sol_transfers", canonical[0].signature()).await, 1);
+        assert_eq!(count(&repo, "raydium_swaps", canonical[1].signature()).await, 1);
+        assert_eq!(count(&repo, "sol_transfers", stale[0].signature()).await, 0);
+        assert_eq!(count(&repo, "raydium_swaps", stale[1].signature()).await, 0);
+        assert_eq!(count(&repo, "jupiter_swaps", stale[2].signature()).await, 0);
+        assert_eq!(count(&repo, "jupiter_route_steps", stale[2].signature()).await, 0);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a scratch Postgres in TEST_DATABASE_URL"]
+    async fn pool_stats_count_held_connections_as_busy() {
+        let _db = DB.lock().await;
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must point at a scratch database");
+        let repo = PostgresRepository::connect(&url, 3, DEFAULT_ACQUIRE_TIMEOUT).await.unwrap();
+        // Open all three, then hand them back so every one sits idle
+        let warm = [repo.pool.acquire().await.unwrap(), repo.pool.acquire().await.unwrap(), repo.pool.acquire().await.unwrap()];
+        drop(warm);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let before = repo.pool_stats();
+
+        let _first = repo.pool.acquire().await.unwrap();
+        let _second = repo.pool.acquire().await.unwrap();
+        let held = repo.pool_stats();
+
+        assert_eq!(before.size, 3);
+        assert_eq!(before.idle, 3);
+        assert_eq!(held.size, 3);
+        assert_eq!(held.idle, 1);
+    }
+
+    /// Knows one pool and one token account; everything else is unknown to it.
+    struct StubMints {
+        pool: String,
+        token_account: String,
+    }
+
+    #[async_trait]
+    impl MintResolver for StubMints {
+        async fn account_mints(&self, accounts: &[String]) -> Result<HashMap<String, AccountMints>> {
+            let mut found = HashMap::new();
+            for account in accounts {
+                if *account == self.pool {
+                    found.insert(account.clone(), AccountMints::Pool("BackfilledMint".to_string(), WSOL_MINT.to_string()));
+                } else if *account == self.token_account {
+                    found.insert(account.clone(), AccountMints::TokenAccount("BackfilledMint".to_string()));
+                }
+            }
+            Ok(found)
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a scratch Postgres in TEST_DATABASE_URL"]
+    async fn backfill_repairs_placeholder_mints() {
+        let _db = DB.lock().await;
+        const BASE: u64 = 627_000_000;
+        let repo = repo().await;
+        repo.delete_events_from_slot(BASE).await.unwrap();
+        let TransactionEvent::RaydiumSwap(mut swap) = raydium_swap(193, BASE, "unknown", 900) else { unreachable!() };
+        swap.amm_pool = "BackfillPool".to_string();
+        let TransactionEvent::TokenTransfer(mut transfer) = token_transfer(194, BASE, [195u8; 32], [196u8; 32]) else { unreachable!() };
+        transfer.mint = None;
+        let (swap, transfer) = (TransactionEvent::RaydiumSwap(swap), TransactionEvent::TokenTransfer(transfer));
+        repo.save_batch(&[swap.clone(), transfer.clone()], BASE).await.unwrap();
+
+        let resolver = StubMints { pool: "BackfillPool".to_string(), token_account: Pubkey58::from([195u8; 32]).to_string() };
+        let report = repo.backfill_mints(&resolver, MintBackfillConfig { batch_size: 10, requests_per_sec: 1_000 }).await.unwrap();
+
+        assert!(report.updated >= 2, "{:?}", report);
+        let (source, destination): (String, String) =
+            sqlx::query_as("SELECT mint_source, mint_destination FROM raydium_swaps WHERE signature = $1")
+                .bind(swap.signature())
+                .fetch_one(&repo.pool)
+                .await
+                .unwrap();
+        assert_eq!((source.as_str(), destination.as_str()), ("BackfilledMint", WSOL_MINT));
+        let mint: String = sqlx::query_scalar("SELECT mint FROM token_transfers WHERE signature = $1")
+            .bind(transfer.signature())
+            .fetch_one(&repo.pool)
+            .await
+            .unwrap();
+        assert_eq!(mint, "BackfilledMint");
+        // A finished run leaves no cursor to resume from
+        let cursors: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM mint_backfill_cursors").fetch_one(&repo.pool).await.unwrap();
+        assert_eq!(cursors, 0);
+    }
 }
@@ -0,0 +1,88 @@
+use crate::application::{AppError, AppResult};
+
+/// Which repository `DATABASE_URL` selects, by its scheme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepositoryKind {
+    /// `postgres://` or `postgresql://`.
+    Postgres,
+    /// `memory://`: keeps every event in process memory; for local runs only.
+    Memory,
+    /// `null` or `null://`: counts events and persists nothing.
+    Null,
+}
+
+impl RepositoryKind {
+    /// Backends with a known scheme but no repository in this build are rejected
+    /// by name, so a typo and a missing backend read differently.
+    pub fn from_url(url: &str) -> AppResult<Self> {
+        let scheme = url.split_once("://").map_or(url, |(scheme, _)| scheme);
+        match scheme.to_ascii_lowercase().as_str() {
+            "postgres" | "postgresql" => Ok(Self::Postgres),
+            "memory" => Ok(Self::Memory),
+            "null" => Ok(Self::Null),
+            other @ ("sqlite" | "kafka" | "file") => Err(AppError::ConfigError(format!(
+                "DATABASE_URL scheme `{}` has no repository in this build; use postgres://, memory:// or null",
+                other
+            ))),
+            other => Err(AppError::ConfigError(format!("unknown DATABASE_URL scheme `{}`", other))),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Postgres => "postgres",
+            Self::Memory => "memory",
+            Self::Null => "null",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_error(url: &str) -> String {
+        match RepositoryKind::from_url(url) {
+            Err(AppError::ConfigError(reason)) => reason,
+            other => panic!("expected a ConfigError for {}, got {:?}", url, other),
+        }
+    }
+
+    #[test]
+    fn each_scheme_selects_its_repository() {
+        let cases = [
+            ("postgres://indexer@localhost/indexer", RepositoryKind::Postgres),
+            ("postgresql://indexer@localhost/indexer", RepositoryKind::Postgres),
+            ("memory://", RepositoryKind::Memory),
+            ("null", RepositoryKind::Null),
+            ("null://", RepositoryKind::Null),
+            ("POSTGRES://indexer@localhost/indexer", RepositoryKind::Postgres),
+        ];
+
+        for (url, kind) in cases {
+            assert_eq!(RepositoryKind::from_url(url).unwrap(), kind, "{}", url);
+        }
+    }
+
+    #[test]
+    fn known_backends_without_a_repository_are_named() {
+        for url in ["sqlite://indexer.db", "kafka://broker:9092/events", "file:///var/lib/indexer"] {
+            let reason = config_error(url);
+
+            assert!(reason.contains("has no repository in this build"), "{}", reason);
+        }
+    }
+
+    #[test]
+    fn unknown_scheme_is_a_config_error() {
+        assert!(config_error("mysql://localhost/indexer").contains("unknown DATABASE_URL scheme `mysql`"));
+        assert!(config_error("localhost:5432").contains("unknown DATABASE_URL scheme"));
+    }
+
+    #[test]
+    fn kind_names_match_their_schemes() {
+        for kind in [RepositoryKind::Postgres, RepositoryKind::Memory, RepositoryKind::Null] {
+            assert_eq!(RepositoryKind::from_url(&format!("{}://", kind.as_str())).unwrap(), kind);
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use std::{collections::HashMap, str::FromStr};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{account::Account, pubkey::Pubkey};
+
+use crate::{
+    application::{AccountMints, MintResolver},
+    domain,
+};
+
+/// `getMultipleAccounts` takes at most this many addresses per call.
+const MAX_ACCOUNTS_PER_CALL: usize = 100;
+
+// Offsets of the mint fields in each account layout
+const TOKEN_ACCOUNT_MINT: usize = 0;
+const AMM_V4_BASE_MINT: usize = 400;
+const AMM_V4_QUOTE_MINT: usize = 432;
+const CLMM_TOKEN_MINT_0: usize = 73;
+const CLMM_TOKEN_MINT_1: usize = 105;
+
+/// Reads mints straight from account data over RPC: SPL token accounts (both
+/// token programs), Raydium AMM v4 pools and Raydium CLMM pools.
+pub struct RpcMintResolver {
+    client: RpcClient,
+}
+
+impl RpcMintResolver {
+    pub fn new(rpc_url: String) -> Self {
+        Self { client: RpcClient::new(rpc_url) }
+    }
+
+    fn pubkey_at(data: &[u8], offset: usize) -> Option<String> {
+        let bytes: [u8; 32] = data.get(offset..offset + 32)?.try_into().ok()?;
+        Some(Pubkey::new_from_array(bytes).to_string())
+    }
+
+    fn decode(account: &Account) -> Option<AccountMints> {
+        let owner = account.owner.to_string();
+        let data = &account.data;
+        match owner.as_str() {
+            domain::TOKEN_PROGRAM_ID | domain::TOKEN_2022_PROGRAM_ID => {
+                Some(AccountMints::TokenAccount(Self::pubkey_at(data, TOKEN_ACCOUNT_MINT)?))
+            }
+            domain::RAYDIUM_V4_PROGRAM_ID => Some(AccountMints::Pool(
+                Self::pubkey_at(data, AMM_V4_BASE_MINT)?,
+                Self::pubkey_at(data, AMM_V4_QUOTE_MINT)?,
+            )),
+            domain::RAYDIUM_CLMM_PROGRAM_ID => Some(AccountMints::Pool(
+                Self::pubkey_at(data, CLMM_TOKEN_MINT_0)?,
+                Self::pubkey_at(data, CLMM_TOKEN_MINT_1)?,
+            )),
+            _ => None,
+        }
+    }
+}
+
+#[async_trait]
+impl MintResolver for RpcMintResolver {
+    async fn account_mints(&self, accounts: &[String]) -> Result<HashMap<String, AccountMints>> {
+        let keys: Vec<(String, Pubkey)> = accounts
+            .iter()
+            .filter_map(|a| Pubkey::from_str(a).ok().map(|k| (a.clone(), k)))
+            .collect();
+
+        let mut mints = HashMap::new();
+        for chunk in keys.chunks(MAX_ACCOUNTS_PER_CALL) {
+            let pubkeys: Vec<Pubkey> = chunk.iter().map(|(_, k)| *k).collect();
+            let fetched = self.client.get_multiple_accounts(&pubkeys).await?;
+            for ((address, _), account) in chunk.iter().zip(fetched) {
+                if let Some(resolved) = account.as_ref().and_then(Self::decode) {
+                    mints.insert(address.clone(), resolved);
+                }
+            }
+        }
+        Ok(mints)
+    }
+}
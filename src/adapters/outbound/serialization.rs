@@ -0,0 +1,610 @@
+use std::str::FromStr;
+
+use prost::Message;
+
+use crate::{
+    application::{AppError, AppResult},
+    domain::TransactionEvent,
+};
+
+/// Wire format for events leaving the indexer through a sink.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SerializationFormat {
+    #[default]
+    Json,
+    /// serde + bincode 1.x; compact, but only readable from Rust.
+    Bincode,
+    /// The `proto` messages below; compact and language-neutral.
+    Protobuf,
+}
+
+impl FromStr for SerializationFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "bincode" => Ok(Self::Bincode),
+            "protobuf" | "proto" => Ok(Self::Protobuf),
+            _ => Err(format!("unknown serialization format `{}`", value)),
+        }
+    }
+}
+
+impl SerializationFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::Bincode => "application/octet-stream",
+            Self::Protobuf => "application/x-protobuf",
+        }
+    }
+}
+
+fn encode_error(format: SerializationFormat, reason: impl ToString) -> AppError {
+    AppError::SinkError { sink: format!("{:?} encoder", format), reason: reason.to_string() }
+}
+
+pub fn serialize_event(event: &TransactionEvent, format: SerializationFormat) -> AppResult<Vec<u8>> {
+    match format {
+        SerializationFormat::Json => serde_json::to_vec(event).map_err(|e| encode_error(format, e)),
+        SerializationFormat::Bincode => bincode::serialize(event).map_err(|e| encode_error(format, e)),
+        SerializationFormat::Protobuf => Ok(proto::Event::from(event).encode_to_vec()),
+    }
+}
+
+/// A whole batch: a JSON array, a bincode `Vec`, or a `proto::EventBatch`.
+pub fn serialize_events(events: &[TransactionEvent], format: SerializationFormat) -> AppResult<Vec<u8>> {
+    match format {
+        SerializationFormat::Json => serde_json::to_vec(events).map_err(|e| encode_error(format, e)),
+        SerializationFormat::Bincode => bincode::serialize(events).map_err(|e| encode_error(format, e)),
+        SerializationFormat::Protobuf => Ok(proto::EventBatch {
+            events: events.iter().map(proto::Event::from).collect(),
+        }
+        .encode_to_vec()),
+    }
+}
+
+pub fn deserialize_events(bytes: &[u8], format: SerializationFormat) -> AppResult<Vec<TransactionEvent>> {
+    match format {
+        SerializationFormat::Json => serde_json::from_slice(bytes).map_err(|e| encode_error(format, e)),
+        SerializationFormat::Bincode => bincode::deserialize(bytes).map_err(|e| encode_error(format, e)),
+        SerializationFormat::Protobuf => proto::EventBatch::decode(bytes)
+            .map_err(|e| encode_error(format, e))?
+            .events
+            .into_iter()
+            .map(|ev| TransactionEvent::try_from(ev).map_err(|e| encode_error(format, e)))
+            .collect(),
+    }
+}
+
+/// Hand-written prost messages; field numbers are part of the wire contract and
+/// must never be reused.
+pub mod proto {
+    use crate::domain::{
+        ComputeBudgetEvent, JupiterSwapEvent, Pubkey58, PumpAmmSwapEvent, PumpFunTrade as DomainPumpFunTrade, RaydiumPoolType, RaydiumSwapEvent, RouteStep as DomainRouteStep, SolTransfer as DomainSolTransfer,
+        Signature58, TokenTransfer as DomainTokenTransfer, TransactionEvent,
+    };
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct EventBatch {
+        #[prost(message, repeated, tag = "1")]
+        pub events: Vec<Event>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Event {
+        #[prost(oneof = "event::Kind", tags = "1, 2, 3, 4, 5, 6, 7")]
+        pub kind: Option<event::Kind>,
+    }
+
+    pub mod event {
+        #[derive(Clone, PartialEq, prost::Oneof)]
+        pub enum Kind {
+            #[prost(message, tag = "1")]
+            TokenTransfer(super::TokenTransfer),
+            #[prost(message, tag = "2")]
+            RaydiumSwap(super::RaydiumSwap),
+            #[prost(message, tag = "3")]
+            JupiterSwap(super::JupiterSwap),
+            #[prost(message, tag = "4")]
+            PumpFunTrade(super::PumpFunTrade),
+            #[prost(message, tag = "5")]
+            ComputeBudget(super::ComputeBudget),
+            #[prost(message, tag = "6")]
+            SolTransfer(super::SolTransfer),
+            #[prost(message, tag = "7")]
+            PumpAmmSwap(super::PumpAmmSwap),
+        }
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct TokenTransfer {
+        #[prost(string, tag = "1")] pub signature: String,
+        #[prost(uint32, tag = "2")] pub instruction_index: u32,
+        #[prost(uint32, optional, tag = "3")] pub inner_index: Option<u32>,
+        #[prost(uint64, tag = "4")] pub slot: u64,
+        #[prost(int64, optional, tag = "5")] pub block_time: Option<i64>,
+        #[prost(string, tag = "6")] pub from: String,
+        #[prost(string, tag = "7")] pub to: String,
+        #[prost(string, optional, tag = "8")] pub mint: Option<String>,
+        #[prost(uint64, tag = "9")] pub amount: u64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct RaydiumSwap {
+        #[prost(string, tag = "1")] pub signature: String,
+        #[prost(uint32, tag = "2")] pub instruction_index: u32,
+        #[prost(uint32, optional, tag = "3")] pub inner_index: Option<u32>,
+        #[prost(uint64, tag = "4")] pub slot: u64,
+        #[prost(int64, optional, tag = "5")] pub block_time: Option<i64>,
+        #[prost(string, tag = "6")] pub pool_type: String,
+        #[prost(string, tag = "7")] pub amm_pool: String,
+        #[prost(string, tag = "8")] pub signer: String,
+        #[prost(uint64, tag = "9")] pub amount_in: u64,
+        #[prost(uint64, tag = "10")] pub min_amount_out: u64,
+        #[prost(uint64, tag = "11")] pub amount_received: u64,
+        #[prost(string, tag = "12")] pub mint_source: String,
+        #[prost(string, tag = "13")] pub mint_destination: String,
+        #[prost(uint32, tag = "14")] pub cpi_depth: u32,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct RouteStep {
+        #[prost(string, tag = "1")] pub swap_label: String,
+        #[prost(uint32, tag = "2")] pub percent: u32,
+        #[prost(uint32, tag = "3")] pub input_index: u32,
+        #[prost(uint32, tag = "4")] pub output_index: u32,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct JupiterSwap {
+        #[prost(string, tag = "1")] pub signature: String,
+        #[prost(uint32, tag = "2")] pub instruction_index: u32,
+        #[prost(uint32, optional, tag = "3")] pub inner_index: Option<u32>,
+        #[prost(uint64, tag = "4")] pub slot: u64,
+        #[prost(int64, optional, tag = "5")] pub block_time: Option<i64>,
+        #[prost(string, tag = "6")] pub signer: String,
+        #[prost(string, tag = "7")] pub amm_pool: String,
+        #[prost(string, tag = "8")] pub mint_in: String,
+        #[prost(string, tag = "9")] pub mint_out: String,
+        #[prost(uint64, tag = "10")] pub amount_in: u64,
+        #[prost(uint64, tag = "11")] pub amount_out: u64,
+        #[prost(uint32, tag = "12")] pub slippage_bps: u32,
+        #[prost(uint32, tag = "13")] pub platform_fee_bps: u32,
+        #[prost(message, repeated, tag = "14")] pub route_plan: Vec<RouteStep>,
+        #[prost(uint32, tag = "15")] pub cpi_depth: u32,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct PumpFunTrade {
+        #[prost(string, tag = "1")] pub signature: String,
+        #[prost(uint32, tag = "2")] pub instruction_index: u32,
+        #[prost(uint32, optional, tag = "3")] pub inner_index: Option<u32>,
+        #[prost(uint64, tag = "4")] pub slot: u64,
+        #[prost(int64, optional, tag = "5")] pub block_time: Option<i64>,
+        #[prost(string, tag = "6")] pub mint: String,
+        #[prost(bool, tag = "7")] pub is_buy: bool,
+        #[prost(string, tag = "8")] pub user: String,
+        #[prost(int64, optional, tag = "9")] pub timestamp: Option<i64>,
+        #[prost(uint64, tag = "10")] pub token_amount: u64,
+        #[prost(uint64, tag = "11")] pub sol_amount: u64,
+        #[prost(uint32, tag = "12")] pub cpi_depth: u32,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct PumpAmmSwap {
+        #[prost(string, tag = "1")] pub signature: String,
+        #[prost(uint32, tag = "2")] pub instruction_index: u32,
+        #[prost(uint32, optional, tag = "3")] pub inner_index: Option<u32>,
+        #[prost(uint64, tag = "4")] pub slot: u64,
+        #[prost(int64, optional, tag = "5")] pub block_time: Option<i64>,
+        #[prost(string, tag = "6")] pub pool: String,
+        #[prost(string, tag = "7")] pub user: String,
+        #[prost(string, tag = "8")] pub base_mint: String,
+        #[prost(string, tag = "9")] pub quote_mint: String,
+        #[prost(bool, tag = "10")] pub is_buy: bool,
+        #[prost(uint64, tag = "11")] pub base_amount: u64,
+        #[prost(uint64, tag = "12")] pub quote_amount: u64,
+        #[prost(uint64, optional, tag = "13")] pub pool_base_reserve: Option<u64>,
+        #[prost(uint64, optional, tag = "14")] pub pool_quote_reserve: Option<u64>,
+        #[prost(uint32, tag = "15")] pub cpi_depth: u32,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ComputeBudget {
+        #[prost(string, tag = "1")] pub signature: String,
+        #[prost(uint32, tag = "2")] pub instruction_index: u32,
+        #[prost(uint64, tag = "3")] pub slot: u64,
+        #[prost(int64, optional, tag = "4")] pub block_time: Option<i64>,
+        #[prost(uint32, optional, tag = "5")] pub unit_limit: Option<u32>,
+        #[prost(uint64, optional, tag = "6")] pub unit_price_micro_lamports: Option<u64>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SolTransfer {
+        #[prost(string, tag = "1")] pub signature: String,
+        #[prost(uint32, tag = "2")] pub instruction_index: u32,
+        #[prost(uint32, optional, tag = "3")] pub inner_index: Option<u32>,
+        #[prost(uint64, tag = "4")] pub slot: u64,
+        #[prost(int64, optional, tag = "5")] pub block_time: Option<i64>,
+        #[prost(string, tag = "6")] pub from: String,
+        #[prost(string, tag = "7")] pub to: String,
+        #[prost(uint64, tag = "8")] pub lamports: u64,
+    }
+
+    impl From<&TransactionEvent> for Event {
+        fn from(event: &TransactionEvent) -> Self {
+            let kind = match event {
+                TransactionEvent::TokenTransfer(t) => event::Kind::TokenTransfer(TokenTransfer {
+                    signature: t.signature.to_string(),
+                    instruction_index: t.instruction_index,
+                    inner_index: t.inner_index,
+                    slot: t.slot,
+                    block_time: t.block_time,
+                    from: t.from.to_string(),
+                    to: t.to.to_string(),
+                    mint: t.mint.clone(),
+                    amount: t.amount,
+                }),
+                TransactionEvent::RaydiumSwap(s) => event::Kind::RaydiumSwap(RaydiumSwap {
+                    signature: s.signature.to_string(),
+                    instruction_index: s.instruction_index,
+                    inner_index: s.inner_index,
+                    cpi_depth: s.cpi_depth,
+                    slot: s.slot,
+                    block_time: s.block_time,
+                    pool_type: s.pool_type.as_str().to_string(),
+                    amm_pool: s.amm_pool.clone(),
+                    signer: s.signer.clone(),
+                    amount_in: s.amount_in,
+                    min_amount_out: s.min_amount_out,
+                    amount_received: s.amount_received,
+                    mint_source: s.mint_source.clone(),
+                    mint_destination: s.mint_destination.clone(),
+                }),
+                TransactionEvent::JupiterSwap(s) => event::Kind::JupiterSwap(JupiterSwap {
+                    signature: s.signature.to_string(),
+                    instruction_index: s.instruction_index,
+                    inner_index: s.inner_index,
+                    cpi_depth: s.cpi_depth,
+                    slot: s.slot,
+                    block_time: s.block_time,
+                    signer: s.signer.clone(),
+                    amm_pool: s.amm_pool.clone(),
+                    mint_in: s.mint_in.clone(),
+                    mint_out: s.mint_out.clone(),
+                    amount_in: s.amount_in,
+                    amount_out: s.amount_out,
+                    slippage_bps: s.slippage_bps as u32,
+                    platform_fee_bps: s.platform_fee_bps as u32,
+                    route_plan: s.route_plan.iter().map(|r| RouteStep {
+                        swap_label: r.swap_label.clone(),
+                        percent: r.percent as u32,
+                        input_index: r.input_index as u32,
+                        output_index: r.output_index as u32,
+                    }).collect(),
+                }),
+                TransactionEvent::PumpFunTrade(t) => event::Kind::PumpFunTrade(PumpFunTrade {
+                    signature: t.signature.to_string(),
+                    instruction_index: t.instruction_index,
+                    inner_index: t.inner_index,
+                    slot: t.slot,
+                    block_time: t.block_time,
+                    mint: t.mint.clone(),
+                    is_buy: t.direction.is_buy(),
+                    user: t.user.clone(),
+                    timestamp: t.timestamp,
+                    token_amount: t.token_amount,
+                    sol_amount: t.sol_amount,
+                    cpi_depth: t.cpi_depth,
+                }),
+                TransactionEvent::PumpAmmSwap(s) => event::Kind::PumpAmmSwap(PumpAmmSwap {
+                    signature: s.signature.to_string(),
+                    instruction_index: s.instruction_index,
+                    inner_index: s.inner_index,
+                    slot: s.slot,
+                    block_time: s.block_time,
+                    pool: s.pool.clone(),
+                    user: s.user.clone(),
+                    base_mint: s.base_mint.clone(),
+                    quote_mint: s.quote_mint.clone(),
+                    is_buy: s.direction.is_buy(),
+                    base_amount: s.base_amount,
+                    quote_amount: s.quote_amount,
+                    pool_base_reserve: s.pool_base_reserve,
+                    pool_quote_reserve: s.pool_quote_reserve,
+                    cpi_depth: s.cpi_depth,
+                }),
+                TransactionEvent::ComputeBudget(b) => event::Kind::ComputeBudget(ComputeBudget {
+                    signature: b.signature.to_string(),
+                    instruction_index: b.instruction_index,
+                    slot: b.slot,
+                    block_time: b.block_time,
+                    unit_limit: b.unit_limit,
+                    unit_price_micro_lamports: b.unit_price_micro_lamports,
+                }),
+                TransactionEvent::SolTransfer(t) => event::Kind::SolTransfer(SolTransfer {
+                    signature: t.signature.to_string(),
+                    instruction_index: t.instruction_index,
+                    inner_index: t.inner_index,
+                    slot: t.slot,
+                    block_time: t.block_time,
+                    from: t.from.to_string(),
+                    to: t.to.to_string(),
+                    lamports: t.lamports,
+                }),
+            };
+            Event { kind: Some(kind) }
+        }
+    }
+
+    impl TryFrom<Event> for TransactionEvent {
+        type Error = String;
+
+        fn try_from(event: Event) -> Result<Self, Self::Error> {
+            let narrow = |v: u32, field: &str| u8::try_from(v).map_err(|_| format!("{} out of range: {}", field, v));
+            let signature = |v: String| Signature58::try_from(v).map_err(|e| e.to_string());
+            let address = |v: String| Pubkey58::try_from(v).map_err(|e| e.to_string());
+
+            Ok(match event.kind.ok_or("event with no kind")? {
+                event::Kind::TokenTransfer(t) => TransactionEvent::TokenTransfer(DomainTokenTransfer {
+                    from: address(t.from)?,
+                    to: address(t.to)?,
+                    slot: t.slot,
+                    block_time: t.block_time,
+                    amount: t.amount,
+                    signature: signature(t.signature)?,
+                    mint: t.mint,
+                    instruction_index: t.instruction_index,
+                    inner_index: t.inner_index,
+                }),
+                event::Kind::RaydiumSwap(s) => TransactionEvent::RaydiumSwap(RaydiumSwapEvent {
+                    pool_type: s.pool_type.parse::<RaydiumPoolType>()?,
+                    amm_pool: s.amm_pool,
+                    signer: s.signer,
+                    amount_in: s.amount_in,
+                    min_amount_out: s.min_amount_out,
+                    amount_received: s.amount_received,
+                    mint_source: s.mint_source,
+                    mint_destination: s.mint_destination,
+                    slot: s.slot,
+                    block_time: s.block_time,
+                    signature: signature(s.signature)?,
+                    instruction_index: s.instruction_index,
+                    inner_index: s.inner_index,
+                    cpi_depth: s.cpi_depth,
+                }),
+                event::Kind::JupiterSwap(s) => TransactionEvent::JupiterSwap(JupiterSwapEvent {
+                    signature: signature(s.signature)?,
+                    instruction_index: s.instruction_index,
+                    inner_index: s.inner_index,
+                    cpi_depth: s.cpi_depth,
+                    slot: s.slot,
+                    block_time: s.block_time,
+                    signer: s.signer,
+                    amm_pool: s.amm_pool,
+                    mint_in: s.mint_in,
+                    mint_out: s.mint_out,
+                    amount_in: s.amount_in,
+                    amount_out: s.amount_out,
+                    slippage_bps: u16::try_from(s.slippage_bps)
+                        .map_err(|_| format!("slippage_bps out of range: {}", s.slippage_bps))?,
+                    platform_fee_bps: narrow(s.platform_fee_bps, "platform_fee_bps")?,
+                    route_plan: s.route_plan.into_iter().map(|r| Ok(DomainRouteStep {
+                        swap_label: r.swap_label,
+                        percent: narrow(r.percent, "percent")?,
+                        input_index: narrow(r.input_index, "input_index")?,
+                        output_index: narrow(r.output_index, "output_index")?,
+                    })).collect::<Result<_, String>>()?,
+                }),
+                event::Kind::PumpFunTrade(t) => TransactionEvent::PumpFunTrade(DomainPumpFunTrade {
+                    signature: signature(t.signature)?,
+                    instruction_index: t.instruction_index,
+                    inner_index: t.inner_index,
+                    slot: t.slot,
+                    block_time: t.block_time,
+                    mint: t.mint,
+                    direction: t.is_buy.into(),
+                    user: t.user,
+                    timestamp: t.timestamp,
+                    token_amount: t.token_amount,
+                    sol_amount: t.sol_amount,
+                    cpi_depth: t.cpi_depth,
+                }),
+                event::Kind::PumpAmmSwap(s) => TransactionEvent::PumpAmmSwap(PumpAmmSwapEvent {
+                    signature: signature(s.signature)?,
+                    instruction_index: s.instruction_index,
+                    inner_index: s.inner_index,
+                    cpi_depth: s.cpi_depth,
+                    slot: s.slot,
+                    block_time: s.block_time,
+                    pool: s.pool,
+                    user: s.user,
+                    base_mint: s.base_mint,
+                    quote_mint: s.quote_mint,
+                    direction: s.is_buy.into(),
+                    base_amount: s.base_amount,
+                    quote_amount: s.quote_amount,
+                    pool_base_reserve: s.pool_base_reserve,
+                    pool_quote_reserve: s.pool_quote_reserve,
+                }),
+                event::Kind::ComputeBudget(b) => TransactionEvent::ComputeBudget(ComputeBudgetEvent {
+                    signature: signature(b.signature)?,
+                    instruction_index: b.instruction_index,
+                    slot: b.slot,
+                    block_time: b.block_time,
+                    unit_limit: b.unit_limit,
+                    unit_price_micro_lamports: b.unit_price_micro_lamports,
+                }),
+                event::Kind::SolTransfer(t) => TransactionEvent::SolTransfer(DomainSolTransfer {
+                    from: address(t.from)?,
+                    to: address(t.to)?,
+                    slot: t.slot,
+                    block_time: t.block_time,
+                    lamports: t.lamports,
+                    signature: signature(t.signature)?,
+                    instruction_index: t.instruction_index,
+                    inner_index: t.inner_index,
+                }),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{
+        ComputeBudgetEvent, JupiterSwapEvent, Pubkey58, PumpAmmSwapEvent, PumpFunTrade, RaydiumPoolType, RaydiumSwapEvent, RouteStep,
+        Signature58, SolTransfer, SwapDirection, TokenTransfer,
+    };
+
+    const FORMATS: [SerializationFormat; 3] = [SerializationFormat::Json, SerializationFormat::Bincode, SerializationFormat::Protobuf];
+
+    /// One event of every variant, with optional fields both set and unset.
+    fn every_variant() -> Vec<TransactionEvent> {
+        vec![
+            TransactionEvent::TokenTransfer(TokenTransfer {
+                from: Pubkey58::from([1u8; 32]),
+                to: Pubkey58::from([2u8; 32]),
+                slot: 10,
+                block_time: Some(1_700_000_000),
+                amount: 5_000,
+                signature: Signature58::from([1u8; 64]),
+                mint: Some("Mint".to_string()),
+                instruction_index: 1,
+                inner_index: Some(2),
+            }),
+            TransactionEvent::RaydiumSwap(RaydiumSwapEvent {
+                pool_type: RaydiumPoolType::Clmm,
+                amm_pool: "pool".to_string(),
+                signer: "signer".to_string(),
+                amount_in: 1_000,
+                min_amount_out: 900,
+                amount_received: 950,
+                mint_source: "MintA".to_string(),
+                mint_destination: "MintB".to_string(),
+                slot: 11,
+                block_time: None,
+                signature: Signature58::from([2u8; 64]),
+                instruction_index: 0,
+                inner_index: None,
+                cpi_depth: 1,
+            }),
+            TransactionEvent::JupiterSwap(JupiterSwapEvent {
+                signature: Signature58::from([3u8; 64]),
+                instruction_index: 3,
+                inner_index: None,
+                cpi_depth: 0,
+                slot: 12,
+                block_time: Some(1_700_000_001),
+                signer: "signer".to_string(),
+                amm_pool: "pool".to_string(),
+                mint_in: "MintA".to_string(),
+                mint_out: "MintB".to_string(),
+                amount_in: 2_000,
+                amount_out: 1_900,
+                slippage_bps: 50,
+                platform_fee_bps: 2,
+                route_plan: vec![
+                    RouteStep { swap_label: "Raydium".to_string(), percent: 60, input_index: 0, output_index: 1 },
+                    RouteStep { swap_label: "Whirlpool".to_string(), percent: 40, input_index: 0, output_index: 2 },
+                ],
+            }),
+            TransactionEvent::PumpFunTrade(PumpFunTrade {
+                signature: Signature58::from([4u8; 64]),
+                instruction_index: 0,
+                inner_index: Some(0),
+                cpi_depth: 2,
+                slot: 13,
+                mint: "Mint".to_string(),
+                direction: SwapDirection::Sell,
+                user: "user".to_string(),
+                timestamp: Some(1_700_000_002),
+                token_amount: 3_000,
+                sol_amount: 40,
+                block_time: None,
+            }),
+            TransactionEvent::ComputeBudget(ComputeBudgetEvent {
+                signature: Signature58::from([5u8; 64]),
+                instruction_index: 0,
+                slot: 14,
+                block_time: None,
+                unit_limit: Some(200_000),
+                unit_price_micro_lamports: None,
+            }),
+            TransactionEvent::SolTransfer(SolTransfer {
+                from: Pubkey58::from([6u8; 32]),
+                to: Pubkey58::from([7u8; 32]),
+                slot: 15,
+                block_time: None,
+                lamports: 1_000,
+                signature: Signature58::from([6u8; 64]),
+                instruction_index: 2,
+                inner_index: None,
+            }),
+            TransactionEvent::PumpAmmSwap(PumpAmmSwapEvent {
+                signature: Signature58::from([7u8; 64]),
+                instruction_index: 1,
+                inner_index: None,
+                cpi_depth: 0,
+                slot: 16,
+                block_time: Some(1_700_000_003),
+                pool: "pool".to_string(),
+                user: "user".to_string(),
+                base_mint: "Base".to_string(),
+                quote_mint: "Quote".to_string(),
+                direction: SwapDirection::Buy,
+                base_amount: 10,
+                quote_amount: 20,
+                pool_base_reserve: Some(1_000),
+                pool_quote_reserve: None,
+            }),
+        ]
+    }
+
+    /// Events have no `PartialEq`; their JSON form stands in for equality.
+    fn as_json(events: &[TransactionEvent]) -> Vec<String> {
+        events.iter().map(|ev| serde_json::to_string(ev).unwrap()).collect()
+    }
+
+    #[test]
+    fn every_variant_round_trips_in_every_format() {
+        for format in FORMATS {
+            for event in every_variant() {
+                let bytes = serialize_events(std::slice::from_ref(&event), format).unwrap();
+                let back = deserialize_events(&bytes, format).unwrap();
+                assert_eq!(as_json(&back), as_json(&[event.clone()]), "{:?} {}", format, event.kind());
+            }
+        }
+    }
+
+    #[test]
+    fn route_plan_survives_every_format() {
+        for format in FORMATS {
+            let bytes = serialize_events(&every_variant(), format).unwrap();
+            let back = deserialize_events(&bytes, format).unwrap();
+            let Some(TransactionEvent::JupiterSwap(swap)) = back.iter().find(|ev| ev.kind() == "jupiter_swap") else {
+                panic!("{:?} lost the Jupiter swap", format);
+            };
+            let steps: Vec<_> = swap.route_plan.iter().map(|s| (s.swap_label.as_str(), s.percent, s.input_index, s.output_index)).collect();
+            assert_eq!(steps, vec![("Raydium", 60, 0, 1), ("Whirlpool", 40, 0, 2)], "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn single_event_matches_its_batch_entry() {
+        let event = every_variant().remove(2);
+        let single = proto::Event::decode(&*serialize_event(&event, SerializationFormat::Protobuf).unwrap()).unwrap();
+        let back = TransactionEvent::try_from(single).unwrap();
+        assert_eq!(as_json(&[back]), as_json(&[event]));
+    }
+
+    #[test]
+    fn format_names_parse_case_insensitively() {
+        assert_eq!("JSON".parse(), Ok(SerializationFormat::Json));
+        assert_eq!("proto".parse(), Ok(SerializationFormat::Protobuf));
+        assert!("avro".parse::<SerializationFormat>().is_err());
+    }
+}
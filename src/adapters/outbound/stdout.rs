@@ -0,0 +1,244 @@
+use std::{
+    fmt::Write as _,
+    io::{self, Write},
+    str::FromStr,
+    sync::Mutex,
+};
+
+use async_trait::async_trait;
+
+use crate::{
+    application::{AppError, AppResult, EventSink, MintDecimals},
+    domain::{SwapProtocol, TransactionEvent, USDC_MINT, WSOL_MINT},
+};
+
+/// How much of each event `StdoutSink` prints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StdoutFormat {
+    /// Slot, kind, amounts and a shortened signature.
+    #[default]
+    Compact,
+    /// Adds the full signature, instruction position, block time and accounts.
+    Verbose,
+}
+
+impl FromStr for StdoutFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "compact" => Ok(Self::Compact),
+            "verbose" => Ok(Self::Verbose),
+            _ => Err(format!("unknown stdout format `{}`", value)),
+        }
+    }
+}
+
+const RESET: &str = "\x1b[0m";
+const DIM: &str = "\x1b[2m";
+const CYAN: &str = "\x1b[36m";
+const YELLOW: &str = "\x1b[33m";
+const MAGENTA: &str = "\x1b[35m";
+
+/// Prints one line per event, e.g. `[250000123] RAYDIUM swap 1.2 SOL -> 340000 7xKX… sig=5hTq…`,
+/// for watching a live stream with `tail` or `grep`. Amounts are in UI units for
+/// mints with known decimals and raw otherwise.
+pub struct StdoutSink<W: Write + Send = io::Stdout> {
+    out: Mutex<W>,
+    format: StdoutFormat,
+    color: bool,
+    decimals: MintDecimals,
+}
+
+impl StdoutSink {
+    pub fn new() -> Self {
+        Self::with_writer(io::stdout())
+    }
+}
+
+impl<W: Write + Send> StdoutSink<W> {
+    /// Print to `out` instead of stdout.
+    pub fn with_writer(out: W) -> Self {
+        Self { out: Mutex::new(out), format: StdoutFormat::default(), color: true, decimals: MintDecimals::default() }
+    }
+
+    pub fn with_format(mut self, format: StdoutFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_decimals(mut self, decimals: MintDecimals) -> Self {
+        self.decimals = decimals;
+        self
+    }
+
+    /// The writer, e.g. to read back what a buffer received.
+    pub fn into_inner(self) -> W {
+        self.out.into_inner().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn paint(&self, code: &str, text: &str) -> String {
+        if self.color { format!("{code}{text}{RESET}") } else { text.to_string() }
+    }
+
+    fn symbol(mint: &str) -> String {
+        match mint {
+            WSOL_MINT => "SOL".to_string(),
+            USDC_MINT => "USDC".to_string(),
+            other => format!("{}…", other.get(..4).unwrap_or(other)),
+        }
+    }
+
+    fn amount(&self, mint: &str, raw: u64) -> String {
+        format!("{} {}", self.decimals.to_ui(mint, raw as u128), Self::symbol(mint))
+    }
+
+    fn protocol_label(protocol: SwapProtocol) -> &'static str {
+        match protocol {
+            SwapProtocol::Raydium => "RAYDIUM",
+            SwapProtocol::Jupiter => "JUPITER",
+            SwapProtocol::PumpFun => "PUMPFUN",
+            SwapProtocol::PumpAmm => "PUMPSWAP",
+        }
+    }
+
+    /// One line, without the trailing newline.
+    pub fn format_event(&self, event: &TransactionEvent) -> String {
+        let mut line = self.paint(DIM, &format!("[{}]", event.slot()));
+        line.push(' ');
+
+        match event {
+            TransactionEvent::TokenTransfer(t) => {
+                let mint = t.mint.as_deref().unwrap_or("unknown");
+                let _ = write!(line, "{} {}", self.paint(YELLOW, "TOKEN transfer"), self.amount(mint, t.amount));
+                if self.format == StdoutFormat::Verbose {
+                    let _ = write!(line, " {} -> {}", t.from, t.to);
+                }
+            }
+            TransactionEvent::SolTransfer(t) => {
+                let _ = write!(line, "{} {}", self.paint(YELLOW, "SOL transfer"), self.amount(WSOL_MINT, t.lamports));
+                if self.format == StdoutFormat::Verbose {
+                    let _ = write!(line, " {} -> {}", t.from, t.to);
+                }
+            }
+            TransactionEvent::ComputeBudget(b) => {
+                let _ = write!(line, "{}", self.paint(MAGENTA, "COMPUTE budget"));
+                if let Some(limit) = b.unit_limit {
+                    let _ = write!(line, " limit={}", limit);
+                }
+                if let Some(price) = b.unit_price_micro_lamports {
+                    let _ = write!(line, " price={}µL/CU", price);
+                }
+            }
+            swap => {
+                let Some(s) = swap.as_normalized_swap() else { return line };
+                let label = format!("{} swap", Self::protocol_label(s.protocol));
+                let _ = write!(
+                    line,
+                    "{} {} -> {}",
+                    self.paint(CYAN, &label),
+                    self.amount(&s.mint_in, s.amount_in),
+                    self.amount(&s.mint_out, s.amount_out),
+                );
+                if self.format == StdoutFormat::Verbose {
+                    let _ = write!(line, " pool={} signer={}", s.pool, s.signer);
+                }
+            }
+        }
+
+        let signature = event.signature();
+        match self.format {
+            StdoutFormat::Compact => {
+                let short = format!("sig={}…", signature.get(..8).unwrap_or(signature));
+                let _ = write!(line, " {}", self.paint(DIM, &short));
+            }
+            StdoutFormat::Verbose => {
+                let time = event.block_time().map_or_else(|| "-".to_string(), |t| t.to_string());
+                let full = format!("ix={} time={} sig={}", event.instruction_index(), time, signature);
+                let _ = write!(line, " {}", self.paint(DIM, &full));
+            }
+        }
+        line
+    }
+}
+
+#[async_trait]
+impl<W: Write + Send> EventSink for StdoutSink<W> {
+    async fn emit(&self, events: &[TransactionEvent]) -> AppResult<()> {
+        let mut text = String::new();
+        for event in events {
+            text.push_str(&self.format_event(event));
+            text.push('\n');
+        }
+        let mut out = self.out.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        out.write_all(text.as_bytes())
+            .and_then(|()| out.flush())
+            .map_err(|e| AppError::SinkError { sink: "stdout".to_string(), reason: e.to_string() })
+    }
+
+    fn name(&self) -> &str { "stdout" }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{RaydiumPoolType, RaydiumSwapEvent, Signature58};
+
+    const BONK: &str = "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263";
+
+    fn swap() -> TransactionEvent {
+        TransactionEvent::RaydiumSwap(RaydiumSwapEvent {
+            pool_type: RaydiumPoolType::AmmV4,
+            amm_pool: "pool".to_string(),
+            signer: "signer".to_string(),
+            amount_in: 1_200_000_000,
+            min_amount_out: 300,
+            amount_received: 340,
+            mint_source: WSOL_MINT.to_string(),
+            mint_destination: BONK.to_string(),
+            slot: 250_000_123,
+            block_time: Some(1_700_000_000),
+            signature: Signature58::from([5u8; 64]),
+            instruction_index: 2,
+            inner_index: None,
+            cpi_depth: 0,
+        })
+    }
+
+    async fn printed(sink: StdoutSink<Vec<u8>>) -> String {
+        sink.emit(&[swap()]).await.unwrap();
+        String::from_utf8(sink.into_inner()).unwrap()
+    }
+
+    fn sink() -> StdoutSink<Vec<u8>> {
+        StdoutSink::with_writer(Vec::new()).with_decimals(MintDecimals::empty().with(WSOL_MINT, 9))
+    }
+
+    #[tokio::test]
+    async fn compact_line_has_slot_protocol_amounts_and_short_signature() {
+        let line = printed(sink().with_color(false)).await;
+
+        let signature = swap().signature().to_string();
+        assert_eq!(line, format!("[250000123] RAYDIUM swap 1.2 SOL -> 340 DezX… sig={}…\n", &signature[..8]));
+    }
+
+    #[tokio::test]
+    async fn verbose_line_adds_accounts_position_and_full_signature() {
+        let line = printed(sink().with_color(false).with_format(StdoutFormat::Verbose)).await;
+
+        let signature = swap().signature().to_string();
+        assert!(line.contains(" pool=pool signer=signer "), "{}", line);
+        assert!(line.ends_with(&format!("ix=2 time=1700000000 sig={}\n", signature)), "{}", line);
+    }
+
+    #[tokio::test]
+    async fn color_is_only_added_when_enabled() {
+        assert!(printed(sink()).await.contains(&format!("{CYAN}RAYDIUM swap{RESET}")));
+        assert!(!printed(sink().with_color(false)).await.contains('\x1b'));
+    }
+}
@@ -10,7 +10,7 @@ use teloxide::{
 
 use crate::{
     application::Notifier,
-    domain::{JupiterSwapEvent, PumpFunTrade, RaydiumSwapEvent, SwapEvent},
+    domain::{JupiterSwapEvent, PumpAmmSwapEvent, PumpFunTrade, RaydiumSwapEvent, SwapEvent},
 };
 
 pub struct TelegramNotifier {
@@ -72,7 +72,7 @@ impl TelegramNotifier {
     }
 
     fn fmt_pump_fun(&self, t: &PumpFunTrade) -> String {
-        let action = if t.is_buy { "Buy" } else { "Sell" };
+        let action = if t.direction.is_buy() { "Buy" } else { "Sell" };
         let sol = format!("{:.9}", t.sol_amount as f64 / 1_000_000_000.0);
         format!(
             "🚨 <b>Whale Trade (Pump.fun)</b>\n\n\
@@ -91,11 +91,31 @@ impl TelegramNotifier {
         )
     }
 
+    fn fmt_pump_amm(&self, s: &PumpAmmSwapEvent) -> String {
+        let action = if s.direction.is_buy() { "Buy" } else { "Sell" };
+        format!(
+            "🚨 <b>Whale Swap (PumpSwap)</b>\n\n\
+            <b>Action:</b> {}\n\
+            <b>Pool:</b> <code>{}</code>\n\
+            <b>Base:</b> {} <code>{}</code>\n\
+            <b>Quote:</b> {} <code>{}</code>\n\
+            <b>User:</b> <a href=\"https://solscan.io/account/{}\">{}</a>\n\n\
+            <a href=\"https://solscan.io/tx/{}\">View tx</a>",
+            action,
+            s.pool,
+            s.base_amount, s.base_mint,
+            s.quote_amount, s.quote_mint,
+            s.user, self.short(&s.user),
+            s.signature,
+        )
+    }
+
     fn format_alert(&self, swap: &SwapEvent) -> String {
         match swap {
             SwapEvent::Raydium(s) => self.fmt_raydium(s),
             SwapEvent::Jupiter(s) => self.fmt_jupiter(s),
             SwapEvent::PumpFun(t) => self.fmt_pump_fun(t),
+            SwapEvent::PumpAmm(s) => self.fmt_pump_amm(s),
         }
     }
 }
@@ -0,0 +1,203 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use reqwest::StatusCode;
+use sha2::Sha256;
+
+use crate::{
+    adapters::outbound::{Compression, SerializationFormat, serialize_events},
+    application::{AppError, AppResult, EventSink},
+    domain::TransactionEvent,
+};
+
+/// Header carrying `sha256=<hex HMAC of the body>` when a secret is configured.
+pub const SIGNATURE_HEADER: &str = "X-Indexer-Signature-256";
+
+#[derive(Debug, Clone)]
+pub struct WebhookConfig {
+    pub url: String,
+    /// Shared secret for the signature header. Unsigned when `None`.
+    pub secret: Option<String>,
+    pub timeout: Duration,
+    /// Attempts after the first one, for network errors, 429 and 5xx.
+    pub max_retries: u32,
+    /// Delay before the first retry; doubles on each subsequent one.
+    pub retry_backoff: Duration,
+    pub format: SerializationFormat,
+    /// Sent with a matching `Content-Encoding`; the signature covers the compressed body.
+    pub compression: Compression,
+}
+
+impl WebhookConfig {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            secret: None,
+            timeout: Duration::from_secs(10),
+            max_retries: 3,
+            retry_backoff: Duration::from_millis(500),
+            format: SerializationFormat::Json,
+            compression: Compression::None,
+        }
+    }
+}
+
+/// POSTs each batch, as a JSON array of events by default.
+pub struct WebhookSink {
+    client: reqwest::Client,
+    config: WebhookConfig,
+}
+
+impl WebhookSink {
+    pub fn new(config: WebhookConfig) -> AppResult<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(config.timeout)
+            .build()
+            .map_err(|e| Self::error(e.to_string()))?;
+        Ok(Self { client, config })
+    }
+
+    /// Hex HMAC-SHA256 of `body` under `secret`.
+    pub fn sign(secret: &str, body: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+            .expect("HMAC accepts keys of any length");
+        mac.update(body);
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    fn error(reason: String) -> AppError {
+        AppError::SinkError { sink: "webhook".to_string(), reason }
+    }
+
+    fn is_retryable(status: StatusCode) -> bool {
+        status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+    }
+
+    async fn post(&self, body: &[u8]) -> Result<(), (AppError, bool)> {
+        let mut request = self.client
+            .post(&self.config.url)
+            .header(reqwest::header::CONTENT_TYPE, self.config.format.content_type())
+            .body(body.to_vec());
+        if let Some(encoding) = self.config.compression.content_encoding() {
+            request = request.header(reqwest::header::CONTENT_ENCODING, encoding);
+        }
+        if let Some(secret) = &self.config.secret {
+            request = request.header(SIGNATURE_HEADER, format!("sha256={}", Self::sign(secret, body)));
+        }
+
+        match request.send().await {
+            Ok(resp) if resp.status().is_success() => Ok(()),
+            Ok(resp) => {
+                let status = resp.status();
+                Err((Self::error(format!("{} returned {}", self.config.url, status)), Self::is_retryable(status)))
+            }
+            Err(e) => Err((Self::error(e.to_string()), true)),
+        }
+    }
+}
+
+#[async_trait]
+impl EventSink for WebhookSink {
+    async fn emit(&self, events: &[TransactionEvent]) -> AppResult<()> {
+        if events.is_empty() {
+            return Ok(());
+        }
+
+        let body = self.config.compression.compress(&serialize_events(events, self.config.format)?)?;
+        let mut backoff = self.config.retry_backoff;
+        let mut attempt = 0;
+
+        loop {
+            match self.post(&body).await {
+                Ok(()) => return Ok(()),
+                Err((e, true)) if attempt < self.config.max_retries => {
+                    attempt += 1;
+                    tracing::warn!("Webhook attempt {} failed, retrying in {:?}: {}", attempt, backoff, e);
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+                Err((e, _)) => return Err(e),
+            }
+        }
+    }
+
+    fn name(&self) -> &str { "webhook" }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::VecDeque,
+        sync::{Arc, Mutex},
+    };
+
+    use axum::{Router, body::Bytes, extract::State, http::HeaderMap, routing::post};
+
+    use super::*;
+    use crate::domain::fixtures::transfer;
+
+    /// What the mock endpoint answers, in order (200 once exhausted), and what it was sent.
+    #[derive(Clone, Default)]
+    struct Endpoint {
+        statuses: Arc<Mutex<VecDeque<StatusCode>>>,
+        received: Arc<Mutex<Vec<(HeaderMap, Bytes)>>>,
+    }
+
+    async fn receive(State(endpoint): State<Endpoint>, headers: HeaderMap, body: Bytes) -> axum::http::StatusCode {
+        endpoint.received.lock().unwrap().push((headers, body));
+        let status = endpoint.statuses.lock().unwrap().pop_front().unwrap_or(StatusCode::OK);
+        axum::http::StatusCode::from_u16(status.as_u16()).unwrap()
+    }
+
+    /// Serves `endpoint` on a free local port and returns a sink config pointing at it.
+    async fn serve(endpoint: Endpoint) -> WebhookConfig {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let router = Router::new().route("/events", post(receive)).with_state(endpoint);
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        WebhookConfig { retry_backoff: Duration::from_millis(10), ..WebhookConfig::new(format!("http://{}/events", addr)) }
+    }
+
+    #[tokio::test]
+    async fn batch_is_posted_as_json_with_a_valid_signature() {
+        let endpoint = Endpoint::default();
+        let config = WebhookConfig { secret: Some("s3cret".to_string()), ..serve(endpoint.clone()).await };
+        let events = vec![transfer(1), transfer(2)];
+
+        WebhookSink::new(config).unwrap().emit(&events).await.unwrap();
+
+        let received = endpoint.received.lock().unwrap();
+        let [(headers, body)] = received.as_slice() else { panic!("expected one request, got {}", received.len()) };
+        let posted: Vec<TransactionEvent> = serde_json::from_slice(body).unwrap();
+        let posted: Vec<_> = posted.iter().map(|ev| ev.key()).collect();
+        assert_eq!(posted, events.iter().map(|ev| ev.key()).collect::<Vec<_>>());
+        let expected = format!("sha256={}", WebhookSink::sign("s3cret", body));
+        assert_eq!(headers[SIGNATURE_HEADER].to_str().unwrap(), expected);
+    }
+
+    #[tokio::test]
+    async fn server_errors_are_retried() {
+        let endpoint = Endpoint::default();
+        endpoint.statuses.lock().unwrap().extend([StatusCode::INTERNAL_SERVER_ERROR, StatusCode::TOO_MANY_REQUESTS]);
+        let config = serve(endpoint.clone()).await;
+
+        WebhookSink::new(config).unwrap().emit(&[transfer(1)]).await.unwrap();
+
+        assert_eq!(endpoint.received.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn client_error_fails_without_retrying() {
+        let endpoint = Endpoint::default();
+        endpoint.statuses.lock().unwrap().push_back(StatusCode::BAD_REQUEST);
+        let config = serve(endpoint.clone()).await;
+
+        let err = WebhookSink::new(config).unwrap().emit(&[transfer(1)]).await.unwrap_err();
+
+        assert!(matches!(err, AppError::SinkError { ref reason, .. } if reason.contains("400")));
+        assert_eq!(endpoint.received.lock().unwrap().len(), 1);
+        // Unsigned without a secret
+        assert!(!endpoint.received.lock().unwrap()[0].0.contains_key(SIGNATURE_HEADER));
+    }
+}
@@ -0,0 +1,211 @@
+use prost::Message;
+use solana_sdk::transaction::VersionedTransaction;
+use yellowstone_grpc_proto::geyser::SubscribeUpdate;
+
+use crate::{
+    adapters::parsers::VixenUtils,
+    application::{ParserResult, TransactionParser},
+    domain::{self, ComputeBudgetEvent, Signature58, SolanaTransaction, TransactionEvent, TxData},
+};
+
+// ComputeBudget instruction tags (first byte of the instruction data)
+const SET_COMPUTE_UNIT_LIMIT: u8 = 2;
+const SET_COMPUTE_UNIT_PRICE: u8 = 3;
+
+/// Limit and price gathered from every ComputeBudget instruction of one transaction.
+#[derive(Default)]
+struct Budget {
+    first_ix: Option<u32>,
+    unit_limit: Option<u32>,
+    unit_price: Option<u64>,
+}
+
+impl Budget {
+    fn apply(&mut self, ix_idx: usize, data: &[u8]) {
+        match data.first() {
+            Some(&SET_COMPUTE_UNIT_LIMIT) => {
+                let Some(bytes) = data.get(1..5) else { return };
+                self.unit_limit = Some(u32::from_le_bytes(bytes.try_into().unwrap()));
+            }
+            Some(&SET_COMPUTE_UNIT_PRICE) => {
+                let Some(bytes) = data.get(1..9) else { return };
+                self.unit_price = Some(u64::from_le_bytes(bytes.try_into().unwrap()));
+            }
+            _ => return,
+        }
+        self.first_ix.get_or_insert(ix_idx as u32);
+    }
+
+    fn into_event(self, signature: Signature58, slot: u64, block_time: Option<i64>) -> Option<TransactionEvent> {
+        let instruction_index = self.first_ix?;
+        Some(TransactionEvent::ComputeBudget(ComputeBudgetEvent {
+            signature,
+            instruction_index,
+            slot,
+            block_time,
+            unit_limit: self.unit_limit,
+            unit_price_micro_lamports: self.unit_price,
+        }))
+    }
+}
+
+pub struct ComputeBudgetParser;
+
+impl ComputeBudgetParser {
+    pub fn new() -> Self { Self }
+
+    fn parse_protobuf(&self, raw_bytes: &[u8], block_time: Option<i64>) -> ParserResult<Option<Vec<TransactionEvent>>> {
+        let update = SubscribeUpdate::decode(raw_bytes)?;
+        let mut events: Vec<TransactionEvent> = Vec::new();
+
+        if let Some(yellowstone_grpc_proto::geyser::subscribe_update::UpdateOneof::Transaction(tx_info)) = update.update_oneof {
+            let slot = tx_info.slot;
+            let Some(tx_details) = tx_info.transaction else { return Ok(None) };
+            let signature = Signature58::try_from(tx_details.signature.as_slice())?;
+            let Some(meta) = tx_details.meta else { return Ok(None) };
+            let Some(message) = tx_details.transaction.and_then(|t| t.message) else { return Ok(None) };
+
+            let account_keys = VixenUtils::account_strings(&VixenUtils::extract_accounts_from_message(&message, &meta));
+
+            let Some(pgm_idx) = account_keys.iter().position(|k| &**k == domain::COMPUTE_BUDGET_PROGRAM_ID) else {
+                return Ok(Some(events));
+            };
+
+            let mut budget = Budget::default();
+            for (ix_idx, ix) in message.instructions.iter().enumerate() {
+                if ix.program_id_index as usize == pgm_idx {
+                    budget.apply(ix_idx, &ix.data);
+                }
+            }
+            events.extend(budget.into_event(signature, slot, block_time));
+        }
+
+        Ok(Some(events))
+    }
+
+    fn parse_rpc(
+        &self,
+        tx: VersionedTransaction,
+        slot: u64,
+        signature: &str,
+        block_time: Option<i64>,
+    ) -> ParserResult<Option<Vec<TransactionEvent>>> {
+        let mut events: Vec<TransactionEvent> = Vec::new();
+
+        // ComputeBudget must be invoked from the static key list, never via a lookup table
+        let Some(pgm_idx) = tx.message.static_account_keys().iter()
+            .position(|k| k.to_string() == domain::COMPUTE_BUDGET_PROGRAM_ID) else {
+            return Ok(Some(events));
+        };
+
+        let mut budget = Budget::default();
+        for (ix_idx, ix) in tx.message.instructions().iter().enumerate() {
+            if ix.program_id_index as usize == pgm_idx {
+                budget.apply(ix_idx, &ix.data);
+            }
+        }
+        events.extend(budget.into_event(Signature58::try_from(signature)?, slot, block_time));
+
+        Ok(Some(events))
+    }
+}
+
+impl TransactionParser for ComputeBudgetParser {
+    fn name(&self) -> &str { "compute_budget" }
+
+    fn emitted_event_kinds(&self) -> &[&str] { &["compute_budget"] }
+
+    fn parse(&self, txn: SolanaTransaction) -> ParserResult<Option<Vec<TransactionEvent>>> {
+        match txn.data {
+            // Simulated sources emit empty frames; there is nothing to decode
+            TxData::Grpc(bytes) if bytes.is_empty() => Ok(None),
+            TxData::Grpc(bytes) => self.parse_protobuf(&bytes, txn.block_time),
+            TxData::Rpc { tx, .. } => self.parse_rpc(tx, txn.slot, &txn.signature, txn.block_time),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use solana_sdk::pubkey::Pubkey;
+    use yellowstone_grpc_proto::{
+        geyser::{SubscribeUpdateTransaction, SubscribeUpdateTransactionInfo, subscribe_update::UpdateOneof},
+        prelude::{CompiledInstruction, Message as GrpcMessage, Transaction, TransactionStatusMeta},
+    };
+
+    use super::*;
+    use crate::domain::{COMPUTE_BUDGET_PROGRAM_ID, SIGNATURE_LEN};
+
+    const FEE_PAYER: [u8; 32] = [9; 32];
+
+    /// Instructions over the fee payer (0) and the ComputeBudget program (1).
+    fn transaction(instructions: Vec<CompiledInstruction>) -> SolanaTransaction {
+        let program = Pubkey::from_str(COMPUTE_BUDGET_PROGRAM_ID).unwrap();
+        let message = GrpcMessage {
+            account_keys: vec![FEE_PAYER.to_vec(), program.to_bytes().to_vec()],
+            instructions,
+            ..Default::default()
+        };
+        let update = SubscribeUpdate {
+            update_oneof: Some(UpdateOneof::Transaction(SubscribeUpdateTransaction {
+                slot: 10,
+                transaction: Some(SubscribeUpdateTransactionInfo {
+                    signature: vec![7; SIGNATURE_LEN],
+                    transaction: Some(Transaction { signatures: vec![vec![7; SIGNATURE_LEN]], message: Some(message) }),
+                    meta: Some(TransactionStatusMeta::default()),
+                    ..Default::default()
+                }),
+            })),
+            ..Default::default()
+        };
+        SolanaTransaction {
+            signature: Signature58::from([7u8; 64]).to_string(),
+            success: true,
+            data: TxData::Grpc(update.encode_to_vec()),
+            slot: 10,
+            block_time: Some(1_700_000_000),
+            fee: None,
+            compute_units_consumed: None,
+            fee_payer: Pubkey::new_from_array(FEE_PAYER).to_string(),
+            recent_blockhash: String::new(),
+        }
+    }
+
+    fn budget_ix(tag: u8, value: &[u8]) -> CompiledInstruction {
+        let mut data = vec![tag];
+        data.extend(value);
+        CompiledInstruction { program_id_index: 1, accounts: Vec::new(), data }
+    }
+
+    #[test]
+    fn unit_limit_and_price_are_both_decoded() {
+        let txn = transaction(vec![
+            budget_ix(SET_COMPUTE_UNIT_LIMIT, &300_000u32.to_le_bytes()),
+            budget_ix(SET_COMPUTE_UNIT_PRICE, &25_000u64.to_le_bytes()),
+        ]);
+
+        let events = ComputeBudgetParser::new().parse(txn).unwrap().unwrap();
+
+        let [TransactionEvent::ComputeBudget(budget)] = events.as_slice() else { panic!("unexpected events {:?}", events) };
+        assert_eq!(budget.unit_limit, Some(300_000));
+        assert_eq!(budget.unit_price_micro_lamports, Some(25_000));
+        assert_eq!((budget.instruction_index, budget.slot, budget.block_time), (0, 10, Some(1_700_000_000)));
+    }
+
+    #[test]
+    fn truncated_price_is_left_unset() {
+        let txn = transaction(vec![
+            budget_ix(SET_COMPUTE_UNIT_PRICE, &[1, 2, 3]),
+            budget_ix(SET_COMPUTE_UNIT_LIMIT, &200_000u32.to_le_bytes()),
+        ]);
+
+        let events = ComputeBudgetParser::new().parse(txn).unwrap().unwrap();
+
+        let [TransactionEvent::ComputeBudget(budget)] = events.as_slice() else { panic!("unexpected events {:?}", events) };
+        assert_eq!((budget.unit_limit, budget.unit_price_micro_lamports), (Some(200_000), None));
+        // The first instruction decoded nothing, so the event points at the second
+        assert_eq!(budget.instruction_index, 1);
+    }
+}
@@ -0,0 +1,156 @@
+use std::{collections::HashMap, path::Path};
+
+use anyhow::Result;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::application::{ParserError, ParserResult};
+
+pub type Discriminator = [u8; 8];
+
+/// Instruction discriminators a parser accepts, grouped by the kind of instruction
+/// they decode as. Built-in entries come from the parser; more can be loaded from
+/// a JSON file so a program upgrade that adds an instruction variant does not need
+/// a rebuild.
+#[derive(Debug, Clone, Default)]
+pub struct DiscriminatorTable {
+    kinds: HashMap<Discriminator, String>,
+}
+
+/// One discriminator in the JSON file: eight bytes, sixteen hex characters, or an
+/// Anchor instruction name whose discriminator is derived with [`DiscriminatorTable::anchor`].
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawDiscriminator {
+    Bytes(Discriminator),
+    Text(String),
+}
+
+impl RawDiscriminator {
+    fn resolve(self) -> Discriminator {
+        match self {
+            RawDiscriminator::Bytes(bytes) => bytes,
+            RawDiscriminator::Text(text) => {
+                let mut bytes = [0u8; 8];
+                match hex::decode_to_slice(&text, &mut bytes) {
+                    Ok(()) => bytes,
+                    Err(_) => DiscriminatorTable::anchor(&text),
+                }
+            }
+        }
+    }
+}
+
+impl DiscriminatorTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, kind: &str, discriminator: Discriminator) -> Self {
+        self.insert(kind, discriminator);
+        self
+    }
+
+    /// Later entries win, so a loaded file can re-point a built-in discriminator.
+    pub fn insert(&mut self, kind: &str, discriminator: Discriminator) {
+        self.kinds.insert(discriminator, kind.to_string());
+    }
+
+    pub fn extend(&mut self, other: DiscriminatorTable) {
+        self.kinds.extend(other.kinds);
+    }
+
+    /// The kind `data`'s leading eight bytes are registered as, if any.
+    pub fn kind_of(&self, data: &[u8]) -> Option<&str> {
+        let discriminator: &Discriminator = data.get(..8)?.try_into().ok()?;
+        self.kinds.get(discriminator).map(String::as_str)
+    }
+
+    /// `data` with its discriminator replaced by the built-in one for its kind, for
+    /// IDL decoders that only match the discriminators they were generated with.
+    /// `None` when the table does not know the discriminator; a kind missing from
+    /// `builtin` is `UnknownDiscriminator`.
+    pub fn canonical(&self, data: &[u8], builtin: &[(&str, Discriminator)]) -> ParserResult<Option<Vec<u8>>> {
+        let Some(kind) = self.kind_of(data) else { return Ok(None) };
+        let Some((_, discriminator)) = builtin.iter().find(|(name, _)| *name == kind) else {
+            return Err(ParserError::UnknownDiscriminator(data[..8].to_vec()));
+        };
+        let mut canonical = discriminator.to_vec();
+        canonical.extend_from_slice(&data[8..]);
+        Ok(Some(canonical))
+    }
+
+    /// Anchor's instruction discriminator: `sha256("global:<name>")[..8]`.
+    pub fn anchor(name: &str) -> Discriminator {
+        let hash = Sha256::digest(format!("global:{}", name).as_bytes());
+        hash[..8].try_into().expect("sha256 is 32 bytes")
+    }
+
+    /// Tables keyed by parser name, from JSON shaped like
+    /// `{"raydium_clmm": {"swap": ["f8c69e91e17587c8", "swap_v3", [43, 4, 237, 11, 26, 201, 30, 98]]}}`.
+    pub fn from_json(json: &str) -> Result<HashMap<String, DiscriminatorTable>> {
+        let raw: HashMap<String, HashMap<String, Vec<RawDiscriminator>>> = serde_json::from_str(json)?;
+        let mut tables = HashMap::new();
+        for (parser, kinds) in raw {
+            let mut table = DiscriminatorTable::new();
+            for (kind, discriminators) in kinds {
+                for raw in discriminators {
+                    table.insert(&kind, raw.resolve());
+                }
+            }
+            tables.insert(parser, table);
+        }
+        Ok(tables)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<HashMap<String, DiscriminatorTable>> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_json_form_resolves_to_the_same_discriminator() {
+        let swap = DiscriminatorTable::anchor("swap");
+        let json = format!(
+            r#"{{"raydium_clmm": {{"swap": ["{}"], "swap_v2": [{:?}], "swap_v3": ["swap"]}}}}"#,
+            hex::encode(swap),
+            swap
+        );
+
+        let tables = DiscriminatorTable::from_json(&json).unwrap();
+
+        // All three name the same bytes, so exactly one kind survives
+        let table = &tables["raydium_clmm"];
+        assert_eq!(table.kinds.len(), 1);
+        assert!(table.kind_of(&swap).is_some());
+    }
+
+    #[test]
+    fn anchor_discriminator_matches_the_known_swap_value() {
+        assert_eq!(DiscriminatorTable::anchor("swap"), [248, 198, 158, 145, 225, 117, 135, 200]);
+    }
+
+    #[test]
+    fn canonical_swaps_in_the_builtin_discriminator() {
+        let builtin = [("route", [1; 8])];
+        let table = DiscriminatorTable::new().with("route", [1; 8]).with("route", [2; 8]).with("other", [3; 8]);
+
+        let mut loaded = vec![2; 8];
+        loaded.extend([7, 7]);
+        assert_eq!(table.canonical(&loaded, &builtin).unwrap(), Some(vec![1, 1, 1, 1, 1, 1, 1, 1, 7, 7]));
+        assert_eq!(table.canonical(&[4; 8], &builtin).unwrap(), None);
+        assert!(matches!(table.canonical(&[3; 8], &builtin), Err(ParserError::UnknownDiscriminator(_))));
+    }
+
+    #[test]
+    fn short_data_has_no_kind() {
+        let table = DiscriminatorTable::new().with("swap", [1; 8]);
+
+        assert_eq!(table.kind_of(&[1; 8]), Some("swap"));
+        assert_eq!(table.kind_of(&[1; 7]), None);
+    }
+}
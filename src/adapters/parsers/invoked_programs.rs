@@ -0,0 +1,65 @@
+use anyhow::Result;
+use prost::Message;
+use solana_sdk::pubkey::Pubkey;
+use solana_transaction_status::{UiInstruction, option_serializer::OptionSerializer};
+use yellowstone_grpc_proto::geyser::SubscribeUpdate;
+
+use crate::{
+    adapters::parsers::VixenUtils,
+    application::ProgramInspector,
+    domain::{SolanaTransaction, TxData},
+};
+
+/// Reads program IDs off top-level and inner instructions, resolving indices
+/// through the full (lookup-table-extended) account list.
+pub struct InvokedPrograms;
+
+impl InvokedPrograms {
+    pub fn new() -> Self { Self }
+
+    fn collect(indices: impl IntoIterator<Item = usize>, accounts: &[Pubkey]) -> Vec<String> {
+        let mut programs: Vec<Pubkey> = indices.into_iter().filter_map(|i| accounts.get(i).copied()).collect();
+        programs.sort_unstable();
+        programs.dedup();
+        VixenUtils::account_strings(&programs).iter().map(|k| k.to_string()).collect()
+    }
+}
+
+impl ProgramInspector for InvokedPrograms {
+    fn invoked_programs(&self, txn: &SolanaTransaction) -> Result<Vec<String>> {
+        match &txn.data {
+            TxData::Grpc(bytes) if bytes.is_empty() => Ok(Vec::new()),
+            TxData::Grpc(bytes) => {
+                let update = SubscribeUpdate::decode(bytes.as_slice())?;
+                let Some(yellowstone_grpc_proto::geyser::subscribe_update::UpdateOneof::Transaction(tx_info)) = update.update_oneof else {
+                    return Ok(Vec::new());
+                };
+                let Some(tx_details) = tx_info.transaction else { return Ok(Vec::new()) };
+                let (Some(meta), Some(message)) = (tx_details.meta, tx_details.transaction.and_then(|t| t.message)) else {
+                    return Ok(Vec::new());
+                };
+                let accounts = VixenUtils::extract_accounts_from_message(&message, &meta);
+                let outer = message.instructions.iter().map(|ix| ix.program_id_index as usize);
+                let inner = meta.inner_instructions.iter()
+                    .flat_map(|group| &group.instructions)
+                    .map(|ix| ix.program_id_index as usize);
+                Ok(Self::collect(outer.chain(inner), &accounts))
+            }
+            TxData::Rpc { tx, meta } => {
+                let accounts = VixenUtils::extract_accounts_from_rpc(tx, meta);
+                let outer = tx.message.instructions().iter().map(|ix| ix.program_id_index as usize);
+                let inner: Vec<usize> = match &meta.inner_instructions {
+                    OptionSerializer::Some(groups) => groups.iter()
+                        .flat_map(|group| &group.instructions)
+                        .filter_map(|ix| match ix {
+                            UiInstruction::Compiled(c) => Some(c.program_id_index as usize),
+                            _ => None,
+                        })
+                        .collect(),
+                    _ => Vec::new(),
+                };
+                Ok(Self::collect(outer.chain(inner), &accounts))
+            }
+        }
+    }
+}
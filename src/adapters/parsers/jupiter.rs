@@ -1,25 +1,64 @@
 use std::sync::Arc;
 
-use anyhow::Result;
 use prost::Message;
 use solana_sdk::{pubkey::Pubkey, transaction::VersionedTransaction};
-use solana_transaction_status::{UiTransactionStatusMeta, option_serializer::OptionSerializer};
+use solana_transaction_status::{UiTransactionStatusMeta, UiTransactionTokenBalance, option_serializer::OptionSerializer};
 use yellowstone_grpc_proto::geyser::SubscribeUpdate;
 use yellowstone_vixen_core::{Parser, instruction::{InstructionShared, InstructionUpdate, Path}};
 use yellowstone_vixen_proc_macro::include_vixen_parser;
 
 use crate::{
-    adapters::parsers::VixenUtils,
-    application::TransactionParser,
-    domain::{JupiterSwapEvent, RouteStep, SolanaTransaction, TransactionEvent, TxData},
+    adapters::parsers::{Base58Cache, Discriminator, DiscriminatorTable, VixenUtils},
+    application::{ParserError, ParserResult, TransactionParser},
+    domain::{JupiterSwapEvent, RouteStep, Signature58, SolanaTransaction, TransactionEvent, TxData},
 };
 
 include_vixen_parser!("idls/jupiter_v6.json");
 
-pub struct JupiterVixenParser;
+/// Fixed accounts of `sharedAccountsRoute`, the longer of the two indexed layouts.
+const ROUTE_ACCOUNTS: usize = 13;
+/// Stands in for accounts a short instruction leaves out. It is the System
+/// Program's key, which never fills an authority or mint position.
+const UNRESOLVED: [u8; 32] = [0; 32];
+/// The route instructions this parser indexes, with the discriminators the IDL
+/// decoder matches on.
+const ROUTE_KINDS: [(&str, Discriminator); 2] = [
+    ("route", [229, 23, 203, 151, 122, 227, 173, 42]),
+    ("shared_accounts_route", [193, 32, 155, 51, 65, 214, 156, 129]),
+];
+
+/// What the indexed route variants have in common once their account layouts
+/// are resolved.
+struct RouteSwap {
+    pool_label: &'static str,
+    signer: String,
+    mint_in: String,
+    mint_out: String,
+    amount_in: u64,
+    quoted_out_amount: u64,
+    platform_fee_bps: u8,
+    slippage_bps: u16,
+    route_plan: Vec<RouteStep>,
+}
+
+pub struct JupiterVixenParser {
+    discriminators: DiscriminatorTable,
+}
 
 impl JupiterVixenParser {
-    pub fn new() -> Self { Self }
+    pub fn new() -> Self {
+        let mut discriminators = DiscriminatorTable::new();
+        for (kind, discriminator) in ROUTE_KINDS {
+            discriminators.insert(kind, discriminator);
+        }
+        Self { discriminators }
+    }
+
+    /// Add to (or override) the built-in route discriminators.
+    pub fn with_discriminators(mut self, table: DiscriminatorTable) -> Self {
+        self.discriminators.extend(table);
+        self
+    }
 
     fn map_route_plan(plan: Vec<jupiter_v6::RoutePlanStep>) -> Vec<RouteStep> {
         plan.into_iter()
@@ -32,25 +71,115 @@ impl JupiterVixenParser {
             .collect()
     }
 
-    fn parse_protobuf(&self, raw_bytes: &[u8], block_time: i64) -> Result<Option<Vec<TransactionEvent>>> {
-        let update = match SubscribeUpdate::decode(raw_bytes) {
-            Ok(u) => u,
-            Err(_) => return Ok(None),
-        };
+    /// An instruction's accounts as the IDL decoder reads them. The decoder rejects
+    /// an instruction with fewer accounts than its layout, which would lose a swap
+    /// whose amounts decode fine, so the positions a short instruction leaves out
+    /// hold `UNRESOLVED`; `resolve_signer` and the mint helpers never report that
+    /// key as a real account. An index past the resolved keys is still an error.
+    fn route_accounts(indices: &[u8], all_accounts: &[Pubkey]) -> ParserResult<Vec<yellowstone_vixen_parser::Pubkey>> {
+        let mut accounts = VixenUtils::instruction_accounts(indices, all_accounts)?;
+        let missing = ROUTE_ACCOUNTS.saturating_sub(accounts.len());
+        accounts.extend(std::iter::repeat_with(|| yellowstone_vixen_parser::Pubkey::from(UNRESOLVED)).take(missing));
+        Ok(accounts)
+    }
+
+    /// The swap's transfer authority, or the fee payer when the instruction did
+    /// not carry a resolvable one (see `route_accounts`).
+    fn resolve_signer(authority: &yellowstone_vixen_parser::Pubkey, all_accounts: &[Pubkey], signature: &str) -> ParserResult<String> {
+        if authority.0 != UNRESOLVED {
+            return Ok(authority.to_string());
+        }
+        let fee_payer = all_accounts.first().ok_or(ParserError::MissingAccount { index: 0 })?;
+        tracing::debug!("Jupiter swap in {} has an unresolved authority, using the fee payer", signature);
+        Ok(fee_payer.to_string())
+    }
+
+    /// A mint named by the instruction's accounts, or "unknown" when unresolved.
+    fn resolved_mint(mint: &yellowstone_vixen_parser::Pubkey) -> String {
+        if mint.0 == UNRESOLVED { "unknown".to_string() } else { mint.to_string() }
+    }
+
+    /// A mint read through a token account's balances, or "unknown" when the
+    /// account is unresolved.
+    fn balance_mint(
+        account: &yellowstone_vixen_parser::Pubkey,
+        all_accounts: &[Pubkey],
+        pre_balances: &OptionSerializer<Vec<UiTransactionTokenBalance>>,
+    ) -> String {
+        if account.0 == UNRESOLVED { "unknown".to_string() } else { VixenUtils::get_mint(account, all_accounts, pre_balances) }
+    }
+
+    /// Resolve `route` and `sharedAccountsRoute`; other instructions give `None`.
+    /// `route` names its input mint only through the user's source token account,
+    /// so the mint comes from the pre-token balances; `sharedAccountsRoute` lists
+    /// the source mint among its accounts.
+    fn route_swap(
+        instruction: jupiter_v6::instruction::Instruction,
+        all_accounts: &[Pubkey],
+        pre_balances: &OptionSerializer<Vec<UiTransactionTokenBalance>>,
+        signature: &str,
+    ) -> ParserResult<Option<RouteSwap>> {
+        use jupiter_v6::instruction::Instruction;
+
+        Ok(Some(match instruction {
+            Instruction::Route { accounts, args } => RouteSwap {
+                pool_label: "Jupiter V6",
+                signer: Self::resolve_signer(&accounts.user_transfer_authority, all_accounts, signature)?,
+                mint_in: Self::balance_mint(&accounts.user_source_token_account, all_accounts, pre_balances),
+                mint_out: Self::resolved_mint(&accounts.destination_mint),
+                amount_in: args.in_amount,
+                quoted_out_amount: args.quoted_out_amount,
+                platform_fee_bps: args.platform_fee_bps,
+                slippage_bps: args.slippage_bps,
+                route_plan: Self::map_route_plan(args.route_plan),
+            },
+            Instruction::SharedAccountsRoute { accounts, args } => RouteSwap {
+                pool_label: "Jupiter V6 Shared",
+                signer: Self::resolve_signer(&accounts.user_transfer_authority, all_accounts, signature)?,
+                mint_in: Self::resolved_mint(&accounts.source_mint),
+                mint_out: Self::resolved_mint(&accounts.destination_mint),
+                amount_in: args.in_amount,
+                quoted_out_amount: args.quoted_out_amount,
+                platform_fee_bps: args.platform_fee_bps,
+                slippage_bps: args.slippage_bps,
+                route_plan: Self::map_route_plan(args.route_plan),
+            },
+            _ => return Ok(None),
+        }))
+    }
+
+    fn swap_event(swap: RouteSwap, signature: &Signature58, slot: u64, ix_idx: usize, block_time: Option<i64>) -> TransactionEvent {
+        TransactionEvent::JupiterSwap(JupiterSwapEvent {
+            amm_pool: swap.pool_label.to_string(),
+            signer: swap.signer,
+            amount_in: swap.amount_in,
+            amount_out: swap.quoted_out_amount,
+            mint_in: swap.mint_in,
+            mint_out: swap.mint_out,
+            slot,
+            signature: signature.clone(),
+            instruction_index: ix_idx as u32,
+            inner_index: None,
+            cpi_depth: 0,
+            block_time,
+            platform_fee_bps: swap.platform_fee_bps,
+            route_plan: swap.route_plan,
+            slippage_bps: swap.slippage_bps,
+        })
+    }
+
+    fn parse_protobuf(&self, raw_bytes: &[u8], block_time: Option<i64>) -> ParserResult<Option<Vec<TransactionEvent>>> {
+        let update = SubscribeUpdate::decode(raw_bytes)?;
 
         if let Some(yellowstone_grpc_proto::geyser::subscribe_update::UpdateOneof::Transaction(tx_info)) = update.update_oneof {
             let slot = tx_info.slot;
             let tx_details = match tx_info.transaction { Some(t) => t, None => return Ok(None) };
             let sig_bytes = tx_details.signature;
-            let sig_str = bs58::encode(&sig_bytes).into_string();
+            let sig_str = Signature58::try_from(sig_bytes.as_slice())?;
             let meta = match tx_details.meta { Some(m) => m, None => return Ok(None) };
             let message = match tx_details.transaction.and_then(|t| t.message) { Some(m) => m, None => return Ok(None) };
 
-            let all_accounts = VixenUtils::extract_accounts_from_grpc(
-                &message.account_keys,
-                &meta.loaded_writable_addresses,
-                &meta.loaded_readonly_addresses,
-            );
+            let all_accounts = VixenUtils::extract_accounts_from_message(&message, &meta);
 
             let pre_balances = VixenUtils::convert_token_balances_grpc(&meta.pre_token_balances);
             let mut events = Vec::new();
@@ -58,7 +187,8 @@ impl JupiterVixenParser {
             for (ix_idx, ix) in message.instructions.iter().enumerate() {
                 let pgm_idx = ix.program_id_index as usize;
                 if pgm_idx >= all_accounts.len() { continue; }
-                if all_accounts[pgm_idx].to_string() != crate::domain::JUPITER_V6_PROGRAM_ID { continue; }
+                if &*Base58Cache::global().encode(&all_accounts[pgm_idx]) != crate::domain::JUPITER_V6_PROGRAM_ID { continue; }
+                let Some(data) = self.discriminators.canonical(&ix.data, &ROUTE_KINDS)? else { continue };
 
                 let shared = Arc::new(InstructionShared {
                     signature: sig_bytes.clone(),
@@ -66,10 +196,7 @@ impl JupiterVixenParser {
                     ..Default::default()
                 });
 
-                let vixen_accounts: Vec<yellowstone_vixen_parser::Pubkey> = ix.accounts.iter()
-                    .filter_map(|&i| all_accounts.get(i as usize))
-                    .map(|a| yellowstone_vixen_parser::Pubkey::from(a.to_bytes()))
-                    .collect();
+                let vixen_accounts = Self::route_accounts(&ix.accounts, &all_accounts)?;
 
                 let inner_group = match meta.inner_instructions.iter().find(|g| g.index == ix_idx as u32) {
                     Some(g) => g,
@@ -84,7 +211,7 @@ impl JupiterVixenParser {
                 let update = InstructionUpdate {
                     program: yellowstone_vixen_parser::Pubkey::from(all_accounts[pgm_idx].to_bytes()),
                     accounts: vixen_accounts,
-                    data: ix.data.clone(),
+                    data,
                     shared,
                     inner,
                     path: Path::from(vec![]),
@@ -95,41 +222,9 @@ impl JupiterVixenParser {
                     tokio::runtime::Handle::current().block_on(jupiter_v6::InstructionParser.parse(&update))
                 });
 
-                match parsed {
-                    Ok(jupiter_v6::Instructions { instruction: jupiter_v6::instruction::Instruction::Route { accounts, args } }) => {
-                        let mint_in = VixenUtils::get_mint(&accounts.user_source_token_account, &all_accounts, &pre_balances);
-                        events.push(TransactionEvent::JupiterSwap(JupiterSwapEvent {
-                            amm_pool: "Jupiter V6".to_string(),
-                            signer: accounts.user_transfer_authority.to_string(),
-                            amount_in: args.in_amount,
-                            amount_out: args.quoted_out_amount,
-                            mint_in,
-                            mint_out: accounts.destination_mint.to_string(),
-                            slot,
-                            signature: sig_str.clone(),
-                            block_time,
-                            platform_fee_bps: args.platform_fee_bps,
-                            route_plan: Self::map_route_plan(args.route_plan),
-                            slippage_bps: args.slippage_bps,
-                        }));
-                    }
-                    Ok(jupiter_v6::Instructions { instruction: jupiter_v6::instruction::Instruction::SharedAccountsRoute { accounts, args } }) => {
-                        events.push(TransactionEvent::JupiterSwap(JupiterSwapEvent {
-                            amm_pool: "Jupiter V6 Shared".to_string(),
-                            signer: accounts.user_transfer_authority.to_string(),
-                            amount_in: args.in_amount,
-                            amount_out: args.quoted_out_amount,
-                            mint_in: accounts.source_mint.to_string(),
-                            mint_out: accounts.destination_mint.to_string(),
-                            slot,
-                            signature: sig_str.clone(),
-                            block_time,
-                            platform_fee_bps: args.platform_fee_bps,
-                            route_plan: Self::map_route_plan(args.route_plan),
-                            slippage_bps: args.slippage_bps,
-                        }));
-                    }
-                    _ => {}
+                let Ok(jupiter_v6::Instructions { instruction }) = parsed else { continue };
+                if let Some(swap) = Self::route_swap(instruction, &all_accounts, &pre_balances, &sig_str)? {
+                    events.push(Self::swap_event(swap, &sig_str, slot, ix_idx, block_time));
                 }
             }
 
@@ -145,21 +240,19 @@ impl JupiterVixenParser {
         meta: UiTransactionStatusMeta,
         slot: u64,
         signature: &str,
-        block_time: i64,
-    ) -> Result<Option<Vec<TransactionEvent>>> {
+        block_time: Option<i64>,
+    ) -> ParserResult<Option<Vec<TransactionEvent>>> {
         let mut events: Vec<TransactionEvent> = Vec::new();
         let msg = &tx.message;
+        let sig = Signature58::try_from(signature)?;
 
-        let mut all_accounts: Vec<Pubkey> = msg.static_account_keys().to_vec();
-        if let OptionSerializer::Some(loaded) = &meta.loaded_addresses {
-            for a in &loaded.writable { if let Ok(pk) = a.parse() { all_accounts.push(pk); } }
-            for a in &loaded.readonly  { if let Ok(pk) = a.parse() { all_accounts.push(pk); } }
-        }
+        let all_accounts: Vec<Pubkey> = VixenUtils::extract_accounts_from_rpc(&tx, &meta);
 
         for (ix_idx, ix) in msg.instructions().iter().enumerate() {
             let pgm_idx = ix.program_id_index as usize;
             if pgm_idx >= all_accounts.len() { continue; }
-            if all_accounts[pgm_idx].to_string() != crate::domain::JUPITER_V6_PROGRAM_ID { continue; }
+            if &*Base58Cache::global().encode(&all_accounts[pgm_idx]) != crate::domain::JUPITER_V6_PROGRAM_ID { continue; }
+            let Some(data) = self.discriminators.canonical(&ix.data, &ROUTE_KINDS)? else { continue };
 
             let inner_group = if let OptionSerializer::Some(ref groups) = meta.inner_instructions {
                 groups.iter().find(|g| g.index == ix_idx as u8)
@@ -169,7 +262,8 @@ impl JupiterVixenParser {
 
             let update = VixenUtils::to_vixen_update_rpc(
                 &all_accounts[pgm_idx],
-                &ix.data,
+                &data,
+                Self::route_accounts(&ix.accounts, &all_accounts)?,
                 &all_accounts,
                 signature,
                 slot,
@@ -180,41 +274,9 @@ impl JupiterVixenParser {
                 tokio::runtime::Handle::current().block_on(jupiter_v6::InstructionParser.parse(&update))
             });
 
-            match parsed {
-                Ok(jupiter_v6::Instructions { instruction: jupiter_v6::instruction::Instruction::Route { accounts, args } }) => {
-                    let mint_in = VixenUtils::get_mint(&accounts.user_source_token_account, &all_accounts, &meta.pre_token_balances);
-                    events.push(TransactionEvent::JupiterSwap(JupiterSwapEvent {
-                        amm_pool: "Jupiter V6".to_string(),
-                        signer: accounts.user_transfer_authority.to_string(),
-                        amount_in: args.in_amount,
-                        amount_out: args.quoted_out_amount,
-                        mint_in,
-                        mint_out: accounts.destination_mint.to_string(),
-                        slot,
-                        signature: signature.to_string(),
-                        block_time,
-                        platform_fee_bps: args.platform_fee_bps,
-                        route_plan: Self::map_route_plan(args.route_plan),
-                        slippage_bps: args.slippage_bps,
-                    }));
-                }
-                Ok(jupiter_v6::Instructions { instruction: jupiter_v6::instruction::Instruction::SharedAccountsRoute { accounts, args } }) => {
-                    events.push(TransactionEvent::JupiterSwap(JupiterSwapEvent {
-                        amm_pool: "Jupiter V6 Shared".to_string(),
-                        signer: accounts.user_transfer_authority.to_string(),
-                        amount_in: args.in_amount,
-                        amount_out: args.quoted_out_amount,
-                        mint_in: accounts.source_mint.to_string(),
-                        mint_out: accounts.destination_mint.to_string(),
-                        slot,
-                        signature: signature.to_string(),
-                        block_time,
-                        platform_fee_bps: args.platform_fee_bps,
-                        route_plan: Self::map_route_plan(args.route_plan),
-                        slippage_bps: args.slippage_bps,
-                    }));
-                }
-                _ => {}
+            let Ok(jupiter_v6::Instructions { instruction }) = parsed else { continue };
+            if let Some(swap) = Self::route_swap(instruction, &all_accounts, &meta.pre_token_balances, signature)? {
+                events.push(Self::swap_event(swap, &sig, slot, ix_idx, block_time));
             }
         }
 
@@ -225,10 +287,166 @@ impl JupiterVixenParser {
 impl TransactionParser for JupiterVixenParser {
     fn name(&self) -> &str { "jupiter_vixen" }
 
-    fn parse(&self, txn: SolanaTransaction) -> Result<Option<Vec<TransactionEvent>>> {
+    fn emitted_event_kinds(&self) -> &[&str] { &["jupiter_swap"] }
+
+    fn parse(&self, txn: SolanaTransaction) -> ParserResult<Option<Vec<TransactionEvent>>> {
         match txn.data {
+            // Simulated sources emit empty frames; there is nothing to decode
+            TxData::Grpc(bytes) if bytes.is_empty() => Ok(None),
             TxData::Grpc(bytes) => self.parse_protobuf(&bytes, txn.block_time),
             TxData::Rpc { tx, meta } => self.parse_rpc(tx, meta, txn.slot, &txn.signature, txn.block_time),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use yellowstone_grpc_proto::{
+        geyser::{SubscribeUpdateTransaction, SubscribeUpdateTransactionInfo, subscribe_update::UpdateOneof},
+        prelude::{CompiledInstruction, InnerInstructions, Message as GrpcMessage, Transaction, TransactionStatusMeta},
+    };
+
+    use super::*;
+    use crate::domain::{JUPITER_V6_PROGRAM_ID, SIGNATURE_LEN};
+
+    const FEE_PAYER: [u8; 32] = [9; 32];
+
+    /// `route` with an empty plan.
+    fn route_data(in_amount: u64, quoted_out_amount: u64) -> Vec<u8> {
+        let mut data = hex::decode("e517cb977ae3ad2a").unwrap();
+        data.extend(0u32.to_le_bytes());
+        data.extend(in_amount.to_le_bytes());
+        data.extend(quoted_out_amount.to_le_bytes());
+        data.extend(50u16.to_le_bytes());
+        data.push(0);
+        data
+    }
+
+    fn grpc_transaction(bytes: Vec<u8>) -> SolanaTransaction {
+        SolanaTransaction {
+            signature: Signature58::from([7u8; 64]).to_string(),
+            success: true,
+            data: TxData::Grpc(bytes),
+            slot: 10,
+            block_time: None,
+            fee: None,
+            compute_units_consumed: None,
+            fee_payer: Pubkey::new_from_array(FEE_PAYER).to_string(),
+            recent_blockhash: String::new(),
+        }
+    }
+
+    /// One top-level Jupiter instruction over the fee payer (0) and the program (1).
+    fn jupiter_transaction(accounts: Vec<u8>, data: Vec<u8>) -> SolanaTransaction {
+        jupiter_transaction_with_keys(Vec::new(), accounts, data)
+    }
+
+    /// `jupiter_transaction` with `extra_keys` from index 2 on.
+    fn jupiter_transaction_with_keys(extra_keys: Vec<[u8; 32]>, accounts: Vec<u8>, data: Vec<u8>) -> SolanaTransaction {
+        let program = Pubkey::from_str(JUPITER_V6_PROGRAM_ID).unwrap();
+        let mut account_keys = vec![FEE_PAYER.to_vec(), program.to_bytes().to_vec()];
+        account_keys.extend(extra_keys.iter().map(|key| key.to_vec()));
+        let message = GrpcMessage {
+            account_keys,
+            instructions: vec![CompiledInstruction { program_id_index: 1, accounts, data }],
+            ..Default::default()
+        };
+        let update = SubscribeUpdate {
+            update_oneof: Some(UpdateOneof::Transaction(SubscribeUpdateTransaction {
+                slot: 10,
+                transaction: Some(SubscribeUpdateTransactionInfo {
+                    signature: vec![7; SIGNATURE_LEN],
+                    transaction: Some(Transaction { signatures: vec![vec![7; SIGNATURE_LEN]], message: Some(message) }),
+                    meta: Some(TransactionStatusMeta {
+                        inner_instructions: vec![InnerInstructions { index: 0, instructions: Vec::new() }],
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+            })),
+            ..Default::default()
+        };
+        grpc_transaction(update.encode_to_vec())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn single_account_route_falls_back_to_the_fee_payer() {
+        let txn = jupiter_transaction(vec![0], route_data(1_000, 990));
+
+        let events = JupiterVixenParser::new().parse(txn).unwrap().expect("the swap is indexed");
+
+        let [TransactionEvent::JupiterSwap(swap)] = events.as_slice() else { panic!("unexpected events {:?}", events) };
+        assert_eq!(swap.signer, Pubkey::new_from_array(FEE_PAYER).to_string());
+        assert_eq!((swap.amount_in, swap.amount_out), (1_000, 990));
+        assert_eq!((swap.mint_in.as_str(), swap.mint_out.as_str()), ("unknown", "unknown"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn account_index_past_the_keys_is_an_error() {
+        let txn = jupiter_transaction(vec![0, 5], route_data(1_000, 990));
+
+        let err = JupiterVixenParser::new().parse(txn).unwrap_err();
+
+        assert!(matches!(err, ParserError::MissingAccount { index: 1 }));
+    }
+
+    #[test]
+    fn undecodable_update_is_an_error() {
+        let err = JupiterVixenParser::new().parse(grpc_transaction(vec![0xff; 8])).unwrap_err();
+
+        assert!(matches!(err, ParserError::DecodeFailed(_)));
+    }
+
+    const AUTHORITY: [u8; 32] = [21; 32];
+    const SOURCE_MINT: [u8; 32] = [22; 32];
+    const DESTINATION_MINT: [u8; 32] = [23; 32];
+    const OTHER: [u8; 32] = [24; 32];
+
+    /// `sharedAccountsRoute` with an empty plan.
+    fn shared_accounts_route_data(in_amount: u64, quoted_out_amount: u64) -> Vec<u8> {
+        let mut data = hex::decode("c1209b3341d69c81").unwrap();
+        data.push(3);
+        data.extend(0u32.to_le_bytes());
+        data.extend(in_amount.to_le_bytes());
+        data.extend(quoted_out_amount.to_le_bytes());
+        data.extend(75u16.to_le_bytes());
+        data.push(20);
+        data
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn shared_accounts_route_reads_its_signer_and_mints_from_its_accounts() {
+        // Keys: authority (2), source mint (3), destination mint (4), anything else (5)
+        let keys = vec![AUTHORITY, SOURCE_MINT, DESTINATION_MINT, OTHER];
+        let accounts = vec![5, 5, 2, 5, 5, 5, 5, 3, 4, 5, 5, 5, 1];
+        let txn = jupiter_transaction_with_keys(keys, accounts, shared_accounts_route_data(2_500, 2_400));
+
+        let events = JupiterVixenParser::new().parse(txn).unwrap().expect("the swap is indexed");
+
+        let [TransactionEvent::JupiterSwap(swap)] = events.as_slice() else { panic!("unexpected events {:?}", events) };
+        assert_eq!(swap.amm_pool, "Jupiter V6 Shared");
+        assert_eq!(swap.signer, Pubkey::new_from_array(AUTHORITY).to_string());
+        assert_eq!(swap.mint_in, Pubkey::new_from_array(SOURCE_MINT).to_string());
+        assert_eq!(swap.mint_out, Pubkey::new_from_array(DESTINATION_MINT).to_string());
+        assert_eq!((swap.amount_in, swap.amount_out), (2_500, 2_400));
+        assert_eq!((swap.slippage_bps, swap.platform_fee_bps), (75, 20));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn route_discriminator_loaded_from_json_is_indexed() {
+        let mut data = route_data(1_000, 990);
+        data[..8].copy_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        let txn = || jupiter_transaction(vec![0], data.clone());
+        let mut tables = DiscriminatorTable::from_json(r#"{"jupiter": {"route": ["0102030405060708"]}}"#).unwrap();
+        let parser = JupiterVixenParser::new().with_discriminators(tables.remove("jupiter").unwrap());
+
+        assert!(JupiterVixenParser::new().parse(txn()).unwrap().is_none());
+        let events = parser.parse(txn()).unwrap().expect("loaded discriminator not recognised");
+
+        let [TransactionEvent::JupiterSwap(swap)] = events.as_slice() else { panic!("unexpected events {:?}", events) };
+        assert_eq!(swap.amm_pool, "Jupiter V6");
+        assert_eq!((swap.amount_in, swap.amount_out), (1_000, 990));
+    }
+}
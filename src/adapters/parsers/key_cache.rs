@@ -0,0 +1,118 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        Arc, Mutex, OnceLock,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use solana_sdk::pubkey::Pubkey;
+
+pub const DEFAULT_KEY_CACHE_CAPACITY: usize = 10_000;
+
+static GLOBAL: OnceLock<Base58Cache> = OnceLock::new();
+
+/// Bounded memo of pubkey -> base58 string. Program IDs, token programs and busy
+/// pools appear in most transactions, so this skips re-encoding and re-allocating
+/// them on every parse. Oldest keys are evicted first once full.
+pub struct Base58Cache {
+    capacity: usize,
+    inner: Mutex<CacheInner>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+#[derive(Default)]
+struct CacheInner {
+    map: HashMap<[u8; 32], Arc<str>>,
+    order: VecDeque<[u8; 32]>,
+}
+
+impl Base58Cache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            inner: Mutex::new(CacheInner::default()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Cache shared by all parsers. Sized by `init_global`, or the default if
+    /// something encodes a key first.
+    pub fn global() -> &'static Base58Cache {
+        GLOBAL.get_or_init(|| Self::new(DEFAULT_KEY_CACHE_CAPACITY))
+    }
+
+    /// Set the shared cache's capacity. Returns false if it was already created.
+    pub fn init_global(capacity: usize) -> bool {
+        GLOBAL.set(Self::new(capacity)).is_ok()
+    }
+
+    pub fn encode(&self, key: &Pubkey) -> Arc<str> {
+        let bytes = key.to_bytes();
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+
+        if let Some(s) = inner.map.get(&bytes) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return s.clone();
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+
+        if inner.order.len() >= self.capacity {
+            if let Some(oldest) = inner.order.pop_front() {
+                inner.map.remove(&oldest);
+            }
+        }
+
+        let s: Arc<str> = Arc::from(key.to_string());
+        inner.map.insert(bytes, s.clone());
+        inner.order.push_back(bytes);
+        s
+    }
+
+    pub fn hits(&self) -> u64 { self.hits.load(Ordering::Relaxed) }
+    pub fn misses(&self) -> u64 { self.misses.load(Ordering::Relaxed) }
+
+    pub fn len(&self) -> usize {
+        self.inner.lock().map(|i| i.order.len()).unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_key_is_served_from_the_cache() {
+        let cache = Base58Cache::new(8);
+        let key = Pubkey::new_from_array([7; 32]);
+
+        let first = cache.encode(&key);
+        let second = cache.encode(&key);
+
+        assert_eq!(&*first, key.to_string());
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!((cache.hits(), cache.misses()), (1, 1));
+    }
+
+    #[test]
+    fn oldest_key_is_evicted_once_full() {
+        let cache = Base58Cache::new(2);
+        let keys: Vec<_> = (1..=3).map(|seed| Pubkey::new_from_array([seed; 32])).collect();
+        for key in &keys {
+            cache.encode(key);
+        }
+
+        assert_eq!(cache.len(), 2);
+        // The first key was evicted and is encoded again; the last is still cached
+        assert_eq!(&*cache.encode(&keys[0]), keys[0].to_string());
+        assert_eq!(cache.misses(), 4);
+        cache.encode(&keys[0]);
+        assert_eq!(cache.hits(), 1);
+    }
+}
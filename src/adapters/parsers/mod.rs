@@ -1,11 +1,134 @@
 mod spl_token;
 mod raydium_amm;
+mod raydium_clmm;
 mod jupiter;
 mod pump_fun;
+mod pump_amm;
+mod compute_budget;
+mod system_transfer;
 mod vixen_utils;
+mod key_cache;
+mod discriminators;
+mod invoked_programs;
 
 pub use spl_token::*;
 pub use raydium_amm::*;
+pub use raydium_clmm::*;
 pub use jupiter::*;
 pub use pump_fun::*;
+pub use pump_amm::*;
+pub use compute_budget::*;
+pub use system_transfer::*;
 pub use vixen_utils::*;
+pub use key_cache::*;
+pub use discriminators::*;
+pub use invoked_programs::*;
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use prost::Message;
+    use yellowstone_grpc_proto::geyser::{SubscribeUpdate, SubscribeUpdateSlot, subscribe_update::UpdateOneof};
+
+    use super::*;
+    use crate::{
+        application::{ParserResult, TransactionParser},
+        domain::{SolanaTransaction, TransactionEvent, TxData},
+    };
+
+    fn every_parser() -> Vec<Box<dyn TransactionParser>> {
+        vec![
+            Box::new(SplTokenTransfer::new()),
+            Box::new(SplTokenTransfer::new().with_mode(SplTransferMode::BalanceDiff)),
+            Box::new(RaydiumAmmParser::new()),
+            Box::new(RaydiumClmmParser::new()),
+            Box::new(JupiterVixenParser::new()),
+            Box::new(PumpFunParser::new()),
+            Box::new(PumpAmmParser::new()),
+            Box::new(ComputeBudgetParser::new()),
+            Box::new(SystemTransferParser::new()),
+        ]
+    }
+
+    fn grpc_transaction(bytes: Vec<u8>) -> SolanaTransaction {
+        SolanaTransaction {
+            signature: "sim_sig_1".to_string(),
+            success: true,
+            data: TxData::Grpc(bytes),
+            slot: 10,
+            block_time: None,
+            fee: None,
+            compute_units_consumed: None,
+            fee_payer: String::new(),
+            recent_blockhash: String::new(),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn empty_frame_parses_to_nothing() {
+        for parser in every_parser() {
+            let result = parser.parse(grpc_transaction(Vec::new()));
+
+            assert!(matches!(result, Ok(None)), "{} returned {:?}", parser.name(), result);
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn update_without_a_transaction_yields_no_events() {
+        let update = SubscribeUpdate {
+            update_oneof: Some(UpdateOneof::Slot(SubscribeUpdateSlot { slot: 10, ..Default::default() })),
+            ..Default::default()
+        };
+
+        for parser in every_parser() {
+            let result = parser.parse(grpc_transaction(update.encode_to_vec()));
+
+            let nothing = matches!(&result, Ok(None)) || matches!(&result, Ok(Some(events)) if events.is_empty());
+            assert!(nothing, "{} returned {:?}", parser.name(), result);
+        }
+    }
+
+    fn assert_shareable<T: Send + Sync + ?Sized>() {}
+
+    #[test]
+    fn parsers_are_plain_shared_trait_objects() {
+        assert_shareable::<dyn TransactionParser>();
+        let parsers: Vec<Arc<dyn TransactionParser>> = every_parser().into_iter().map(Arc::from).collect();
+
+        // parse hands back a result directly, not a future to poll
+        let system = parsers.last().unwrap().clone();
+        let result: ParserResult<Option<Vec<TransactionEvent>>> =
+            std::thread::spawn(move || system.parse(grpc_transaction(Vec::new()))).join().unwrap();
+
+        assert!(matches!(result, Ok(None)));
+        assert_eq!(parsers.iter().map(|p| p.name()).filter(|name| name.is_empty()).count(), 0);
+    }
+
+    #[test]
+    fn every_parser_declares_the_kinds_it_emits() {
+        for parser in every_parser() {
+            let expected: &[&str] = match parser.name() {
+                "spl_token_transfer" => &["token_transfer"],
+                "raydium_amm" | "raydium_clmm" => &["raydium_swap"],
+                "jupiter_vixen" => &["jupiter_swap"],
+                "pump_fun" => &["pump_fun_trade"],
+                "pump_amm" => &["pump_amm_swap"],
+                "compute_budget" => &["compute_budget"],
+                "system_transfer" => &["sol_transfer"],
+                other => panic!("no expected kinds for parser {}", other),
+            };
+
+            assert_eq!(parser.emitted_event_kinds(), expected, "{}", parser.name());
+        }
+    }
+
+    #[test]
+    fn every_event_kind_has_a_parser() {
+        let declared: Vec<&str> = every_parser().iter().flat_map(|p| p.emitted_event_kinds().to_vec()).collect();
+
+        for kind in ["token_transfer", "raydium_swap", "jupiter_swap", "pump_fun_trade", "pump_amm_swap", "compute_budget", "sol_transfer"] {
+            assert!(declared.contains(&kind), "no parser declares {}", kind);
+        }
+    }
+}
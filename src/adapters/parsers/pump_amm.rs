@@ -0,0 +1,338 @@
+use prost::Message;
+use solana_transaction_status::{UiInstruction, UiTransactionStatusMeta, UiTransactionTokenBalance, option_serializer::OptionSerializer};
+use solana_sdk::transaction::VersionedTransaction;
+use yellowstone_grpc_proto::geyser::SubscribeUpdate;
+
+use crate::{
+    adapters::parsers::{IxPosition, TransferLeg, VixenUtils},
+    application::{ParserError, ParserResult, TransactionParser},
+    domain::{self, PumpAmmSwapEvent, Signature58, SolanaTransaction, TransactionEvent, TxData},
+};
+
+// Anchor discriminators: sha256("global:<name>")[..8]
+const BUY_DISCM: [u8; 8] = [102, 6, 61, 18, 1, 218, 235, 234];
+const SELL_DISCM: [u8; 8] = [51, 230, 133, 164, 1, 127, 131, 173];
+
+// Account positions shared by buy and sell
+const POOL: usize = 0;
+const USER: usize = 1;
+const BASE_MINT: usize = 3;
+const QUOTE_MINT: usize = 4;
+const POOL_BASE_TOKEN_ACCOUNT: usize = 7;
+const POOL_QUOTE_TOKEN_ACCOUNT: usize = 8;
+
+/// Both instructions take two u64s: the exact base amount and a quote limit.
+const SWAP_ARGS_LEN: usize = 8 + 8;
+
+/// Accounts and amounts of one PumpSwap buy or sell, before key resolution.
+struct AmmSwap {
+    is_buy: bool,
+    /// `base_amount_out` on a buy, `base_amount_in` on a sell.
+    base_amount: u64,
+    /// `max_quote_amount_in` on a buy, `min_quote_amount_out` on a sell.
+    quote_limit: u64,
+    pool_idx: usize,
+    user_idx: usize,
+    base_mint_idx: usize,
+    quote_mint_idx: usize,
+    base_vault_idx: usize,
+    quote_vault_idx: usize,
+}
+
+impl AmmSwap {
+    /// The quote side moves into the pool's quote vault on a buy and out of it on a sell.
+    fn quote_leg(&self) -> TransferLeg {
+        if self.is_buy { TransferLeg::Destination } else { TransferLeg::Source }
+    }
+}
+
+/// Swaps on the PumpSwap AMM, where a Pump.fun token trades after its bonding
+/// curve completes. Instructions are decoded by hand; the quote amount actually
+/// moved comes from the vault transfer, falling back to the instruction's limit.
+pub struct PumpAmmParser;
+
+impl PumpAmmParser {
+    pub fn new() -> Self { Self }
+
+    fn decode_swap(data: &[u8], accounts: &[u8]) -> ParserResult<Option<AmmSwap>> {
+        let is_buy = match data.get(..8) {
+            Some(d) if d == BUY_DISCM => true,
+            Some(d) if d == SELL_DISCM => false,
+            _ => return Ok(None),
+        };
+        if accounts.len() <= POOL_QUOTE_TOKEN_ACCOUNT {
+            return Err(ParserError::MissingAccount { index: POOL_QUOTE_TOKEN_ACCOUNT });
+        }
+        if data.len() < 8 + SWAP_ARGS_LEN {
+            return Err(ParserError::TruncatedData { expected: 8 + SWAP_ARGS_LEN, got: data.len() });
+        }
+
+        let u64_at = |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().expect("length checked"));
+        Ok(Some(AmmSwap {
+            is_buy,
+            base_amount: u64_at(8),
+            quote_limit: u64_at(16),
+            pool_idx: accounts[POOL] as usize,
+            user_idx: accounts[USER] as usize,
+            base_mint_idx: accounts[BASE_MINT] as usize,
+            quote_mint_idx: accounts[QUOTE_MINT] as usize,
+            base_vault_idx: accounts[POOL_BASE_TOKEN_ACCOUNT] as usize,
+            quote_vault_idx: accounts[POOL_QUOTE_TOKEN_ACCOUNT] as usize,
+        }))
+    }
+
+    fn parse_protobuf(&self, raw_bytes: &[u8], block_time: Option<i64>) -> ParserResult<Option<Vec<TransactionEvent>>> {
+        let update = SubscribeUpdate::decode(raw_bytes)?;
+        let mut events: Vec<TransactionEvent> = Vec::new();
+
+        if let Some(yellowstone_grpc_proto::geyser::subscribe_update::UpdateOneof::Transaction(tx_info)) = update.update_oneof {
+            let slot = tx_info.slot;
+            let Some(tx_details) = tx_info.transaction else { return Ok(None) };
+            let signature = Signature58::try_from(tx_details.signature.as_slice())?;
+            let Some(message) = tx_details.transaction.and_then(|t| t.message) else { return Ok(None) };
+            let Some(meta) = tx_details.meta else { return Ok(None) };
+
+            let account_keys = VixenUtils::account_strings(&VixenUtils::extract_accounts_from_message(&message, &meta));
+
+            let Some(pgm_idx) = account_keys.iter().position(|k| &**k == domain::PUMP_AMM_PROGRAM_ID) else {
+                return Ok(Some(events));
+            };
+
+            // Top-level swaps first, then swaps routed through another program via CPI
+            let mut found: Vec<(IxPosition, AmmSwap)> = Vec::new();
+            for (ix_idx, ix) in message.instructions.iter().enumerate() {
+                if ix.program_id_index as usize != pgm_idx { continue; }
+                let Some(swap) = Self::decode_swap(&ix.data, &ix.accounts)? else { continue };
+                found.push((IxPosition::top_level(ix_idx), swap));
+            }
+            for group in &meta.inner_instructions {
+                for (inner_idx, ix) in group.instructions.iter().enumerate() {
+                    if ix.program_id_index as usize != pgm_idx { continue; }
+                    let Some(swap) = Self::decode_swap(&ix.data, &ix.accounts)? else { continue };
+                    found.push((IxPosition::inner(group.index as usize, inner_idx, ix.stack_height), swap));
+                }
+            }
+
+            let positions: Vec<IxPosition> = found.iter().map(|(pos, _)| *pos).collect();
+            for (pos, swap) in found {
+                let key = |idx: usize| account_keys.get(idx).map(|k| k.to_string());
+                let (Some(pool), Some(user), Some(base_mint), Some(quote_mint)) =
+                    (key(swap.pool_idx), key(swap.user_idx), key(swap.base_mint_idx), key(swap.quote_mint_idx))
+                else {
+                    tracing::warn!("PumpSwap ix in {} references accounts beyond the resolved key list", signature);
+                    continue;
+                };
+
+                let quote_amount = VixenUtils::find_transfer_amount_grpc(pos.instruction_index, pos.transfer_window(&positions), swap.quote_vault_idx, swap.quote_leg(), &meta.inner_instructions)
+                    .unwrap_or(swap.quote_limit);
+
+                events.push(TransactionEvent::PumpAmmSwap(PumpAmmSwapEvent {
+                    signature: signature.clone(),
+                    instruction_index: pos.instruction_index as u32,
+                    inner_index: pos.inner_index,
+                    cpi_depth: pos.cpi_depth,
+                    slot,
+                    block_time,
+                    pool,
+                    user,
+                    base_mint,
+                    quote_mint,
+                    direction: swap.is_buy.into(),
+                    base_amount: swap.base_amount,
+                    quote_amount,
+                    pool_base_reserve: VixenUtils::token_account_amount_grpc(swap.base_vault_idx, &meta.post_token_balances),
+                    pool_quote_reserve: VixenUtils::token_account_amount_grpc(swap.quote_vault_idx, &meta.post_token_balances),
+                }));
+            }
+        }
+
+        Ok(Some(events))
+    }
+
+    fn parse_rpc(
+        &self,
+        tx: VersionedTransaction,
+        meta: UiTransactionStatusMeta,
+        slot: u64,
+        signature: &str,
+        block_time: Option<i64>,
+    ) -> ParserResult<Option<Vec<TransactionEvent>>> {
+        let mut events: Vec<TransactionEvent> = Vec::new();
+        let signature = Signature58::try_from(signature)?;
+
+        let all_keys = VixenUtils::account_strings(&VixenUtils::extract_accounts_from_rpc(&tx, &meta));
+
+        let Some(pgm_idx) = all_keys.iter().position(|k| &**k == domain::PUMP_AMM_PROGRAM_ID) else {
+            return Ok(Some(events));
+        };
+
+        // Top-level swaps first, then swaps routed through another program via CPI
+        let mut found: Vec<(IxPosition, AmmSwap)> = Vec::new();
+        for (ix_idx, ix) in tx.message.instructions().iter().enumerate() {
+            if ix.program_id_index as usize != pgm_idx { continue; }
+            let Some(swap) = Self::decode_swap(&ix.data, &ix.accounts)? else { continue };
+            found.push((IxPosition::top_level(ix_idx), swap));
+        }
+        if let OptionSerializer::Some(groups) = &meta.inner_instructions {
+            for group in groups {
+                for (inner_idx, inner_ix) in group.instructions.iter().enumerate() {
+                    let UiInstruction::Compiled(c) = inner_ix else { continue };
+                    if c.program_id_index as usize != pgm_idx { continue; }
+                    let Ok(raw) = bs58::decode(&c.data).into_vec() else { continue };
+                    let Some(swap) = Self::decode_swap(&raw, &c.accounts)? else { continue };
+                    found.push((IxPosition::inner(group.index as usize, inner_idx, c.stack_height), swap));
+                }
+            }
+        }
+
+        let empty: Vec<UiTransactionTokenBalance> = vec![];
+        let post = if let OptionSerializer::Some(v) = &meta.post_token_balances { v.as_slice() } else { &empty };
+
+        let positions: Vec<IxPosition> = found.iter().map(|(pos, _)| *pos).collect();
+        for (pos, swap) in found {
+            let key = |idx: usize| all_keys.get(idx).map(|k| k.to_string());
+            let (Some(pool), Some(user), Some(base_mint), Some(quote_mint)) =
+                (key(swap.pool_idx), key(swap.user_idx), key(swap.base_mint_idx), key(swap.quote_mint_idx))
+            else {
+                tracing::warn!("PumpSwap ix in {} references accounts beyond the resolved key list", signature);
+                continue;
+            };
+
+            let quote_amount = VixenUtils::find_transfer_amount_rpc(pos.instruction_index, pos.transfer_window(&positions), swap.quote_vault_idx, swap.quote_leg(), &meta.inner_instructions)
+                .unwrap_or(swap.quote_limit);
+
+            events.push(TransactionEvent::PumpAmmSwap(PumpAmmSwapEvent {
+                signature: signature.clone(),
+                instruction_index: pos.instruction_index as u32,
+                inner_index: pos.inner_index,
+                cpi_depth: pos.cpi_depth,
+                slot,
+                block_time,
+                pool,
+                user,
+                base_mint,
+                quote_mint,
+                direction: swap.is_buy.into(),
+                base_amount: swap.base_amount,
+                quote_amount,
+                pool_base_reserve: VixenUtils::token_account_amount_rpc(swap.base_vault_idx, post),
+                pool_quote_reserve: VixenUtils::token_account_amount_rpc(swap.quote_vault_idx, post),
+            }));
+        }
+
+        Ok(Some(events))
+    }
+}
+
+impl TransactionParser for PumpAmmParser {
+    fn name(&self) -> &str { "pump_amm" }
+
+    fn emitted_event_kinds(&self) -> &[&str] { &["pump_amm_swap"] }
+
+    fn parse(&self, txn: SolanaTransaction) -> ParserResult<Option<Vec<TransactionEvent>>> {
+        match txn.data {
+            // Simulated sources emit empty frames; there is nothing to decode
+            TxData::Grpc(bytes) if bytes.is_empty() => Ok(None),
+            TxData::Grpc(bytes) => self.parse_protobuf(&bytes, txn.block_time),
+            TxData::Rpc { tx, meta } => self.parse_rpc(tx, meta, txn.slot, &txn.signature, txn.block_time),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use solana_sdk::pubkey::Pubkey;
+    use yellowstone_grpc_proto::{
+        geyser::{SubscribeUpdateTransaction, SubscribeUpdateTransactionInfo, subscribe_update::UpdateOneof},
+        prelude::{
+            CompiledInstruction, InnerInstruction, InnerInstructions, Message as GrpcMessage, TokenBalance, Transaction, TransactionStatusMeta,
+            UiTokenAmount,
+        },
+    };
+
+    use super::*;
+
+    fn key(seed: u8) -> Vec<u8> {
+        vec![seed; 32]
+    }
+
+    fn address(seed: u8) -> String {
+        Pubkey::new_from_array([seed; 32]).to_string()
+    }
+
+    fn token_transfer(amount: u64, source: u8, destination: u8) -> InnerInstruction {
+        let mut data = vec![3];
+        data.extend(amount.to_le_bytes());
+        InnerInstruction { program_id_index: 8, accounts: vec![source, destination, 0], data, stack_height: Some(2) }
+    }
+
+    fn vault_balance(account_index: u32, amount: u64) -> TokenBalance {
+        TokenBalance {
+            account_index,
+            ui_token_amount: Some(UiTokenAmount { amount: amount.to_string(), ..Default::default() }),
+            ..Default::default()
+        }
+    }
+
+    fn parse(message: GrpcMessage, meta: TransactionStatusMeta) -> Vec<TransactionEvent> {
+        let update = SubscribeUpdate {
+            update_oneof: Some(UpdateOneof::Transaction(SubscribeUpdateTransaction {
+                slot: 10,
+                transaction: Some(SubscribeUpdateTransactionInfo {
+                    signature: vec![7; domain::SIGNATURE_LEN],
+                    transaction: Some(Transaction { signatures: vec![vec![7; domain::SIGNATURE_LEN]], message: Some(message) }),
+                    meta: Some(meta),
+                    ..Default::default()
+                }),
+            })),
+            ..Default::default()
+        };
+        let txn = SolanaTransaction {
+            signature: Signature58::from([7u8; 64]).to_string(),
+            success: true,
+            data: TxData::Grpc(update.encode_to_vec()),
+            slot: 10,
+            block_time: None,
+            fee: None,
+            compute_units_consumed: None,
+            fee_payer: address(1),
+            recent_blockhash: String::new(),
+        };
+        PumpAmmParser::new().parse(txn).unwrap().unwrap_or_default()
+    }
+
+    #[test]
+    fn graduated_token_buy_records_amounts_and_pool_reserves() {
+        // User (0), program (1), pool (2), base and quote mints (3, 4), the pool's
+        // base and quote vaults (5, 6), the user's quote account (7), filler (8)
+        let program = Pubkey::from_str(domain::PUMP_AMM_PROGRAM_ID).unwrap().to_bytes().to_vec();
+        let mut data = BUY_DISCM.to_vec();
+        data.extend(5_000u64.to_le_bytes());
+        data.extend(1_100u64.to_le_bytes());
+        let message = GrpcMessage {
+            account_keys: vec![key(1), program, key(2), key(3), key(4), key(5), key(6), key(7), key(8)],
+            instructions: vec![CompiledInstruction { program_id_index: 1, accounts: vec![2, 0, 8, 3, 4, 8, 7, 5, 6], data }],
+            ..Default::default()
+        };
+        let meta = TransactionStatusMeta {
+            inner_instructions: vec![InnerInstructions {
+                index: 0,
+                instructions: vec![token_transfer(1_000, 7, 6), token_transfer(5_000, 5, 8)],
+            }],
+            post_token_balances: vec![vault_balance(5, 995_000), vault_balance(6, 201_000)],
+            ..Default::default()
+        };
+
+        let events = parse(message, meta);
+
+        let [TransactionEvent::PumpAmmSwap(swap)] = events.as_slice() else { panic!("unexpected events {:?}", events) };
+        assert!(swap.direction.is_buy());
+        assert_eq!((swap.pool.as_str(), swap.user.as_str()), (address(2).as_str(), address(1).as_str()));
+        assert_eq!((swap.base_mint.as_str(), swap.quote_mint.as_str()), (address(3).as_str(), address(4).as_str()));
+        // The quote actually paid, not the instruction's limit
+        assert_eq!((swap.base_amount, swap.quote_amount), (5_000, 1_000));
+        assert_eq!((swap.pool_base_reserve, swap.pool_quote_reserve), (Some(995_000), Some(201_000)));
+    }
+}
@@ -1,6 +1,5 @@
 use std::sync::Arc;
 
-use anyhow::Result;
 use prost::Message;
 use solana_sdk::pubkey::Pubkey;
 use yellowstone_grpc_proto::geyser::SubscribeUpdate;
@@ -8,17 +7,38 @@ use yellowstone_vixen_core::{Parser, instruction::{InstructionShared, Instructio
 use yellowstone_vixen_proc_macro::include_vixen_parser;
 
 use crate::{
-    adapters::parsers::VixenUtils,
-    application::TransactionParser,
-    domain::{self, PumpFunTrade, SolanaTransaction, TransactionEvent, TxData},
+    adapters::parsers::{Base58Cache, Discriminator, DiscriminatorTable, VixenUtils},
+    application::{ParserResult, TransactionParser},
+    domain::{self, PumpFunTrade, Signature58, SolanaTransaction, SwapDirection, TransactionEvent, TxData},
 };
 
 include_vixen_parser!("idls/pump_fun.json");
 
-pub struct PumpFunParser;
+/// The instructions this parser indexes, with the discriminators the IDL decoder
+/// matches on.
+const TRADE_KINDS: [(&str, Discriminator); 2] = [
+    ("buy", [102, 6, 61, 18, 1, 218, 235, 234]),
+    ("sell", [51, 230, 133, 164, 1, 127, 131, 173]),
+];
+
+pub struct PumpFunParser {
+    discriminators: DiscriminatorTable,
+}
 
 impl PumpFunParser {
-    pub fn new() -> Self { Self }
+    pub fn new() -> Self {
+        let mut discriminators = DiscriminatorTable::new();
+        for (kind, discriminator) in TRADE_KINDS {
+            discriminators.insert(kind, discriminator);
+        }
+        Self { discriminators }
+    }
+
+    /// Add to (or override) the built-in buy and sell discriminators.
+    pub fn with_discriminators(mut self, table: DiscriminatorTable) -> Self {
+        self.discriminators.extend(table);
+        self
+    }
 
     /// Sum SOL sent FROM a specific account in System Program Transfer inner ixs
     fn sol_sent_from(inner_ixs: &[InstructionUpdate], from: &yellowstone_vixen_parser::Pubkey) -> u64 {
@@ -41,7 +61,7 @@ impl PumpFunParser {
             .saturating_sub(pre.get(idx).copied().unwrap_or(0))
     }
 
-    fn parse_protobuf(&self, raw_bytes: &[u8], block_time: i64) -> Result<Option<Vec<TransactionEvent>>> {
+    fn parse_protobuf(&self, raw_bytes: &[u8], block_time: Option<i64>) -> ParserResult<Option<Vec<TransactionEvent>>> {
         let update = match SubscribeUpdate::decode(raw_bytes) {
             Ok(u) => u,
             Err(_) => return Ok(None),
@@ -51,22 +71,19 @@ impl PumpFunParser {
             let slot = tx_info.slot;
             let tx_details = match tx_info.transaction { Some(t) => t, None => return Ok(None) };
             let sig_bytes = tx_details.signature;
-            let sig_str = bs58::encode(&sig_bytes).into_string();
+            let sig_str = Signature58::try_from(sig_bytes.as_slice())?;
             let meta = match tx_details.meta { Some(m) => m, None => return Ok(None) };
             let message = match tx_details.transaction.and_then(|t| t.message) { Some(m) => m, None => return Ok(None) };
 
-            let all_accounts = VixenUtils::extract_accounts_from_grpc(
-                &message.account_keys,
-                &meta.loaded_writable_addresses,
-                &meta.loaded_readonly_addresses,
-            );
+            let all_accounts = VixenUtils::extract_accounts_from_message(&message, &meta);
 
             let mut events = Vec::new();
 
             for (ix_idx, ix) in message.instructions.iter().enumerate() {
                 let pgm_idx = ix.program_id_index as usize;
                 if pgm_idx >= all_accounts.len() { continue; }
-                if all_accounts[pgm_idx].to_string() != domain::PUMP_FUN_PROGRAM_ID { continue; }
+                if &*Base58Cache::global().encode(&all_accounts[pgm_idx]) != domain::PUMP_FUN_PROGRAM_ID { continue; }
+                let Some(data) = self.discriminators.canonical(&ix.data, &TRADE_KINDS)? else { continue };
 
                 let shared = Arc::new(InstructionShared {
                     signature: sig_bytes.clone(),
@@ -92,7 +109,7 @@ impl PumpFunParser {
                 let instruction_update = InstructionUpdate {
                     program: yellowstone_vixen_parser::Pubkey::from(all_accounts[pgm_idx].to_bytes()),
                     accounts: vixen_accounts,
-                    data: ix.data.clone(),
+                    data,
                     shared,
                     inner,
                     path: Path::from(vec![]),
@@ -108,11 +125,14 @@ impl PumpFunParser {
                         let sol_spent = Self::sol_sent_from(&instruction_update.inner, &accounts.user);
                         events.push(TransactionEvent::PumpFunTrade(PumpFunTrade {
                             signature: sig_str.clone(),
+                            instruction_index: ix_idx as u32,
+                            inner_index: None,
+                            cpi_depth: 0,
                             slot,
                             block_time,
                             timestamp: block_time,
                             mint: accounts.mint.to_string(),
-                            is_buy: true,
+                            direction: SwapDirection::Buy,
                             user: accounts.user.to_string(),
                             token_amount: args.amount,
                             sol_amount: sol_spent,
@@ -127,11 +147,14 @@ impl PumpFunParser {
 
                         events.push(TransactionEvent::PumpFunTrade(PumpFunTrade {
                             signature: sig_str.clone(),
+                            instruction_index: ix_idx as u32,
+                            inner_index: None,
+                            cpi_depth: 0,
                             slot,
                             block_time,
                             timestamp: block_time,
                             mint: accounts.mint.to_string(),
-                            is_buy: false,
+                            direction: SwapDirection::Sell,
                             user: accounts.user.to_string(),
                             token_amount: args.amount,
                             sol_amount: sol_received,
@@ -151,10 +174,103 @@ impl PumpFunParser {
 impl TransactionParser for PumpFunParser {
     fn name(&self) -> &str { "pump_fun" }
 
-    fn parse(&self, txn: SolanaTransaction) -> Result<Option<Vec<TransactionEvent>>> {
+    fn emitted_event_kinds(&self) -> &[&str] { &["pump_fun_trade"] }
+
+    fn parse(&self, txn: SolanaTransaction) -> ParserResult<Option<Vec<TransactionEvent>>> {
         match txn.data {
+            // Simulated sources emit empty frames; there is nothing to decode
+            TxData::Grpc(bytes) if bytes.is_empty() => Ok(None),
             TxData::Grpc(bytes) => self.parse_protobuf(&bytes, txn.block_time),
             TxData::Rpc { .. } => Ok(None), // RPC backfill not yet supported for PumpFun
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use yellowstone_grpc_proto::{
+        geyser::{SubscribeUpdateTransaction, SubscribeUpdateTransactionInfo, subscribe_update::UpdateOneof},
+        prelude::{CompiledInstruction, InnerInstructions, Message as GrpcMessage, Transaction, TransactionStatusMeta},
+    };
+
+    use super::*;
+    use crate::domain::{PUMP_FUN_PROGRAM_ID, SIGNATURE_LEN};
+
+    const USER: [u8; 32] = [9; 32];
+    const MINT: [u8; 32] = [22; 32];
+    const OTHER: [u8; 32] = [24; 32];
+
+    fn buy_data(discriminator: Discriminator, amount: u64, max_sol_cost: u64) -> Vec<u8> {
+        let mut data = discriminator.to_vec();
+        data.extend(amount.to_le_bytes());
+        data.extend(max_sol_cost.to_le_bytes());
+        data
+    }
+
+    /// One top-level `buy` over keys: user (0), the program (1), mint (2) and
+    /// anything else (3).
+    fn buy_transaction(data: Vec<u8>) -> SolanaTransaction {
+        let program = Pubkey::from_str(PUMP_FUN_PROGRAM_ID).unwrap();
+        let account_keys = vec![USER.to_vec(), program.to_bytes().to_vec(), MINT.to_vec(), OTHER.to_vec()];
+        let accounts = vec![3, 3, 2, 3, 3, 3, 0, 3, 3, 3, 3, 1, 3, 3, 3, 3];
+        let message = GrpcMessage {
+            account_keys,
+            instructions: vec![CompiledInstruction { program_id_index: 1, accounts, data }],
+            ..Default::default()
+        };
+        let update = SubscribeUpdate {
+            update_oneof: Some(UpdateOneof::Transaction(SubscribeUpdateTransaction {
+                slot: 10,
+                transaction: Some(SubscribeUpdateTransactionInfo {
+                    signature: vec![7; SIGNATURE_LEN],
+                    transaction: Some(Transaction { signatures: vec![vec![7; SIGNATURE_LEN]], message: Some(message) }),
+                    meta: Some(TransactionStatusMeta {
+                        inner_instructions: vec![InnerInstructions { index: 0, instructions: Vec::new() }],
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+            })),
+            ..Default::default()
+        };
+        SolanaTransaction {
+            signature: Signature58::from([7u8; 64]).to_string(),
+            success: true,
+            data: TxData::Grpc(update.encode_to_vec()),
+            slot: 10,
+            block_time: None,
+            fee: None,
+            compute_units_consumed: None,
+            fee_payer: Pubkey::new_from_array(USER).to_string(),
+            recent_blockhash: String::new(),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn builtin_buy_discriminator_is_indexed() {
+        let txn = buy_transaction(buy_data(TRADE_KINDS[0].1, 5_000, 100));
+
+        let events = PumpFunParser::new().parse(txn).unwrap().expect("the buy is indexed");
+
+        let [TransactionEvent::PumpFunTrade(trade)] = events.as_slice() else { panic!("unexpected events {:?}", events) };
+        assert_eq!(trade.direction, SwapDirection::Buy);
+        assert_eq!(trade.token_amount, 5_000);
+        assert_eq!(trade.mint, Pubkey::new_from_array(MINT).to_string());
+        assert_eq!(trade.user, Pubkey::new_from_array(USER).to_string());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn buy_discriminator_loaded_from_json_is_indexed() {
+        let txn = || buy_transaction(buy_data([1, 2, 3, 4, 5, 6, 7, 8], 5_000, 100));
+        let mut tables = DiscriminatorTable::from_json(r#"{"pump_fun": {"buy": ["0102030405060708"]}}"#).unwrap();
+        let parser = PumpFunParser::new().with_discriminators(tables.remove("pump_fun").unwrap());
+
+        assert!(PumpFunParser::new().parse(txn()).unwrap().is_none());
+        let events = parser.parse(txn()).unwrap().expect("loaded discriminator not recognised");
+
+        let [TransactionEvent::PumpFunTrade(trade)] = events.as_slice() else { panic!("unexpected events {:?}", events) };
+        assert_eq!((trade.direction, trade.token_amount), (SwapDirection::Buy, 5_000));
+    }
+}
@@ -1,79 +1,129 @@
-use anyhow::Result;
 use borsh::{BorshDeserialize, BorshSerialize};
 use prost::Message;
-use solana_transaction_status::{UiInnerInstructions, UiInstruction, UiTransactionStatusMeta, UiTransactionTokenBalance, option_serializer::OptionSerializer};
+use solana_transaction_status::{UiInstruction, UiTransactionStatusMeta, UiTransactionTokenBalance, option_serializer::OptionSerializer};
 use solana_sdk::transaction::VersionedTransaction;
-use yellowstone_grpc_proto::{geyser::SubscribeUpdate, prelude::TokenBalance};
+use yellowstone_grpc_proto::geyser::SubscribeUpdate;
 
 use crate::{
-    adapters::parsers::VixenUtils,
-    application::TransactionParser,
-    domain::{self, RaydiumSwapEvent, SolanaTransaction, TransactionEvent, TxData},
+    adapters::parsers::{IxPosition, TransferLeg, VixenUtils},
+    application::{ParserError, ParserResult, TransactionParser},
+    domain::{self, RaydiumPoolType, RaydiumSwapEvent, Signature58, SolanaTransaction, TransactionEvent, TxData},
 };
 
+const SWAP_BASE_IN: u8 = 9;
+const SWAP_BASE_OUT: u8 = 11;
+
 #[derive(BorshDeserialize, BorshSerialize, Debug)]
 pub struct RaydiumSwapInstruction {
     pub amount_in: u64,
     pub min_amount_out: u64,
 }
 
+#[derive(BorshDeserialize, BorshSerialize, Debug)]
+pub struct RaydiumSwapBaseOutInstruction {
+    pub max_amount_in: u64,
+    pub amount_out: u64,
+}
+
+/// Accounts of one AMM v4 swap instruction, before key resolution.
+struct AmmSwap {
+    opcode: u8,
+    pool_idx: usize,
+    src_idx: usize,
+    dst_idx: usize,
+    signer_idx: usize,
+}
+
 pub struct RaydiumAmmParser;
 
 impl RaydiumAmmParser {
     pub fn new() -> Self { Self }
 
-    fn parse_protobuf(&self, raw_bytes: &[u8], block_time: i64) -> Result<Option<Vec<TransactionEvent>>> {
+    fn decode_swap(data: &[u8], accounts: &[u8]) -> Option<AmmSwap> {
+        let opcode = match data.first().copied() {
+            Some(op @ (SWAP_BASE_IN | SWAP_BASE_OUT)) => op,
+            _ => return None,
+        };
+        if data.len() < 17 || accounts.len() <= 17 { return None; }
+
+        Some(AmmSwap {
+            opcode,
+            pool_idx: accounts[1] as usize,
+            src_idx: accounts[15] as usize,
+            dst_idx: accounts[16] as usize,
+            signer_idx: accounts[17] as usize,
+        })
+    }
+
+    fn parse_protobuf(&self, raw_bytes: &[u8], block_time: Option<i64>) -> ParserResult<Option<Vec<TransactionEvent>>> {
         let update = SubscribeUpdate::decode(raw_bytes)?;
         let mut events: Vec<TransactionEvent> = Vec::new();
 
         if let Some(yellowstone_grpc_proto::geyser::subscribe_update::UpdateOneof::Transaction(tx_info)) = update.update_oneof {
             let slot = tx_info.slot;
-            let tx_details = tx_info.transaction.unwrap();
-            let signature = bs58::encode(&tx_details.signature).into_string();
-            let message = tx_details.transaction.unwrap().message.unwrap();
-            let meta = tx_details.meta.unwrap();
-
-            let all_accounts = VixenUtils::extract_accounts_from_grpc(
-                &message.account_keys,
-                &meta.loaded_writable_addresses,
-                &meta.loaded_readonly_addresses,
-            );
-            let account_keys: Vec<String> = all_accounts.iter().map(|k| k.to_string()).collect();
-
-            let raydium_idx = account_keys.iter().position(|k| k == domain::RAYDIUM_V4_PROGRAM_ID);
-
-            if let Some(pgm_idx) = raydium_idx {
-                for (ix_idx, ix) in message.instructions.iter().enumerate() {
+            let Some(tx_details) = tx_info.transaction else { return Ok(None) };
+            let signature = Signature58::try_from(tx_details.signature.as_slice())?;
+            let Some(message) = tx_details.transaction.and_then(|t| t.message) else { return Ok(None) };
+            let Some(meta) = tx_details.meta else { return Ok(None) };
+
+            let all_accounts = VixenUtils::extract_accounts_from_message(&message, &meta);
+            let account_keys = VixenUtils::account_strings(&all_accounts);
+
+            let Some(pgm_idx) = account_keys.iter().position(|k| &**k == domain::RAYDIUM_V4_PROGRAM_ID) else {
+                return Ok(Some(events));
+            };
+
+            // Top-level swaps first, then swaps routed through another program via CPI
+            let mut found: Vec<(IxPosition, &[u8], AmmSwap)> = Vec::new();
+            for (ix_idx, ix) in message.instructions.iter().enumerate() {
+                if ix.program_id_index as usize != pgm_idx { continue; }
+                let Some(swap) = Self::decode_swap(&ix.data, &ix.accounts) else { continue };
+                found.push((IxPosition::top_level(ix_idx), &ix.data, swap));
+            }
+            for group in &meta.inner_instructions {
+                for (inner_idx, ix) in group.instructions.iter().enumerate() {
                     if ix.program_id_index as usize != pgm_idx { continue; }
-                    if ix.data.first().copied() != Some(9) { continue; }
-                    if ix.accounts.len() <= 17 { continue; }
-
-                    let args = RaydiumSwapInstruction::try_from_slice(&ix.data[1..17])
-                        .map_err(|e| anyhow::anyhow!("Raydium decode error: {:?}", e))?;
-
-                    let signer_idx = ix.accounts[17] as usize;
-                    let amm_idx    = ix.accounts[1]  as usize;
-                    let src_idx    = ix.accounts[15] as usize;
-                    let dst_idx    = ix.accounts[16] as usize;
-
-                    let amount_received = Self::find_cpi_amount_grpc(ix_idx, dst_idx, &meta.inner_instructions).unwrap_or(0);
-                    let mint_source      = Self::resolve_mint_grpc(src_idx, &meta.pre_token_balances, &meta.post_token_balances);
-                    let mint_destination = Self::resolve_mint_grpc(dst_idx, &meta.pre_token_balances, &meta.post_token_balances);
-
-                    events.push(TransactionEvent::RaydiumSwap(RaydiumSwapEvent {
-                        amm_pool: account_keys[amm_idx].clone(),
-                        signer: account_keys[signer_idx].clone(),
-                        amount_in: args.amount_in,
-                        min_amount_out: args.min_amount_out,
-                        amount_received,
-                        mint_source,
-                        mint_destination,
-                        slot,
-                        block_time,
-                        signature: signature.clone(),
-                    }));
+                    let Some(swap) = Self::decode_swap(&ix.data, &ix.accounts) else { continue };
+                    found.push((IxPosition::inner(group.index as usize, inner_idx, ix.stack_height), &ix.data, swap));
                 }
             }
+
+            let positions: Vec<IxPosition> = found.iter().map(|(pos, ..)| *pos).collect();
+            for (pos, data, swap) in found {
+                let (Some(amm_pool), Some(signer)) = (account_keys.get(swap.pool_idx), account_keys.get(swap.signer_idx)) else {
+                    tracing::warn!("Raydium ix in {} references accounts beyond the resolved key list", signature);
+                    continue;
+                };
+
+                let transfer = |idx, leg| {
+                    VixenUtils::find_transfer_amount_grpc(pos.instruction_index, pos.transfer_window(&positions), idx, leg, &meta.inner_instructions)
+                };
+                let (amount_in, min_amount_out, amount_received) = Self::decode_amounts(
+                    swap.opcode,
+                    &data[1..17],
+                    transfer(swap.src_idx, TransferLeg::Source),
+                    transfer(swap.dst_idx, TransferLeg::Destination),
+                )?;
+                let mint_source      = VixenUtils::token_account_mint_grpc(swap.src_idx, &meta.pre_token_balances, &meta.post_token_balances);
+                let mint_destination = VixenUtils::token_account_mint_grpc(swap.dst_idx, &meta.pre_token_balances, &meta.post_token_balances);
+
+                events.push(TransactionEvent::RaydiumSwap(RaydiumSwapEvent {
+                    pool_type: RaydiumPoolType::AmmV4,
+                    amm_pool: amm_pool.to_string(),
+                    signer: signer.to_string(),
+                    amount_in,
+                    min_amount_out,
+                    amount_received,
+                    mint_source,
+                    mint_destination,
+                    slot,
+                    block_time,
+                    signature: signature.clone(),
+                    instruction_index: pos.instruction_index as u32,
+                    inner_index: pos.inner_index,
+                    cpi_depth: pos.cpi_depth,
+                }));
+            }
         }
 
         Ok(Some(events))
@@ -85,137 +135,306 @@ impl RaydiumAmmParser {
         meta: UiTransactionStatusMeta,
         slot: u64,
         signature: &str,
-    ) -> Result<Option<Vec<TransactionEvent>>> {
+        block_time: Option<i64>,
+    ) -> ParserResult<Option<Vec<TransactionEvent>>> {
         let mut events: Vec<TransactionEvent> = Vec::new();
-        let message = &tx.message;
-
-        let mut all_keys: Vec<String> = message.static_account_keys().iter().map(|k| k.to_string()).collect();
-        if let OptionSerializer::Some(loaded) = &meta.loaded_addresses {
-            for a in &loaded.writable { all_keys.push(a.clone()); }
-            for a in &loaded.readonly { all_keys.push(a.clone()); }
-        }
-
-        let raydium_idx = all_keys.iter().position(|k| k == domain::RAYDIUM_V4_PROGRAM_ID);
-        if let Some(pgm_idx) = raydium_idx {
-            let pgm_idx = pgm_idx as u8;
+        let signature = Signature58::try_from(signature)?;
 
-            for (ix_idx, ix) in message.instructions().iter().enumerate() {
-                if ix.program_id_index != pgm_idx { continue; }
-                if ix.data.len() < 17 || ix.data[0] != 9 { continue; }
+        let all_keys = VixenUtils::account_strings(&VixenUtils::extract_accounts_from_rpc(&tx, &meta));
 
-                let args = RaydiumSwapInstruction::try_from_slice(&ix.data[1..17])
-                    .map_err(|e| anyhow::anyhow!("Raydium decode error: {:?}", e))?;
+        let Some(pgm_idx) = all_keys.iter().position(|k| &**k == domain::RAYDIUM_V4_PROGRAM_ID) else {
+            return Ok(Some(events));
+        };
 
-                let amm_idx    = *ix.accounts.get(1).ok_or_else(|| anyhow::anyhow!("missing amm_pool"))? as usize;
-                let src_idx    = *ix.accounts.get(15).ok_or_else(|| anyhow::anyhow!("missing src"))? as usize;
-                let dst_idx    = *ix.accounts.get(16).ok_or_else(|| anyhow::anyhow!("missing dst"))? as usize;
-                let signer_idx = *ix.accounts.get(17).ok_or_else(|| anyhow::anyhow!("missing signer"))? as usize;
-
-                let amount_received = Self::find_cpi_amount_rpc(ix_idx, dst_idx, &meta.inner_instructions).unwrap_or(0);
-
-                let empty: Vec<UiTransactionTokenBalance> = vec![];
-                let pre  = if let OptionSerializer::Some(v) = &meta.pre_token_balances  { v.as_slice() } else { &empty };
-                let post = if let OptionSerializer::Some(v) = &meta.post_token_balances { v.as_slice() } else { &empty };
-                let mint_source      = Self::resolve_mint_rpc(src_idx, pre, post);
-                let mint_destination = Self::resolve_mint_rpc(dst_idx, pre, post);
-
-                events.push(TransactionEvent::RaydiumSwap(RaydiumSwapEvent {
-                    amm_pool: all_keys[amm_idx].clone(),
-                    signer: all_keys[signer_idx].clone(),
-                    amount_in: args.amount_in,
-                    min_amount_out: args.min_amount_out,
-                    amount_received,
-                    mint_source,
-                    mint_destination,
-                    slot,
-                    block_time: 0,
-                    signature: signature.to_string(),
-                }));
+        // Top-level swaps first, then swaps routed through another program via CPI
+        let mut found: Vec<(IxPosition, Vec<u8>, AmmSwap)> = Vec::new();
+        for (ix_idx, ix) in tx.message.instructions().iter().enumerate() {
+            if ix.program_id_index as usize != pgm_idx { continue; }
+            let Some(swap) = Self::decode_swap(&ix.data, &ix.accounts) else { continue };
+            found.push((IxPosition::top_level(ix_idx), ix.data.clone(), swap));
+        }
+        if let OptionSerializer::Some(groups) = &meta.inner_instructions {
+            for group in groups {
+                for (inner_idx, inner_ix) in group.instructions.iter().enumerate() {
+                    let UiInstruction::Compiled(c) = inner_ix else { continue };
+                    if c.program_id_index as usize != pgm_idx { continue; }
+                    let Ok(raw) = bs58::decode(&c.data).into_vec() else { continue };
+                    let Some(swap) = Self::decode_swap(&raw, &c.accounts) else { continue };
+                    found.push((IxPosition::inner(group.index as usize, inner_idx, c.stack_height), raw, swap));
+                }
             }
         }
 
-        Ok(Some(events))
-    }
+        let empty: Vec<UiTransactionTokenBalance> = vec![];
+        let pre  = if let OptionSerializer::Some(v) = &meta.pre_token_balances  { v.as_slice() } else { &empty };
+        let post = if let OptionSerializer::Some(v) = &meta.post_token_balances { v.as_slice() } else { &empty };
 
-    // ─── CPI helpers ────────────────────────────────────────────────────────────
-
-    fn find_cpi_amount_grpc(
-        parent_idx: usize,
-        target_dst: usize,
-        inner_ixs: &[yellowstone_grpc_proto::prelude::InnerInstructions],
-    ) -> Option<u64> {
-        let group = inner_ixs.iter().find(|g| g.index == parent_idx as u32)?;
-
-        for ix in &group.instructions {
-            let (amount, dst) = if ix.data.first() == Some(&3) && ix.data.len() >= 9 {
-                let mut b = [0u8; 8];
-                b.copy_from_slice(&ix.data[1..9]);
-                (u64::from_le_bytes(b), ix.accounts.get(1)?)
-            } else if ix.data.first() == Some(&12) && ix.data.len() >= 9 {
-                let mut b = [0u8; 8];
-                b.copy_from_slice(&ix.data[1..9]);
-                (u64::from_le_bytes(b), ix.accounts.get(2)?)
-            } else {
+        let positions: Vec<IxPosition> = found.iter().map(|(pos, ..)| *pos).collect();
+        for (pos, data, swap) in found {
+            let (Some(amm_pool), Some(signer)) = (all_keys.get(swap.pool_idx), all_keys.get(swap.signer_idx)) else {
+                tracing::warn!("Raydium ix in {} references accounts beyond the resolved key list", signature);
                 continue;
             };
 
-            if *dst == target_dst as u8 { return Some(amount); }
-        }
-        None
-    }
-
-    fn find_cpi_amount_rpc(
-        parent_idx: usize,
-        target_dst: usize,
-        inner_ixs: &OptionSerializer<Vec<UiInnerInstructions>>,
-    ) -> Option<u64> {
-        if let OptionSerializer::Some(groups) = inner_ixs {
-            let group = groups.iter().find(|g| g.index == parent_idx as u8)?;
-            for ix in &group.instructions {
-                if let UiInstruction::Compiled(c) = ix {
-                    let raw = bs58::decode(&c.data).into_vec().ok()?;
-                    let (amount, dst) = if raw.first() == Some(&3) && raw.len() >= 9 {
-                        let mut b = [0u8; 8];
-                        b.copy_from_slice(&raw[1..9]);
-                        (u64::from_le_bytes(b), c.accounts.get(1)?)
-                    } else if raw.first() == Some(&12) && raw.len() >= 9 {
-                        let mut b = [0u8; 8];
-                        b.copy_from_slice(&raw[1..9]);
-                        (u64::from_le_bytes(b), c.accounts.get(2)?)
-                    } else {
-                        continue;
-                    };
-                    if *dst as usize == target_dst { return Some(amount); }
-                }
-            }
+            let transfer = |idx, leg| {
+                VixenUtils::find_transfer_amount_rpc(pos.instruction_index, pos.transfer_window(&positions), idx, leg, &meta.inner_instructions)
+            };
+            let (amount_in, min_amount_out, amount_received) = Self::decode_amounts(
+                swap.opcode,
+                &data[1..17],
+                transfer(swap.src_idx, TransferLeg::Source),
+                transfer(swap.dst_idx, TransferLeg::Destination),
+            )?;
+            let mint_source      = VixenUtils::token_account_mint_rpc(swap.src_idx, pre, post);
+            let mint_destination = VixenUtils::token_account_mint_rpc(swap.dst_idx, pre, post);
+
+            events.push(TransactionEvent::RaydiumSwap(RaydiumSwapEvent {
+                pool_type: RaydiumPoolType::AmmV4,
+                amm_pool: amm_pool.to_string(),
+                signer: signer.to_string(),
+                amount_in,
+                min_amount_out,
+                amount_received,
+                mint_source,
+                mint_destination,
+                slot,
+                block_time,
+                signature: signature.clone(),
+                instruction_index: pos.instruction_index as u32,
+                inner_index: pos.inner_index,
+                cpi_depth: pos.cpi_depth,
+            }));
         }
-        None
-    }
 
-    fn resolve_mint_grpc(idx: usize, pre: &[TokenBalance], post: &[TokenBalance]) -> String {
-        pre.iter()
-            .find(|b| b.account_index == idx as u32)
-            .or_else(|| post.iter().find(|b| b.account_index == idx as u32))
-            .map(|b| b.mint.clone())
-            .unwrap_or_else(|| "unknown".to_string())
+        Ok(Some(events))
     }
 
-    fn resolve_mint_rpc(idx: usize, pre: &[UiTransactionTokenBalance], post: &[UiTransactionTokenBalance]) -> String {
-        pre.iter()
-            .find(|b| b.account_index == idx as u8)
-            .or_else(|| post.iter().find(|b| b.account_index == idx as u8))
-            .map(|b| b.mint.clone())
-            .unwrap_or_else(|| "unknown".to_string())
+    /// Returns (amount_in, min_amount_out, amount_received) for either swap
+    /// direction, given the amounts of the swap's own CPI transfers.
+    /// SwapBaseOut only caps the input, so the input is the amount actually
+    /// debited, never `max_amount_in`; the exact requested output stands in as
+    /// the minimum. A transfer that is not found is recorded as 0.
+    fn decode_amounts(opcode: u8, args: &[u8], spent: Option<u64>, received: Option<u64>) -> ParserResult<(u64, u64, u64)> {
+        if opcode == SWAP_BASE_OUT {
+            let args = RaydiumSwapBaseOutInstruction::try_from_slice(args)
+                .map_err(|e| ParserError::DecodeFailed(format!("Raydium SwapBaseOut args: {}", e)))?;
+            Ok((spent.unwrap_or(0), args.amount_out, received.unwrap_or(0)))
+        } else {
+            let args = RaydiumSwapInstruction::try_from_slice(args)
+                .map_err(|e| ParserError::DecodeFailed(format!("Raydium SwapBaseIn args: {}", e)))?;
+            Ok((args.amount_in, args.min_amount_out, received.unwrap_or(0)))
+        }
     }
 }
 
 impl TransactionParser for RaydiumAmmParser {
     fn name(&self) -> &str { "raydium_amm" }
 
-    fn parse(&self, txn: SolanaTransaction) -> Result<Option<Vec<TransactionEvent>>> {
+    fn emitted_event_kinds(&self) -> &[&str] { &["raydium_swap"] }
+
+    fn parse(&self, txn: SolanaTransaction) -> ParserResult<Option<Vec<TransactionEvent>>> {
         match txn.data {
+            // Simulated sources emit empty frames; there is nothing to decode
+            TxData::Grpc(bytes) if bytes.is_empty() => Ok(None),
             TxData::Grpc(bytes) => self.parse_protobuf(&bytes, txn.block_time),
-            TxData::Rpc { tx, meta } => self.parse_rpc(tx, meta, txn.slot, &txn.signature),
+            TxData::Rpc { tx, meta } => self.parse_rpc(tx, meta, txn.slot, &txn.signature, txn.block_time),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use solana_sdk::pubkey::Pubkey;
+    use yellowstone_grpc_proto::{
+        geyser::{SubscribeUpdateTransaction, SubscribeUpdateTransactionInfo, subscribe_update::UpdateOneof},
+        prelude::{CompiledInstruction, InnerInstruction, InnerInstructions, Message, Transaction, TransactionStatusMeta},
+    };
+
+    use super::*;
+
+    fn key(seed: u8) -> Vec<u8> {
+        vec![seed; 32]
+    }
+
+    fn address(seed: u8) -> String {
+        Pubkey::new_from_array([seed; 32]).to_string()
+    }
+
+    fn program() -> Vec<u8> {
+        Pubkey::from_str(domain::RAYDIUM_V4_PROGRAM_ID).unwrap().to_bytes().to_vec()
+    }
+
+    fn swap_base_in(amount_in: u64, min_amount_out: u64) -> Vec<u8> {
+        let mut data = vec![SWAP_BASE_IN];
+        data.extend(amount_in.to_le_bytes());
+        data.extend(min_amount_out.to_le_bytes());
+        data
+    }
+
+    fn swap_base_out(max_amount_in: u64, amount_out: u64) -> Vec<u8> {
+        let mut data = vec![SWAP_BASE_OUT];
+        data.extend(max_amount_in.to_le_bytes());
+        data.extend(amount_out.to_le_bytes());
+        data
+    }
+
+    /// An SPL Token `Transfer` CPI from `source` to `destination`.
+    fn token_transfer(amount: u64, source: u8, destination: u8, stack_height: u32) -> InnerInstruction {
+        let mut data = vec![3];
+        data.extend(amount.to_le_bytes());
+        InnerInstruction { program_id_index: 5, accounts: vec![source, destination, 0], data, stack_height: Some(stack_height) }
+    }
+
+    /// Keys shared by the CPI tests: user (0), program (1), pool (2), the user's
+    /// source and destination token accounts (3, 4), a filler (5), the pool's
+    /// vault (6) and a routing program (7).
+    fn routed_message(instructions: Vec<CompiledInstruction>) -> Message {
+        Message {
+            account_keys: vec![key(1), program(), key(2), key(3), key(4), key(5), key(6), key(7)],
+            instructions,
+            ..Default::default()
+        }
+    }
+
+    /// A swap's 18 account indices: pool, source and destination token accounts
+    /// and the user where the parser reads them, `filler` everywhere else.
+    fn swap_accounts(pool: u8, src: u8, dst: u8, signer: u8, filler: u8) -> Vec<u8> {
+        let mut accounts = vec![filler; 18];
+        accounts[1] = pool;
+        accounts[15] = src;
+        accounts[16] = dst;
+        accounts[17] = signer;
+        accounts
+    }
+
+    fn swaps(message: Message, meta: TransactionStatusMeta) -> Vec<RaydiumSwapEvent> {
+        let update = SubscribeUpdate {
+            update_oneof: Some(UpdateOneof::Transaction(SubscribeUpdateTransaction {
+                slot: 10,
+                transaction: Some(SubscribeUpdateTransactionInfo {
+                    signature: vec![7; domain::SIGNATURE_LEN],
+                    transaction: Some(Transaction { signatures: vec![vec![7; domain::SIGNATURE_LEN]], message: Some(message) }),
+                    meta: Some(meta),
+                    ..Default::default()
+                }),
+            })),
+            ..Default::default()
+        };
+        let txn = SolanaTransaction {
+            signature: Signature58::from([7u8; 64]).to_string(),
+            success: true,
+            data: TxData::Grpc(update.encode_to_vec()),
+            slot: 10,
+            block_time: None,
+            fee: None,
+            compute_units_consumed: None,
+            fee_payer: address(1),
+            recent_blockhash: String::new(),
+        };
+        RaydiumAmmParser::new()
+            .parse(txn)
+            .unwrap()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|ev| match ev {
+                TransactionEvent::RaydiumSwap(swap) => swap,
+                other => panic!("unexpected event {:?}", other),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn pool_and_signer_resolve_through_address_lookup_tables() {
+        // Static keys are the user (0) and the program (1); the pool and both token
+        // accounts come from lookup tables, writable (2..=4) then readonly (5)
+        let message = Message {
+            account_keys: vec![key(1), program()],
+            instructions: vec![CompiledInstruction { program_id_index: 1, accounts: swap_accounts(2, 3, 4, 0, 5), data: swap_base_in(1_000, 900) }],
+            versioned: true,
+            ..Default::default()
+        };
+        let meta = TransactionStatusMeta {
+            loaded_writable_addresses: vec![key(2), key(3), key(4)],
+            loaded_readonly_addresses: vec![key(5)],
+            ..Default::default()
+        };
+
+        let swaps = swaps(message, meta);
+
+        assert_eq!(swaps.len(), 1);
+        assert_eq!(swaps[0].amm_pool, address(2));
+        assert_eq!(swaps[0].signer, address(1));
+        assert_eq!((swaps[0].amount_in, swaps[0].min_amount_out), (1_000, 900));
+    }
+
+    #[test]
+    fn cpi_swaps_in_one_group_read_their_own_transfers() {
+        // A router (7) swaps twice through the same pool and token accounts; each
+        // swap's transfers follow it in the shared CPI group
+        let swap = |amount_in, min_out| InnerInstruction {
+            program_id_index: 1,
+            accounts: swap_accounts(2, 3, 4, 0, 5),
+            data: swap_base_in(amount_in, min_out),
+            stack_height: Some(2),
+        };
+        let message = routed_message(vec![CompiledInstruction { program_id_index: 7, accounts: vec![], data: vec![] }]);
+        let meta = TransactionStatusMeta {
+            inner_instructions: vec![InnerInstructions {
+                index: 0,
+                instructions: vec![
+                    swap(1_000, 900),
+                    token_transfer(1_000, 3, 6, 3),
+                    token_transfer(950, 6, 4, 3),
+                    swap(2_000, 1_800),
+                    token_transfer(2_000, 3, 6, 3),
+                    token_transfer(1_990, 6, 4, 3),
+                ],
+            }],
+            ..Default::default()
+        };
+
+        let swaps = swaps(message, meta);
+
+        let received: Vec<_> = swaps.iter().map(|s| (s.inner_index, s.amount_in, s.amount_received)).collect();
+        assert_eq!(received, vec![(Some(0), 1_000, 950), (Some(3), 2_000, 1_990)]);
+    }
+
+    #[test]
+    fn swap_base_out_records_the_transferred_amounts_not_the_cap() {
+        let message = routed_message(vec![CompiledInstruction {
+            program_id_index: 1,
+            accounts: swap_accounts(2, 3, 4, 0, 5),
+            data: swap_base_out(5_000, 700),
+        }]);
+        let meta = TransactionStatusMeta {
+            inner_instructions: vec![InnerInstructions {
+                index: 0,
+                instructions: vec![token_transfer(4_200, 3, 6, 2), token_transfer(700, 6, 4, 2)],
+            }],
+            ..Default::default()
+        };
+
+        let swaps = swaps(message, meta);
+
+        assert_eq!(swaps.len(), 1);
+        assert_eq!((swaps[0].amount_in, swaps[0].min_amount_out, swaps[0].amount_received), (4_200, 700, 700));
+    }
+
+    #[test]
+    fn swap_base_out_without_transfers_is_still_indexed() {
+        let message = routed_message(vec![CompiledInstruction {
+            program_id_index: 1,
+            accounts: swap_accounts(2, 3, 4, 0, 5),
+            data: swap_base_out(5_000, 700),
+        }]);
+
+        let swaps = swaps(message, TransactionStatusMeta::default());
+
+        // Without the transfers the amounts are unknown; the cap is never taken for one
+        assert_eq!(swaps.len(), 1);
+        assert_eq!((swaps[0].amount_in, swaps[0].min_amount_out, swaps[0].amount_received), (0, 700, 0));
+        assert_eq!((swaps[0].amm_pool.as_str(), swaps[0].signer.as_str()), (address(2).as_str(), address(1).as_str()));
+    }
+}
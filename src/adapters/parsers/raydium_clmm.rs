@@ -0,0 +1,365 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use prost::Message;
+use solana_transaction_status::{UiTransactionStatusMeta, UiTransactionTokenBalance, option_serializer::OptionSerializer};
+use solana_sdk::transaction::VersionedTransaction;
+use yellowstone_grpc_proto::geyser::SubscribeUpdate;
+
+use crate::{
+    adapters::parsers::{DiscriminatorTable, TransferLeg, VixenUtils, WHOLE_GROUP},
+    application::{ParserError, ParserResult, TransactionParser},
+    domain::{self, RaydiumPoolType, RaydiumSwapEvent, Signature58, SolanaTransaction, TransactionEvent, TxData},
+};
+
+// Anchor discriminators: sha256("global:<name>")[..8]
+const SWAP_DISCM: [u8; 8] = [248, 198, 158, 145, 225, 117, 135, 200];
+const SWAP_V2_DISCM: [u8; 8] = [43, 4, 237, 11, 26, 201, 30, 98];
+
+// Account positions shared by swap and swap_v2
+const PAYER: usize = 0;
+const POOL_STATE: usize = 2;
+const INPUT_TOKEN_ACCOUNT: usize = 3;
+const OUTPUT_TOKEN_ACCOUNT: usize = 4;
+
+/// Borsh size of `RaydiumClmmSwapArgs`: two u64s, a u128 and a bool.
+const SWAP_ARGS_LEN: usize = 8 + 8 + 16 + 1;
+
+#[derive(BorshDeserialize, BorshSerialize, Debug)]
+pub struct RaydiumClmmSwapArgs {
+    pub amount: u64,
+    pub other_amount_threshold: u64,
+    pub sqrt_price_limit_x64: u128,
+    pub is_base_input: bool,
+}
+
+/// Accounts and amounts of one CLMM swap instruction, before key resolution.
+struct ClmmSwap {
+    args: RaydiumClmmSwapArgs,
+    payer_idx: usize,
+    pool_idx: usize,
+    src_idx: usize,
+    dst_idx: usize,
+}
+
+/// Instruction kinds this parser can decode. Both share the swap account layout
+/// and argument encoding; a discriminator loaded under any other kind fails the
+/// parse with `UnknownDiscriminator`.
+const SWAP_KINDS: [&str; 2] = ["swap", "swap_v2"];
+
+pub struct RaydiumClmmParser {
+    discriminators: DiscriminatorTable,
+}
+
+impl RaydiumClmmParser {
+    pub fn new() -> Self {
+        Self {
+            discriminators: DiscriminatorTable::new().with("swap", SWAP_DISCM).with("swap_v2", SWAP_V2_DISCM),
+        }
+    }
+
+    /// Add to (or override) the built-in swap discriminators.
+    pub fn with_discriminators(mut self, table: DiscriminatorTable) -> Self {
+        self.discriminators.extend(table);
+        self
+    }
+
+    fn decode_swap(&self, data: &[u8], accounts: &[u8]) -> ParserResult<Option<ClmmSwap>> {
+        let Some(kind) = self.discriminators.kind_of(data) else { return Ok(None) };
+        if !SWAP_KINDS.contains(&kind) {
+            return Err(ParserError::UnknownDiscriminator(data[..8].to_vec()));
+        }
+        if accounts.len() <= OUTPUT_TOKEN_ACCOUNT { return Ok(None); }
+        if data.len() < 8 + SWAP_ARGS_LEN {
+            return Err(ParserError::TruncatedData { expected: 8 + SWAP_ARGS_LEN, got: data.len() });
+        }
+
+        let args = RaydiumClmmSwapArgs::deserialize(&mut &data[8..])
+            .map_err(|e| ParserError::DecodeFailed(format!("Raydium CLMM swap args: {}", e)))?;
+
+        Ok(Some(ClmmSwap {
+            args,
+            payer_idx: accounts[PAYER] as usize,
+            pool_idx: accounts[POOL_STATE] as usize,
+            src_idx: accounts[INPUT_TOKEN_ACCOUNT] as usize,
+            dst_idx: accounts[OUTPUT_TOKEN_ACCOUNT] as usize,
+        }))
+    }
+
+    /// Returns (amount_in, min_amount_out). Exact-input swaps carry the input and the
+    /// output floor; exact-output swaps carry the output and an input cap, so the input
+    /// actually debited comes from the CPI transfer (falling back to the cap).
+    fn amounts(args: &RaydiumClmmSwapArgs, spent: impl FnOnce() -> Option<u64>) -> (u64, u64) {
+        if args.is_base_input {
+            (args.amount, args.other_amount_threshold)
+        } else {
+            (spent().unwrap_or(args.other_amount_threshold), args.amount)
+        }
+    }
+
+    fn parse_protobuf(&self, raw_bytes: &[u8], block_time: Option<i64>) -> ParserResult<Option<Vec<TransactionEvent>>> {
+        let update = SubscribeUpdate::decode(raw_bytes)?;
+        let mut events: Vec<TransactionEvent> = Vec::new();
+
+        if let Some(yellowstone_grpc_proto::geyser::subscribe_update::UpdateOneof::Transaction(tx_info)) = update.update_oneof {
+            let slot = tx_info.slot;
+            let Some(tx_details) = tx_info.transaction else { return Ok(None) };
+            let signature = Signature58::try_from(tx_details.signature.as_slice())?;
+            let Some(meta) = tx_details.meta else { return Ok(None) };
+            let Some(message) = tx_details.transaction.and_then(|t| t.message) else { return Ok(None) };
+
+            let account_keys = VixenUtils::account_strings(&VixenUtils::extract_accounts_from_message(&message, &meta));
+
+            let Some(pgm_idx) = account_keys.iter().position(|k| &**k == domain::RAYDIUM_CLMM_PROGRAM_ID) else {
+                return Ok(Some(events));
+            };
+
+            for (ix_idx, ix) in message.instructions.iter().enumerate() {
+                if ix.program_id_index as usize != pgm_idx { continue; }
+                let Some(swap) = self.decode_swap(&ix.data, &ix.accounts)? else { continue };
+
+                let (Some(amm_pool), Some(signer)) = (account_keys.get(swap.pool_idx), account_keys.get(swap.payer_idx)) else {
+                    tracing::warn!("Raydium CLMM ix in {} references accounts beyond the resolved key list", signature);
+                    continue;
+                };
+
+                let (amount_in, min_amount_out) = Self::amounts(&swap.args, || {
+                    VixenUtils::find_transfer_amount_grpc(ix_idx, WHOLE_GROUP, swap.src_idx, TransferLeg::Source, &meta.inner_instructions)
+                });
+                let amount_received = VixenUtils::find_transfer_amount_grpc(ix_idx, WHOLE_GROUP, swap.dst_idx, TransferLeg::Destination, &meta.inner_instructions).unwrap_or(0);
+
+                events.push(TransactionEvent::RaydiumSwap(RaydiumSwapEvent {
+                    pool_type: RaydiumPoolType::Clmm,
+                    amm_pool: amm_pool.to_string(),
+                    signer: signer.to_string(),
+                    amount_in,
+                    min_amount_out,
+                    amount_received,
+                    mint_source: VixenUtils::token_account_mint_grpc(swap.src_idx, &meta.pre_token_balances, &meta.post_token_balances),
+                    mint_destination: VixenUtils::token_account_mint_grpc(swap.dst_idx, &meta.pre_token_balances, &meta.post_token_balances),
+                    slot,
+                    block_time,
+                    signature: signature.clone(),
+                    instruction_index: ix_idx as u32,
+                    inner_index: None,
+                    cpi_depth: 0,
+                }));
+            }
+        }
+
+        Ok(Some(events))
+    }
+
+    fn parse_rpc(
+        &self,
+        tx: VersionedTransaction,
+        meta: UiTransactionStatusMeta,
+        slot: u64,
+        signature: &str,
+        block_time: Option<i64>,
+    ) -> ParserResult<Option<Vec<TransactionEvent>>> {
+        let mut events: Vec<TransactionEvent> = Vec::new();
+        let signature = Signature58::try_from(signature)?;
+
+        let all_keys = VixenUtils::account_strings(&VixenUtils::extract_accounts_from_rpc(&tx, &meta));
+
+        let Some(pgm_idx) = all_keys.iter().position(|k| &**k == domain::RAYDIUM_CLMM_PROGRAM_ID) else {
+            return Ok(Some(events));
+        };
+
+        let empty: Vec<UiTransactionTokenBalance> = vec![];
+        let pre  = if let OptionSerializer::Some(v) = &meta.pre_token_balances  { v.as_slice() } else { &empty };
+        let post = if let OptionSerializer::Some(v) = &meta.post_token_balances { v.as_slice() } else { &empty };
+
+        for (ix_idx, ix) in tx.message.instructions().iter().enumerate() {
+            if ix.program_id_index as usize != pgm_idx { continue; }
+            let Some(swap) = self.decode_swap(&ix.data, &ix.accounts)? else { continue };
+
+            let (Some(amm_pool), Some(signer)) = (all_keys.get(swap.pool_idx), all_keys.get(swap.payer_idx)) else {
+                tracing::warn!("Raydium CLMM ix in {} references accounts beyond the resolved key list", signature);
+                continue;
+            };
+
+            let (amount_in, min_amount_out) = Self::amounts(&swap.args, || {
+                VixenUtils::find_transfer_amount_rpc(ix_idx, WHOLE_GROUP, swap.src_idx, TransferLeg::Source, &meta.inner_instructions)
+            });
+            let amount_received = VixenUtils::find_transfer_amount_rpc(ix_idx, WHOLE_GROUP, swap.dst_idx, TransferLeg::Destination, &meta.inner_instructions).unwrap_or(0);
+
+            events.push(TransactionEvent::RaydiumSwap(RaydiumSwapEvent {
+                pool_type: RaydiumPoolType::Clmm,
+                amm_pool: amm_pool.to_string(),
+                signer: signer.to_string(),
+                amount_in,
+                min_amount_out,
+                amount_received,
+                mint_source: VixenUtils::token_account_mint_rpc(swap.src_idx, pre, post),
+                mint_destination: VixenUtils::token_account_mint_rpc(swap.dst_idx, pre, post),
+                slot,
+                block_time,
+                signature: signature.clone(),
+                instruction_index: ix_idx as u32,
+                inner_index: None,
+                cpi_depth: 0,
+            }));
+        }
+
+        Ok(Some(events))
+    }
+}
+
+impl TransactionParser for RaydiumClmmParser {
+    fn name(&self) -> &str { "raydium_clmm" }
+
+    fn emitted_event_kinds(&self) -> &[&str] { &["raydium_swap"] }
+
+    fn parse(&self, txn: SolanaTransaction) -> ParserResult<Option<Vec<TransactionEvent>>> {
+        match txn.data {
+            // Simulated sources emit empty frames; there is nothing to decode
+            TxData::Grpc(bytes) if bytes.is_empty() => Ok(None),
+            TxData::Grpc(bytes) => self.parse_protobuf(&bytes, txn.block_time),
+            TxData::Rpc { tx, meta } => self.parse_rpc(tx, meta, txn.slot, &txn.signature, txn.block_time),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use solana_sdk::pubkey::Pubkey;
+    use yellowstone_grpc_proto::{
+        geyser::{SubscribeUpdateTransaction, SubscribeUpdateTransactionInfo, subscribe_update::UpdateOneof},
+        prelude::{CompiledInstruction, InnerInstruction, InnerInstructions, Message as GrpcMessage, TokenBalance, Transaction, TransactionStatusMeta},
+    };
+
+    use super::*;
+
+    fn address(seed: u8) -> String {
+        Pubkey::new_from_array([seed; 32]).to_string()
+    }
+
+    fn swap_data(discriminator: [u8; 8], amount: u64, other_amount_threshold: u64, is_base_input: bool) -> Vec<u8> {
+        let mut data = discriminator.to_vec();
+        data.extend(amount.to_le_bytes());
+        data.extend(other_amount_threshold.to_le_bytes());
+        data.extend(0u128.to_le_bytes());
+        data.push(is_base_input as u8);
+        data
+    }
+
+    /// An SPL Token `Transfer` CPI from `source` to `destination`.
+    fn token_transfer(amount: u64, source: u8, destination: u8) -> InnerInstruction {
+        let mut data = vec![3];
+        data.extend(amount.to_le_bytes());
+        InnerInstruction { program_id_index: 6, accounts: vec![source, destination, 0], data, stack_height: Some(2) }
+    }
+
+    fn mint_of(account_index: u32, mint: &str) -> TokenBalance {
+        TokenBalance { account_index, mint: mint.to_string(), ..Default::default() }
+    }
+
+    /// One top-level CLMM swap over payer (0), the program (1), config (2), pool
+    /// (3), input and output token accounts (4, 5) and a pool vault (6), whose
+    /// transfers run in the swap's CPI group.
+    fn swap(data: Vec<u8>, transfers: Vec<InnerInstruction>) -> Vec<RaydiumSwapEvent> {
+        let program = Pubkey::from_str(domain::RAYDIUM_CLMM_PROGRAM_ID).unwrap().to_bytes().to_vec();
+        let message = GrpcMessage {
+            account_keys: vec![vec![1; 32], program, vec![2; 32], vec![3; 32], vec![4; 32], vec![5; 32], vec![6; 32]],
+            instructions: vec![CompiledInstruction { program_id_index: 1, accounts: vec![0, 2, 3, 4, 5, 6], data }],
+            ..Default::default()
+        };
+        let meta = TransactionStatusMeta {
+            inner_instructions: vec![InnerInstructions { index: 0, instructions: transfers }],
+            pre_token_balances: vec![mint_of(4, "MintIn"), mint_of(5, "MintOut")],
+            ..Default::default()
+        };
+        let update = SubscribeUpdate {
+            update_oneof: Some(UpdateOneof::Transaction(SubscribeUpdateTransaction {
+                slot: 10,
+                transaction: Some(SubscribeUpdateTransactionInfo {
+                    signature: vec![7; domain::SIGNATURE_LEN],
+                    transaction: Some(Transaction { signatures: vec![vec![7; domain::SIGNATURE_LEN]], message: Some(message) }),
+                    meta: Some(meta),
+                    ..Default::default()
+                }),
+            })),
+            ..Default::default()
+        };
+        let txn = SolanaTransaction {
+            signature: Signature58::from([7u8; 64]).to_string(),
+            success: true,
+            data: TxData::Grpc(update.encode_to_vec()),
+            slot: 10,
+            block_time: None,
+            fee: None,
+            compute_units_consumed: None,
+            fee_payer: address(1),
+            recent_blockhash: String::new(),
+        };
+        RaydiumClmmParser::new()
+            .parse(txn)
+            .unwrap()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|ev| match ev {
+                TransactionEvent::RaydiumSwap(swap) => swap,
+                other => panic!("unexpected event {:?}", other),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn exact_input_swap_v2_decodes_every_field() {
+        let swaps = swap(swap_data(SWAP_V2_DISCM, 1_000, 900, true), vec![token_transfer(1_000, 4, 6), token_transfer(955, 6, 5)]);
+
+        let [swap] = swaps.as_slice() else { panic!("unexpected swaps {:?}", swaps) };
+        assert_eq!(swap.pool_type, RaydiumPoolType::Clmm);
+        assert_eq!((swap.amm_pool.as_str(), swap.signer.as_str()), (address(3).as_str(), address(1).as_str()));
+        assert_eq!((swap.amount_in, swap.min_amount_out, swap.amount_received), (1_000, 900, 955));
+        assert_eq!((swap.mint_source.as_str(), swap.mint_destination.as_str()), ("MintIn", "MintOut"));
+        assert_eq!((swap.instruction_index, swap.inner_index), (0, None));
+    }
+
+    #[test]
+    fn exact_output_swap_takes_the_input_from_the_transfer() {
+        let swaps = swap(swap_data(SWAP_DISCM, 700, 5_000, false), vec![token_transfer(4_200, 4, 6), token_transfer(700, 6, 5)]);
+
+        let [swap] = swaps.as_slice() else { panic!("unexpected swaps {:?}", swaps) };
+        assert_eq!((swap.amount_in, swap.min_amount_out, swap.amount_received), (4_200, 700, 700));
+    }
+
+    #[test]
+    fn truncated_swap_args_are_an_error() {
+        let Err(err) = RaydiumClmmParser::new().decode_swap(&SWAP_DISCM, &[0, 1, 2, 3, 4]) else {
+            panic!("a bare discriminator decoded as a swap");
+        };
+
+        assert!(matches!(err, ParserError::TruncatedData { expected: 41, got: 8 }));
+    }
+
+    fn parser_with(json: &str) -> RaydiumClmmParser {
+        let mut tables = DiscriminatorTable::from_json(json).unwrap();
+        RaydiumClmmParser::new().with_discriminators(tables.remove("raydium_clmm").unwrap())
+    }
+
+    #[test]
+    fn discriminator_loaded_from_json_is_decoded_as_a_swap() {
+        let data = swap_data(DiscriminatorTable::anchor("swap_v3"), 1_000, 900, true);
+        let accounts = [0, 1, 2, 3, 4, 5];
+        assert!(RaydiumClmmParser::new().decode_swap(&data, &accounts).unwrap().is_none());
+
+        let parser = parser_with(r#"{"raydium_clmm": {"swap_v2": ["swap_v3"]}}"#);
+
+        let swap = parser.decode_swap(&data, &accounts).unwrap().expect("loaded discriminator not recognised");
+        assert_eq!((swap.args.amount, swap.args.other_amount_threshold), (1_000, 900));
+        // Built-ins still decode
+        assert!(parser.decode_swap(&swap_data(SWAP_DISCM, 1, 1, true), &accounts).unwrap().is_some());
+    }
+
+    #[test]
+    fn discriminator_loaded_under_an_unknown_kind_is_an_error() {
+        let parser = parser_with(r#"{"raydium_clmm": {"close_position": ["0102030405060708"]}}"#);
+
+        let err = parser.decode_swap(&[1, 2, 3, 4, 5, 6, 7, 8], &[0, 1, 2, 3, 4, 5]).unwrap_err();
+
+        assert!(matches!(err, ParserError::UnknownDiscriminator(ref d) if d == &[1, 2, 3, 4, 5, 6, 7, 8]));
+    }
+}
@@ -1,13 +1,15 @@
-use anyhow::Result;
+use std::{collections::BTreeMap, str::FromStr};
+
 use borsh::BorshDeserialize;
 use prost::Message;
-use solana_transaction_status::{UiInstruction, UiTransactionStatusMeta, option_serializer::OptionSerializer};
+use solana_transaction_status::{UiInstruction, UiTransactionStatusMeta, UiTransactionTokenBalance, option_serializer::OptionSerializer};
 use solana_sdk::transaction::VersionedTransaction;
-use yellowstone_grpc_proto::geyser::SubscribeUpdate;
+use yellowstone_grpc_proto::{geyser::SubscribeUpdate, prelude::TokenBalance};
 
 use crate::{
-    application::TransactionParser,
-    domain::{self, SolanaTransaction, TokenTransfer, TransactionEvent, TxData},
+    adapters::parsers::VixenUtils,
+    application::{ParserError, ParserResult, TransactionParser},
+    domain::{self, Pubkey58, Signature58, SolanaTransaction, TokenTransfer, TransactionEvent, TxData},
 };
 
 #[derive(BorshDeserialize, Debug)]
@@ -21,39 +23,176 @@ struct SplTransferCheckedArgs {
     pub decimals: u8,
 }
 
-pub struct SplTokenTransfer;
+/// How `SplTokenTransfer` finds transfers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SplTransferMode {
+    /// Decode top-level `Transfer` / `TransferChecked` instructions (plus inner ones
+    /// over RPC). Amounts are what the instruction asked for.
+    #[default]
+    Instruction,
+    /// Derive transfers from each token account's pre/post balance, per mint. Captures
+    /// CPI transfers and reports the net effect, but not the individual hops.
+    BalanceDiff,
+}
+
+impl FromStr for SplTransferMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "instruction" => Ok(Self::Instruction),
+            "balance_diff" | "balance-diff" => Ok(Self::BalanceDiff),
+            _ => Err(format!("unknown SPL transfer mode `{}`", s)),
+        }
+    }
+}
+
+pub struct SplTokenTransfer {
+    mode: SplTransferMode,
+}
 
 impl SplTokenTransfer {
-    pub fn new() -> Self { Self }
+    pub fn new() -> Self { Self { mode: SplTransferMode::default() } }
+
+    pub fn with_mode(mut self, mode: SplTransferMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Pair each mint's net outflows with its net inflows, largest first. With a
+    /// single sender and receiver this is the exact transfer; with several it is
+    /// one consistent decomposition of the net flow. Derived transfers have no
+    /// instruction behind them, so `instruction_index` just numbers them in order.
+    fn balance_diff_transfers(
+        balances: impl IntoIterator<Item = (usize, String, i128)>,
+        account_keys: &[impl AsRef<str>],
+        slot: u64,
+        signature: &Signature58,
+        block_time: Option<i64>,
+    ) -> Vec<TransactionEvent> {
+        let mut deltas: BTreeMap<(String, usize), i128> = BTreeMap::new();
+        for (idx, mint, delta) in balances {
+            *deltas.entry((mint, idx)).or_default() += delta;
+        }
+
+        let mut by_mint: BTreeMap<&str, (Vec<(usize, u128)>, Vec<(usize, u128)>)> = BTreeMap::new();
+        for ((mint, idx), delta) in &deltas {
+            let (out, inc) = by_mint.entry(mint.as_str()).or_default();
+            if *delta < 0 {
+                out.push((*idx, delta.unsigned_abs()));
+            } else if *delta > 0 {
+                inc.push((*idx, *delta as u128));
+            }
+        }
+
+        let mut transfers = Vec::new();
+        for (mint, (mut out, mut inc)) in by_mint {
+            out.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+            inc.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+            let (mut i, mut j) = (0, 0);
+            while i < out.len() && j < inc.len() {
+                let moved = out[i].1.min(inc[j].1);
+                let address = |idx: usize| account_keys.get(idx).and_then(|k| Pubkey58::try_from(k.as_ref()).ok());
+                let (Some(from), Some(to)) = (address(out[i].0), address(inc[j].0)) else { break };
+                transfers.push(TransactionEvent::TokenTransfer(TokenTransfer {
+                    from,
+                    to,
+                    mint: Some(mint.to_string()),
+                    slot,
+                    block_time,
+                    amount: u64::try_from(moved).unwrap_or(u64::MAX),
+                    signature: signature.clone(),
+                    instruction_index: transfers.len() as u32,
+                    inner_index: None,
+                }));
+                out[i].1 -= moved;
+                inc[j].1 -= moved;
+                if out[i].1 == 0 { i += 1; }
+                if inc[j].1 == 0 { j += 1; }
+            }
+        }
+        transfers
+    }
 
-    fn parse_protobuf(raw_bytes: &[u8]) -> Result<Option<Vec<TransactionEvent>>> {
+    /// (account index, mint, signed raw amount): pre balances negated, post as is.
+    fn grpc_balances<'a>(pre: &'a [TokenBalance], post: &'a [TokenBalance]) -> impl Iterator<Item = (usize, String, i128)> + 'a {
+        let amount = |b: &TokenBalance| b.ui_token_amount.as_ref().and_then(|a| a.amount.parse::<i128>().ok()).unwrap_or(0);
+        pre.iter()
+            .map(move |b| (b.account_index as usize, b.mint.clone(), -amount(b)))
+            .chain(post.iter().map(move |b| (b.account_index as usize, b.mint.clone(), amount(b))))
+    }
+
+    fn rpc_balances<'a>(
+        pre: &'a [UiTransactionTokenBalance],
+        post: &'a [UiTransactionTokenBalance],
+    ) -> impl Iterator<Item = (usize, String, i128)> + 'a {
+        let amount = |b: &UiTransactionTokenBalance| b.ui_token_amount.amount.parse::<i128>().unwrap_or(0);
+        pre.iter()
+            .map(move |b| (b.account_index as usize, b.mint.clone(), -amount(b)))
+            .chain(post.iter().map(move |b| (b.account_index as usize, b.mint.clone(), amount(b))))
+    }
+
+    fn parse_grpc_balance_diff(raw_bytes: &[u8], block_time: Option<i64>) -> ParserResult<Option<Vec<TransactionEvent>>> {
+        let update = SubscribeUpdate::decode(raw_bytes)?;
+        let Some(yellowstone_grpc_proto::geyser::subscribe_update::UpdateOneof::Transaction(tx_info)) = update.update_oneof else {
+            return Ok(Some(Vec::new()));
+        };
+        let tx_details = tx_info.transaction.ok_or_else(|| ParserError::DecodeFailed("missing transaction".into()))?;
+        let signature = Signature58::try_from(tx_details.signature.as_slice())?;
+        let message = tx_details.transaction.and_then(|t| t.message).ok_or_else(|| ParserError::DecodeFailed("missing message".into()))?;
+        let Some(meta) = tx_details.meta.as_ref() else {
+            tracing::debug!("Transaction {} has no meta, so no balances to diff", signature);
+            return Ok(Some(Vec::new()));
+        };
+
+        let account_keys = VixenUtils::account_strings(&VixenUtils::extract_accounts_from_message(&message, &meta));
+        let balances = Self::grpc_balances(&meta.pre_token_balances, &meta.post_token_balances);
+
+        Ok(Some(Self::balance_diff_transfers(balances, &account_keys, tx_info.slot, &signature, block_time)))
+    }
+
+    fn parse_rpc_balance_diff(
+        tx: &VersionedTransaction,
+        meta: &UiTransactionStatusMeta,
+        slot: u64,
+        sig: &str,
+        block_time: Option<i64>,
+    ) -> ParserResult<Option<Vec<TransactionEvent>>> {
+        let all_keys = VixenUtils::account_strings(&VixenUtils::extract_accounts_from_rpc(tx, meta));
+        let empty: Vec<UiTransactionTokenBalance> = vec![];
+        let pre  = if let OptionSerializer::Some(v) = &meta.pre_token_balances  { v.as_slice() } else { &empty };
+        let post = if let OptionSerializer::Some(v) = &meta.post_token_balances { v.as_slice() } else { &empty };
+
+        let sig = Signature58::try_from(sig)?;
+        Ok(Some(Self::balance_diff_transfers(Self::rpc_balances(pre, post), &all_keys, slot, &sig, block_time)))
+    }
+
+    fn parse_protobuf(raw_bytes: &[u8], block_time: Option<i64>) -> ParserResult<Option<Vec<TransactionEvent>>> {
         let update = SubscribeUpdate::decode(raw_bytes)?;
         let mut transfers: Vec<TransactionEvent> = Vec::new();
 
         if let Some(yellowstone_grpc_proto::geyser::subscribe_update::UpdateOneof::Transaction(tx_info)) = update.update_oneof {
             let slot = tx_info.slot;
-            let tx_details = tx_info.transaction.unwrap();
-            let signature = bs58::encode(&tx_details.signature).into_string();
-            let message = tx_details.transaction.unwrap().message.unwrap();
-            let meta = tx_details.meta.as_ref().ok_or_else(|| anyhow::anyhow!("Missing meta"))?;
-
-            let mut account_keys: Vec<String> = message.account_keys.iter()
-                .map(|k| bs58::encode(k).into_string())
-                .collect();
-
-            let token_prog_idx = account_keys.iter().position(|k| k == domain::TOKEN_PROGRAM_ID);
+            let Some(tx_details) = tx_info.transaction else { return Ok(None) };
+            let signature = Signature58::try_from(tx_details.signature.as_slice())?;
+            let Some(message) = tx_details.transaction.and_then(|t| t.message) else { return Ok(None) };
+            // Without meta the lookup-table addresses are unknown; transfers that only
+            // touch static keys still resolve, the rest fall out of range below
+            let accounts = match tx_details.meta.as_ref() {
+                Some(meta) => VixenUtils::extract_accounts_from_message(&message, meta),
+                None => {
+                    tracing::debug!("Transaction {} has no meta, resolving static keys only", signature);
+                    VixenUtils::extract_accounts_from_grpc(&message.account_keys, &[], &[])
+                }
+            };
+            let account_keys = VixenUtils::account_strings(&accounts);
 
-            for acc in &meta.loaded_writable_addresses {
-                account_keys.push(bs58::encode(acc).into_string());
-            }
-            for acc in &meta.loaded_readonly_addresses {
-                account_keys.push(bs58::encode(acc).into_string());
-            }
+            let token_prog_idx = account_keys.iter().position(|k| &**k == domain::TOKEN_PROGRAM_ID);
 
             if let Some(pgm_idx) = token_prog_idx {
                 let pgm_idx = pgm_idx as u32;
 
-                for ix in message.instructions {
+                for (ix_idx, ix) in message.instructions.into_iter().enumerate() {
                     if ix.program_id_index != pgm_idx { continue; }
 
                     match ix.data.first().copied() {
@@ -64,12 +203,15 @@ impl SplTokenTransfer {
                                 let to_idx = ix.accounts[1] as usize;
                                 if from_idx >= account_keys.len() || to_idx >= account_keys.len() { continue; }
                                 transfers.push(TransactionEvent::TokenTransfer(TokenTransfer {
-                                    from: account_keys[from_idx].clone(),
-                                    to: account_keys[to_idx].clone(),
+                                    from: Pubkey58::try_from(&*account_keys[from_idx])?,
+                                    to: Pubkey58::try_from(&*account_keys[to_idx])?,
                                     mint: None,
                                     slot,
+                                    block_time,
                                     amount: args.amount,
                                     signature: signature.clone(),
+                                    instruction_index: ix_idx as u32,
+                                    inner_index: None,
                                 }));
                             }
                         }
@@ -81,12 +223,15 @@ impl SplTokenTransfer {
                                 let to_idx = ix.accounts[2] as usize;
                                 if from_idx >= account_keys.len() || to_idx >= account_keys.len() || mint_idx >= account_keys.len() { continue; }
                                 transfers.push(TransactionEvent::TokenTransfer(TokenTransfer {
-                                    from: account_keys[from_idx].clone(),
-                                    to: account_keys[to_idx].clone(),
-                                    mint: Some(account_keys[mint_idx].clone()),
+                                    from: Pubkey58::try_from(&*account_keys[from_idx])?,
+                                    to: Pubkey58::try_from(&*account_keys[to_idx])?,
+                                    mint: Some(account_keys[mint_idx].to_string()),
                                     slot,
+                                    block_time,
                                     amount: args.amount,
                                     signature: signature.clone(),
+                                    instruction_index: ix_idx as u32,
+                                    inner_index: None,
                                 }));
                             }
                         }
@@ -104,53 +249,51 @@ impl SplTokenTransfer {
         meta: &UiTransactionStatusMeta,
         slot: u64,
         sig: &str,
-    ) -> Result<Option<Vec<TransactionEvent>>> {
+        block_time: Option<i64>,
+    ) -> ParserResult<Option<Vec<TransactionEvent>>> {
         let mut transfers: Vec<TransactionEvent> = Vec::new();
         let message = &tx.message;
+        let sig = Signature58::try_from(sig)?;
 
-        let mut all_keys: Vec<String> = message.static_account_keys().iter().map(|k| k.to_string()).collect();
-        if let OptionSerializer::Some(loaded) = &meta.loaded_addresses {
-            for acc in &loaded.writable { all_keys.push(acc.clone()); }
-            for acc in &loaded.readonly { all_keys.push(acc.clone()); }
-        }
+        let all_keys = VixenUtils::account_strings(&VixenUtils::extract_accounts_from_rpc(tx, meta));
 
-        let token_prog_idx = match all_keys.iter().position(|k| k == domain::TOKEN_PROGRAM_ID) {
+        let token_prog_idx = match all_keys.iter().position(|k| &**k == domain::TOKEN_PROGRAM_ID) {
             Some(idx) => idx as u8,
             None => return Ok(Some(transfers)),
         };
 
-        let parse_ix = |pgm_id: u8, data: &[u8], accounts: &[u8]| -> Option<TokenTransfer> {
+        let parse_ix = |pgm_id: u8, data: &[u8], accounts: &[u8], instruction_index: u32, inner_index: Option<u32>| -> Option<TokenTransfer> {
             if pgm_id != token_prog_idx { return None; }
             match data.first() {
                 Some(3) if data.len() >= 9 => {
                     let args = SplTransferArgs::try_from_slice(&data[1..9]).ok()?;
-                    let from = all_keys.get(*accounts.get(0)? as usize)?.clone();
-                    let to = all_keys.get(*accounts.get(1)? as usize)?.clone();
-                    Some(TokenTransfer { from, to, mint: None, slot, amount: args.amount, signature: sig.to_string() })
+                    let from = Pubkey58::try_from(&**all_keys.get(*accounts.get(0)? as usize)?).ok()?;
+                    let to = Pubkey58::try_from(&**all_keys.get(*accounts.get(1)? as usize)?).ok()?;
+                    Some(TokenTransfer { from, to, mint: None, slot, block_time, amount: args.amount, signature: sig.clone(), instruction_index, inner_index })
                 }
                 Some(12) if data.len() >= 10 => {
                     let args = SplTransferCheckedArgs::try_from_slice(&data[1..10]).ok()?;
-                    let from = all_keys.get(*accounts.get(0)? as usize)?.clone();
-                    let mint = Some(all_keys.get(*accounts.get(1)? as usize)?.clone());
-                    let to = all_keys.get(*accounts.get(2)? as usize)?.clone();
-                    Some(TokenTransfer { from, to, mint, slot, amount: args.amount, signature: sig.to_string() })
+                    let from = Pubkey58::try_from(&**all_keys.get(*accounts.get(0)? as usize)?).ok()?;
+                    let mint = Some(all_keys.get(*accounts.get(1)? as usize)?.to_string());
+                    let to = Pubkey58::try_from(&**all_keys.get(*accounts.get(2)? as usize)?).ok()?;
+                    Some(TokenTransfer { from, to, mint, slot, block_time, amount: args.amount, signature: sig.clone(), instruction_index, inner_index })
                 }
                 _ => None,
             }
         };
 
-        for ix in message.instructions() {
-            if let Some(t) = parse_ix(ix.program_id_index, &ix.data, &ix.accounts) {
+        for (ix_idx, ix) in message.instructions().iter().enumerate() {
+            if let Some(t) = parse_ix(ix.program_id_index, &ix.data, &ix.accounts, ix_idx as u32, None) {
                 transfers.push(TransactionEvent::TokenTransfer(t));
             }
         }
 
         if let OptionSerializer::Some(inner_groups) = &meta.inner_instructions {
             for group in inner_groups {
-                for inner_ix in &group.instructions {
+                for (inner_idx, inner_ix) in group.instructions.iter().enumerate() {
                     if let UiInstruction::Compiled(c) = inner_ix {
                         if let Ok(raw) = bs58::decode(&c.data).into_vec() {
-                            if let Some(t) = parse_ix(c.program_id_index, &raw, &c.accounts) {
+                            if let Some(t) = parse_ix(c.program_id_index, &raw, &c.accounts, group.index as u32, Some(inner_idx as u32)) {
                                 transfers.push(TransactionEvent::TokenTransfer(t));
                             }
                         }
@@ -166,10 +309,180 @@ impl SplTokenTransfer {
 impl TransactionParser for SplTokenTransfer {
     fn name(&self) -> &str { "spl_token_transfer" }
 
-    fn parse(&self, txn: SolanaTransaction) -> Result<Option<Vec<TransactionEvent>>> {
-        match txn.data {
-            TxData::Grpc(bytes) => Self::parse_protobuf(&bytes),
-            TxData::Rpc { tx, meta } => Self::parse_rpc(&tx, &meta, txn.slot, &txn.signature),
+    fn emitted_event_kinds(&self) -> &[&str] { &["token_transfer"] }
+
+    fn parse(&self, txn: SolanaTransaction) -> ParserResult<Option<Vec<TransactionEvent>>> {
+        match (self.mode, txn.data) {
+            // Simulated sources emit empty frames; there is nothing to decode
+            (_, TxData::Grpc(bytes)) if bytes.is_empty() => Ok(None),
+            (SplTransferMode::Instruction, TxData::Grpc(bytes)) => Self::parse_protobuf(&bytes, txn.block_time),
+            (SplTransferMode::Instruction, TxData::Rpc { tx, meta }) => Self::parse_rpc(&tx, &meta, txn.slot, &txn.signature, txn.block_time),
+            (SplTransferMode::BalanceDiff, TxData::Grpc(bytes)) => Self::parse_grpc_balance_diff(&bytes, txn.block_time),
+            (SplTransferMode::BalanceDiff, TxData::Rpc { tx, meta }) => {
+                Self::parse_rpc_balance_diff(&tx, &meta, txn.slot, &txn.signature, txn.block_time)
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use yellowstone_grpc_proto::{
+        geyser::{SubscribeUpdateTransaction, SubscribeUpdateTransactionInfo, subscribe_update::UpdateOneof},
+        prelude::{CompiledInstruction, Message as GrpcMessage, Transaction, TransactionStatusMeta, UiTokenAmount},
+    };
+
+    use super::*;
+    use crate::domain::SIGNATURE_LEN;
+
+    // Account keys: fee payer (0), sender's token account (1), receiver's token account (2)
+    const KEYS: [[u8; 32]; 3] = [[9; 32], [1; 32], [2; 32]];
+
+    fn balance(account_index: u32, mint: &str, amount: u64) -> TokenBalance {
+        TokenBalance {
+            account_index,
+            mint: mint.to_string(),
+            ui_token_amount: Some(UiTokenAmount { amount: amount.to_string(), decimals: 6, ..Default::default() }),
+            ..Default::default()
+        }
+    }
+
+    /// A transaction with no token instruction of its own, only balance changes,
+    /// as when the transfer happens inside another program's CPI.
+    fn transaction(pre: Vec<TokenBalance>, post: Vec<TokenBalance>) -> SolanaTransaction {
+        let message = GrpcMessage { account_keys: KEYS.iter().map(|k| k.to_vec()).collect(), ..Default::default() };
+        let update = SubscribeUpdate {
+            update_oneof: Some(UpdateOneof::Transaction(SubscribeUpdateTransaction {
+                slot: 10,
+                transaction: Some(SubscribeUpdateTransactionInfo {
+                    signature: vec![7; SIGNATURE_LEN],
+                    transaction: Some(Transaction { signatures: vec![vec![7; SIGNATURE_LEN]], message: Some(message) }),
+                    meta: Some(TransactionStatusMeta { pre_token_balances: pre, post_token_balances: post, ..Default::default() }),
+                    ..Default::default()
+                }),
+            })),
+            ..Default::default()
+        };
+        SolanaTransaction {
+            signature: Signature58::from([7u8; 64]).to_string(),
+            success: true,
+            data: TxData::Grpc(update.encode_to_vec()),
+            slot: 10,
+            block_time: None,
+            fee: None,
+            compute_units_consumed: None,
+            fee_payer: Pubkey58::from(KEYS[0]).to_string(),
+            recent_blockhash: String::new(),
+        }
+    }
+
+    fn transfers(parser: SplTokenTransfer, txn: SolanaTransaction) -> Vec<TokenTransfer> {
+        parser
+            .parse(txn)
+            .unwrap()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|ev| match ev {
+                TransactionEvent::TokenTransfer(transfer) => transfer,
+                other => panic!("unexpected event {:?}", other),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn net_balance_move_becomes_a_transfer() {
+        let txn = transaction(
+            vec![balance(1, "Mint", 1_000), balance(2, "Mint", 0)],
+            vec![balance(1, "Mint", 700), balance(2, "Mint", 300)],
+        );
+
+        let found = transfers(SplTokenTransfer::new().with_mode(SplTransferMode::BalanceDiff), txn);
+
+        assert_eq!(found.len(), 1);
+        assert_eq!((found[0].from.clone(), found[0].to.clone()), (Pubkey58::from(KEYS[1]), Pubkey58::from(KEYS[2])));
+        assert_eq!((found[0].amount, found[0].mint.as_deref()), (300, Some("Mint")));
+    }
+
+    #[test]
+    fn instruction_mode_misses_the_cpi_transfer() {
+        let txn = transaction(vec![balance(1, "Mint", 1_000)], vec![balance(1, "Mint", 700), balance(2, "Mint", 300)]);
+
+        assert!(transfers(SplTokenTransfer::new(), txn).is_empty());
+    }
+
+    #[test]
+    fn fee_on_transfer_reports_what_each_side_actually_moved() {
+        // 300 left the sender; the receiver got 290 and the fee account (0) 10
+        let balances = vec![
+            (1, "Mint".to_string(), -300),
+            (2, "Mint".to_string(), 290),
+            (0, "Mint".to_string(), 10),
+        ];
+        let keys: Vec<String> = KEYS.iter().map(|k| Pubkey58::from(*k).to_string()).collect();
+
+        let events = SplTokenTransfer::balance_diff_transfers(balances, &keys, 10, &Signature58::from([7u8; 64]), None);
+
+        let moved: Vec<_> = events
+            .iter()
+            .map(|ev| match ev {
+                TransactionEvent::TokenTransfer(t) => (t.to.clone(), t.amount, t.instruction_index),
+                other => panic!("unexpected event {:?}", other),
+            })
+            .collect();
+        assert_eq!(moved, vec![(Pubkey58::from(KEYS[2]), 290, 0), (Pubkey58::from(KEYS[0]), 10, 1)]);
+    }
+
+    #[test]
+    fn transfer_mode_names_parse() {
+        assert_eq!("balance-diff".parse(), Ok(SplTransferMode::BalanceDiff));
+        assert_eq!("Instruction".parse(), Ok(SplTransferMode::Instruction));
+        assert!("both".parse::<SplTransferMode>().is_err());
+    }
+
+    /// A v0 transaction delivered without meta: two token transfers, the second
+    /// to an account (5) that only a lookup table could have supplied.
+    fn transaction_without_meta() -> SolanaTransaction {
+        let mut account_keys: Vec<Vec<u8>> = KEYS.iter().map(|k| k.to_vec()).collect();
+        account_keys.push(bs58::decode(domain::TOKEN_PROGRAM_ID).into_vec().unwrap());
+        let transfer = |amount: u64, to: u8| {
+            let mut data = vec![3];
+            data.extend(amount.to_le_bytes());
+            CompiledInstruction { program_id_index: 3, accounts: vec![1, to, 0], data }
+        };
+        let message = GrpcMessage {
+            account_keys,
+            instructions: vec![transfer(500, 2), transfer(700, 5)],
+            versioned: true,
+            ..Default::default()
+        };
+        let update = SubscribeUpdate {
+            update_oneof: Some(UpdateOneof::Transaction(SubscribeUpdateTransaction {
+                slot: 10,
+                transaction: Some(SubscribeUpdateTransactionInfo {
+                    signature: vec![7; SIGNATURE_LEN],
+                    transaction: Some(Transaction { signatures: vec![vec![7; SIGNATURE_LEN]], message: Some(message) }),
+                    meta: None,
+                    ..Default::default()
+                }),
+            })),
+            ..Default::default()
+        };
+        SolanaTransaction { data: TxData::Grpc(update.encode_to_vec()), ..transaction(Vec::new(), Vec::new()) }
+    }
+
+    #[test]
+    fn missing_meta_still_yields_transfers_between_static_keys() {
+        let found = transfers(SplTokenTransfer::new(), transaction_without_meta());
+
+        assert_eq!(found.len(), 1);
+        assert_eq!((found[0].from.clone(), found[0].to.clone()), (Pubkey58::from(KEYS[1]), Pubkey58::from(KEYS[2])));
+        assert_eq!((found[0].amount, found[0].instruction_index), (500, 0));
+    }
+
+    #[test]
+    fn missing_meta_leaves_nothing_to_diff() {
+        let parser = SplTokenTransfer::new().with_mode(SplTransferMode::BalanceDiff);
+
+        assert!(transfers(parser, transaction_without_meta()).is_empty());
+    }
+}
@@ -0,0 +1,283 @@
+use std::sync::Arc;
+
+use prost::Message;
+use solana_transaction_status::{UiInstruction, UiTransactionStatusMeta, option_serializer::OptionSerializer};
+use solana_sdk::transaction::VersionedTransaction;
+use yellowstone_grpc_proto::geyser::SubscribeUpdate;
+
+use crate::{
+    adapters::parsers::{IxPosition, VixenUtils},
+    application::{ParserResult, TransactionParser},
+    domain::{self, Pubkey58, Signature58, SolTransfer, SolanaTransaction, TransactionEvent, TxData},
+};
+
+// System program instructions are tagged with a little-endian u32
+const TRANSFER: u32 = 2;
+const TRANSFER_WITH_SEED: u32 = 11;
+
+pub struct SystemTransferParser;
+
+impl SystemTransferParser {
+    pub fn new() -> Self { Self }
+
+    /// Returns (from_idx, to_idx, lamports) for a transfer instruction.
+    /// `Transfer` lists [from, to]; `TransferWithSeed` lists [from, base, to].
+    fn decode(data: &[u8], accounts: &[u8]) -> Option<(usize, usize, u64)> {
+        let tag = u32::from_le_bytes(data.get(..4)?.try_into().ok()?);
+        let lamports = u64::from_le_bytes(data.get(4..12)?.try_into().ok()?);
+        let (from, to) = match tag {
+            TRANSFER => (*accounts.first()?, *accounts.get(1)?),
+            TRANSFER_WITH_SEED => (*accounts.first()?, *accounts.get(2)?),
+            _ => return None,
+        };
+        Some((from as usize, to as usize, lamports))
+    }
+
+    fn transfer(
+        keys: &[Arc<str>],
+        data: &[u8],
+        accounts: &[u8],
+        pos: IxPosition,
+        signature: &Signature58,
+        slot: u64,
+        block_time: Option<i64>,
+    ) -> Option<TransactionEvent> {
+        let (from_idx, to_idx, lamports) = Self::decode(data, accounts)?;
+        Some(TransactionEvent::SolTransfer(SolTransfer {
+            from: Pubkey58::try_from(&**keys.get(from_idx)?).ok()?,
+            to: Pubkey58::try_from(&**keys.get(to_idx)?).ok()?,
+            slot,
+            block_time,
+            lamports,
+            signature: signature.clone(),
+            instruction_index: pos.instruction_index as u32,
+            inner_index: pos.inner_index,
+        }))
+    }
+
+    fn parse_protobuf(&self, raw_bytes: &[u8], block_time: Option<i64>) -> ParserResult<Option<Vec<TransactionEvent>>> {
+        let update = SubscribeUpdate::decode(raw_bytes)?;
+        let mut events: Vec<TransactionEvent> = Vec::new();
+
+        if let Some(yellowstone_grpc_proto::geyser::subscribe_update::UpdateOneof::Transaction(tx_info)) = update.update_oneof {
+            let slot = tx_info.slot;
+            let Some(tx_details) = tx_info.transaction else { return Ok(None) };
+            let signature = Signature58::try_from(tx_details.signature.as_slice())?;
+            let Some(meta) = tx_details.meta else { return Ok(None) };
+            let Some(message) = tx_details.transaction.and_then(|t| t.message) else { return Ok(None) };
+
+            let account_keys = VixenUtils::account_strings(&VixenUtils::extract_accounts_from_message(&message, &meta));
+
+            let Some(pgm_idx) = account_keys.iter().position(|k| &**k == domain::SYSTEM_PROGRAM) else {
+                return Ok(Some(events));
+            };
+            let pgm_idx = pgm_idx as u32;
+
+            for (ix_idx, ix) in message.instructions.iter().enumerate() {
+                if ix.program_id_index != pgm_idx { continue; }
+                let pos = IxPosition::top_level(ix_idx);
+                events.extend(Self::transfer(&account_keys, &ix.data, &ix.accounts, pos, &signature, slot, block_time));
+            }
+
+            for group in &meta.inner_instructions {
+                for (inner_idx, ix) in group.instructions.iter().enumerate() {
+                    if ix.program_id_index != pgm_idx { continue; }
+                    let pos = IxPosition::inner(group.index as usize, inner_idx, ix.stack_height);
+                    events.extend(Self::transfer(&account_keys, &ix.data, &ix.accounts, pos, &signature, slot, block_time));
+                }
+            }
+        }
+
+        Ok(Some(events))
+    }
+
+    fn parse_rpc(
+        &self,
+        tx: &VersionedTransaction,
+        meta: &UiTransactionStatusMeta,
+        slot: u64,
+        signature: &str,
+        block_time: Option<i64>,
+    ) -> ParserResult<Option<Vec<TransactionEvent>>> {
+        let mut events: Vec<TransactionEvent> = Vec::new();
+        let signature = &Signature58::try_from(signature)?;
+
+        let all_keys = VixenUtils::account_strings(&VixenUtils::extract_accounts_from_rpc(tx, meta));
+
+        let Some(pgm_idx) = all_keys.iter().position(|k| &**k == domain::SYSTEM_PROGRAM) else {
+            return Ok(Some(events));
+        };
+
+        for (ix_idx, ix) in tx.message.instructions().iter().enumerate() {
+            if ix.program_id_index as usize != pgm_idx { continue; }
+            let pos = IxPosition::top_level(ix_idx);
+            events.extend(Self::transfer(&all_keys, &ix.data, &ix.accounts, pos, signature, slot, block_time));
+        }
+
+        if let OptionSerializer::Some(inner_groups) = &meta.inner_instructions {
+            for group in inner_groups {
+                for (inner_idx, inner_ix) in group.instructions.iter().enumerate() {
+                    let UiInstruction::Compiled(c) = inner_ix else { continue };
+                    if c.program_id_index as usize != pgm_idx { continue; }
+                    let Ok(raw) = bs58::decode(&c.data).into_vec() else { continue };
+                    let pos = IxPosition::inner(group.index as usize, inner_idx, c.stack_height);
+                    events.extend(Self::transfer(&all_keys, &raw, &c.accounts, pos, signature, slot, block_time));
+                }
+            }
+        }
+
+        Ok(Some(events))
+    }
+}
+
+impl TransactionParser for SystemTransferParser {
+    fn name(&self) -> &str { "system_transfer" }
+
+    fn emitted_event_kinds(&self) -> &[&str] { &["sol_transfer"] }
+
+    fn parse(&self, txn: SolanaTransaction) -> ParserResult<Option<Vec<TransactionEvent>>> {
+        match txn.data {
+            // Simulated sources emit empty frames; there is nothing to decode
+            TxData::Grpc(bytes) if bytes.is_empty() => Ok(None),
+            TxData::Grpc(bytes) => self.parse_protobuf(&bytes, txn.block_time),
+            TxData::Rpc { tx, meta } => self.parse_rpc(&tx, &meta, txn.slot, &txn.signature, txn.block_time),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use yellowstone_grpc_proto::{
+        geyser::{SubscribeUpdateTransaction, SubscribeUpdateTransactionInfo, subscribe_update::UpdateOneof},
+        prelude::{CompiledInstruction, InnerInstruction, InnerInstructions, Message as GrpcMessage, Transaction, TransactionStatusMeta},
+    };
+
+    use super::*;
+    use crate::{application::ParserError, domain::SIGNATURE_LEN};
+
+    // Account keys: from (0), to (1), the system program (2)
+    const FROM: [u8; 32] = [1; 32];
+    const TO: [u8; 32] = [2; 32];
+
+    fn transfer_data(lamports: u64) -> Vec<u8> {
+        let mut data = TRANSFER.to_le_bytes().to_vec();
+        data.extend(lamports.to_le_bytes());
+        data
+    }
+
+    fn system_ix(data: Vec<u8>) -> CompiledInstruction {
+        CompiledInstruction { program_id_index: 2, accounts: vec![0, 1], data }
+    }
+
+    fn transaction(instructions: Vec<CompiledInstruction>, inner: Vec<InnerInstructions>, block_time: Option<i64>) -> SolanaTransaction {
+        let message = GrpcMessage {
+            account_keys: vec![FROM.to_vec(), TO.to_vec(), [0u8; 32].to_vec()],
+            instructions,
+            ..Default::default()
+        };
+        let update = SubscribeUpdate {
+            update_oneof: Some(UpdateOneof::Transaction(SubscribeUpdateTransaction {
+                slot: 10,
+                transaction: Some(SubscribeUpdateTransactionInfo {
+                    signature: vec![7; SIGNATURE_LEN],
+                    transaction: Some(Transaction { signatures: vec![vec![7; SIGNATURE_LEN]], message: Some(message) }),
+                    meta: Some(TransactionStatusMeta { inner_instructions: inner, ..Default::default() }),
+                    ..Default::default()
+                }),
+            })),
+            ..Default::default()
+        };
+        SolanaTransaction {
+            signature: Signature58::from([7u8; 64]).to_string(),
+            success: true,
+            data: TxData::Grpc(update.encode_to_vec()),
+            slot: 10,
+            block_time,
+            fee: None,
+            compute_units_consumed: None,
+            fee_payer: Pubkey58::from(FROM).to_string(),
+            recent_blockhash: String::new(),
+        }
+    }
+
+    fn transfers(txn: SolanaTransaction) -> Vec<SolTransfer> {
+        let events = SystemTransferParser::new().parse(txn).unwrap().unwrap_or_default();
+        events
+            .into_iter()
+            .map(|ev| match ev {
+                TransactionEvent::SolTransfer(transfer) => transfer,
+                other => panic!("unexpected event {:?}", other),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn second_instruction_records_its_own_index() {
+        // A CreateAccount (tag 0) first, then the transfer
+        let mut create = 0u32.to_le_bytes().to_vec();
+        create.extend([0u8; 8]);
+        let txn = transaction(vec![system_ix(create), system_ix(transfer_data(500))], Vec::new(), None);
+
+        let found = transfers(txn);
+
+        assert_eq!(found.len(), 1);
+        assert_eq!((found[0].instruction_index, found[0].inner_index), (1, None));
+        assert_eq!(found[0].lamports, 500);
+    }
+
+    #[test]
+    fn inner_transfer_records_its_parent_and_position() {
+        let inner = InnerInstructions {
+            index: 1,
+            instructions: vec![InnerInstruction {
+                program_id_index: 2,
+                accounts: vec![0, 1],
+                data: transfer_data(700),
+                stack_height: Some(2),
+            }],
+        };
+        // Instruction 1 stands in for a program that pays out through the system program
+        let txn = transaction(vec![system_ix(transfer_data(300)), CompiledInstruction::default()], vec![inner], None);
+
+        let found = transfers(txn);
+
+        let positions: Vec<_> = found.iter().map(|t| (t.instruction_index, t.inner_index, t.lamports)).collect();
+        assert_eq!(positions, vec![(0, None, 300), (1, Some(0), 700)]);
+    }
+
+    #[test]
+    fn two_account_transfer_carries_its_lamports() {
+        let txn = transaction(vec![system_ix(transfer_data(1_500_000))], Vec::new(), Some(1_700_000_000));
+
+        let found = transfers(txn);
+
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].from, Pubkey58::from(FROM));
+        assert_eq!(found[0].to, Pubkey58::from(TO));
+        assert_eq!(found[0].lamports, 1_500_000);
+        assert_eq!((found[0].slot, found[0].block_time), (10, Some(1_700_000_000)));
+    }
+
+    #[test]
+    fn transfer_with_seed_pays_the_third_account() {
+        // [from, base, to]; base is the system program key here, which is never paid
+        let mut data = TRANSFER_WITH_SEED.to_le_bytes().to_vec();
+        data.extend(800u64.to_le_bytes());
+        let ix = CompiledInstruction { program_id_index: 2, accounts: vec![0, 2, 1], data };
+
+        let found = transfers(transaction(vec![ix], Vec::new(), None));
+
+        assert_eq!(found.len(), 1);
+        assert_eq!((found[0].from.clone(), found[0].to.clone(), found[0].lamports), (Pubkey58::from(FROM), Pubkey58::from(TO), 800));
+    }
+
+    #[test]
+    fn malformed_frame_is_a_decode_failure() {
+        // Field 1 claims 255 bytes that are not there
+        let txn = SolanaTransaction { data: TxData::Grpc(vec![0x0a, 0xff, 0x01]), ..transaction(Vec::new(), Vec::new(), None) };
+
+        let err = SystemTransferParser::new().parse(txn).unwrap_err();
+
+        assert!(matches!(&err, ParserError::DecodeFailed(reason) if reason.starts_with("protobuf")), "got {:?}", err);
+    }
+}
@@ -1,14 +1,113 @@
-use std::sync::Arc;
+use std::{ops::Range, sync::Arc};
 
 use solana_account_decoder_client_types::token::UiTokenAmount;
-use solana_transaction_status::{UiInnerInstructions, UiInstruction, UiParsedInstruction, UiTransactionTokenBalance, option_serializer::OptionSerializer};
-use solana_sdk::pubkey::Pubkey;
-use yellowstone_grpc_proto::prelude::InnerInstruction;
+use solana_transaction_status::{UiInnerInstructions, UiInstruction, UiParsedInstruction, UiTransactionStatusMeta, UiTransactionTokenBalance, option_serializer::OptionSerializer};
+use solana_sdk::{message::VersionedMessage, pubkey::Pubkey, transaction::VersionedTransaction};
+use yellowstone_grpc_proto::prelude::{InnerInstruction, InnerInstructions, Message, TokenBalance, TransactionStatusMeta};
 use yellowstone_vixen_core::instruction::{InstructionShared, InstructionUpdate, Path};
 
+use crate::{adapters::parsers::Base58Cache, application::{ParserError, ParserResult}};
+
+impl From<prost::DecodeError> for ParserError {
+    fn from(e: prost::DecodeError) -> Self {
+        Self::DecodeFailed(format!("protobuf: {}", e))
+    }
+}
+
+/// Which side of an SPL transfer to match when scanning CPI instructions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferLeg {
+    Source,
+    Destination,
+}
+
+/// Legacy messages address accounts by static key only; v0 messages may append
+/// keys loaded from address lookup tables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageVersion {
+    Legacy,
+    V0,
+}
+
+impl MessageVersion {
+    pub fn of_grpc(message: &Message) -> Self {
+        if message.versioned { Self::V0 } else { Self::Legacy }
+    }
+
+    pub fn of_rpc(tx: &VersionedTransaction) -> Self {
+        match tx.message {
+            VersionedMessage::Legacy(_) => Self::Legacy,
+            VersionedMessage::V0(_) => Self::V0,
+        }
+    }
+}
+
+/// Where a swap instruction sits: its top-level instruction, its position in that
+/// instruction's CPI group (None at top level) and its CPI depth.
+#[derive(Debug, Clone, Copy)]
+pub struct IxPosition {
+    pub instruction_index: usize,
+    pub inner_index: Option<u32>,
+    pub cpi_depth: u32,
+}
+
+impl IxPosition {
+    pub fn top_level(ix_idx: usize) -> Self {
+        Self { instruction_index: ix_idx, inner_index: None, cpi_depth: 0 }
+    }
+
+    /// `stack_height` counts the top-level instruction as 1; old nodes omit it,
+    /// in which case a direct CPI is assumed.
+    pub fn inner(ix_idx: usize, inner_idx: usize, stack_height: Option<u32>) -> Self {
+        Self {
+            instruction_index: ix_idx,
+            inner_index: Some(inner_idx as u32),
+            cpi_depth: stack_height.map_or(1, |h| h.saturating_sub(1).max(1)),
+        }
+    }
+
+    /// The part of the CPI group that holds this swap's transfers: all of it for a
+    /// top-level swap; for a CPI'd one, the instructions after it up to the next of
+    /// `swaps` in the same group, so a route that swaps twice reads each swap's own.
+    pub fn transfer_window(&self, swaps: &[IxPosition]) -> Range<usize> {
+        let Some(own) = self.inner_index else { return WHOLE_GROUP };
+        let next = swaps
+            .iter()
+            .filter(|s| s.instruction_index == self.instruction_index)
+            .filter_map(|s| s.inner_index)
+            .filter(|&i| i > own)
+            .min();
+        own as usize + 1..next.map_or(usize::MAX, |i| i as usize)
+    }
+}
+
+/// Transfer window covering a whole CPI group.
+pub const WHOLE_GROUP: Range<usize> = 0..usize::MAX;
+
 pub struct VixenUtils;
 
 impl VixenUtils {
+    /// Base58 strings for a resolved account list, via the shared key cache.
+    pub fn account_strings(accounts: &[Pubkey]) -> Vec<Arc<str>> {
+        let cache = Base58Cache::global();
+        accounts.iter().map(|k| cache.encode(k)).collect()
+    }
+
+    /// An instruction's account list, position for position. An index past the
+    /// resolved keys fails with the position it sits at: dropping it would shift
+    /// every later account into the wrong slot, and a placeholder key would be
+    /// indexed as if it were real.
+    pub fn instruction_accounts(indices: &[u8], all_accounts: &[Pubkey]) -> ParserResult<Vec<yellowstone_vixen_parser::Pubkey>> {
+        indices
+            .iter()
+            .enumerate()
+            .map(|(position, &i)| {
+                let account = all_accounts.get(i as usize).ok_or(ParserError::MissingAccount { index: position })?;
+                Ok(yellowstone_vixen_parser::Pubkey::from(account.to_bytes()))
+            })
+            .collect()
+    }
+
     /// Convert gRPC inner instructions into Vixen InstructionUpdates
     pub fn convert_protobuf_inner_instruction(
         inner_ixs: &Vec<InnerInstruction>,
@@ -98,10 +197,12 @@ impl VixenUtils {
         }
     }
 
-    /// Build a Vixen InstructionUpdate from an RPC instruction + optional inner instructions
+    /// Build a Vixen InstructionUpdate from an RPC instruction's resolved accounts
+    /// + optional inner instructions
     pub fn to_vixen_update_rpc(
         program_id: &Pubkey,
         data: &[u8],
+        ix_accounts: Vec<yellowstone_vixen_parser::Pubkey>,
         accounts: &[Pubkey],
         signature: &str,
         slot: u64,
@@ -125,7 +226,7 @@ impl VixenUtils {
 
         InstructionUpdate {
             program: yellowstone_vixen_parser::Pubkey::from(program_id.to_bytes()),
-            accounts: accounts.iter().map(|a| yellowstone_vixen_parser::Pubkey::from(a.to_bytes())).collect(),
+            accounts: ix_accounts,
             data: data.to_vec(),
             shared,
             inner,
@@ -185,11 +286,214 @@ impl VixenUtils {
         let mut accounts = Vec::with_capacity(static_keys.len() + loaded_writable.len() + loaded_readonly.len());
 
         for bytes in static_keys.iter().chain(loaded_writable).chain(loaded_readonly) {
-            if let Ok(arr) = bytes.clone().try_into() {
-                accounts.push(Pubkey::new_from_array(arr));
+            match <[u8; 32]>::try_from(bytes.as_slice()) {
+                Ok(arr) => accounts.push(Pubkey::new_from_array(arr)),
+                Err(_) => {
+                    // Keep later indices aligned even if one entry is malformed
+                    tracing::warn!("Skipping {}-byte account key", bytes.len());
+                    accounts.push(Pubkey::default());
+                }
+            }
+        }
+
+        accounts
+    }
+
+    /// The account list instruction indices refer to, for either message version:
+    /// static keys, then lookup-table writable keys, then lookup-table readonly keys,
+    /// the order the runtime loads them in. A legacy message never has loaded keys.
+    pub fn extract_accounts_from_message(message: &Message, meta: &TransactionStatusMeta) -> Vec<Pubkey> {
+        match MessageVersion::of_grpc(message) {
+            MessageVersion::V0 => Self::extract_accounts_from_grpc(
+                &message.account_keys,
+                &meta.loaded_writable_addresses,
+                &meta.loaded_readonly_addresses,
+            ),
+            MessageVersion::Legacy => {
+                if !meta.loaded_writable_addresses.is_empty() || !meta.loaded_readonly_addresses.is_empty() {
+                    tracing::warn!("Ignoring loaded addresses on a legacy message");
+                }
+                Self::extract_accounts_from_grpc(&message.account_keys, &[], &[])
+            }
+        }
+    }
+
+    /// Reconstruct the full account list for an RPC transaction: static keys followed by
+    /// Address Lookup Table entries (writable, then readonly), matching on-chain index order
+    pub fn extract_accounts_from_rpc(tx: &VersionedTransaction, meta: &UiTransactionStatusMeta) -> Vec<Pubkey> {
+        let mut accounts: Vec<Pubkey> = tx.message.static_account_keys().to_vec();
+        if MessageVersion::of_rpc(tx) == MessageVersion::Legacy {
+            return accounts;
+        }
+
+        if let OptionSerializer::Some(loaded) = &meta.loaded_addresses {
+            for a in loaded.writable.iter().chain(&loaded.readonly) {
+                match a.parse() {
+                    Ok(pk) => accounts.push(pk),
+                    Err(_) => {
+                        // Keep later indices aligned even if one entry is malformed
+                        tracing::warn!("Unparseable loaded address {}", a);
+                        accounts.push(Pubkey::default());
+                    }
+                }
             }
         }
 
         accounts
     }
+
+    /// Account position of the matched leg: Transfer is [src, dst, auth],
+    /// TransferChecked is [src, mint, dst, auth].
+    pub fn transfer_leg_position(opcode: u8, leg: TransferLeg) -> usize {
+        match (leg, opcode) {
+            (TransferLeg::Source, _) => 0,
+            (TransferLeg::Destination, 12) => 2,
+            (TransferLeg::Destination, _) => 1,
+        }
+    }
+
+    /// Amount of the first SPL Transfer/TransferChecked within `window` of instruction
+    /// `parent_idx`'s CPI group whose `leg` account is the message account `target`
+    pub fn find_transfer_amount_grpc(
+        parent_idx: usize,
+        window: Range<usize>,
+        target: usize,
+        leg: TransferLeg,
+        inner_ixs: &[InnerInstructions],
+    ) -> Option<u64> {
+        let group = inner_ixs.iter().find(|g| g.index == parent_idx as u32)?;
+
+        for ix in group.instructions.iter().take(window.end).skip(window.start) {
+            let opcode = match ix.data.first() {
+                Some(&op @ (3 | 12)) if ix.data.len() >= 9 => op,
+                _ => continue,
+            };
+            let mut b = [0u8; 8];
+            b.copy_from_slice(&ix.data[1..9]);
+            let account = ix.accounts.get(Self::transfer_leg_position(opcode, leg))?;
+
+            if *account as usize == target { return Some(u64::from_le_bytes(b)); }
+        }
+        None
+    }
+
+    /// RPC counterpart of `find_transfer_amount_grpc`
+    pub fn find_transfer_amount_rpc(
+        parent_idx: usize,
+        window: Range<usize>,
+        target: usize,
+        leg: TransferLeg,
+        inner_ixs: &OptionSerializer<Vec<UiInnerInstructions>>,
+    ) -> Option<u64> {
+        if let OptionSerializer::Some(groups) = inner_ixs {
+            let group = groups.iter().find(|g| g.index == parent_idx as u8)?;
+            for ix in group.instructions.iter().take(window.end).skip(window.start) {
+                if let UiInstruction::Compiled(c) = ix {
+                    let raw = bs58::decode(&c.data).into_vec().ok()?;
+                    let opcode = match raw.first() {
+                        Some(&op @ (3 | 12)) if raw.len() >= 9 => op,
+                        _ => continue,
+                    };
+                    let mut b = [0u8; 8];
+                    b.copy_from_slice(&raw[1..9]);
+                    let account = c.accounts.get(Self::transfer_leg_position(opcode, leg))?;
+
+                    if *account as usize == target { return Some(u64::from_le_bytes(b)); }
+                }
+            }
+        }
+        None
+    }
+
+    /// Mint of a token account from pre/post token balances, or "unknown"
+    pub fn token_account_mint_grpc(idx: usize, pre: &[TokenBalance], post: &[TokenBalance]) -> String {
+        pre.iter()
+            .find(|b| b.account_index == idx as u32)
+            .or_else(|| post.iter().find(|b| b.account_index == idx as u32))
+            .map(|b| b.mint.clone())
+            .unwrap_or_else(|| "unknown".to_string())
+    }
+
+    /// RPC counterpart of `token_account_mint_grpc`
+    pub fn token_account_mint_rpc(idx: usize, pre: &[UiTransactionTokenBalance], post: &[UiTransactionTokenBalance]) -> String {
+        pre.iter()
+            .find(|b| b.account_index == idx as u8)
+            .or_else(|| post.iter().find(|b| b.account_index == idx as u8))
+            .map(|b| b.mint.clone())
+            .unwrap_or_else(|| "unknown".to_string())
+    }
+
+    /// Raw token amount held by account `idx` in a token-balance list, if listed
+    pub fn token_account_amount_grpc(idx: usize, balances: &[TokenBalance]) -> Option<u64> {
+        balances.iter()
+            .find(|b| b.account_index == idx as u32)
+            .and_then(|b| b.ui_token_amount.as_ref()?.amount.parse().ok())
+    }
+
+    /// RPC counterpart of `token_account_amount_grpc`
+    pub fn token_account_amount_rpc(idx: usize, balances: &[UiTransactionTokenBalance]) -> Option<u64> {
+        balances.iter()
+            .find(|b| b.account_index == idx as u8)
+            .and_then(|b| b.ui_token_amount.amount.parse().ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn instruction_accounts_keep_their_positions() {
+        let keys = [Pubkey::new_from_array([1; 32]), Pubkey::new_from_array([2; 32])];
+
+        let accounts = VixenUtils::instruction_accounts(&[1, 0, 1], &keys).unwrap();
+
+        assert_eq!(accounts.iter().map(|a| a.0[0]).collect::<Vec<_>>(), vec![2, 1, 2]);
+    }
+
+    #[test]
+    fn instruction_accounts_reject_an_index_past_the_keys() {
+        let keys = [Pubkey::new_from_array([1; 32])];
+
+        let err = VixenUtils::instruction_accounts(&[0, 0, 3], &keys).unwrap_err();
+
+        assert!(matches!(err, ParserError::MissingAccount { index: 2 }));
+    }
+
+    fn key(seed: u8) -> Vec<u8> {
+        vec![seed; 32]
+    }
+
+    /// Two static keys, one writable and one readonly lookup-table key.
+    fn message_and_meta(versioned: bool) -> (Message, TransactionStatusMeta) {
+        let message = Message { account_keys: vec![key(1), key(2)], versioned, ..Default::default() };
+        let meta = TransactionStatusMeta {
+            loaded_writable_addresses: vec![key(3)],
+            loaded_readonly_addresses: vec![key(4)],
+            ..Default::default()
+        };
+        (message, meta)
+    }
+
+    #[test]
+    fn v0_indices_past_the_static_keys_resolve_to_lookup_table_addresses() {
+        let (message, meta) = message_and_meta(true);
+
+        let accounts = VixenUtils::extract_accounts_from_message(&message, &meta);
+
+        assert_eq!(MessageVersion::of_grpc(&message), MessageVersion::V0);
+        assert_eq!(accounts.len(), 4);
+        assert_eq!(accounts[2], Pubkey::new_from_array([3; 32]));
+        assert_eq!(accounts[3], Pubkey::new_from_array([4; 32]));
+    }
+
+    #[test]
+    fn legacy_message_uses_only_its_static_keys() {
+        let (message, meta) = message_and_meta(false);
+
+        let accounts = VixenUtils::extract_accounts_from_message(&message, &meta);
+
+        assert_eq!(MessageVersion::of_grpc(&message), MessageVersion::Legacy);
+        assert_eq!(accounts, vec![Pubkey::new_from_array([1; 32]), Pubkey::new_from_array([2; 32])]);
+    }
 }
@@ -1,9 +1,11 @@
 use thiserror::Error;
 
+use crate::domain::InvalidBase58;
+
 #[derive(Debug, Error)]
 pub enum AppError {
-    #[error("Invalid source type provided")]
-    InvalidSource,
+    #[error("Invalid source type provided: {0}")]
+    InvalidSource(String),
 
     #[error("Error reading from gRPC stream")]
     GrpcStreamingError,
@@ -16,6 +18,115 @@ pub enum AppError {
 
     #[error("Channel send error: buffer closed or full")]
     ErrorSendingMessageViaBuffer,
+
+    #[error("Configuration error: {0}")]
+    ConfigError(String),
+
+    #[error("Connection error: {0}")]
+    ConnectionError(String),
+
+    #[error("Sink `{sink}` failed: {reason}")]
+    SinkError { sink: String, reason: String },
+
+    #[error("Database connection pool exhausted")]
+    PoolExhausted,
+
+    #[error("Write failed: {0}")]
+    WriteError(String),
+
+    #[error("Parse failed: {0}")]
+    ParseError(#[from] ParserError),
+}
+
+impl AppError {
+    /// Retrying cannot help; the caller should stop.
+    pub fn is_fatal(&self) -> bool {
+        matches!(self, Self::ConfigError(_) | Self::InvalidSource(_))
+    }
+
+    /// Likely to clear on its own; the caller should back off and retry.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            Self::ConnectionError(_) | Self::GrpcStreamingError | Self::ErrorFetchingDataFromGrpc | Self::PoolExhausted
+        )
+    }
 }
 
 pub type AppResult<T> = Result<T, AppError>;
+
+/// Why a parser could not turn a transaction it recognised into events.
+/// "Not for this parser" is `Ok(None)`, never an error.
+#[derive(Debug, Error)]
+pub enum ParserError {
+    /// The instruction is registered with this parser but under a kind it has no decoder for.
+    #[error("No decoder for instruction discriminator {}", hex::encode(.0))]
+    UnknownDiscriminator(Vec<u8>),
+
+    #[error("Instruction data truncated: expected {expected} bytes, got {got}")]
+    TruncatedData { expected: usize, got: usize },
+
+    #[error("Account #{index} is missing")]
+    MissingAccount { index: usize },
+
+    #[error("Decode failed: {0}")]
+    DecodeFailed(String),
+}
+
+impl From<InvalidBase58> for ParserError {
+    fn from(e: InvalidBase58) -> Self {
+        Self::DecodeFailed(e.to_string())
+    }
+}
+
+pub type ParserResult<T> = Result<T, ParserError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_variant_has_a_readable_message() {
+        let cases = [
+            (AppError::InvalidSource("carrier-pigeon".to_string()), "Invalid source type provided: carrier-pigeon"),
+            (AppError::GrpcStreamingError, "Error reading from gRPC stream"),
+            (AppError::ErrorFetchingDataFromGrpc, "Error fetching data from gRPC endpoint"),
+            (AppError::RPCParsingError, "Error parsing RPC response"),
+            (AppError::ErrorSendingMessageViaBuffer, "Channel send error: buffer closed or full"),
+            (AppError::ConfigError("RPC_URL is not set".to_string()), "Configuration error: RPC_URL is not set"),
+            (AppError::ConnectionError("refused".to_string()), "Connection error: refused"),
+            (
+                AppError::SinkError { sink: "webhook".to_string(), reason: "HTTP 502".to_string() },
+                "Sink `webhook` failed: HTTP 502",
+            ),
+            (AppError::PoolExhausted, "Database connection pool exhausted"),
+            (AppError::WriteError("value out of range".to_string()), "Write failed: value out of range"),
+            (
+                AppError::ParseError(ParserError::MissingAccount { index: 3 }),
+                "Parse failed: Account #3 is missing",
+            ),
+        ];
+
+        for (error, message) in cases {
+            assert_eq!(error.to_string(), message);
+        }
+    }
+
+    #[test]
+    fn parser_errors_have_readable_messages() {
+        assert_eq!(ParserError::UnknownDiscriminator(vec![0xab, 0x01]).to_string(), "No decoder for instruction discriminator ab01");
+        assert_eq!(
+            ParserError::TruncatedData { expected: 24, got: 9 }.to_string(),
+            "Instruction data truncated: expected 24 bytes, got 9"
+        );
+        assert_eq!(ParserError::DecodeFailed("bad varint".to_string()).to_string(), "Decode failed: bad varint");
+    }
+
+    #[test]
+    fn boxes_as_dyn_error_and_keeps_the_parser_error_as_its_source() {
+        let boxed: Box<dyn std::error::Error + Send + Sync> = AppError::from(ParserError::MissingAccount { index: 0 }).into();
+
+        assert_eq!(boxed.to_string(), "Parse failed: Account #0 is missing");
+        assert_eq!(boxed.source().map(|e| e.to_string()).as_deref(), Some("Account #0 is missing"));
+    }
+}
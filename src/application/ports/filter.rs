@@ -0,0 +1,8 @@
+use crate::domain::TransactionEvent;
+
+/// Decides whether a parsed event continues to batching and alerts. Filters run
+/// after dedup, in the order they were added to the pipeline.
+pub trait EventFilter: Send + Sync {
+    fn keep(&self, event: &TransactionEvent) -> bool;
+    fn name(&self) -> &str;
+}
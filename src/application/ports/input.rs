@@ -1,7 +1,85 @@
 use async_trait::async_trait;
+use futures::{StreamExt, stream::BoxStream};
 use crate::{application::AppResult, domain::ChainEvent};
 
 #[async_trait]
 pub trait TransactionSource: Send + Sync {
     async fn next_event(&mut self) -> AppResult<Option<ChainEvent>>;
+
+    /// Drop the current connection and open a new one, used when the source
+    /// has gone quiet. Sources without a connection have nothing to do.
+    async fn reconnect(&mut self) -> AppResult<()> {
+        Ok(())
+    }
+}
+
+/// Stream view of any owned source, for use with `futures` combinators
+/// (`take`, `filter`, `chunks`, ...).
+pub trait TransactionSourceExt: TransactionSource + Sized + 'static {
+    /// Yields events until the source is exhausted. Errors are yielded as items;
+    /// a fatal one is the last item, while a transient one leaves the stream
+    /// open so the consumer can decide whether to keep polling.
+    fn into_stream(self) -> BoxStream<'static, AppResult<ChainEvent>> {
+        futures::stream::unfold(Some(self), |source| async move {
+            let mut source = source?;
+            match source.next_event().await {
+                Ok(Some(event)) => Some((Ok(event), Some(source))),
+                Ok(None) => None,
+                Err(e) => {
+                    let keep = (!e.is_fatal()).then_some(source);
+                    Some((Err(e), keep))
+                }
+            }
+        })
+        .boxed()
+    }
+}
+
+impl<S: TransactionSource + Sized + 'static> TransactionSourceExt for S {}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use super::*;
+    use crate::application::AppError;
+
+    /// Replays scripted results, then ends.
+    struct ScriptedSource(VecDeque<AppResult<Option<ChainEvent>>>);
+
+    #[async_trait]
+    impl TransactionSource for ScriptedSource {
+        async fn next_event(&mut self) -> AppResult<Option<ChainEvent>> {
+            self.0.pop_front().unwrap_or(Ok(None))
+        }
+    }
+
+    fn rollback(slot: u64) -> AppResult<Option<ChainEvent>> {
+        Ok(Some(ChainEvent::Rollback { slot }))
+    }
+
+    #[tokio::test]
+    async fn transient_error_leaves_the_stream_open() {
+        let source = ScriptedSource(VecDeque::from([rollback(1), Err(AppError::GrpcStreamingError), rollback(2)]));
+
+        let items: Vec<_> = source.into_stream().collect().await;
+
+        assert_eq!(items.len(), 3);
+        assert!(matches!(items[1], Err(AppError::GrpcStreamingError)));
+        assert!(matches!(items[2], Ok(ChainEvent::Rollback { slot: 2 })));
+    }
+
+    #[tokio::test]
+    async fn fatal_error_is_the_last_item() {
+        let source = ScriptedSource(VecDeque::from([
+            rollback(1),
+            Err(AppError::ConfigError("bad endpoint".to_string())),
+            rollback(2),
+        ]));
+
+        let items: Vec<_> = source.into_stream().collect().await;
+
+        assert_eq!(items.len(), 2);
+        assert!(matches!(items[1], Err(AppError::ConfigError(_))));
+    }
 }
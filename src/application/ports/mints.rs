@@ -0,0 +1,33 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+/// Mints an on-chain account is tied to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccountMints {
+    /// A token account holds exactly one mint.
+    TokenAccount(String),
+    /// A two-sided pool; order is the pool's own (base/quote, mint 0/mint 1).
+    Pool(String, String),
+}
+
+impl AccountMints {
+    /// The pool's other side, when `mint` is one of its two mints.
+    pub fn counterpart(&self, mint: &str) -> Option<&str> {
+        match self {
+            Self::Pool(a, b) if a == mint => Some(b),
+            Self::Pool(a, b) if b == mint => Some(a),
+            _ => None,
+        }
+    }
+}
+
+/// Looks up the mints behind account addresses, for repairing rows parsed
+/// before their mint was known.
+#[async_trait]
+pub trait MintResolver: Send + Sync {
+    /// Addresses that no longer exist or are not a known account layout are
+    /// left out of the result.
+    async fn account_mints(&self, accounts: &[String]) -> Result<HashMap<String, AccountMints>>;
+}
@@ -1,11 +1,29 @@
 mod input;
 mod output;
 mod buffer;
+mod clock;
+mod filter;
+mod mints;
 mod parser;
 mod notifier;
+mod parse_errors;
+mod programs;
+mod sink;
+mod summary;
+mod transformer;
+mod wal;
 
 pub use input::*;
 pub use output::*;
 pub use buffer::*;
+pub use clock::*;
+pub use filter::*;
+pub use mints::*;
 pub use parser::*;
 pub use notifier::*;
+pub use parse_errors::*;
+pub use programs::*;
+pub use sink::*;
+pub use summary::*;
+pub use transformer::*;
+pub use wal::*;
@@ -1,6 +1,33 @@
 use anyhow::Result;
 use async_trait::async_trait;
-use crate::domain::{IndexerState, SolanaTransaction, TransactionEvent};
+use crate::{
+    application::AppError,
+    domain::{IndexedTransaction, IndexerState, SolanaTransaction, TransactionEvent},
+};
+
+/// Outcome of a best-effort batch write.
+#[derive(Debug, Default)]
+pub struct BatchResult {
+    pub persisted: usize,
+    pub failed: Vec<(TransactionEvent, AppError)>,
+}
+
+/// Outcome of finalizing a slot under two-phase persistence.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FinalizeResult {
+    /// Pending events at or below the finalized slot moved into the event tables.
+    pub promoted: usize,
+    /// Pending rows that could not be decoded, deleted without being promoted.
+    pub dropped: u64,
+}
+
+/// Connection pool occupancy at one instant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolStats {
+    /// Open connections, idle or in use.
+    pub size: u32,
+    pub idle: usize,
+}
 
 #[async_trait]
 pub trait TransactionRepository: Send + Sync {
@@ -8,4 +35,129 @@ pub trait TransactionRepository: Send + Sync {
     async fn get_last_slot(&self) -> Result<u64>;
     async fn save_batch(&self, events: &[TransactionEvent], current_slot: u64) -> Result<()>;
     async fn save_dlq(&self, txn: &SolanaTransaction, parser_name: &str, error: &str) -> Result<()>;
+    /// Dead-letter a parsed event that could not be persisted.
+    async fn save_event_dlq(&self, event: &TransactionEvent, error: &str) -> Result<()>;
+
+    /// Like `save_batch`, but an event that cannot be written no longer sinks the
+    /// rest. The batch is tried as one write first; if that fails, each event is
+    /// written on its own and the ones that still fail are returned. An exhausted
+    /// pool or a DB that no longer answers `ping` fails the call as a whole, so
+    /// the caller can back off instead of dead-lettering events that are fine.
+    async fn save_batch_best_effort(&self, events: &[TransactionEvent], current_slot: u64) -> Result<BatchResult> {
+        match self.save_batch(events, current_slot).await {
+            Ok(()) => return Ok(BatchResult { persisted: events.len(), failed: Vec::new() }),
+            Err(e) if matches!(e.downcast_ref(), Some(AppError::PoolExhausted)) => return Err(e),
+            Err(e) if self.ping().await.is_err() => return Err(e),
+            Err(e) => tracing::warn!("Batch of {} failed ({}), retrying event by event", events.len(), e),
+        }
+
+        let mut result = BatchResult::default();
+        for event in events {
+            match self.save_batch(std::slice::from_ref(event), current_slot).await {
+                Ok(()) => result.persisted += 1,
+                Err(e) => result.failed.push((event.clone(), AppError::WriteError(e.to_string()))),
+            }
+        }
+        Ok(result)
+    }
+    /// Record fee payer, success and matching parsers of transactions whose events
+    /// were just stored. Repositories without a per-transaction table ignore it.
+    async fn save_transactions(&self, _transactions: &[IndexedTransaction]) -> Result<()> {
+        Ok(())
+    }
+    /// Remove every event at or above `slot` after the chain rolled it back, and
+    /// rewind the slot cursor below it. Returns the number of events removed.
+    async fn delete_events_from_slot(&self, slot: u64) -> Result<u64>;
+    /// Two-phase persistence: promote the pending events at or below a finalized
+    /// `slot`. Events of dead slots are removed earlier, by
+    /// `delete_events_from_slot`. Repositories that write straight through have
+    /// nothing to do.
+    async fn finalize_slot(&self, _slot: u64) -> Result<FinalizeResult> {
+        Ok(FinalizeResult::default())
+    }
+    /// Cheap round-trip to the backing store, used by readiness checks.
+    async fn ping(&self) -> Result<()>;
+    /// Pool occupancy, for repositories backed by a connection pool.
+    fn pool_stats(&self) -> Option<PoolStats> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::domain::fixtures::transfer;
+
+    /// Rejects any batch holding `poisoned`, as a constraint violation would;
+    /// with `exhausted` set, every write fails for want of a connection.
+    #[derive(Default)]
+    struct ConstrainedRepo {
+        stored: Mutex<Vec<String>>,
+        poisoned: String,
+        exhausted: bool,
+    }
+
+    #[async_trait]
+    impl TransactionRepository for ConstrainedRepo {
+        async fn get_state(&self) -> Result<IndexerState> {
+            Ok(IndexerState { last_slot: 0, last_block_hash: String::new() })
+        }
+
+        async fn get_last_slot(&self) -> Result<u64> {
+            Ok(0)
+        }
+
+        async fn save_batch(&self, events: &[TransactionEvent], _current_slot: u64) -> Result<()> {
+            if self.exhausted {
+                return Err(AppError::PoolExhausted.into());
+            }
+            if events.iter().any(|ev| ev.signature() == self.poisoned) {
+                return Err(AppError::WriteError("violates check constraint".to_string()).into());
+            }
+            self.stored.lock().unwrap().extend(events.iter().map(|ev| ev.signature().to_string()));
+            Ok(())
+        }
+
+        async fn save_dlq(&self, _txn: &SolanaTransaction, _parser_name: &str, _error: &str) -> Result<()> {
+            Ok(())
+        }
+
+        async fn save_event_dlq(&self, _event: &TransactionEvent, _error: &str) -> Result<()> {
+            Ok(())
+        }
+
+        async fn delete_events_from_slot(&self, _slot: u64) -> Result<u64> {
+            Ok(0)
+        }
+
+        async fn ping(&self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn failing_event_is_reported_and_the_rest_persist() {
+        let events = vec![transfer(1), transfer(2), transfer(3)];
+        let repo = ConstrainedRepo { poisoned: events[1].signature().to_string(), ..Default::default() };
+
+        let result = repo.save_batch_best_effort(&events, 10).await.unwrap();
+
+        assert_eq!(result.persisted, 2);
+        assert_eq!(result.failed.len(), 1);
+        assert_eq!(result.failed[0].0.signature(), events[1].signature());
+        assert!(matches!(&result.failed[0].1, AppError::WriteError(reason) if reason.contains("constraint")));
+        let stored = repo.stored.lock().unwrap().clone();
+        assert_eq!(stored, vec![events[0].signature().to_string(), events[2].signature().to_string()]);
+    }
+
+    #[tokio::test]
+    async fn exhausted_pool_fails_the_whole_call() {
+        let repo = ConstrainedRepo { exhausted: true, ..Default::default() };
+
+        let err = repo.save_batch_best_effort(&[transfer(1), transfer(2)], 10).await.unwrap_err();
+
+        assert!(matches!(err.downcast_ref(), Some(AppError::PoolExhausted)));
+    }
 }
@@ -0,0 +1,10 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crate::domain::SolanaTransaction;
+
+/// Keeps the raw transaction behind each parser failure so it can be replayed
+/// against fixed parser code later.
+#[async_trait]
+pub trait ParseErrorSink: Send + Sync {
+    async fn record(&self, txn: &SolanaTransaction, parser_name: &str, error: &str) -> Result<()>;
+}
@@ -1,7 +1,188 @@
-use anyhow::Result;
-use crate::domain::{SolanaTransaction, TransactionEvent};
+use async_trait::async_trait;
+use crate::{application::ParserResult, domain::{SolanaTransaction, TransactionEvent}};
 
+/// Synchronous by design: parsing is pure CPU work on the transaction bytes, so
+/// `dyn TransactionParser` calls carry no boxed futures. Parsers that must await
+/// a lookup implement `AsyncTransactionParser` instead.
 pub trait TransactionParser: Send + Sync {
-    fn parse(&self, txn: SolanaTransaction) -> Result<Option<Vec<TransactionEvent>>>;
+    fn parse(&self, txn: SolanaTransaction) -> ParserResult<Option<Vec<TransactionEvent>>>;
     fn name(&self) -> &str;
+
+    /// `TransactionEvent::kind` of every event this parser can produce, for
+    /// coverage reports and startup checks. Empty means undeclared.
+    fn emitted_event_kinds(&self) -> &[&str] {
+        &[]
+    }
+
+    /// Parse a slice of transactions, returning events keyed by their index in `txns`.
+    /// Transactions that yield nothing are omitted. Parse errors are logged and skipped,
+    /// so callers that need the DLQ should keep using `parse`.
+    fn parse_batch(&self, txns: &[SolanaTransaction]) -> Vec<(usize, Vec<TransactionEvent>)> {
+        txns.iter()
+            .enumerate()
+            .filter_map(|(i, txn)| match self.parse(txn.clone()) {
+                Ok(Some(events)) if !events.is_empty() => Some((i, events)),
+                Ok(_) => None,
+                Err(e) => {
+                    tracing::warn!("Parser {} failed on {}: {:?}", self.name(), txn.signature, e);
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+/// A parser that has to await something, e.g. an RPC lookup for a mint or pool,
+/// before it can build its events. The pipeline runs these after the sync
+/// parsers, one at a time, so keep lookups cached where possible.
+#[async_trait]
+pub trait AsyncTransactionParser: Send + Sync {
+    async fn parse_async(&self, txn: SolanaTransaction) -> ParserResult<Option<Vec<TransactionEvent>>>;
+    fn name(&self) -> &str;
+
+    /// See `TransactionParser::emitted_event_kinds`.
+    fn emitted_event_kinds(&self) -> &[&str] {
+        &[]
+    }
+}
+
+/// Every sync parser can be registered where an async one is expected.
+#[async_trait]
+impl<T: TransactionParser + ?Sized> AsyncTransactionParser for T {
+    async fn parse_async(&self, txn: SolanaTransaction) -> ParserResult<Option<Vec<TransactionEvent>>> {
+        self.parse(txn)
+    }
+
+    fn name(&self) -> &str {
+        TransactionParser::name(self)
+    }
+
+    fn emitted_event_kinds(&self) -> &[&str] {
+        TransactionParser::emitted_event_kinds(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        application::ParserError,
+        domain::{SolTransfer, TxData, fixtures::{sol_transfer, transfer_at}},
+    };
+
+    /// One transfer per `transfer…` signature, an error for `bad…`, nothing otherwise.
+    struct PrefixParser;
+
+    impl TransactionParser for PrefixParser {
+        fn parse(&self, txn: SolanaTransaction) -> ParserResult<Option<Vec<TransactionEvent>>> {
+            if txn.signature.starts_with("bad") {
+                return Err(ParserError::DecodeFailed("bad".to_string()));
+            }
+            Ok(txn.signature.starts_with("transfer").then(|| {
+                vec![transfer_at(txn.signature.len() as u8, txn.slot)]
+            }))
+        }
+
+        fn name(&self) -> &str {
+            "prefix"
+        }
+    }
+
+    fn transaction(signature: &str, slot: u64) -> SolanaTransaction {
+        SolanaTransaction {
+            signature: signature.to_string(),
+            success: true,
+            data: TxData::Grpc(Vec::new()),
+            slot,
+            block_time: None,
+            fee: None,
+            compute_units_consumed: None,
+            fee_payer: String::new(),
+            recent_blockhash: String::new(),
+        }
+    }
+
+    #[test]
+    fn parse_batch_matches_parse_per_transaction() {
+        let txns = vec![
+            transaction("transfer-1", 10),
+            transaction("vote", 11),
+            transaction("bad", 12),
+            transaction("transfer-22", 13),
+        ];
+
+        let batched: Vec<_> = PrefixParser
+            .parse_batch(&txns)
+            .into_iter()
+            .map(|(i, events)| (i, events.iter().map(|ev| ev.key()).collect::<Vec<_>>()))
+            .collect();
+        let one_by_one: Vec<_> = txns
+            .iter()
+            .enumerate()
+            .filter_map(|(i, txn)| match PrefixParser.parse(txn.clone()) {
+                Ok(Some(events)) => Some((i, events.iter().map(|ev| ev.key()).collect::<Vec<_>>())),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(batched, one_by_one);
+        assert_eq!(batched.iter().map(|(i, _)| *i).collect::<Vec<_>>(), vec![0, 3]);
+    }
+
+    /// Stands in for an RPC client: resolves a transfer's amount after a delay.
+    struct AmountLookup;
+
+    impl AmountLookup {
+        async fn lamports_for(&self, signature: &str) -> u64 {
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+            signature.len() as u64 * 1_000_000
+        }
+    }
+
+    /// Emits one transfer per transaction, its amount filled in by the lookup.
+    struct EnrichingParser {
+        lookup: AmountLookup,
+    }
+
+    #[async_trait]
+    impl AsyncTransactionParser for EnrichingParser {
+        async fn parse_async(&self, txn: SolanaTransaction) -> ParserResult<Option<Vec<TransactionEvent>>> {
+            let lamports = self.lookup.lamports_for(&txn.signature).await;
+            Ok(Some(vec![TransactionEvent::SolTransfer(SolTransfer { slot: txn.slot, lamports, ..sol_transfer(3) })]))
+        }
+
+        fn name(&self) -> &str {
+            "enriching"
+        }
+    }
+
+    fn lamports(events: &[TransactionEvent]) -> Vec<u64> {
+        events
+            .iter()
+            .map(|ev| match ev {
+                TransactionEvent::SolTransfer(t) => t.lamports,
+                other => panic!("unexpected event {:?}", other),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn async_parser_emits_the_looked_up_value() {
+        let parser: Box<dyn AsyncTransactionParser> = Box::new(EnrichingParser { lookup: AmountLookup });
+
+        let events = parser.parse_async(transaction("abc", 10)).await.unwrap().unwrap();
+
+        assert_eq!(lamports(&events), vec![3_000_000]);
+    }
+
+    #[tokio::test]
+    async fn sync_parser_runs_unchanged_behind_the_async_trait() {
+        let parser: Box<dyn AsyncTransactionParser> = Box::new(PrefixParser);
+
+        let events = parser.parse_async(transaction("transfer-1", 10)).await.unwrap().unwrap();
+
+        assert_eq!(AsyncTransactionParser::name(parser.as_ref()), "prefix");
+        assert_eq!(lamports(&events), vec![1_000]);
+        assert!(parser.parse_async(transaction("vote", 10)).await.unwrap().is_none());
+    }
 }
@@ -0,0 +1,9 @@
+use anyhow::Result;
+use crate::domain::SolanaTransaction;
+
+/// Lists the programs a transaction invokes, for coverage reporting. Kept apart
+/// from the parsers so it sees every program, not just the ones we decode.
+pub trait ProgramInspector: Send + Sync {
+    /// Base58 program IDs, each once, top-level and CPI alike.
+    fn invoked_programs(&self, txn: &SolanaTransaction) -> Result<Vec<String>>;
+}
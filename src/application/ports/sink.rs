@@ -0,0 +1,11 @@
+use async_trait::async_trait;
+use crate::{application::AppResult, domain::TransactionEvent};
+
+/// Destination for parsed events with no cursor or DLQ semantics — a database,
+/// a webhook, a message bus, stdout. Emits should be safe to repeat, since a
+/// batch may be re-delivered after a failure.
+#[async_trait]
+pub trait EventSink: Send + Sync {
+    async fn emit(&self, events: &[TransactionEvent]) -> AppResult<()>;
+    fn name(&self) -> &str;
+}
@@ -0,0 +1,10 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crate::domain::SlotSummary;
+
+/// Receives one rollup per completed slot, for consumers that want per-slot
+/// totals rather than every event.
+#[async_trait]
+pub trait SlotSummarySink: Send + Sync {
+    async fn emit_summary(&self, summary: &SlotSummary) -> Result<()>;
+}
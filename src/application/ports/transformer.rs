@@ -0,0 +1,10 @@
+use crate::domain::TransactionEvent;
+
+/// Rewrites parsed events before they are batched: tag, redact, split or drop.
+/// Returning an empty vec drops the event; returning several replaces it with all
+/// of them. Transformers run after filters, in the order they were added, each
+/// on the output of the one before.
+pub trait EventTransformer: Send + Sync {
+    fn transform(&self, event: TransactionEvent) -> Vec<TransactionEvent>;
+    fn name(&self) -> &str;
+}
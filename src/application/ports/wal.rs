@@ -0,0 +1,22 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crate::domain::TransactionEvent;
+
+/// A batch that was logged but whose commit was never recorded.
+#[derive(Debug, Clone)]
+pub struct WalEntry {
+    pub id: u64,
+    pub slot: u64,
+    pub events: Vec<TransactionEvent>,
+}
+
+/// Durable record of batches between parsing and DB commit.
+#[async_trait]
+pub trait WriteAheadLog: Send + Sync {
+    /// Persist a batch before it is handed to the writers; returns its id.
+    async fn append(&self, events: &[TransactionEvent], slot: u64) -> Result<u64>;
+    /// Mark a batch as stored so it is not replayed.
+    async fn commit(&self, id: u64) -> Result<()>;
+    /// Batches appended in a previous run that never committed.
+    async fn uncommitted(&self) -> Result<Vec<WalEntry>>;
+}
@@ -0,0 +1,80 @@
+/// Batch size target that follows load. It doubles (up to `max`) while the queue
+/// still holds at least a full batch after a flush, and halves (down to `min`)
+/// each time the interval timer has to flush a partial batch.
+#[derive(Debug, Clone)]
+pub struct AdaptiveBatchSize {
+    min: usize,
+    max: usize,
+    current: usize,
+}
+
+impl AdaptiveBatchSize {
+    /// Bounds are normalised so that `1 <= min <= max`. Starts at `min`.
+    pub fn new(min: usize, max: usize) -> Self {
+        let min = min.max(1);
+        let max = max.max(min);
+        Self { min, max, current: min }
+    }
+
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    /// A full batch was just flushed with `backlog` events still queued.
+    pub fn on_full_flush(&mut self, backlog: usize) {
+        if backlog >= self.current {
+            self.current = (self.current * 2).min(self.max);
+        }
+    }
+
+    /// The interval timer flushed a batch that never filled up.
+    pub fn on_timer_flush(&mut self) {
+        self.current = (self.current / 2).max(self.min);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grows_under_a_burst_and_shrinks_when_idle() {
+        let mut size = AdaptiveBatchSize::new(50, 1_000);
+
+        // Burst: every flush leaves plenty queued
+        let mut seen = Vec::new();
+        for _ in 0..10 {
+            size.on_full_flush(10_000);
+            seen.push(size.current());
+        }
+        assert_eq!(seen, vec![100, 200, 400, 800, 1_000, 1_000, 1_000, 1_000, 1_000, 1_000]);
+
+        // Idle: only the timer flushes
+        let mut seen = Vec::new();
+        for _ in 0..6 {
+            size.on_timer_flush();
+            seen.push(size.current());
+        }
+        assert_eq!(seen, vec![500, 250, 125, 62, 50, 50]);
+    }
+
+    #[test]
+    fn small_backlog_holds_the_size() {
+        let mut size = AdaptiveBatchSize::new(50, 1_000);
+        size.on_full_flush(10_000);
+
+        size.on_full_flush(99);
+
+        assert_eq!(size.current(), 100);
+    }
+
+    #[test]
+    fn bounds_are_normalised() {
+        let size = AdaptiveBatchSize::new(0, 0);
+        assert_eq!(size.current(), 1);
+
+        let mut inverted = AdaptiveBatchSize::new(100, 10);
+        inverted.on_full_flush(1_000);
+        assert_eq!(inverted.current(), 100);
+    }
+}
@@ -0,0 +1,153 @@
+const MAGIC: &[u8; 4] = b"BLM1";
+const HEADER_LEN: usize = 4 + 8 + 4;
+
+/// Fixed-size Bloom filter over signatures, used to skip transactions a previous
+/// run already indexed.
+///
+/// A "seen" answer can be wrong. At the sized capacity roughly `fp_rate` of
+/// never-seen transactions are reported as seen and skipped, and the rate climbs
+/// once more signatures than `capacity` are inserted. "Not seen" is always right.
+/// The DB's unique keys still absorb whatever gets past the filter.
+#[derive(Debug, Clone)]
+pub struct BloomFilter {
+    words: Vec<u64>,
+    num_bits: u64,
+    hashes: u32,
+}
+
+impl BloomFilter {
+    /// Sized for `capacity` entries at a false-positive rate of `fp_rate`.
+    pub fn new(capacity: usize, fp_rate: f64) -> Self {
+        let n = capacity.max(1) as f64;
+        let p = fp_rate.clamp(1e-9, 0.5);
+        let ln2 = std::f64::consts::LN_2;
+        let num_bits = ((-n * p.ln()) / (ln2 * ln2)).ceil().max(64.0) as u64;
+        let hashes = ((num_bits as f64 / n) * ln2).round().clamp(1.0, 32.0) as u32;
+        Self { words: vec![0; num_bits.div_ceil(64) as usize], num_bits, hashes }
+    }
+
+    /// Records `key`. Returns true if it was (probably) not present before.
+    pub fn insert(&mut self, key: &[u8]) -> bool {
+        let mut added = false;
+        for bit in self.bit_positions(key) {
+            let (word, mask) = ((bit / 64) as usize, 1u64 << (bit % 64));
+            added |= self.words[word] & mask == 0;
+            self.words[word] |= mask;
+        }
+        added
+    }
+
+    pub fn contains(&self, key: &[u8]) -> bool {
+        self.bit_positions(key).all(|bit| self.words[(bit / 64) as usize] & (1u64 << (bit % 64)) != 0)
+    }
+
+    /// Size of the bit array in bytes.
+    pub fn size_bytes(&self) -> usize {
+        self.words.len() * 8
+    }
+
+    /// `BLM1`, bit count (u64 LE), hash count (u32 LE), then the bit array.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(HEADER_LEN + self.size_bytes());
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&self.num_bits.to_le_bytes());
+        out.extend_from_slice(&self.hashes.to_le_bytes());
+        for word in &self.words {
+            out.extend_from_slice(&word.to_le_bytes());
+        }
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < HEADER_LEN || &bytes[..4] != MAGIC {
+            return None;
+        }
+        let num_bits = u64::from_le_bytes(bytes[4..12].try_into().ok()?);
+        let hashes = u32::from_le_bytes(bytes[12..16].try_into().ok()?);
+        let body = &bytes[HEADER_LEN..];
+        if num_bits == 0 || hashes == 0 || body.len() as u64 != num_bits.div_ceil(64) * 8 {
+            return None;
+        }
+        let words = body.chunks_exact(8).map(|c| u64::from_le_bytes(c.try_into().unwrap())).collect();
+        Some(Self { words, num_bits, hashes })
+    }
+
+    /// Kirsch–Mitzenmacher double hashing over two FNV-1a variants, so positions
+    /// are stable across builds and a saved filter stays valid.
+    fn bit_positions(&self, key: &[u8]) -> impl Iterator<Item = u64> + use<> {
+        let h1 = fnv1a(key, 0xcbf2_9ce4_8422_2325);
+        let h2 = fnv1a(key, 0x8422_2325_cbf2_9ce4) | 1;
+        let num_bits = self.num_bits;
+        (0..self.hashes as u64).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % num_bits)
+    }
+}
+
+fn fnv1a(bytes: &[u8], offset: u64) -> u64 {
+    let mut hash = offset;
+    for &b in bytes {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter_with(keys: &[&[u8]]) -> BloomFilter {
+        let mut filter = BloomFilter::new(1_000, 0.01);
+        for key in keys {
+            filter.insert(key);
+        }
+        filter
+    }
+
+    #[test]
+    fn inserted_keys_are_seen() {
+        let mut filter = BloomFilter::new(1_000, 0.01);
+
+        assert!(filter.insert(b"sig-a"));
+        assert!(!filter.insert(b"sig-a"));
+        assert!(filter.contains(b"sig-a"));
+        assert!(!filter.contains(b"sig-b"));
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let filter = filter_with(&[b"sig-a", b"sig-b"]);
+
+        let decoded = BloomFilter::from_bytes(&filter.to_bytes()).expect("a saved filter decodes");
+
+        assert!(decoded.contains(b"sig-a") && decoded.contains(b"sig-b"));
+        assert_eq!(decoded.to_bytes(), filter.to_bytes());
+    }
+
+    #[test]
+    fn truncated_bytes_do_not_decode() {
+        let bytes = filter_with(&[b"sig-a"]).to_bytes();
+
+        assert!(BloomFilter::from_bytes(&bytes[..bytes.len() - 1]).is_none());
+        assert!(BloomFilter::from_bytes(&bytes[..HEADER_LEN - 1]).is_none());
+        assert!(BloomFilter::from_bytes(&[]).is_none());
+    }
+
+    #[test]
+    fn corrupt_header_does_not_decode() {
+        let bytes = filter_with(&[b"sig-a"]).to_bytes();
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        assert!(BloomFilter::from_bytes(&bad_magic).is_none());
+
+        let mut no_hashes = bytes.clone();
+        no_hashes[12..16].copy_from_slice(&0u32.to_le_bytes());
+        assert!(BloomFilter::from_bytes(&no_hashes).is_none());
+
+        // A bit count the body is too short for
+        let mut more_bits = bytes;
+        let num_bits = u64::from_le_bytes(more_bits[4..12].try_into().unwrap());
+        more_bits[4..12].copy_from_slice(&(num_bits + 64).to_le_bytes());
+        assert!(BloomFilter::from_bytes(&more_bits).is_none());
+    }
+}
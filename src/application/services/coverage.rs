@@ -0,0 +1,111 @@
+use std::collections::BTreeMap;
+
+use crate::domain::TransactionEvent;
+
+/// Per-parser tallies of what was parsed, used by dry-run mode to show parser
+/// coverage against live traffic.
+#[derive(Debug, Default)]
+pub struct CoverageReport {
+    transactions: u64,
+    events: BTreeMap<(String, &'static str), u64>,
+    errors: BTreeMap<String, u64>,
+    /// Event kinds each parser declares it can emit.
+    declared: BTreeMap<String, Vec<String>>,
+}
+
+impl CoverageReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the kinds `parser` declares, so the summary can show declared kinds
+    /// that never appeared and flag kinds that were not declared.
+    pub fn declare(&mut self, parser: &str, kinds: &[&str]) {
+        self.declared.insert(parser.to_string(), kinds.iter().map(|k| k.to_string()).collect());
+    }
+
+    pub fn record_transaction(&mut self) {
+        self.transactions += 1;
+    }
+
+    pub fn record_events(&mut self, parser: &str, events: &[TransactionEvent]) {
+        for ev in events {
+            *self.events.entry((parser.to_string(), ev.kind())).or_default() += 1;
+        }
+    }
+
+    pub fn record_error(&mut self, parser: &str) {
+        *self.errors.entry(parser.to_string()).or_default() += 1;
+    }
+
+    pub fn transactions(&self) -> u64 {
+        self.transactions
+    }
+
+    /// Events produced by `parser` of the given `kind`.
+    pub fn count(&self, parser: &str, kind: &str) -> u64 {
+        self.events
+            .iter()
+            .filter(|((p, k), _)| p == parser && *k == kind)
+            .map(|(_, n)| *n)
+            .sum()
+    }
+
+    pub fn errors(&self, parser: &str) -> u64 {
+        self.errors.get(parser).copied().unwrap_or(0)
+    }
+
+    pub fn log_summary(&self) {
+        tracing::info!("[dry-run] {} transactions parsed", self.transactions);
+        for ((parser, kind), n) in &self.events {
+            tracing::info!("[dry-run]   {:<16} {:<16} {}", parser, kind, n);
+        }
+        for (parser, n) in &self.errors {
+            tracing::info!("[dry-run]   {:<16} errors           {}", parser, n);
+        }
+        for (parser, kinds) in &self.declared {
+            for kind in kinds.iter().filter(|k| self.count(parser, k) == 0) {
+                tracing::info!("[dry-run]   {:<16} {:<16} 0", parser, kind);
+            }
+        }
+        for (parser, kind) in self.events.keys() {
+            if self.declared.get(parser).is_some_and(|kinds| !kinds.is_empty() && !kinds.iter().any(|k| k == kind)) {
+                tracing::warn!("[dry-run] {} emitted {} events but does not declare that kind", parser, kind);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::fixtures::{swap, transfer};
+
+    #[test]
+    fn events_are_counted_per_parser_and_kind() {
+        let mut report = CoverageReport::new();
+
+        report.record_events("raydium_amm", &[swap(1, 10, 5), swap(2, 10, 5)]);
+        report.record_events("system_transfer", &[transfer(3)]);
+        report.record_events("raydium_amm", &[swap(4, 10, 5)]);
+
+        assert_eq!(report.count("raydium_amm", "raydium_swap"), 3);
+        assert_eq!(report.count("system_transfer", "sol_transfer"), 1);
+        assert_eq!(report.count("raydium_amm", "sol_transfer"), 0);
+        assert_eq!(report.count("jupiter_vixen", "jupiter_swap"), 0);
+    }
+
+    #[test]
+    fn transactions_and_errors_are_tallied() {
+        let mut report = CoverageReport::new();
+
+        report.record_transaction();
+        report.record_transaction();
+        report.record_error("pump_fun");
+        report.record_error("pump_fun");
+
+        assert_eq!(report.transactions(), 2);
+        assert_eq!(report.errors("pump_fun"), 2);
+        assert_eq!(report.errors("raydium_amm"), 0);
+    }
+}
@@ -0,0 +1,172 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    time::{Duration, Instant},
+};
+
+use crate::domain::TransactionEvent;
+
+/// Identifies one event: signature, top-level instruction, and CPI position.
+pub type EventKey = (String, u32, Option<u32>);
+
+/// Bounded set of recently seen events. Once full, the oldest key is evicted,
+/// so this only catches replays that land within the last `capacity` events.
+/// With a TTL, keys also expire that long after they were first seen, so a
+/// quiet stream does not hold a full window of stale keys.
+pub struct DedupCache {
+    capacity: usize,
+    ttl: Option<Duration>,
+    seen: HashSet<EventKey>,
+    /// Keys in insertion order with when they were inserted; oldest first.
+    order: VecDeque<(EventKey, Instant)>,
+}
+
+impl DedupCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            ttl: None,
+            seen: HashSet::with_capacity(capacity),
+            order: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Records the event and returns true if it had not been seen before.
+    pub fn insert(&mut self, event: &TransactionEvent) -> bool {
+        self.insert_at(event, Instant::now())
+    }
+
+    /// `insert` with an explicit clock reading.
+    pub fn insert_at(&mut self, event: &TransactionEvent, now: Instant) -> bool {
+        self.expire(now);
+        let key = event.key();
+        if self.seen.contains(&key) {
+            return false;
+        }
+
+        if self.order.len() >= self.capacity {
+            if let Some((oldest, _)) = self.order.pop_front() {
+                self.seen.remove(&oldest);
+            }
+        }
+
+        self.seen.insert(key.clone());
+        self.order.push_back((key, now));
+        true
+    }
+
+    /// Drop keys older than the TTL. Insertion order is time order, so only the
+    /// front of the queue needs checking.
+    fn expire(&mut self, now: Instant) {
+        let Some(ttl) = self.ttl else { return };
+        while let Some((key, at)) = self.order.front() {
+            if now.saturating_duration_since(*at) < ttl {
+                break;
+            }
+            self.seen.remove(key);
+            self.order.pop_front();
+        }
+    }
+
+    /// Forget everything, e.g. after a rollback so re-delivered transactions are not dropped.
+    pub fn clear(&mut self) {
+        self.seen.clear();
+        self.order.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+}
+
+/// Drop events whose key already appeared earlier in `events`, keeping the first
+/// of each. Returns how many were dropped. Unlike `DedupCache` this looks at one
+/// batch only, so it always runs: a multi-row upsert cannot touch the same row
+/// twice in one statement anyway.
+pub fn dedup_batch(events: &mut Vec<TransactionEvent>) -> usize {
+    let before = events.len();
+    let mut seen = HashSet::with_capacity(before);
+    events.retain(|ev| seen.insert(ev.key()));
+    before - events.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{SolTransfer, fixtures::sol_transfer};
+
+    fn transfer(seed: u8, instruction_index: u32) -> TransactionEvent {
+        TransactionEvent::SolTransfer(SolTransfer { instruction_index, ..sol_transfer(seed) })
+    }
+
+    #[test]
+    fn repeated_event_is_reported_once() {
+        let mut cache = DedupCache::new(8);
+
+        assert!(cache.insert(&transfer(1, 0)));
+        assert!(!cache.insert(&transfer(1, 0)));
+        // Same signature, another instruction: a different event
+        assert!(cache.insert(&transfer(1, 1)));
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn oldest_key_is_evicted_once_full() {
+        let mut cache = DedupCache::new(2);
+        cache.insert(&transfer(1, 0));
+        cache.insert(&transfer(2, 0));
+
+        assert!(cache.insert(&transfer(3, 0)));
+
+        assert_eq!(cache.len(), 2);
+        assert!(!cache.insert(&transfer(3, 0)));
+        assert!(!cache.insert(&transfer(2, 0)));
+        // Evicted, so a replay of it gets through again
+        assert!(cache.insert(&transfer(1, 0)));
+    }
+
+    #[test]
+    fn key_past_its_ttl_is_treated_as_unseen() {
+        let mut cache = DedupCache::new(8).with_ttl(Duration::from_secs(60));
+        let start = Instant::now();
+        cache.insert_at(&transfer(1, 0), start);
+        cache.insert_at(&transfer(2, 0), start + Duration::from_secs(30));
+
+        // At 60s the first key has expired; the second has 30s left
+        let later = start + Duration::from_secs(60);
+        assert!(cache.insert_at(&transfer(1, 0), later));
+        assert!(!cache.insert_at(&transfer(2, 0), later));
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn ttl_alone_empties_a_quiet_window() {
+        let mut cache = DedupCache::new(1_000).with_ttl(Duration::from_secs(1));
+        let start = Instant::now();
+        for seed in 0..100 {
+            cache.insert_at(&transfer(seed, 0), start);
+        }
+
+        cache.insert_at(&transfer(200, 0), start + Duration::from_secs(2));
+
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn batch_keeps_the_first_of_each_key() {
+        let mut batch = vec![transfer(1, 0), transfer(2, 0), transfer(1, 0), transfer(1, 1)];
+
+        assert_eq!(dedup_batch(&mut batch), 1);
+
+        let keys: Vec<_> = batch.iter().map(|ev| ev.key()).collect();
+        assert_eq!(keys, vec![transfer(1, 0).key(), transfer(2, 0).key(), transfer(1, 1).key()]);
+    }
+}
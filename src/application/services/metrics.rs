@@ -0,0 +1,160 @@
+use std::{
+    sync::atomic::{AtomicI64, AtomicU32, AtomicU64, AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use crate::application::{MintDecimals, VolumeTracker};
+
+/// Upper bounds (ms) of the latency histogram buckets; the last bucket is open-ended.
+const LATENCY_BUCKETS_MS: [u64; 14] = [1, 2, 5, 10, 25, 50, 100, 250, 500, 1_000, 2_500, 5_000, 10_000, 30_000];
+
+/// Fixed-bucket histogram. Quantiles resolve to the upper bound of the bucket they
+/// fall in, which is precise enough to tell "keeping up" from "backing up".
+#[derive(Debug, Default)]
+pub struct LatencyHistogram {
+    buckets: [AtomicU64; LATENCY_BUCKETS_MS.len() + 1],
+    count: AtomicU64,
+}
+
+impl LatencyHistogram {
+    pub fn record(&self, latency: Duration) {
+        let ms = latency.as_millis() as u64;
+        let idx = LATENCY_BUCKETS_MS.iter().position(|&b| ms <= b).unwrap_or(LATENCY_BUCKETS_MS.len());
+        self.buckets[idx].fetch_add(1, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Bucket bound at quantile `q` (0.0–1.0), or `None` before any sample.
+    /// Samples past the last bound report that bound.
+    pub fn quantile(&self, q: f64) -> Option<Duration> {
+        let total = self.count();
+        if total == 0 {
+            return None;
+        }
+
+        let target = ((total as f64 * q).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (idx, bucket) in self.buckets.iter().enumerate() {
+            seen += bucket.load(Ordering::Relaxed);
+            if seen >= target {
+                let ms = LATENCY_BUCKETS_MS[idx.min(LATENCY_BUCKETS_MS.len() - 1)];
+                return Some(Duration::from_millis(ms));
+            }
+        }
+        Some(Duration::from_millis(LATENCY_BUCKETS_MS[LATENCY_BUCKETS_MS.len() - 1]))
+    }
+}
+
+/// Counters the pipeline updates as it runs, shared with anything that reports
+/// on its health. All fields are lock-free so readers never stall ingestion.
+#[derive(Debug, Default)]
+pub struct PipelineMetrics {
+    events_received: AtomicU64,
+    events_persisted: AtomicU64,
+    batches_written: AtomicU64,
+    write_errors: AtomicU64,
+    slots_gapped: AtomicU64,
+    /// Times the source went quiet past the fetcher's stall timeout.
+    source_stalls: AtomicU64,
+    /// Swaps that failed the sanity checks, whether dropped or only flagged.
+    suspicious_swaps: AtomicU64,
+    /// Time from a batch's first parsed event to its successful write.
+    persist_latency: LatencyHistogram,
+    /// Unix seconds of the last event pulled off the buffer; 0 until the first one.
+    last_event_at: AtomicI64,
+    queue_depth: AtomicUsize,
+    queue_capacity: AtomicUsize,
+    /// DB pool gauges, sampled alongside the queue; both stay 0 without a pool.
+    pool_size: AtomicU32,
+    pool_idle: AtomicUsize,
+    /// Per-mint swap volume; `None` unless enabled with `with_volume`.
+    volume: Option<VolumeTracker>,
+}
+
+impl PipelineMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Track swap volume per mint, converted to UI units with `decimals`.
+    pub fn with_volume(mut self, decimals: MintDecimals) -> Self {
+        self.volume = Some(VolumeTracker::new(decimals));
+        self
+    }
+
+    pub fn volume(&self) -> Option<&VolumeTracker> {
+        self.volume.as_ref()
+    }
+
+    pub fn record_event(&self, now: i64) {
+        self.events_received.fetch_add(1, Ordering::Relaxed);
+        self.last_event_at.store(now, Ordering::Relaxed);
+    }
+
+    pub fn record_batch(&self, events: usize) {
+        self.batches_written.fetch_add(1, Ordering::Relaxed);
+        self.events_persisted.fetch_add(events as u64, Ordering::Relaxed);
+    }
+
+    pub fn record_write_error(&self) {
+        self.write_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_persist_latency(&self, latency: Duration) {
+        self.persist_latency.record(latency);
+    }
+
+    pub fn persist_latency(&self) -> &LatencyHistogram {
+        &self.persist_latency
+    }
+
+    pub fn record_gap(&self, slots: u64) {
+        self.slots_gapped.fetch_add(slots, Ordering::Relaxed);
+    }
+
+    pub fn record_source_stall(&self) {
+        self.source_stalls.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_suspicious_swap(&self) {
+        self.suspicious_swaps.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn set_queue(&self, depth: usize, capacity: usize) {
+        self.queue_depth.store(depth, Ordering::Relaxed);
+        self.queue_capacity.store(capacity, Ordering::Relaxed);
+    }
+
+    pub fn set_pool(&self, size: u32, idle: usize) {
+        self.pool_size.store(size, Ordering::Relaxed);
+        self.pool_idle.store(idle, Ordering::Relaxed);
+    }
+
+    /// Overwrite the last-event timestamp, e.g. to seed it at startup.
+    pub fn set_last_event_at(&self, at: i64) {
+        self.last_event_at.store(at, Ordering::Relaxed);
+    }
+
+    pub fn events_received(&self) -> u64 { self.events_received.load(Ordering::Relaxed) }
+    pub fn events_persisted(&self) -> u64 { self.events_persisted.load(Ordering::Relaxed) }
+    pub fn batches_written(&self) -> u64 { self.batches_written.load(Ordering::Relaxed) }
+    pub fn write_errors(&self) -> u64 { self.write_errors.load(Ordering::Relaxed) }
+    pub fn slots_gapped(&self) -> u64 { self.slots_gapped.load(Ordering::Relaxed) }
+    pub fn source_stalls(&self) -> u64 { self.source_stalls.load(Ordering::Relaxed) }
+    pub fn suspicious_swaps(&self) -> u64 { self.suspicious_swaps.load(Ordering::Relaxed) }
+    pub fn queue_depth(&self) -> usize { self.queue_depth.load(Ordering::Relaxed) }
+    pub fn queue_capacity(&self) -> usize { self.queue_capacity.load(Ordering::Relaxed) }
+    pub fn pool_size(&self) -> u32 { self.pool_size.load(Ordering::Relaxed) }
+    pub fn pool_idle(&self) -> usize { self.pool_idle.load(Ordering::Relaxed) }
+
+    pub fn last_event_at(&self) -> Option<i64> {
+        match self.last_event_at.load(Ordering::Relaxed) {
+            0 => None,
+            at => Some(at),
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+
+use crate::domain::{USDC_MINT, WSOL_MINT};
+
+/// Decimals of the mints the indexer can price in UI units. Seeded with WSOL and
+/// USDC; anything else has to be registered, and unknown mints stay in raw units.
+#[derive(Debug, Clone)]
+pub struct MintDecimals {
+    decimals: HashMap<String, u8>,
+}
+
+impl Default for MintDecimals {
+    fn default() -> Self {
+        Self::empty().with(WSOL_MINT, 9).with(USDC_MINT, 6)
+    }
+}
+
+impl MintDecimals {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A registry without the built-in mints.
+    pub fn empty() -> Self {
+        Self { decimals: HashMap::new() }
+    }
+
+    pub fn with(mut self, mint: &str, decimals: u8) -> Self {
+        self.insert(mint, decimals);
+        self
+    }
+
+    pub fn insert(&mut self, mint: &str, decimals: u8) {
+        self.decimals.insert(mint.to_string(), decimals);
+    }
+
+    pub fn get(&self, mint: &str) -> Option<u8> {
+        self.decimals.get(mint).copied()
+    }
+
+    /// `raw` scaled by the mint's decimals, or `raw` itself when they are unknown.
+    pub fn to_ui(&self, mint: &str, raw: u128) -> f64 {
+        match self.get(mint) {
+            Some(decimals) => raw as f64 / 10f64.powi(decimals as i32),
+            None => raw as f64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_knows_wsol_and_usdc() {
+        let decimals = MintDecimals::new();
+
+        assert_eq!(decimals.get(WSOL_MINT), Some(9));
+        assert_eq!(decimals.get(USDC_MINT), Some(6));
+        assert_eq!(MintDecimals::empty().get(WSOL_MINT), None);
+    }
+
+    #[test]
+    fn registered_mint_overrides_a_builtin() {
+        let decimals = MintDecimals::new().with("mint-a", 2).with(USDC_MINT, 8);
+
+        assert_eq!(decimals.get("mint-a"), Some(2));
+        assert_eq!(decimals.get(USDC_MINT), Some(8));
+        assert_eq!(decimals.get("mint-b"), None);
+    }
+
+    #[test]
+    fn known_mints_scale_to_ui_units() {
+        let decimals = MintDecimals::new();
+
+        assert_eq!(decimals.to_ui(WSOL_MINT, 1_500_000_000), 1.5);
+        assert_eq!(decimals.to_ui(USDC_MINT, 2_500_000), 2.5);
+    }
+
+    #[test]
+    fn unknown_mints_stay_in_raw_units() {
+        assert_eq!(MintDecimals::new().to_ui("mint-b", 1_500_000_000), 1_500_000_000.0);
+    }
+}
@@ -1,2 +1,33 @@
+mod batch_size;
+mod bloom;
+mod coverage;
+mod dedup;
+mod metrics;
+mod mint_decimals;
 mod notification;
+mod parser_control;
+mod program_coverage;
+mod rate_limit;
+mod sampling;
+mod signature_filter;
+mod slot_gap;
+mod slot_summary;
+mod swap_validator;
+mod volume;
+
+pub use batch_size::*;
+pub use bloom::*;
+pub use coverage::*;
+pub use dedup::*;
+pub use metrics::*;
+pub use mint_decimals::*;
 pub use notification::*;
+pub use parser_control::*;
+pub use program_coverage::*;
+pub use rate_limit::*;
+pub use sampling::*;
+pub use signature_filter::*;
+pub use slot_gap::*;
+pub use slot_summary::*;
+pub use swap_validator::*;
+pub use volume::*;
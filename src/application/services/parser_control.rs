@@ -0,0 +1,93 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+
+/// Runtime on/off switches for the pipeline's parsers, one per registered parser
+/// in registration order. Clones share the same flags, so a handle given to the
+/// health server takes effect on the next transaction the pipeline parses.
+#[derive(Debug, Clone, Default)]
+pub struct ParserControl {
+    flags: Arc<Vec<(String, AtomicBool)>>,
+}
+
+impl ParserControl {
+    /// Every parser starts enabled.
+    pub fn new<I, S>(names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let flags = names.into_iter().map(|n| (n.into(), AtomicBool::new(true))).collect();
+        Self { flags: Arc::new(flags) }
+    }
+
+    /// Returns false when no parser has that name.
+    pub fn set_enabled(&self, name: &str, enabled: bool) -> bool {
+        let Some((_, flag)) = self.flags.iter().find(|(n, _)| n == name) else { return false };
+        let was = flag.swap(enabled, Ordering::Relaxed);
+        if was != enabled {
+            tracing::warn!("Parser {} {}", name, if enabled { "enabled" } else { "disabled" });
+        }
+        true
+    }
+
+    pub fn is_enabled(&self, name: &str) -> Option<bool> {
+        self.flags.iter().find(|(n, _)| n == name).map(|(_, f)| f.load(Ordering::Relaxed))
+    }
+
+    /// Flag of the parser at `index` in registration order; unknown indexes count as enabled.
+    pub fn is_enabled_at(&self, index: usize) -> bool {
+        self.flags.get(index).is_none_or(|(_, f)| f.load(Ordering::Relaxed))
+    }
+
+    /// (name, enabled) for every parser, in registration order.
+    pub fn states(&self) -> Vec<(String, bool)> {
+        self.flags.iter().map(|(n, f)| (n.clone(), f.load(Ordering::Relaxed))).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsers_start_enabled() {
+        let control = ParserControl::new(["spl_token_transfer", "raydium_amm"]);
+
+        assert_eq!(control.is_enabled("raydium_amm"), Some(true));
+        assert_eq!(control.states(), vec![("spl_token_transfer".to_string(), true), ("raydium_amm".to_string(), true)]);
+    }
+
+    #[test]
+    fn toggling_is_seen_by_name_and_index() {
+        let control = ParserControl::new(["spl_token_transfer", "raydium_amm"]);
+
+        assert!(control.set_enabled("raydium_amm", false));
+        assert_eq!(control.is_enabled("raydium_amm"), Some(false));
+        assert!(!control.is_enabled_at(1));
+        assert!(control.is_enabled_at(0));
+
+        assert!(control.set_enabled("raydium_amm", true));
+        assert!(control.is_enabled_at(1));
+    }
+
+    #[test]
+    fn unknown_parser_is_reported() {
+        let control = ParserControl::new(["raydium_amm"]);
+
+        assert!(!control.set_enabled("jupiter_vixen", false));
+        assert_eq!(control.is_enabled("jupiter_vixen"), None);
+        assert!(control.is_enabled_at(5));
+    }
+
+    #[test]
+    fn clones_share_flags() {
+        let control = ParserControl::new(["raydium_amm"]);
+        let handle = control.clone();
+
+        handle.set_enabled("raydium_amm", false);
+
+        assert_eq!(control.is_enabled("raydium_amm"), Some(false));
+    }
+}
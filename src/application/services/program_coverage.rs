@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ProgramCount {
+    /// Transactions that invoked the program.
+    pub transactions: u64,
+    /// Of those, transactions at least one parser produced events for.
+    pub parsed: u64,
+}
+
+impl ProgramCount {
+    pub fn unparsed(&self) -> u64 {
+        self.transactions - self.parsed
+    }
+}
+
+/// Per-program tallies of invoked vs parsed transactions. A program that shows
+/// up often in transactions nothing was parsed from is a candidate for a new
+/// parser. Attribution is per transaction: every program in a parsed
+/// transaction counts as parsed, including helpers like ComputeBudget.
+#[derive(Debug, Default)]
+pub struct ProgramCoverage {
+    programs: HashMap<String, ProgramCount>,
+}
+
+impl ProgramCoverage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, programs: &[String], parsed: bool) {
+        for program in programs {
+            let count = self.programs.entry(program.clone()).or_default();
+            count.transactions += 1;
+            if parsed {
+                count.parsed += 1;
+            }
+        }
+    }
+
+    /// Programs with the most unparsed transactions, most first, at most `n`.
+    pub fn top_unparsed(&self, n: usize) -> Vec<(&str, ProgramCount)> {
+        let mut top: Vec<_> = self
+            .programs
+            .iter()
+            .filter(|(_, count)| count.unparsed() > 0)
+            .map(|(program, count)| (program.as_str(), *count))
+            .collect();
+        top.sort_by(|a, b| b.1.unparsed().cmp(&a.1.unparsed()).then_with(|| a.0.cmp(b.0)));
+        top.truncate(n);
+        top
+    }
+
+    pub fn log_top_unparsed(&self, n: usize) {
+        let top = self.top_unparsed(n);
+        if top.is_empty() {
+            return;
+        }
+        tracing::info!("[coverage] top {} programs in unparsed transactions:", top.len());
+        for (program, count) in top {
+            tracing::info!("[coverage]   {:<44} {:>8} unparsed / {:>8} seen", program, count.unparsed(), count.transactions);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn programs(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn counts_every_program_of_a_transaction() {
+        let mut coverage = ProgramCoverage::new();
+
+        coverage.record(&programs(&["raydium", "compute_budget"]), true);
+        coverage.record(&programs(&["raydium", "unknown"]), false);
+
+        assert_eq!(coverage.programs["raydium"], ProgramCount { transactions: 2, parsed: 1 });
+        assert_eq!(coverage.programs["compute_budget"], ProgramCount { transactions: 1, parsed: 1 });
+        assert_eq!(coverage.programs["unknown"].unparsed(), 1);
+    }
+
+    #[test]
+    fn top_unparsed_puts_the_most_unparsed_first() {
+        let mut coverage = ProgramCoverage::new();
+        for _ in 0..3 {
+            coverage.record(&programs(&["often"]), false);
+        }
+        coverage.record(&programs(&["once-b"]), false);
+        coverage.record(&programs(&["once-a"]), false);
+        coverage.record(&programs(&["parsed"]), true);
+
+        let top: Vec<_> = coverage.top_unparsed(10).into_iter().map(|(program, count)| (program, count.unparsed())).collect();
+
+        // Ties are broken by name; fully parsed programs are left out
+        assert_eq!(top, vec![("often", 3), ("once-a", 1), ("once-b", 1)]);
+        assert_eq!(coverage.top_unparsed(1).len(), 1);
+    }
+}
@@ -0,0 +1,69 @@
+use tokio::time::{Duration, Instant};
+
+/// Token bucket holding up to one second's worth of tokens, refilled
+/// continuously at `rate` per second. Callers over the rate wait for a token
+/// instead of being turned away, so bursts are smoothed rather than dropped.
+#[derive(Debug)]
+pub struct RateLimiter {
+    rate: f64,
+    capacity: f64,
+    tokens: f64,
+    last: Instant,
+}
+
+impl RateLimiter {
+    /// `per_sec` is clamped to at least 1.
+    pub fn new(per_sec: u32) -> Self {
+        let rate = per_sec.max(1) as f64;
+        Self { rate, capacity: rate, tokens: rate, last: Instant::now() }
+    }
+
+    fn refill(&mut self) {
+        let now = Instant::now();
+        self.tokens = (self.tokens + now.duration_since(self.last).as_secs_f64() * self.rate).min(self.capacity);
+        self.last = now;
+    }
+
+    /// Take one token, sleeping until one is available.
+    pub async fn acquire(&mut self) {
+        self.refill();
+        if self.tokens < 1.0 {
+            let wait = Duration::from_secs_f64((1.0 - self.tokens) / self.rate);
+            tokio::time::sleep(wait).await;
+            self.refill();
+        }
+        self.tokens -= 1.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn events_past_the_burst_wait_for_the_rate() {
+        let mut limiter = RateLimiter::new(20);
+        let started = Instant::now();
+
+        // 20 from the full bucket, then 20 more at 20 per second
+        for _ in 0..40 {
+            limiter.acquire().await;
+        }
+
+        let elapsed = started.elapsed();
+        assert!(elapsed >= Duration::from_millis(900), "took {:?}", elapsed);
+        assert!(elapsed < Duration::from_secs(3), "took {:?}", elapsed);
+    }
+
+    #[tokio::test]
+    async fn burst_within_the_bucket_does_not_wait() {
+        let mut limiter = RateLimiter::new(100);
+        let started = Instant::now();
+
+        for _ in 0..50 {
+            limiter.acquire().await;
+        }
+
+        assert!(started.elapsed() < Duration::from_millis(100));
+    }
+}
@@ -0,0 +1,91 @@
+use crate::{application::EventFilter, domain::TransactionEvent};
+
+/// Keeps a fixed fraction of transactions, chosen by hashing the signature.
+/// The decision depends only on the signature, so every event of a transaction
+/// shares it and a replayed transaction is kept or dropped the same way after a
+/// restart.
+#[derive(Debug, Clone, Copy)]
+pub struct SamplingFilter {
+    rate: f64,
+    threshold: u64,
+}
+
+impl SamplingFilter {
+    /// `rate` is the fraction kept, clamped to 0.0..=1.0.
+    pub fn new(rate: f64) -> Self {
+        let rate = if rate.is_nan() { 1.0 } else { rate.clamp(0.0, 1.0) };
+        Self { rate, threshold: (rate * u64::MAX as f64) as u64 }
+    }
+
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+
+    pub fn keeps_signature(&self, signature: &str) -> bool {
+        if self.rate >= 1.0 {
+            return true;
+        }
+        fnv1a(signature.as_bytes()) < self.threshold
+    }
+}
+
+/// FNV-1a: fixed constants, so the hash is stable across builds and platforms
+/// (unlike `DefaultHasher`).
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for &b in bytes {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
+impl EventFilter for SamplingFilter {
+    fn keep(&self, event: &TransactionEvent) -> bool {
+        self.keeps_signature(event.signature())
+    }
+
+    fn name(&self) -> &str {
+        "sampling"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::Signature58;
+
+    fn signature(i: u64) -> String {
+        let mut bytes = [0u8; 64];
+        bytes[..8].copy_from_slice(&i.to_le_bytes());
+        Signature58::from(bytes).to_string()
+    }
+
+    #[test]
+    fn keeps_roughly_the_configured_fraction() {
+        let filter = SamplingFilter::new(0.25);
+
+        let kept = (0..20_000).filter(|&i| filter.keeps_signature(&signature(i))).count();
+
+        let fraction = kept as f64 / 20_000.0;
+        assert!((0.22..=0.28).contains(&fraction), "kept {}", fraction);
+    }
+
+    #[test]
+    fn decision_is_stable_for_a_signature() {
+        let first = SamplingFilter::new(0.5);
+        // A fresh filter stands in for a restart
+        let second = SamplingFilter::new(0.5);
+
+        for i in 0..1_000 {
+            assert_eq!(first.keeps_signature(&signature(i)), second.keeps_signature(&signature(i)));
+        }
+    }
+
+    #[test]
+    fn rate_is_clamped() {
+        assert!(SamplingFilter::new(1.5).keeps_signature(&signature(1)));
+        assert!(!SamplingFilter::new(-0.5).keeps_signature(&signature(1)));
+        assert_eq!(SamplingFilter::new(f64::NAN).rate(), 1.0);
+    }
+}
@@ -0,0 +1,74 @@
+use std::collections::HashSet;
+
+/// Transaction-level allow / deny list on signatures, checked before parsing.
+/// With an allow list only those signatures pass; the deny list then removes
+/// from whatever is left.
+#[derive(Debug, Clone, Default)]
+pub struct SignatureFilter {
+    allow: Option<HashSet<String>>,
+    deny: HashSet<String>,
+}
+
+impl SignatureFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep only these signatures. An empty list is treated as "no allow list".
+    pub fn with_allow(mut self, signatures: impl IntoIterator<Item = String>) -> Self {
+        let allow: HashSet<_> = signatures.into_iter().collect();
+        self.allow = (!allow.is_empty()).then_some(allow);
+        self
+    }
+
+    pub fn with_deny(mut self, signatures: impl IntoIterator<Item = String>) -> Self {
+        self.deny.extend(signatures);
+        self
+    }
+
+    /// True when neither list has any entries, i.e. the filter passes everything.
+    pub fn is_empty(&self) -> bool {
+        self.allow.is_none() && self.deny.is_empty()
+    }
+
+    pub fn allows(&self, signature: &str) -> bool {
+        self.allow.as_ref().is_none_or(|allow| allow.contains(signature)) && !self.deny.contains(signature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(signatures: &[&str]) -> Vec<String> {
+        signatures.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn only_allow_listed_signatures_pass() {
+        let filter = SignatureFilter::new().with_allow(list(&["sig-a", "sig-b"]));
+
+        let passed: Vec<_> = ["sig-a", "sig-b", "sig-c"].into_iter().filter(|s| filter.allows(s)).collect();
+
+        assert_eq!(passed, vec!["sig-a", "sig-b"]);
+    }
+
+    #[test]
+    fn deny_list_removes_from_what_is_allowed() {
+        let filter = SignatureFilter::new().with_allow(list(&["sig-a", "sig-b"])).with_deny(list(&["sig-b"]));
+        assert!(filter.allows("sig-a"));
+        assert!(!filter.allows("sig-b"));
+
+        let deny_only = SignatureFilter::new().with_deny(list(&["sig-b"]));
+        assert!(deny_only.allows("sig-c"));
+        assert!(!deny_only.allows("sig-b"));
+    }
+
+    #[test]
+    fn empty_allow_list_passes_everything() {
+        let filter = SignatureFilter::new().with_allow(Vec::new());
+
+        assert!(filter.is_empty());
+        assert!(filter.allows("anything"));
+    }
+}
@@ -0,0 +1,55 @@
+use std::ops::RangeInclusive;
+
+/// Watches the block slot sequence and reports slots that were skipped over.
+/// Fed from BlockMeta only: transaction slots are sparse under account filters
+/// and would look like gaps. Leader-skipped slots also show up here, so a gap
+/// means "worth checking", not "definitely missed".
+#[derive(Debug, Default)]
+pub struct SlotGapTracker {
+    last: Option<u64>,
+}
+
+impl SlotGapTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a block slot and return the range skipped since the previous one.
+    /// Repeated or older slots are ignored.
+    pub fn observe(&mut self, slot: u64) -> Option<RangeInclusive<u64>> {
+        let Some(last) = self.last else {
+            self.last = Some(slot);
+            return None;
+        };
+        if slot <= last {
+            return None;
+        }
+
+        self.last = Some(slot);
+        (slot > last + 1).then(|| (last + 1)..=(slot - 1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skipped_slots_are_reported_as_one_range() {
+        let mut tracker = SlotGapTracker::new();
+
+        let gaps: Vec<_> = [100, 101, 105].into_iter().map(|slot| tracker.observe(slot)).collect();
+
+        assert_eq!(gaps, vec![None, None, Some(102..=104)]);
+    }
+
+    #[test]
+    fn repeated_and_older_slots_are_ignored() {
+        let mut tracker = SlotGapTracker::new();
+        tracker.observe(100);
+
+        assert_eq!(tracker.observe(100), None);
+        assert_eq!(tracker.observe(90), None);
+        assert_eq!(tracker.observe(101), None);
+    }
+}
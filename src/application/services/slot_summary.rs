@@ -0,0 +1,100 @@
+use std::collections::BTreeMap;
+
+use crate::domain::{SlotSummary, TransactionEvent};
+
+/// Accumulates swap totals per slot until the slot's block meta arrives.
+/// Transactions can trail their block meta slightly, so a swap for an already
+/// completed slot starts a fresh summary that goes out with the next block.
+#[derive(Debug, Default)]
+pub struct SlotAggregator {
+    pending: BTreeMap<u64, SlotSummary>,
+}
+
+impl SlotAggregator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count `event` if it is a swap; other events are ignored.
+    pub fn record(&mut self, event: &TransactionEvent) {
+        let Some(swap) = event.as_normalized_swap() else { return };
+        let summary = self.pending.entry(swap.slot).or_insert_with(|| SlotSummary { slot: swap.slot, ..Default::default() });
+        summary.swap_count += 1;
+        *summary.total_volume_per_mint.entry(swap.mint_in).or_default() += swap.amount_in as u128;
+        *summary.total_volume_per_mint.entry(swap.mint_out).or_default() += swap.amount_out as u128;
+    }
+
+    /// Close `slot` and anything before it, oldest first. `slot` itself is always
+    /// included, with zero swaps if none were seen.
+    pub fn complete(&mut self, slot: u64) -> Vec<SlotSummary> {
+        let later = self.pending.split_off(&slot.saturating_add(1));
+        let mut done = std::mem::replace(&mut self.pending, later);
+        done.entry(slot).or_insert_with(|| SlotSummary { slot, ..Default::default() });
+        done.into_values().collect()
+    }
+
+    /// Forget slots at or above `slot`, after the chain dropped them.
+    pub fn discard_from(&mut self, slot: u64) {
+        self.pending.split_off(&slot);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{RaydiumSwapEvent, fixtures::{raydium_swap, transfer_at}};
+
+    fn swap(seed: u8, slot: u64, amount_in: u64, amount_received: u64) -> TransactionEvent {
+        TransactionEvent::RaydiumSwap(RaydiumSwapEvent { slot, ..raydium_swap(seed, amount_in, amount_received) })
+    }
+
+    fn transfer(slot: u64) -> TransactionEvent {
+        transfer_at(9, slot)
+    }
+
+    fn volume(summary: &SlotSummary, mint: &str) -> u128 {
+        summary.total_volume_per_mint.get(mint).copied().unwrap_or(0)
+    }
+
+    #[test]
+    fn each_block_meta_closes_its_slot() {
+        let mut aggregator = SlotAggregator::new();
+
+        aggregator.record(&swap(1, 100, 1_000, 500));
+        aggregator.record(&transfer(100));
+        aggregator.record(&swap(2, 100, 2_000, 900));
+        // Slot 101's first swap arrives before slot 100's block meta
+        aggregator.record(&swap(3, 101, 300, 100));
+        let first = aggregator.complete(100);
+        aggregator.record(&swap(4, 101, 700, 200));
+        let second = aggregator.complete(101);
+
+        assert_eq!(first.len(), 1);
+        assert_eq!((first[0].slot, first[0].swap_count), (100, 2));
+        assert_eq!((volume(&first[0], "MintA"), volume(&first[0], "MintB")), (3_000, 1_400));
+        assert_eq!(second.len(), 1);
+        assert_eq!((second[0].slot, second[0].swap_count), (101, 2));
+        assert_eq!((volume(&second[0], "MintA"), volume(&second[0], "MintB")), (1_000, 300));
+    }
+
+    #[test]
+    fn slot_without_swaps_still_gets_a_summary() {
+        let mut aggregator = SlotAggregator::new();
+
+        let done = aggregator.complete(100);
+
+        assert_eq!(done, vec![SlotSummary { slot: 100, ..Default::default() }]);
+    }
+
+    #[test]
+    fn discarded_slots_are_never_summarised() {
+        let mut aggregator = SlotAggregator::new();
+        aggregator.record(&swap(1, 100, 1_000, 500));
+        aggregator.record(&swap(2, 101, 1_000, 500));
+
+        aggregator.discard_from(101);
+
+        let slots: Vec<_> = aggregator.complete(101).iter().map(|s| (s.slot, s.swap_count)).collect();
+        assert_eq!(slots, vec![(100, 1), (101, 0)]);
+    }
+}
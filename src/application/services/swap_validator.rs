@@ -0,0 +1,129 @@
+use std::{str::FromStr, sync::Arc};
+
+use crate::{
+    application::{EventFilter, PipelineMetrics},
+    domain::{NormalizedSwap, TransactionEvent},
+};
+
+/// What `SwapValidator` does with a swap that fails its checks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuspiciousSwapAction {
+    Drop,
+    /// Keep the swap, but log and count it.
+    Flag,
+}
+
+impl FromStr for SuspiciousSwapAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "drop" => Ok(Self::Drop),
+            "flag" => Ok(Self::Flag),
+            _ => Err(format!("unknown suspicious swap action `{}`", s)),
+        }
+    }
+}
+
+/// Sanity checks on swap events: both amounts nonzero, and neither side more than
+/// `max_ratio` times the other. Amounts are raw base units, so the ratio also
+/// absorbs decimal differences between the mints (1 SOL is 1e9, while a 6-decimal
+/// token at a millionth of a cent is ~1e12 per SOL); keep the bound generous.
+/// Non-swap events always pass.
+pub struct SwapValidator {
+    max_ratio: f64,
+    action: SuspiciousSwapAction,
+    metrics: Option<Arc<PipelineMetrics>>,
+}
+
+impl SwapValidator {
+    pub const DEFAULT_MAX_RATIO: f64 = 1e15;
+
+    pub fn new(max_ratio: f64, action: SuspiciousSwapAction) -> Self {
+        Self { max_ratio, action, metrics: None }
+    }
+
+    /// Count every failing swap in `suspicious_swaps`.
+    pub fn with_metrics(mut self, metrics: Arc<PipelineMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Why `swap` looks implausible, or `None` if it passes.
+    pub fn check(&self, swap: &NormalizedSwap) -> Option<&'static str> {
+        if swap.amount_in == 0 || swap.amount_out == 0 {
+            return Some("zero amount");
+        }
+        let (small, large) = if swap.amount_in < swap.amount_out {
+            (swap.amount_in, swap.amount_out)
+        } else {
+            (swap.amount_out, swap.amount_in)
+        };
+        (large as f64 / small as f64 > self.max_ratio).then_some("implausible in/out ratio")
+    }
+}
+
+impl EventFilter for SwapValidator {
+    fn keep(&self, event: &TransactionEvent) -> bool {
+        let Some(swap) = event.as_normalized_swap() else { return true };
+        let Some(reason) = self.check(&swap) else { return true };
+
+        if let Some(metrics) = &self.metrics {
+            metrics.record_suspicious_swap();
+        }
+        tracing::warn!(
+            "Suspicious {:?} swap {} ({}): {} {} -> {} {}",
+            swap.protocol, swap.signature, reason, swap.amount_in, swap.mint_in, swap.amount_out, swap.mint_out
+        );
+        self.action == SuspiciousSwapAction::Flag
+    }
+
+    fn name(&self) -> &str {
+        "swap_validator"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{SolTransfer, fixtures};
+
+    fn swap(amount_in: u64, amount_received: u64) -> TransactionEvent {
+        fixtures::swap(1, amount_in, amount_received)
+    }
+
+    #[test]
+    fn zero_amount_swap_is_dropped_and_counted() {
+        let metrics = Arc::new(PipelineMetrics::default());
+        let validator = SwapValidator::new(SwapValidator::DEFAULT_MAX_RATIO, SuspiciousSwapAction::Drop).with_metrics(metrics.clone());
+
+        assert!(!validator.keep(&swap(1_000, 0)));
+        assert_eq!(metrics.suspicious_swaps(), 1);
+    }
+
+    #[test]
+    fn normal_swap_is_kept() {
+        let metrics = Arc::new(PipelineMetrics::default());
+        let validator = SwapValidator::new(SwapValidator::DEFAULT_MAX_RATIO, SuspiciousSwapAction::Drop).with_metrics(metrics.clone());
+
+        assert!(validator.keep(&swap(1_000_000_000, 150_000_000)));
+        assert_eq!(metrics.suspicious_swaps(), 0);
+    }
+
+    #[test]
+    fn implausible_ratio_is_flagged_but_kept() {
+        let metrics = Arc::new(PipelineMetrics::default());
+        let validator = SwapValidator::new(1_000.0, SuspiciousSwapAction::Flag).with_metrics(metrics.clone());
+
+        assert!(validator.keep(&swap(1, 10_000)));
+        assert_eq!(metrics.suspicious_swaps(), 1);
+    }
+
+    #[test]
+    fn non_swap_events_always_pass() {
+        let validator = SwapValidator::new(1.0, SuspiciousSwapAction::Drop);
+        let transfer = TransactionEvent::SolTransfer(SolTransfer { lamports: 0, ..fixtures::sol_transfer(2) });
+
+        assert!(validator.keep(&transfer));
+    }
+}
@@ -0,0 +1,90 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use serde::Serialize;
+
+use crate::{application::MintDecimals, domain::TransactionEvent};
+
+/// Running swap volume of one mint.
+#[derive(Debug, Clone, Serialize)]
+pub struct MintVolume {
+    pub mint: String,
+    pub raw: u128,
+    /// `None` when the mint's decimals are unknown, in which case `total_volume` is raw units.
+    pub decimals: Option<u8>,
+    pub total_volume: f64,
+}
+
+/// Swap volume per mint since startup. Like the slot summaries, every swap adds
+/// its input amount to `mint_in` and its output amount to `mint_out`. Totals are
+/// kept raw and converted to UI units on read, so registering decimals later
+/// still reports the whole history correctly.
+#[derive(Debug, Default)]
+pub struct VolumeTracker {
+    decimals: MintDecimals,
+    raw: Mutex<HashMap<String, u128>>,
+}
+
+impl VolumeTracker {
+    pub fn new(decimals: MintDecimals) -> Self {
+        Self { decimals, raw: Mutex::new(HashMap::new()) }
+    }
+
+    /// Count `event` if it is a swap; other events are ignored.
+    pub fn record(&self, event: &TransactionEvent) {
+        let Some(swap) = event.as_normalized_swap() else { return };
+        let mut raw = self.raw.lock().expect("volume lock poisoned");
+        *raw.entry(swap.mint_in).or_default() += swap.amount_in as u128;
+        *raw.entry(swap.mint_out).or_default() += swap.amount_out as u128;
+    }
+
+    /// Volume of `mint` in UI units (raw units when its decimals are unknown).
+    pub fn total_volume(&self, mint: &str) -> f64 {
+        let raw = self.raw.lock().expect("volume lock poisoned").get(mint).copied().unwrap_or(0);
+        self.decimals.to_ui(mint, raw)
+    }
+
+    /// Every mint seen so far, highest raw volume first.
+    pub fn snapshot(&self) -> Vec<MintVolume> {
+        let raw = self.raw.lock().expect("volume lock poisoned");
+        let mut volumes: Vec<MintVolume> = raw
+            .iter()
+            .map(|(mint, &raw)| MintVolume {
+                mint: mint.clone(),
+                raw,
+                decimals: self.decimals.get(mint),
+                total_volume: self.decimals.to_ui(mint, raw),
+            })
+            .collect();
+        volumes.sort_by(|a, b| b.raw.cmp(&a.raw));
+        volumes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::fixtures::swap;
+
+    #[test]
+    fn volume_is_reported_in_ui_units_of_known_mints() {
+        let tracker = VolumeTracker::new(MintDecimals::empty().with("MintA", 6));
+
+        tracker.record(&swap(1, 1_500_000, 10));
+        tracker.record(&swap(2, 2_500_000, 32));
+
+        assert_eq!(tracker.total_volume("MintA"), 4.0);
+        // Decimals unknown, so raw units
+        assert_eq!(tracker.total_volume("MintB"), 42.0);
+    }
+
+    #[test]
+    fn snapshot_lists_the_busiest_mint_first() {
+        let tracker = VolumeTracker::new(MintDecimals::empty().with("MintA", 6));
+        tracker.record(&swap(1, 1_500_000, 10));
+
+        let snapshot = tracker.snapshot();
+
+        let mints: Vec<_> = snapshot.iter().map(|v| (v.mint.as_str(), v.raw, v.decimals)).collect();
+        assert_eq!(mints, vec![("MintA", 1_500_000, Some(6)), ("MintB", 10, None)]);
+    }
+}
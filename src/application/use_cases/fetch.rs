@@ -0,0 +1,192 @@
+use std::{sync::Arc, time::Duration};
+
+use tokio::sync::Mutex;
+
+use crate::application::{AppError, EventBuffer, PipelineMetrics, TransactionSource};
+
+const INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Default)]
+pub struct FetcherConfig {
+    /// Longest wait for the source's next event before it counts as stalled.
+    /// `None` waits forever.
+    pub stall_timeout: Option<Duration>,
+    /// On a stall, ask the source to reconnect instead of only logging it.
+    pub reconnect_on_stall: bool,
+}
+
+/// Pulls events from `source` into `buffer` until the source is exhausted, the
+/// buffer closes, or the source reports an error that retrying cannot fix.
+/// Transient errors back off exponentially; the delay resets on the next event.
+/// With a stall timeout, a source that goes quiet for that long is logged and
+/// counted in `source_stalls`, and optionally reconnected.
+///
+/// Generic so a concrete source and buffer are called without dynamic dispatch;
+/// trait objects work too.
+pub async fn run_fetcher<S, B>(
+    source: Arc<Mutex<S>>,
+    buffer: Arc<B>,
+    config: FetcherConfig,
+    metrics: Arc<PipelineMetrics>,
+) -> Result<(), AppError>
+where
+    S: TransactionSource + ?Sized,
+    B: EventBuffer + ?Sized,
+{
+    let mut backoff = INITIAL_BACKOFF;
+
+    loop {
+        let mut source = source.lock().await;
+        let event = match config.stall_timeout {
+            None => source.next_event().await,
+            Some(limit) => match tokio::time::timeout(limit, source.next_event()).await {
+                Ok(event) => event,
+                Err(_) => {
+                    tracing::warn!("Source produced nothing for {:?}, stream may be stalled", limit);
+                    metrics.record_source_stall();
+                    if config.reconnect_on_stall {
+                        match source.reconnect().await {
+                            Ok(()) => tracing::info!("Source reconnected after stall"),
+                            Err(e) => tracing::error!("Source reconnect failed: {}", e),
+                        }
+                    }
+                    continue;
+                }
+            },
+        };
+        drop(source);
+        match event {
+            Ok(Some(ev)) => {
+                backoff = INITIAL_BACKOFF;
+                if buffer.produce(ev).await.is_err() {
+                    tracing::error!("Buffer closed — stopping fetcher");
+                    return Ok(());
+                }
+            }
+            Ok(None) => {
+                tracing::info!("Source stream exhausted");
+                return Ok(());
+            }
+            Err(e) if e.is_fatal() => {
+                tracing::error!("Fatal source error, stopping fetcher: {}", e);
+                return Err(e);
+            }
+            Err(e) if e.is_transient() => {
+                tracing::warn!("Transient source error, retrying in {:?}: {}", backoff, e);
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+            }
+            Err(e) => {
+                tracing::error!("Source error: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use async_trait::async_trait;
+
+    use super::*;
+    use crate::{application::AppResult, domain::ChainEvent, infrastructure::MemoryBuffer};
+
+    /// Replays `script`, then reports a dropped connection forever.
+    struct ScriptedSource {
+        script: VecDeque<AppResult<Option<ChainEvent>>>,
+    }
+
+    #[async_trait]
+    impl TransactionSource for ScriptedSource {
+        async fn next_event(&mut self) -> AppResult<Option<ChainEvent>> {
+            self.script.pop_front().unwrap_or_else(|| Err(AppError::ConnectionError("reset by peer".to_string())))
+        }
+    }
+
+    async fn fetch(script: Vec<AppResult<Option<ChainEvent>>>) -> (Result<(), AppError>, Vec<ChainEvent>) {
+        let source = Arc::new(Mutex::new(ScriptedSource { script: script.into() }));
+        let (buffer, mut rx) = MemoryBuffer::new(16);
+        let fetcher = run_fetcher(source, Arc::new(buffer), FetcherConfig::default(), Arc::new(PipelineMetrics::new()));
+
+        let result = tokio::time::timeout(Duration::from_secs(2), fetcher).await.expect("the fetcher stops");
+        let mut events = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            events.push(event);
+        }
+        (result, events)
+    }
+
+    #[tokio::test]
+    async fn fatal_source_error_stops_the_fetcher() {
+        let (result, events) = fetch(vec![
+            Ok(Some(ChainEvent::Finalized { slot: 1 })),
+            Err(AppError::ConfigError("bad endpoint".to_string())),
+        ])
+        .await;
+
+        assert!(matches!(result, Err(AppError::ConfigError(_))));
+        assert_eq!(events.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn transient_error_is_retried_until_the_source_recovers() {
+        let (result, events) = fetch(vec![
+            Err(AppError::ConnectionError("reset by peer".to_string())),
+            Err(AppError::PoolExhausted),
+            Ok(Some(ChainEvent::Finalized { slot: 2 })),
+            Ok(None),
+        ])
+        .await;
+
+        assert!(result.is_ok());
+        assert!(matches!(events.as_slice(), [ChainEvent::Finalized { slot: 2 }]));
+    }
+
+    /// Never yields until reconnected, then ends the stream.
+    #[derive(Default)]
+    struct SilentSource {
+        reconnected: bool,
+    }
+
+    #[async_trait]
+    impl TransactionSource for SilentSource {
+        async fn next_event(&mut self) -> AppResult<Option<ChainEvent>> {
+            if self.reconnected {
+                return Ok(None);
+            }
+            std::future::pending().await
+        }
+
+        async fn reconnect(&mut self) -> AppResult<()> {
+            self.reconnected = true;
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn silent_source_is_counted_as_stalled_once_per_timeout() {
+        let (buffer, _rx) = MemoryBuffer::new(16);
+        let metrics = Arc::new(PipelineMetrics::new());
+        let config = FetcherConfig { stall_timeout: Some(Duration::from_millis(50)), reconnect_on_stall: false };
+        let fetcher = run_fetcher(Arc::new(Mutex::new(SilentSource::default())), Arc::new(buffer), config, metrics.clone());
+
+        assert!(tokio::time::timeout(Duration::from_millis(230), fetcher).await.is_err(), "a silent source never ends");
+        // Four full windows fit in 230ms; allow one for scheduling slack
+        assert!((3..=4).contains(&metrics.source_stalls()), "stalls: {}", metrics.source_stalls());
+    }
+
+    #[tokio::test]
+    async fn stall_triggers_a_reconnect_when_asked() {
+        let (buffer, _rx) = MemoryBuffer::new(16);
+        let metrics = Arc::new(PipelineMetrics::new());
+        let config = FetcherConfig { stall_timeout: Some(Duration::from_millis(50)), reconnect_on_stall: true };
+        let fetcher = run_fetcher(Arc::new(Mutex::new(SilentSource::default())), Arc::new(buffer), config, metrics.clone());
+
+        let result = tokio::time::timeout(Duration::from_secs(1), fetcher).await.expect("the reconnected source ends the stream");
+
+        assert!(result.is_ok());
+        assert_eq!(metrics.source_stalls(), 1);
+    }
+}
@@ -1,93 +1,1803 @@
-use std::{sync::Arc, time::Duration};
+use std::{collections::{BTreeMap, HashMap, HashSet}, sync::Arc, time::{Duration, Instant}};
 
-use tokio::sync::mpsc;
+use tokio::{sync::{Mutex, OwnedSemaphorePermit, Semaphore, mpsc}, task::JoinHandle};
 
 use crate::{
-    application::{NotificationService, TransactionParser, TransactionRepository},
-    domain::{ChainEvent, SwapEvent, TransactionEvent},
+    application::{
+        AdaptiveBatchSize, AppError, BatchResult, BloomFilter, Clock, CoverageReport, DedupCache, dedup_batch, ProgramCoverage, ProgramInspector, AsyncTransactionParser, EventFilter, EventSink, NotificationService, ParseErrorSink, ParserControl, ParserResult, PipelineMetrics, RateLimiter, SignatureFilter, SlotAggregator, SlotSummarySink, SlotGapTracker, EventTransformer, TransactionParser, TransactionRepository,
+        SystemClock, WriteAheadLog,
+    },
+    domain::{ChainEvent, IndexedTransaction, SolanaTransaction, SwapEvent, TransactionEvent},
 };
 
-pub struct IngestionPipeline {
+/// A full batch handed to the writer pool, with the slot cursor to record alongside it
+/// and its WAL id when a log is configured.
+struct PendingBatch {
+    events: Vec<TransactionEvent>,
+    /// Records of the transactions the events came from, for the parent table.
+    transactions: Vec<IndexedTransaction>,
+    slot: u64,
+    wal_id: Option<u64>,
+    /// When the batch's first event was parsed, for ingest-to-persist latency.
+    started: Instant,
+    /// In-flight capacity held until the writer is done with the batch.
+    _permits: Vec<OwnedSemaphorePermit>,
+}
+
+/// Retries for a batch whose write failed only because no connection was free.
+const POOL_EXHAUSTED_RETRIES: u32 = 3;
+/// Backoff between attempts at a batch whose write failed; doubles up to the max.
+const WRITE_RETRY_INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+const WRITE_RETRY_MAX_BACKOFF: Duration = Duration::from_secs(30);
+/// Whole-batch failures after which a batch is written event by event, so a
+/// single event the DB rejects is dead-lettered instead of blocking the rest.
+const ISOLATE_AFTER_FAILURES: u32 = 3;
+/// Programs listed in each periodic unparsed-coverage report.
+const PROGRAM_COVERAGE_TOP: usize = 10;
+
+/// Save a batch, backing off and retrying while the connection pool is exhausted.
+/// Any other error is returned straight away. In best-effort mode events that
+/// fail on their own come back in the result instead of failing the batch.
+async fn save_batch_with_retry<R>(
+    repo: &R,
+    batch: &[TransactionEvent],
+    slot: u64,
+    writer: usize,
+    best_effort: bool,
+) -> anyhow::Result<BatchResult>
+where
+    R: TransactionRepository + ?Sized,
+{
+    let mut attempt = 0;
+    loop {
+        let result = if best_effort {
+            repo.save_batch_best_effort(batch, slot).await
+        } else {
+            repo.save_batch(batch, slot).await.map(|()| BatchResult { persisted: batch.len(), failed: Vec::new() })
+        };
+        match result {
+            Err(e) if attempt < POOL_EXHAUSTED_RETRIES && matches!(e.downcast_ref(), Some(AppError::PoolExhausted)) => {
+                attempt += 1;
+                let delay = Duration::from_millis(250 * 2u64.pow(attempt));
+                tracing::warn!("Writer {} found the pool exhausted, retry {} in {:?}", writer, attempt, delay);
+                tokio::time::sleep(delay).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// A batch regrouped into commits of at most `max` events. Each slot's events
+/// stay in one commit, packed with neighbouring slots while they fit; only a
+/// slot with more than `max` events is spread over several commits.
+fn slot_commits(batch: Vec<TransactionEvent>, max: usize) -> Vec<Vec<TransactionEvent>> {
+    let mut by_slot: BTreeMap<u64, Vec<TransactionEvent>> = BTreeMap::new();
+    for event in batch {
+        by_slot.entry(event.slot()).or_default().push(event);
+    }
+
+    let mut commits = Vec::new();
+    let mut current: Vec<TransactionEvent> = Vec::new();
+    for (_, mut events) in by_slot {
+        if current.len() + events.len() <= max {
+            current.append(&mut events);
+            continue;
+        }
+        if !current.is_empty() {
+            commits.push(std::mem::take(&mut current));
+        }
+        while events.len() > max {
+            let rest = events.split_off(max);
+            commits.push(std::mem::replace(&mut events, rest));
+        }
+        current = events;
+    }
+    if !current.is_empty() {
+        commits.push(current);
+    }
+    commits
+}
+
+/// What to do with a transaction whose slot is further behind the watermark than
+/// `reorg_tolerance_slots` allows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LateSlotAction {
+    Drop,
+    Warn,
+}
+
+#[derive(Debug, Clone)]
+pub struct PipelineConfig {
+    /// Number of concurrent batch writers. Batches may commit out of order when
+    /// this is above 1; duplicate rows are still absorbed by the ON CONFLICT keys
+    /// and the slot cursor only ever moves forward.
+    pub writers: usize,
+    /// Skip events already seen among the last N parsed, before they are batched
+    /// or alerted on. 0 disables; replays outside the window rely on ON CONFLICT.
+    pub dedup_capacity: usize,
+    /// How long a key stays in the dedup window; `None` keeps it until evicted by size.
+    pub dedup_ttl: Option<Duration>,
+    /// Drop transactions that failed on-chain before parsing. Their instructions
+    /// never took effect, so parsed swaps would not be real.
+    pub skip_failed: bool,
+    /// How far (in slots) a transaction may trail the highest slot seen so far.
+    /// Anything older typically comes from a reorg or a lagging fan-in source.
+    /// `None` accepts every slot.
+    pub reorg_tolerance_slots: Option<u64>,
+    pub late_slot_action: LateSlotAction,
+    /// Batches handed off but not yet written, across all writers. When reached the
+    /// pipeline stops reading the buffer, which in turn blocks the source.
+    /// `None` means twice the writer count.
+    pub max_in_flight_batches: Option<usize>,
+    /// Cap on events held in in-flight batches, for when batches are large.
+    /// `None` leaves only the batch count limit.
+    pub max_in_flight_events: Option<usize>,
+    /// Parse everything but persist, DLQ and alert on nothing; log per-parser
+    /// event counts instead. For checking parser coverage against live traffic.
+    pub dry_run: bool,
+    /// Bounds for the adaptive batch size. The size grows toward the max while
+    /// events queue up and shrinks toward the min when the flush timer fires on
+    /// a partial batch. Equal bounds give a fixed size.
+    pub min_batch_size: usize,
+    pub max_batch_size: usize,
+    /// Write batches best-effort: when a batch fails, retry its events one by one
+    /// and dead-letter only those that still fail. Off keeps batches all-or-nothing
+    /// until one has failed `ISOLATE_AFTER_FAILURES` times in a row.
+    pub best_effort_writes: bool,
+    /// Cap on transactions taken from the source per second. Transactions over
+    /// the rate wait, which backs up the buffer and in turn the source.
+    /// `None` reads as fast as events arrive.
+    pub max_events_per_sec: Option<u32>,
+    /// Events of one slot committed together, at most. A slot with more events,
+    /// say thousands of swaps, is spread over several commits of this size
+    /// instead of one long transaction; smaller slots still share a commit whole.
+    /// Each commit is logged, written and retried on its own. `None` commits
+    /// batches whole.
+    pub max_rows_per_commit: Option<usize>,
+    /// Two-phase persistence: the repository holds batches as pending, and each
+    /// finalized slot from the source is passed on to promote them.
+    pub two_phase: bool,
+}
+
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        Self {
+            writers: 1,
+            dedup_capacity: 0,
+            dedup_ttl: None,
+            skip_failed: true,
+            reorg_tolerance_slots: None,
+            late_slot_action: LateSlotAction::Drop,
+            max_in_flight_batches: None,
+            max_in_flight_events: None,
+            dry_run: false,
+            min_batch_size: 100,
+            max_batch_size: 100,
+            best_effort_writes: false,
+            max_events_per_sec: None,
+            max_rows_per_commit: None,
+            two_phase: false,
+        }
+    }
+}
+
+/// Generic over the repository and parser types so a fixed deployment can use
+/// concrete types and skip vtable dispatch on the hot path. The defaults keep the
+/// usual trait-object form, which is what `main` builds.
+pub struct IngestionPipeline<R: ?Sized = dyn TransactionRepository, P: ?Sized = dyn TransactionParser> {
     rx: mpsc::Receiver<ChainEvent>,
-    repo: Arc<dyn TransactionRepository>,
-    parsers: Vec<Box<dyn TransactionParser>>,
+    repo: Arc<R>,
+    parsers: Vec<Box<P>>,
+    async_parsers: Vec<Box<dyn AsyncTransactionParser>>,
+    parser_control: ParserControl,
     notifier: Option<Arc<NotificationService>>,
+    config: PipelineConfig,
+    dedup: Option<DedupCache>,
+    slot_watermark: u64,
+    metrics: Arc<PipelineMetrics>,
+    wal: Option<Arc<dyn WriteAheadLog>>,
+    sink: Option<Arc<dyn EventSink>>,
+    filters: Vec<Box<dyn EventFilter>>,
+    transformers: Vec<Box<dyn EventTransformer>>,
+    slot_gaps: SlotGapTracker,
+    in_flight_batches: Arc<Semaphore>,
+    in_flight_events: Option<Arc<Semaphore>>,
+    coverage: Option<CoverageReport>,
+    /// Shared with the writers, which add a signature once its events are stored.
+    seen_signatures: Option<Arc<std::sync::Mutex<BloomFilter>>>,
+    /// Records of transactions whose events sit in the open batch, by signature.
+    /// Each leaves with the first commit that carries one of its events.
+    open_transactions: HashMap<String, IndexedTransaction>,
+    parse_errors: Option<Arc<dyn ParseErrorSink>>,
+    rate_limiter: Option<RateLimiter>,
+    signature_filter: Option<SignatureFilter>,
+    slot_summaries: Option<(SlotAggregator, Arc<dyn SlotSummarySink>)>,
+    program_coverage: Option<(ProgramCoverage, Arc<dyn ProgramInspector>)>,
+    /// Slot and block time of the latest block meta that carried one.
+    latest_block_time: Option<(u64, i64)>,
+    clock: Arc<dyn Clock>,
 }
 
-impl IngestionPipeline {
+impl<R, P> IngestionPipeline<R, P>
+where
+    R: TransactionRepository + ?Sized + 'static,
+    P: TransactionParser + ?Sized,
+{
+    /// The repository is always present, so the writers and their queue are too;
+    /// to run without persistence pass a `NullRepository` rather than leaving it out.
     pub fn new(
         rx: mpsc::Receiver<ChainEvent>,
-        repo: Arc<dyn TransactionRepository>,
-        parsers: Vec<Box<dyn TransactionParser>>,
+        repo: Arc<R>,
+        parsers: Vec<Box<P>>,
         notifier: Option<Arc<NotificationService>>,
     ) -> Self {
-        Self { rx, repo, parsers, notifier }
+        let parser_control = ParserControl::new(parsers.iter().map(|p| TransactionParser::name(&**p).to_string()));
+        Self {
+            rx,
+            repo,
+            parsers,
+            async_parsers: Vec::new(),
+            parser_control,
+            notifier,
+            config: PipelineConfig::default(),
+            dedup: None,
+            slot_watermark: 0,
+            metrics: Arc::new(PipelineMetrics::new()),
+            wal: None,
+            sink: None,
+            filters: Vec::new(),
+            transformers: Vec::new(),
+            slot_gaps: SlotGapTracker::new(),
+            in_flight_batches: Arc::new(Semaphore::new(0)),
+            in_flight_events: None,
+            coverage: None,
+            seen_signatures: None,
+            open_transactions: HashMap::new(),
+            parse_errors: None,
+            rate_limiter: None,
+            signature_filter: None,
+            slot_summaries: None,
+            program_coverage: None,
+            latest_block_time: None,
+            clock: Arc::new(SystemClock),
+        }
+        .with_config(PipelineConfig::default())
+    }
+
+    fn max_batches(config: &PipelineConfig) -> usize {
+        config.max_in_flight_batches.unwrap_or(config.writers.max(1) * 2).max(1)
     }
 
+    pub fn with_config(mut self, config: PipelineConfig) -> Self {
+        self.dedup = (config.dedup_capacity > 0).then(|| {
+            let cache = DedupCache::new(config.dedup_capacity);
+            match config.dedup_ttl {
+                Some(ttl) => cache.with_ttl(ttl),
+                None => cache,
+            }
+        });
+        self.in_flight_batches = Arc::new(Semaphore::new(Self::max_batches(&config)));
+        self.in_flight_events = config.max_in_flight_events.map(|n| Arc::new(Semaphore::new(n.max(1))));
+        self.coverage = config.dry_run.then(CoverageReport::new);
+        self.rate_limiter = config.max_events_per_sec.map(RateLimiter::new);
+        self.config = config;
+        self
+    }
+
+    /// Share a metrics handle with e.g. the health server instead of the pipeline's own.
+    pub fn with_metrics(mut self, metrics: Arc<PipelineMetrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    pub fn metrics(&self) -> Arc<PipelineMetrics> {
+        self.metrics.clone()
+    }
+
+    /// Add a parser that awaits lookups while parsing. Register async parsers
+    /// before taking `parser_control`, which is rebuilt to include them.
+    pub fn with_async_parser(mut self, parser: Box<dyn AsyncTransactionParser>) -> Self {
+        self.async_parsers.push(parser);
+        let names = self.parsers.iter().map(|p| TransactionParser::name(&**p))
+            .chain(self.async_parsers.iter().map(|p| p.name()))
+            .map(str::to_string);
+        self.parser_control = ParserControl::new(names);
+        self
+    }
+
+    /// Each parser's name with the event kinds it declares, sync parsers first.
+    pub fn parser_event_kinds(&self) -> Vec<(&str, &[&str])> {
+        self.parsers
+            .iter()
+            .map(|p| (TransactionParser::name(&**p), TransactionParser::emitted_event_kinds(&**p)))
+            .chain(self.async_parsers.iter().map(|p| (p.name(), p.emitted_event_kinds())))
+            .collect()
+    }
+
+    /// Handle for switching individual parsers off and back on while running.
+    pub fn parser_control(&self) -> ParserControl {
+        self.parser_control.clone()
+    }
+
+    /// Log every batch before it is queued for the writers, and replay anything a
+    /// previous run logged but never committed when `run` starts.
+    pub fn with_wal(mut self, wal: Arc<dyn WriteAheadLog>) -> Self {
+        self.wal = Some(wal);
+        self
+    }
+
+    /// Also deliver every committed batch to `sink` (webhook, stream, ...). With a
+    /// `NullRepository` this makes the sink the only output.
+    pub fn with_sink(mut self, sink: Arc<dyn EventSink>) -> Self {
+        self.sink = Some(sink);
+        self
+    }
+
+    /// Skip transactions whose signature is (probably) in `filter`, and add the
+    /// signature of every transaction whose events were written. A batch that never
+    /// reaches the DB leaves its signatures out, so a rerun indexes them. See
+    /// `BloomFilter` for the false-positive tradeoff.
+    pub fn with_seen_signatures(mut self, filter: BloomFilter) -> Self {
+        tracing::info!("Seen-signature filter enabled ({} KiB)", filter.size_bytes() / 1024);
+        self.seen_signatures = Some(Arc::new(std::sync::Mutex::new(filter)));
+        self
+    }
+
+    /// The seen-signature filter as it stands, for persisting after `run` returns.
+    pub fn seen_signatures(&self) -> Option<BloomFilter> {
+        self.seen_signatures.as_ref().map(|f| f.lock().unwrap().clone())
+    }
+
+    fn already_indexed(&self, signature: &str) -> bool {
+        self.seen_signatures
+            .as_ref()
+            .is_some_and(|f| f.lock().unwrap().contains(signature.as_bytes()))
+    }
+
+    /// Record the raw transaction behind every parser failure, for later replay.
+    /// Applies in dry-run mode too.
+    pub fn with_parse_error_sink(mut self, sink: Arc<dyn ParseErrorSink>) -> Self {
+        self.parse_errors = Some(sink);
+        self
+    }
+
+    /// Only process transactions `filter` allows. Checked as each transaction comes
+    /// off the source, before any parsing.
+    pub fn with_signature_filter(mut self, filter: SignatureFilter) -> Self {
+        self.signature_filter = (!filter.is_empty()).then_some(filter);
+        self
+    }
+
+    /// Roll swaps up per slot and send each summary to `sink` once the slot's block
+    /// meta arrives. Counts what was parsed, ahead of the DB write.
+    pub fn with_slot_summary_sink(mut self, sink: Arc<dyn SlotSummarySink>) -> Self {
+        self.slot_summaries = Some((SlotAggregator::new(), sink));
+        self
+    }
+
+    /// Tally, per invoked program, how many transactions produced events, and
+    /// periodically log the programs most often left unparsed.
+    pub fn with_program_coverage(mut self, inspector: Arc<dyn ProgramInspector>) -> Self {
+        self.program_coverage = Some((ProgramCoverage::new(), inspector));
+        self
+    }
+
+    /// Drop parsed events `filter` rejects. Filters apply in the order added.
+    pub fn with_filter(mut self, filter: Box<dyn EventFilter>) -> Self {
+        tracing::info!("Event filter enabled: {}", filter.name());
+        self.filters.push(filter);
+        self
+    }
+
+    /// Time source for the last-event timestamp behind readiness checks.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Rewrite parsed events with `transformer` before they are batched.
+    /// Transformers apply in the order added.
+    pub fn with_transformer(mut self, transformer: Box<dyn EventTransformer>) -> Self {
+        tracing::info!("Event transformer enabled: {}", transformer.name());
+        self.transformers.push(transformer);
+        self
+    }
+
+    /// Run `events` through every transformer in turn.
+    fn transform(&self, mut events: Vec<TransactionEvent>) -> Vec<TransactionEvent> {
+        for transformer in &self.transformers {
+            events = events.into_iter().flat_map(|ev| transformer.transform(ev)).collect();
+        }
+        events
+    }
+
+    /// Re-save batches left uncommitted by a crash. ON CONFLICT absorbs any that
+    /// did reach the DB before the commit record was written.
+    async fn replay_wal(&self) {
+        let Some(wal) = &self.wal else { return };
+
+        let entries = match wal.uncommitted().await {
+            Ok(entries) => entries,
+            Err(e) => {
+                tracing::error!("Failed to read WAL: {}", e);
+                return;
+            }
+        };
+
+        for entry in entries {
+            tracing::info!("Replaying WAL batch {} ({} events, slot {})", entry.id, entry.events.len(), entry.slot);
+            match self.repo.save_batch(&entry.events, entry.slot).await {
+                Ok(()) => {
+                    if let Err(e) = wal.commit(entry.id).await {
+                        tracing::error!("WAL commit failed for replayed batch {}: {}", entry.id, e);
+                    }
+                }
+                Err(e) => tracing::error!("Replay of WAL batch {} failed, keeping it: {}", entry.id, e),
+            }
+        }
+    }
+
+    /// Drop in-batch duplicates and hand the batch to the writers, spreading a
+    /// slot with more than `max_rows_per_commit` events over several commits.
+    async fn enqueue(&mut self, writer_tx: &mpsc::Sender<PendingBatch>, mut batch: Vec<TransactionEvent>, slot: u64, started: Instant) {
+        let duplicates = dedup_batch(&mut batch);
+        if duplicates > 0 {
+            tracing::debug!("Dropped {} duplicate events from batch", duplicates);
+        }
+
+        match self.config.max_rows_per_commit {
+            Some(max) if batch.len() > max => {
+                let commits = slot_commits(batch, max);
+                tracing::debug!("Spreading batch over {} commits of at most {} events", commits.len(), max);
+                for part in commits {
+                    let transactions = self.take_transactions(&part);
+                    self.hand_off(writer_tx, part, transactions, slot, started).await;
+                }
+            }
+            _ => {
+                let transactions = self.take_transactions(&batch);
+                self.hand_off(writer_tx, batch, transactions, slot, started).await;
+            }
+        }
+    }
+
+    /// Remove the records of the transactions behind `events` from the open set.
+    fn take_transactions(&mut self, events: &[TransactionEvent]) -> Vec<IndexedTransaction> {
+        events.iter().filter_map(|ev| self.open_transactions.remove(ev.signature())).collect()
+    }
+
+    /// Log one commit's worth of events (if a WAL is configured) and queue it for the writers.
+    async fn hand_off(
+        &self,
+        writer_tx: &mpsc::Sender<PendingBatch>,
+        batch: Vec<TransactionEvent>,
+        transactions: Vec<IndexedTransaction>,
+        slot: u64,
+        started: Instant,
+    ) {
+        let wal_id = match &self.wal {
+            Some(wal) => match wal.append(&batch, slot).await {
+                Ok(id) => Some(id),
+                Err(e) => {
+                    tracing::error!("WAL append failed, batch is not crash-safe: {}", e);
+                    None
+                }
+            },
+            None => None,
+        };
+
+        let mut permits = Vec::with_capacity(2);
+        // Semaphores are never closed, so acquire only fails if that invariant breaks
+        if let Ok(p) = self.in_flight_batches.clone().acquire_owned().await {
+            permits.push(p);
+        }
+        if let Some(events) = &self.in_flight_events {
+            // A batch larger than the whole cap still goes through, just alone
+            let cap = self.config.max_in_flight_events.unwrap_or(usize::MAX).max(1);
+            let wanted = batch.len().min(cap) as u32;
+            if let Ok(p) = events.clone().acquire_many_owned(wanted).await {
+                permits.push(p);
+            }
+        }
+
+        let pending = PendingBatch { events: batch, transactions, slot, wal_id, started, _permits: permits };
+        if writer_tx.send(pending).await.is_err() {
+            tracing::error!("Batch writers stopped — dropping batch");
+        }
+    }
+
+    /// Advance the slot watermark and report whether `slot` is still acceptable.
+    fn accept_slot(&mut self, slot: u64, signature: &str) -> bool {
+        let Some(tolerance) = self.config.reorg_tolerance_slots else { return true };
+
+        if slot.saturating_add(tolerance) >= self.slot_watermark {
+            self.slot_watermark = self.slot_watermark.max(slot);
+            return true;
+        }
+
+        match self.config.late_slot_action {
+            LateSlotAction::Drop => {
+                tracing::warn!("Dropping {} at slot {} ({} behind watermark {})",
+                    signature, slot, self.slot_watermark - slot, self.slot_watermark);
+                false
+            }
+            LateSlotAction::Warn => {
+                tracing::warn!("Late transaction {} at slot {} ({} behind watermark {})",
+                    signature, slot, self.slot_watermark - slot, self.slot_watermark);
+                true
+            }
+        }
+    }
+
+    /// Undo everything indexed at or above a slot the chain dropped. Waits for the
+    /// writers to finish what is in flight first, so no batch lands after the delete.
+    async fn rollback(&mut self, slot: u64) {
+        self.slot_watermark = self.slot_watermark.min(slot.saturating_sub(1));
+        self.open_transactions.retain(|_, t| t.slot < slot);
+        // The canonical fork may carry the same transactions again
+        if let Some(dedup) = self.dedup.as_mut() {
+            dedup.clear();
+        }
+        if self.config.dry_run {
+            tracing::warn!("Rollback from slot {} (dry run, nothing to delete)", slot);
+            return;
+        }
+
+        let all = Self::max_batches(&self.config) as u32;
+        let _drained = self.in_flight_batches.acquire_many(all).await;
+        if let Err(e) = self.repo.delete_events_from_slot(slot).await {
+            tracing::error!("Rollback from slot {} failed, stale rows remain: {}", slot, e);
+        }
+    }
+
+    /// Promote the pending events of a finalized slot. Waits for in-flight batches
+    /// first, so every pending row of the slot is written before it is promoted.
+    async fn finalize(&mut self, slot: u64) {
+        if self.config.dry_run {
+            return;
+        }
+        let all = Self::max_batches(&self.config) as u32;
+        let _drained = self.in_flight_batches.acquire_many(all).await;
+        match self.repo.finalize_slot(slot).await {
+            Ok(result) => tracing::debug!("Slot {} finalized: {} events promoted, {} dropped", slot, result.promoted, result.dropped),
+            Err(e) => tracing::error!("Finalizing slot {} failed, its events stay pending: {}", slot, e),
+        }
+    }
+
+    /// Emit the summaries of every slot up to `slot` now that its block is complete.
+    async fn complete_slot(&mut self, slot: u64) {
+        let Some((aggregator, sink)) = self.slot_summaries.as_mut() else { return };
+        for summary in aggregator.complete(slot) {
+            if let Err(e) = sink.emit_summary(&summary).await {
+                tracing::error!("Slot summary for {} not delivered: {}", summary.slot, e);
+            }
+        }
+    }
+
+    /// Start the writer pool. The writers exit once the returned sender is dropped
+    /// and every queued batch is written; await the handles to know that happened.
+    fn spawn_writers(&self) -> (mpsc::Sender<PendingBatch>, Vec<JoinHandle<()>>) {
+        let writers = self.config.writers.max(1);
+        let (tx, rx) = mpsc::channel::<PendingBatch>(Self::max_batches(&self.config));
+        let rx = Arc::new(Mutex::new(rx));
+        let mut handles = Vec::with_capacity(writers);
+
+        for id in 0..writers {
+            let rx = rx.clone();
+            let repo = self.repo.clone();
+            let metrics = self.metrics.clone();
+            let wal = self.wal.clone();
+            let sink = self.sink.clone();
+            let seen = self.seen_signatures.clone();
+            let best_effort = self.config.best_effort_writes;
+            handles.push(tokio::spawn(async move {
+                loop {
+                    // Hold the lock only while waiting for the next batch, not while writing it
+                    let next = rx.lock().await.recv().await;
+                    let Some(PendingBatch { events: mut batch, mut transactions, slot, wal_id, started, _permits }) = next else { break };
+                    // A failed write usually means the DB is unreachable, so dead-lettering
+                    // into it would fail too. The batch is retried until it is stored; its
+                    // permits stay held meanwhile, which backs up the pipeline.
+                    let mut failures = 0;
+                    let mut backoff = WRITE_RETRY_INITIAL_BACKOFF;
+                    let BatchResult { persisted, failed } = loop {
+                        let isolate = best_effort || failures >= ISOLATE_AFTER_FAILURES;
+                        match save_batch_with_retry(&*repo, &batch, slot, id, isolate).await {
+                            Ok(result) => break result,
+                            Err(e) => {
+                                failures += 1;
+                                metrics.record_write_error();
+                                tracing::error!("Writer {} DB write error, retrying {} events in {:?} (attempt {}): {}",
+                                    id, batch.len(), backoff, failures, e);
+                                tokio::time::sleep(backoff).await;
+                                backoff = (backoff * 2).min(WRITE_RETRY_MAX_BACKOFF);
+                            }
+                        }
+                    };
+                    if !failed.is_empty() {
+                        tracing::warn!("Writer {} persisted {} of {} events, dead-lettering the rest", id, persisted, batch.len());
+                        for (event, err) in &failed {
+                            metrics.record_write_error();
+                            if let Err(e) = repo.save_event_dlq(event, &err.to_string()).await {
+                                tracing::error!("DLQ write failed for {} (double fault): {}", event.signature(), e);
+                            }
+                        }
+                        // Sinks only see what actually reached the DB
+                        let failed_keys: HashSet<_> = failed.iter().map(|(ev, _)| ev.key()).collect();
+                        batch.retain(|ev| !failed_keys.contains(&ev.key()));
+                        let stored: HashSet<_> = batch.iter().map(|ev| ev.signature()).collect();
+                        transactions.retain(|t| stored.contains(t.signature.as_str()));
+                    }
+                    if let Err(e) = repo.save_transactions(&transactions).await {
+                        tracing::error!("Writer {} could not record {} transactions' details: {}", id, transactions.len(), e);
+                    }
+                    if let Some(seen) = &seen {
+                        let mut seen = seen.lock().unwrap();
+                        for ev in &batch {
+                            seen.insert(ev.signature().as_bytes());
+                        }
+                    }
+                    metrics.record_batch(persisted);
+                    metrics.record_persist_latency(started.elapsed());
+                    if let (Some(wal), Some(batch_id)) = (&wal, wal_id) {
+                        if let Err(e) = wal.commit(batch_id).await {
+                            tracing::error!("Writer {} WAL commit failed for batch {}: {}", id, batch_id, e);
+                        }
+                    }
+                    if let Some(sink) = &sink {
+                        if let Err(e) = sink.emit(&batch).await {
+                            tracing::error!("Writer {} sink {} error: {}", id, sink.name(), e);
+                        }
+                    }
+                }
+            }));
+        }
+
+        tracing::info!("Started {} batch writer(s)", writers);
+        (tx, handles)
+    }
+
+    /// Run every parser over one transaction, returning the events that survive dedup
+    /// and the configured filters. Sync parsers run first, then async ones in turn.
+    /// Parser failures go to the DLQ; swap events are forwarded to the notifier.
+    /// In dry-run mode both are skipped and the results are only counted, so no
+    /// events come back. The parsers that produced events are named either way.
+    #[tracing::instrument(name = "process_transaction", skip_all, fields(signature = %txn.signature, slot = txn.slot))]
+    async fn process_transaction(&mut self, txn: &SolanaTransaction) -> (Vec<TransactionEvent>, Vec<String>) {
+        let mut out = Vec::new();
+        let mut matched = Vec::new();
+        if let Some(coverage) = self.coverage.as_mut() {
+            coverage.record_transaction();
+        }
+
+        let sync_count = self.parsers.len();
+        for idx in 0..sync_count {
+            if !self.parser_control.is_enabled_at(idx) {
+                continue;
+            }
+            let result = self.parsers[idx].parse(txn.clone());
+            if self.absorb(idx, result, txn, &mut out).await {
+                matched.push(self.parser_name(idx).to_string());
+            }
+        }
+        for i in 0..self.async_parsers.len() {
+            if !self.parser_control.is_enabled_at(sync_count + i) {
+                continue;
+            }
+            let result = self.async_parsers[i].parse_async(txn.clone()).await;
+            if self.absorb(sync_count + i, result, txn, &mut out).await {
+                matched.push(self.parser_name(sync_count + i).to_string());
+            }
+        }
+
+        (out, matched)
+    }
+
+    /// Name of parser `idx`, counting sync parsers first, as in `ParserControl`.
+    fn parser_name(&self, idx: usize) -> &str {
+        match idx.checked_sub(self.parsers.len()) {
+            None => TransactionParser::name(&*self.parsers[idx]),
+            Some(i) => self.async_parsers[i].name(),
+        }
+    }
+
+    /// Apply dedup, filters, alerts and error handling to one parser's result.
+    /// `idx` counts sync parsers first, then async ones, as in `ParserControl`.
+    /// Returns whether any of the parser's events were kept, dry run included.
+    async fn absorb(
+        &mut self,
+        idx: usize,
+        result: ParserResult<Option<Vec<TransactionEvent>>>,
+        txn: &SolanaTransaction,
+        out: &mut Vec<TransactionEvent>,
+    ) -> bool {
+        // Names are owned in the arms below, as coverage borrows `self` mutably
+        match result {
+            Ok(Some(mut events)) => {
+                let name = self.parser_name(idx).to_string();
+                if let Some(dedup) = self.dedup.as_mut() {
+                    events.retain(|ev| dedup.insert(ev));
+                }
+                if !self.filters.is_empty() {
+                    events.retain(|ev| self.filters.iter().all(|f| f.keep(ev)));
+                }
+                let kept = !events.is_empty();
+                if let Some(coverage) = self.coverage.as_mut() {
+                    coverage.record_events(&name, &events);
+                    return kept;
+                }
+                if let Some(notifier) = self.notifier.clone() {
+                    for ev in &events {
+                        let alert = match ev {
+                            TransactionEvent::RaydiumSwap(s) => Some(SwapEvent::Raydium(s.clone())),
+                            TransactionEvent::JupiterSwap(s) => Some(SwapEvent::Jupiter(s.clone())),
+                            TransactionEvent::PumpFunTrade(t) => Some(SwapEvent::PumpFun(t.clone())),
+                            TransactionEvent::PumpAmmSwap(s) => Some(SwapEvent::PumpAmm(s.clone())),
+                            _ => None,
+                        };
+                        if let Some(alert) = alert {
+                            notifier.send_to_queue(&alert).await;
+                        }
+                    }
+                }
+                out.extend(events);
+                kept
+            }
+            Ok(None) => false,
+            Err(e) => {
+                let name = self.parser_name(idx).to_string();
+                tracing::warn!("Parser {} failed: {:?}", name, e);
+                if let Some(sink) = &self.parse_errors {
+                    if let Err(log_err) = sink.record(txn, &name, &e.to_string()).await {
+                        tracing::error!("Parse-error log write failed: {}", log_err);
+                    }
+                }
+                if let Some(coverage) = self.coverage.as_mut() {
+                    coverage.record_error(&name);
+                    return false;
+                }
+                if let Err(db_err) = self.repo.save_dlq(txn, &name, &e.to_string()).await {
+                    tracing::error!("DLQ write failed (double fault): {}", db_err);
+                }
+                false
+            }
+        }
+    }
+
+    /// Consume events until every producer is gone. Returns only after the writers
+    /// have written every batch handed to them.
     pub async fn run(&mut self) {
-        let repo_clone = self.repo.clone();
+        if self.config.dry_run {
+            tracing::info!("Dry-run mode: parsing only, nothing will be persisted");
+            if let Some(mut coverage) = self.coverage.take() {
+                for (parser, kinds) in self.parser_event_kinds() {
+                    tracing::info!("[dry-run] {} emits {}", parser, if kinds.is_empty() { "(undeclared)".to_string() } else { kinds.join(", ") });
+                    coverage.declare(parser, kinds);
+                }
+                self.coverage = Some(coverage);
+            }
+        } else {
+            self.replay_wal().await;
+        }
+        let (writer_tx, writer_handles) = self.spawn_writers();
 
-        let mut batch: Vec<TransactionEvent> = Vec::with_capacity(100);
+        let mut batch_size = AdaptiveBatchSize::new(self.config.min_batch_size, self.config.max_batch_size);
+        let mut batch: Vec<TransactionEvent> = Vec::with_capacity(batch_size.current());
         let mut latest_slot: u64 = 0;
+        let mut batch_started = Instant::now();
 
         let flush_interval = tokio::time::interval(Duration::from_millis(1000));
         tokio::pin!(flush_interval);
+        let report_interval = tokio::time::interval(Duration::from_secs(30));
+        tokio::pin!(report_interval);
 
         loop {
             tokio::select! {
-                Some(event) = self.rx.recv() => {
+                event = self.rx.recv() => {
+                    let Some(event) = event else {
+                        // Every producer is gone: flush what is left and stop
+                        if !batch.is_empty() {
+                            let pending = std::mem::take(&mut batch);
+                            self.enqueue(&writer_tx, pending, latest_slot, batch_started).await;
+                        }
+                        tracing::info!("Event channel closed, pipeline stopping");
+                        break;
+                    };
+                    self.metrics.record_event(self.clock.now());
+                    self.metrics.set_queue(self.rx.len(), self.rx.max_capacity());
+                    if let Some(pool) = self.repo.pool_stats() {
+                        self.metrics.set_pool(pool.size, pool.idle);
+                    }
                     match event {
-                        ChainEvent::BlockMeta { slot, .. } => {
+                        ChainEvent::BlockMeta { slot, block_time, .. } => {
+                            if let Some(time) = block_time {
+                                self.latest_block_time = Some((slot, time));
+                            }
+                            if let Some(gap) = self.slot_gaps.observe(slot) {
+                                let missed = gap.end() - gap.start() + 1;
+                                tracing::warn!("Slot gap: no block for {}–{} ({} slots)", gap.start(), gap.end(), missed);
+                                self.metrics.record_gap(missed);
+                            }
                             latest_slot = slot;
+                            self.complete_slot(slot).await;
+                        }
+                        ChainEvent::Rollback { slot } => {
+                            if let Some((aggregator, _)) = self.slot_summaries.as_mut() {
+                                aggregator.discard_from(slot);
+                            }
+                            batch.retain(|ev| ev.slot() < slot);
+                            latest_slot = latest_slot.min(slot.saturating_sub(1));
+                            self.rollback(slot).await;
+                        }
+                        ChainEvent::Finalized { slot } if self.config.two_phase => {
+                            // Events of the slot may still sit in the open batch
+                            if batch.iter().any(|ev| ev.slot() <= slot) {
+                                let pending = std::mem::replace(&mut batch, Vec::with_capacity(batch_size.current()));
+                                self.enqueue(&writer_tx, pending, latest_slot, batch_started).await;
+                            }
+                            self.finalize(slot).await;
+                        }
+                        ChainEvent::Finalized { .. } => {}
+                        ChainEvent::Transaction(txn)
+                            if self.signature_filter.as_ref().is_some_and(|f| !f.allows(&txn.signature)) => {}
+                        ChainEvent::Transaction(txn) if !txn.success && self.config.skip_failed => {
+                            tracing::debug!("Skipping failed transaction {}", txn.signature);
                         }
-                        ChainEvent::Transaction(txn) => {
-                            for parser in &self.parsers {
-                                match parser.parse(txn.clone()) {
-                                    Ok(Some(events)) => {
-                                        if let Some(notifier) = self.notifier.clone() {
-                                            for ev in &events {
-                                                let alert = match ev {
-                                                    TransactionEvent::RaydiumSwap(s) => Some(SwapEvent::Raydium(s.clone())),
-                                                    TransactionEvent::JupiterSwap(s) => Some(SwapEvent::Jupiter(s.clone())),
-                                                    TransactionEvent::PumpFunTrade(t) => Some(SwapEvent::PumpFun(t.clone())),
-                                                    _ => None,
-                                                };
-                                                if let Some(alert) = alert {
-                                                    notifier.send_to_queue(&alert).await;
-                                                }
-                                            }
-                                        }
-                                        batch.extend(events);
-                                    }
-                                    Ok(None) => continue,
-                                    Err(e) => {
-                                        tracing::warn!("Parser {} failed: {:?}", parser.name(), e);
-                                        if let Err(db_err) = repo_clone.save_dlq(&txn, parser.name(), &e.to_string()).await {
-                                            tracing::error!("DLQ write failed (double fault): {}", db_err);
-                                        }
-                                    }
-                                }
+                        ChainEvent::Transaction(txn) if !self.accept_slot(txn.slot, &txn.signature) => {}
+                        ChainEvent::Transaction(txn)
+                            if self.already_indexed(&txn.signature) =>
+                        {
+                            tracing::debug!("Skipping already indexed transaction {}", txn.signature);
+                        }
+                        ChainEvent::Transaction(mut txn) => {
+                            // The block's own time beats whatever the source guessed (often nothing, or wall-clock)
+                            if let Some((_, time)) = self.latest_block_time.filter(|&(slot, _)| slot == txn.slot) {
+                                txn.block_time = Some(time);
+                            }
+                            if let Some(limiter) = self.rate_limiter.as_mut() {
+                                limiter.acquire().await;
+                            }
+                            let programs = self.program_coverage.as_ref().map(|(_, inspector)| {
+                                inspector.invoked_programs(&txn).unwrap_or_else(|e| {
+                                    tracing::debug!("Could not list programs of {}: {}", txn.signature, e);
+                                    Vec::new()
+                                })
+                            });
+                            let (events, parsers) = self.process_transaction(&txn).await;
+                            if let (Some((coverage, _)), Some(programs)) = (self.program_coverage.as_mut(), programs) {
+                                coverage.record(&programs, !parsers.is_empty());
                             }
+                            let events = self.transform(events);
+                            if let Some((aggregator, _)) = self.slot_summaries.as_mut() {
+                                events.iter().for_each(|ev| aggregator.record(ev));
+                            }
+                            if let Some(volume) = self.metrics.volume() {
+                                events.iter().for_each(|ev| volume.record(ev));
+                            }
+                            if batch.is_empty() && !events.is_empty() {
+                                batch_started = Instant::now();
+                            }
+                            if !events.is_empty() {
+                                self.open_transactions.insert(txn.signature.clone(), IndexedTransaction::new(&txn, parsers));
+                            }
+                            batch.extend(events);
 
-                            if batch.len() >= 100 {
-                                if let Err(e) = repo_clone.save_batch(&batch, latest_slot).await {
-                                    tracing::error!("Batch DB write error: {}", e);
-                                }
-                                batch.clear();
+                            if batch.len() >= batch_size.current() {
+                                batch_size.on_full_flush(self.rx.len());
+                                let full = std::mem::replace(&mut batch, Vec::with_capacity(batch_size.current()));
+                                self.enqueue(&writer_tx, full, latest_slot, batch_started).await;
                             }
                         }
                     }
                 }
 
+                _ = report_interval.tick(), if self.coverage.is_some() || self.program_coverage.is_some() => {
+                    if let Some(coverage) = &self.coverage {
+                        coverage.log_summary();
+                    }
+                    if let Some((coverage, _)) = &self.program_coverage {
+                        coverage.log_top_unparsed(PROGRAM_COVERAGE_TOP);
+                    }
+                }
+
                 _ = flush_interval.tick() => {
                     if !batch.is_empty() {
-                        if let Err(e) = repo_clone.save_batch(&batch, latest_slot).await {
-                            tracing::error!("Flush DB write error: {}", e);
-                        }
-                        batch.clear();
+                        batch_size.on_timer_flush();
+                        let pending = std::mem::replace(&mut batch, Vec::with_capacity(batch_size.current()));
+                        self.enqueue(&writer_tx, pending, latest_slot, batch_started).await;
                     }
                 }
             }
         }
+
+        // Closing the queue lets each writer finish the batches still in it and exit.
+        // Returning before they do would let `main` exit mid-write.
+        drop(writer_tx);
+        tracing::info!("Waiting for writers to drain queued batches");
+        for handle in writer_handles {
+            if let Err(e) = handle.await {
+                tracing::error!("Batch writer panicked: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use anyhow::Result;
+    use async_trait::async_trait;
+
+    use super::*;
+    use crate::{
+        adapters::{InMemoryRepository, NullRepository},
+        application::{AppResult, ParserError, ProgramCount},
+        infrastructure::{FileParseErrorLog, FileWal},
+        domain::{IndexerState, SolTransfer, TxData, fixtures::{sol_transfer, transfer, transfer_at}},
+    };
+
+    /// Stores into an `InMemoryRepository`, but is unreachable for the first
+    /// `unreachable_writes` writes and rejects any batch holding `poisoned`.
+    #[derive(Default)]
+    struct FlakyRepo {
+        stored: InMemoryRepository,
+        unreachable_writes: AtomicU32,
+        poisoned: Option<String>,
+        /// Size of every batch that was stored, in commit order.
+        committed: std::sync::Mutex<Vec<usize>>,
+        /// How long every successful write takes.
+        write_delay: Duration,
+    }
+
+    impl FlakyRepo {
+        fn unreachable(&self) -> bool {
+            self.unreachable_writes.load(Ordering::SeqCst) > 0
+        }
+    }
+
+    #[async_trait]
+    impl TransactionRepository for FlakyRepo {
+        async fn get_state(&self) -> Result<IndexerState> {
+            self.stored.get_state().await
+        }
+
+        async fn get_last_slot(&self) -> Result<u64> {
+            self.stored.get_last_slot().await
+        }
+
+        async fn save_batch(&self, events: &[TransactionEvent], current_slot: u64) -> Result<()> {
+            if self.unreachable() {
+                self.unreachable_writes.fetch_sub(1, Ordering::SeqCst);
+                return Err(AppError::ConnectionError("connection refused".to_string()).into());
+            }
+            if events.iter().any(|ev| Some(ev.signature()) == self.poisoned.as_deref()) {
+                return Err(AppError::WriteError("value out of range".to_string()).into());
+            }
+            tokio::time::sleep(self.write_delay).await;
+            self.stored.save_batch(events, current_slot).await?;
+            self.committed.lock().unwrap().push(events.len());
+            Ok(())
+        }
+
+        async fn save_dlq(&self, txn: &SolanaTransaction, parser_name: &str, error: &str) -> Result<()> {
+            self.stored.save_dlq(txn, parser_name, error).await
+        }
+
+        async fn save_event_dlq(&self, event: &TransactionEvent, error: &str) -> Result<()> {
+            if self.unreachable() {
+                return Err(AppError::ConnectionError("connection refused".to_string()).into());
+            }
+            self.stored.save_event_dlq(event, error).await
+        }
+
+        async fn save_transactions(&self, transactions: &[IndexedTransaction]) -> Result<()> {
+            self.stored.save_transactions(transactions).await
+        }
+
+        async fn delete_events_from_slot(&self, slot: u64) -> Result<u64> {
+            self.stored.delete_events_from_slot(slot).await
+        }
+
+        async fn ping(&self) -> Result<()> {
+            if self.unreachable() {
+                return Err(AppError::ConnectionError("connection refused".to_string()).into());
+            }
+            Ok(())
+        }
+    }
+
+    fn record(event: &TransactionEvent) -> IndexedTransaction {
+        IndexedTransaction {
+            signature: event.signature().to_string(),
+            slot: event.slot(),
+            block_time: None,
+            fee_payer: "payer".to_string(),
+            success: true,
+            parsers: vec!["system".to_string()],
+        }
+    }
+
+    /// Run one batch through a single writer and wait for it to be done with it.
+    /// Each event stands for its own transaction.
+    async fn write(repo: Arc<FlakyRepo>, config: PipelineConfig, events: Vec<TransactionEvent>) -> Arc<PipelineMetrics> {
+        let (_tx, rx) = mpsc::channel(1);
+        let pipeline = IngestionPipeline::<FlakyRepo>::new(rx, repo, Vec::new(), None).with_config(config);
+        write_through(&pipeline, events).await;
+        pipeline.metrics()
+    }
+
+    async fn write_through(pipeline: &IngestionPipeline<FlakyRepo>, events: Vec<TransactionEvent>) {
+        write_batches(pipeline, vec![events]).await;
+    }
+
+    async fn write_batches(pipeline: &IngestionPipeline<FlakyRepo>, batches: Vec<Vec<TransactionEvent>>) {
+        let (writer_tx, handles) = pipeline.spawn_writers();
+        for events in batches {
+            let transactions = events.iter().map(record).collect();
+            let batch = PendingBatch { events, transactions, slot: 10, wal_id: None, started: Instant::now(), _permits: Vec::new() };
+            writer_tx.send(batch).await.unwrap();
+        }
+        drop(writer_tx);
+        for handle in handles {
+            handle.await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn batch_is_retried_while_the_db_is_unreachable_instead_of_dead_lettered() {
+        let repo = Arc::new(FlakyRepo { unreachable_writes: AtomicU32::new(2), ..Default::default() });
+
+        let metrics = write(repo.clone(), PipelineConfig::default(), vec![transfer(1), transfer(2)]).await;
+
+        assert_eq!(repo.stored.events().len(), 2);
+        assert!(repo.stored.dlq_entries().is_empty());
+        assert_eq!(metrics.write_errors(), 2);
+        assert_eq!(repo.stored.transactions().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn best_effort_batch_is_retried_while_the_db_is_unreachable() {
+        // The second write finds the DB back; the first must not split the batch
+        let repo = Arc::new(FlakyRepo { unreachable_writes: AtomicU32::new(2), ..Default::default() });
+        let config = PipelineConfig { best_effort_writes: true, ..Default::default() };
+
+        write(repo.clone(), config, vec![transfer(1), transfer(2)]).await;
+
+        assert_eq!(repo.stored.events().len(), 2);
+        assert!(repo.stored.dlq_entries().is_empty());
+    }
+
+    #[tokio::test]
+    async fn event_the_db_keeps_rejecting_is_isolated_and_dead_lettered() {
+        let poisoned = transfer(2);
+        let repo = Arc::new(FlakyRepo { poisoned: Some(poisoned.signature().to_string()), ..Default::default() });
+
+        write(repo.clone(), PipelineConfig::default(), vec![transfer(1), poisoned.clone(), transfer(3)]).await;
+
+        assert_eq!(repo.stored.events().len(), 2);
+        let dlq = repo.stored.dlq_entries();
+        assert_eq!(dlq.len(), 1);
+        assert_eq!(dlq[0].0, poisoned.signature());
+        // Only transactions with events in the DB get a parent record
+        let recorded: Vec<_> = repo.stored.transactions().into_iter().map(|t| t.signature).collect();
+        assert_eq!(recorded, vec![transfer(1).signature().to_string(), transfer(3).signature().to_string()]);
+    }
+
+    #[tokio::test]
+    async fn only_signatures_whose_events_were_written_are_marked_seen() {
+        let poisoned = transfer(2);
+        let repo = Arc::new(FlakyRepo { poisoned: Some(poisoned.signature().to_string()), ..Default::default() });
+        let (_tx, rx) = mpsc::channel(1);
+        let pipeline = IngestionPipeline::<FlakyRepo>::new(rx, repo, Vec::new(), None)
+            .with_seen_signatures(BloomFilter::new(1_000, 0.001));
+
+        write_through(&pipeline, vec![transfer(1), poisoned.clone()]).await;
+
+        let seen = pipeline.seen_signatures().unwrap();
+        assert!(seen.contains(transfer(1).signature().as_bytes()));
+        // Dead-lettered, so a rerun must still index it
+        assert!(!seen.contains(poisoned.signature().as_bytes()));
+    }
+
+    /// Time for `writers` to store four batches against a repository that takes
+    /// 50ms per write.
+    async fn time_to_write_four_batches(writers: usize) -> Duration {
+        let repo = Arc::new(FlakyRepo { write_delay: Duration::from_millis(50), ..Default::default() });
+        let (_tx, rx) = mpsc::channel(1);
+        let pipeline = IngestionPipeline::<FlakyRepo>::new(rx, repo.clone(), Vec::new(), None)
+            .with_config(PipelineConfig { writers, ..Default::default() });
+        let batches = (0..4).map(|i| vec![transfer(2 * i), transfer(2 * i + 1)]).collect();
+
+        let started = Instant::now();
+        write_batches(&pipeline, batches).await;
+        let elapsed = started.elapsed();
+
+        assert_eq!(repo.stored.events().len(), 8);
+        elapsed
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn several_writers_commit_batches_concurrently() {
+        let single = time_to_write_four_batches(1).await;
+        let pooled = time_to_write_four_batches(4).await;
+
+        assert!(single >= Duration::from_millis(200), "one writer took {:?}", single);
+        assert!(pooled < single / 2, "four writers took {:?}, one took {:?}", pooled, single);
+    }
+
+    fn commit_slots(commits: &[Vec<TransactionEvent>]) -> Vec<Vec<u64>> {
+        commits.iter().map(|c| c.iter().map(|ev| ev.slot()).collect()).collect()
+    }
+
+    #[test]
+    fn only_a_slot_over_the_limit_is_spread_over_commits() {
+        let batch = (0..10).map(|i| transfer_at(i, 20)).chain([transfer_at(10, 21), transfer_at(11, 22)]).collect();
+
+        let commits = slot_commits(batch, 4);
+
+        assert_eq!(commit_slots(&commits), vec![vec![20; 4], vec![20; 4], vec![20, 20, 21, 22]]);
+    }
+
+    #[test]
+    fn slots_within_the_limit_are_never_split() {
+        let batch = (0..3).map(|i| transfer_at(i, 30)).chain((3..6).map(|i| transfer_at(i, 31))).collect();
+
+        let commits = slot_commits(batch, 4);
+
+        assert_eq!(commit_slots(&commits), vec![vec![30; 3], vec![31; 3]]);
+    }
+
+    #[tokio::test]
+    async fn busy_slot_reaches_the_repository_as_bounded_commits() {
+        let repo = Arc::new(FlakyRepo::default());
+        let (_tx, rx) = mpsc::channel(1);
+        let mut pipeline = IngestionPipeline::<FlakyRepo>::new(rx, repo.clone(), Vec::new(), None)
+            .with_config(PipelineConfig { max_rows_per_commit: Some(25), ..Default::default() });
+        let (writer_tx, handles) = pipeline.spawn_writers();
+
+        let busy_slot: Vec<_> = (0..100).map(|i| transfer_at(i, 40)).collect();
+        pipeline.enqueue(&writer_tx, busy_slot, 40, Instant::now()).await;
+        drop(writer_tx);
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        assert_eq!(*repo.committed.lock().unwrap(), vec![25; 4]);
+        assert_eq!(repo.stored.events().len(), 100);
+    }
+
+    /// Transaction whose signature tells the stubs below what it invokes.
+    fn transaction(signature: &str) -> SolanaTransaction {
+        SolanaTransaction {
+            signature: signature.to_string(),
+            success: true,
+            data: TxData::Grpc(Vec::new()),
+            slot: 10,
+            block_time: None,
+            fee: None,
+            compute_units_consumed: None,
+            fee_payer: "payer".to_string(),
+            recent_blockhash: String::new(),
+        }
+    }
+
+    /// Parses transactions whose signature starts with `known`.
+    struct KnownProgramParser;
+
+    impl TransactionParser for KnownProgramParser {
+        fn parse(&self, txn: SolanaTransaction) -> ParserResult<Option<Vec<TransactionEvent>>> {
+            let seed = txn.signature.len() as u8;
+            Ok(txn.signature.starts_with("known").then(|| vec![transfer(seed)]))
+        }
+
+        fn name(&self) -> &str {
+            "known"
+        }
+    }
+
+    /// `known…` transactions invoke KnownProgram, the rest UnknownProgram; all
+    /// of them invoke ComputeBudget.
+    struct SignatureInspector;
+
+    impl ProgramInspector for SignatureInspector {
+        fn invoked_programs(&self, txn: &SolanaTransaction) -> Result<Vec<String>> {
+            let program = if txn.signature.starts_with("known") { "KnownProgram" } else { "UnknownProgram" };
+            Ok(vec![program.to_string(), "ComputeBudget".to_string()])
+        }
+    }
+
+    #[tokio::test]
+    async fn dry_run_reports_only_programs_without_a_parser_as_unparsed() {
+        let repo = Arc::new(FlakyRepo::default());
+        let (tx, rx) = mpsc::channel(16);
+        let parsers: Vec<Box<dyn TransactionParser>> = vec![Box::new(KnownProgramParser)];
+        let mut pipeline = IngestionPipeline::<FlakyRepo>::new(rx, repo.clone(), parsers, None)
+            .with_config(PipelineConfig { dry_run: true, ..Default::default() })
+            .with_program_coverage(Arc::new(SignatureInspector));
+
+        for signature in ["known-1", "known-22", "other-1", "other-22", "other-333"] {
+            tx.send(ChainEvent::Transaction(transaction(signature))).await.unwrap();
+        }
+        drop(tx);
+        pipeline.run().await;
+
+        let (coverage, _) = pipeline.program_coverage.as_ref().unwrap();
+        let top = coverage.top_unparsed(10);
+        assert_eq!(top, vec![
+            ("ComputeBudget", ProgramCount { transactions: 5, parsed: 2 }),
+            ("UnknownProgram", ProgramCount { transactions: 3, parsed: 0 }),
+        ]);
+        assert!(repo.stored.events().is_empty());
+    }
+
+    #[tokio::test]
+    async fn parsing_alone_does_not_mark_a_signature_seen() {
+        let (_tx, rx) = mpsc::channel(1);
+        let parsers: Vec<Box<dyn TransactionParser>> = vec![Box::new(KnownProgramParser)];
+        let mut pipeline = IngestionPipeline::<FlakyRepo>::new(rx, Arc::new(FlakyRepo::default()), parsers, None)
+            .with_seen_signatures(BloomFilter::new(1_000, 0.001));
+
+        let (events, _) = pipeline.process_transaction(&transaction("known-1")).await;
+
+        assert_eq!(events.len(), 1);
+        assert!(!pipeline.seen_signatures().unwrap().contains(b"known-1"));
+    }
+
+    #[tokio::test]
+    async fn replayed_transaction_reaches_the_repository_once() {
+        let repo = Arc::new(FlakyRepo::default());
+        let (tx, rx) = mpsc::channel(16);
+        let parsers: Vec<Box<dyn TransactionParser>> = vec![Box::new(KnownProgramParser)];
+        let mut pipeline = IngestionPipeline::<FlakyRepo>::new(rx, repo.clone(), parsers, None)
+            .with_config(PipelineConfig { dedup_capacity: 16, ..Default::default() });
+
+        let (first, _) = pipeline.process_transaction(&transaction("known-1")).await;
+        let (replay, _) = pipeline.process_transaction(&transaction("known-1")).await;
+        assert_eq!((first.len(), replay.len()), (1, 0));
+
+        // End to end, the replay never makes it into a batch
+        for _ in 0..2 {
+            tx.send(ChainEvent::Transaction(transaction("known-22"))).await.unwrap();
+        }
+        drop(tx);
+        pipeline.run().await;
+
+        assert_eq!(repo.stored.events().len(), 1);
+        assert_eq!(repo.committed.lock().unwrap().iter().sum::<usize>(), 1);
+    }
+
+    fn late_slot_pipeline(late_slot_action: LateSlotAction) -> IngestionPipeline<FlakyRepo> {
+        let (_tx, rx) = mpsc::channel(1);
+        IngestionPipeline::<FlakyRepo>::new(rx, Arc::new(FlakyRepo::default()), Vec::new(), None).with_config(PipelineConfig {
+            reorg_tolerance_slots: Some(5),
+            late_slot_action,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn older_slot_is_dropped_only_outside_the_tolerance() {
+        let mut pipeline = late_slot_pipeline(LateSlotAction::Drop);
+
+        assert!(pipeline.accept_slot(100, "a"));
+        assert!(pipeline.accept_slot(95, "within"));
+        assert!(!pipeline.accept_slot(94, "outside"));
+        // The watermark only moves forward
+        assert!(pipeline.accept_slot(110, "b"));
+        assert!(!pipeline.accept_slot(100, "now outside"));
+    }
+
+    #[test]
+    fn warn_keeps_late_slots() {
+        let mut pipeline = late_slot_pipeline(LateSlotAction::Warn);
+
+        assert!(pipeline.accept_slot(100, "a"));
+        assert!(pipeline.accept_slot(50, "late"));
+    }
+
+    #[tokio::test]
+    async fn batch_left_in_the_wal_by_a_crash_is_replayed_to_the_repository() {
+        let path = std::env::temp_dir().join(format!("indexer-ingest-wal-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let wal = FileWal::open(&path).await.unwrap();
+        wal.append(&[transfer(1), transfer(2)], 10).await.unwrap();
+        // Crash: the batch never reached the DB and was never committed
+        drop(wal);
+
+        let repo = Arc::new(FlakyRepo::default());
+        let wal = Arc::new(FileWal::open(&path).await.unwrap());
+        let (_tx, rx) = mpsc::channel(1);
+        let pipeline = IngestionPipeline::<FlakyRepo>::new(rx, repo.clone(), Vec::new(), None).with_wal(wal.clone());
+        pipeline.replay_wal().await;
+
+        let stored: Vec<_> = repo.stored.events().iter().map(|ev| ev.signature().to_string()).collect();
+        assert_eq!(stored, vec![transfer(1).signature().to_string(), transfer(2).signature().to_string()]);
+        assert!(wal.uncommitted().await.unwrap().is_empty());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    /// Keeps every batch it is given.
+    #[derive(Default)]
+    struct CapturingSink {
+        batches: std::sync::Mutex<Vec<Vec<TransactionEvent>>>,
+    }
+
+    #[async_trait]
+    impl EventSink for CapturingSink {
+        async fn emit(&self, events: &[TransactionEvent]) -> AppResult<()> {
+            self.batches.lock().unwrap().push(events.to_vec());
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            "capture"
+        }
+    }
+
+    #[tokio::test]
+    async fn committed_batches_are_delivered_to_the_sink() {
+        let poisoned = transfer(2);
+        let repo = Arc::new(FlakyRepo { poisoned: Some(poisoned.signature().to_string()), ..Default::default() });
+        let sink = Arc::new(CapturingSink::default());
+        let (_tx, rx) = mpsc::channel(1);
+        let pipeline = IngestionPipeline::<FlakyRepo>::new(rx, repo, Vec::new(), None).with_sink(sink.clone());
+
+        write_through(&pipeline, vec![transfer(1), poisoned, transfer(3)]).await;
+
+        // The sink sees what reached the repository, not the dead-lettered event
+        let batches = sink.batches.lock().unwrap();
+        let delivered: Vec<_> = batches.iter().flatten().map(|ev| ev.signature().to_string()).collect();
+        assert_eq!(delivered, vec![transfer(1).signature().to_string(), transfer(3).signature().to_string()]);
+    }
+
+    #[tokio::test]
+    async fn slot_gap_is_counted_in_the_metrics() {
+        let (tx, rx) = mpsc::channel(16);
+        let mut pipeline = IngestionPipeline::<FlakyRepo>::new(rx, Arc::new(FlakyRepo::default()), Vec::new(), None);
+
+        for slot in [100, 101, 105] {
+            let block = ChainEvent::BlockMeta { slot, block_hash: String::new(), parent_block_hash: String::new(), block_time: None };
+            tx.send(block).await.unwrap();
+        }
+        drop(tx);
+        pipeline.run().await;
+
+        assert_eq!(pipeline.metrics().slots_gapped(), 3);
+    }
+
+    /// How long handing three batches to one writer takes, against a repository
+    /// that takes 300ms per write.
+    async fn time_to_hand_off_three_batches(max_in_flight_batches: usize) -> Duration {
+        let repo = Arc::new(FlakyRepo { write_delay: Duration::from_millis(300), ..Default::default() });
+        let (_tx, rx) = mpsc::channel(1);
+        let mut pipeline = IngestionPipeline::<FlakyRepo>::new(rx, repo, Vec::new(), None).with_config(PipelineConfig {
+            writers: 1,
+            max_in_flight_batches: Some(max_in_flight_batches),
+            ..Default::default()
+        });
+        let (writer_tx, handles) = pipeline.spawn_writers();
+
+        let started = Instant::now();
+        for seed in 0..3 {
+            pipeline.enqueue(&writer_tx, vec![transfer(seed)], 10, Instant::now()).await;
+        }
+        let elapsed = started.elapsed();
+
+        drop(writer_tx);
+        for handle in handles {
+            handle.await.unwrap();
+        }
+        elapsed
+    }
+
+    #[tokio::test]
+    async fn hand_off_blocks_once_the_in_flight_limit_is_reached() {
+        // One batch in flight: the third waits for the first two writes
+        assert!(time_to_hand_off_three_batches(1).await >= Duration::from_millis(500));
+        // Room for all three: nothing waits
+        assert!(time_to_hand_off_three_batches(3).await < Duration::from_millis(250));
+    }
+
+    const MIXED_SIGNATURES: [&str; 4] = ["known-1", "other-1", "known-22", "known-333"];
+
+    /// Feeds `signatures` to `pipeline` and runs it to the end of the stream.
+    async fn run_pipeline<R, P>(tx: mpsc::Sender<ChainEvent>, pipeline: &mut IngestionPipeline<R, P>, signatures: &[&str])
+    where
+        R: TransactionRepository + ?Sized + 'static,
+        P: TransactionParser + ?Sized,
+    {
+        for signature in signatures {
+            tx.send(ChainEvent::Transaction(transaction(signature))).await.unwrap();
+        }
+        drop(tx);
+        pipeline.run().await;
+    }
+
+    fn stored_signatures(repo: &FlakyRepo) -> Vec<String> {
+        let mut signatures: Vec<_> = repo.stored.events().iter().map(|ev| ev.signature().to_string()).collect();
+        signatures.sort();
+        signatures
+    }
+
+    #[tokio::test]
+    async fn generic_pipeline_persists_what_the_dyn_pipeline_persists() {
+        let generic_repo = Arc::new(FlakyRepo::default());
+        let (tx, rx) = mpsc::channel(16);
+        let mut generic =
+            IngestionPipeline::<FlakyRepo, KnownProgramParser>::new(rx, generic_repo.clone(), vec![Box::new(KnownProgramParser)], None);
+        run_pipeline(tx, &mut generic, &MIXED_SIGNATURES).await;
+
+        let dyn_repo = Arc::new(FlakyRepo::default());
+        let (tx, rx) = mpsc::channel(16);
+        let repo: Arc<dyn TransactionRepository> = dyn_repo.clone();
+        let parsers: Vec<Box<dyn TransactionParser>> = vec![Box::new(KnownProgramParser)];
+        let mut dynamic = IngestionPipeline::new(rx, repo, parsers, None);
+        run_pipeline(tx, &mut dynamic, &MIXED_SIGNATURES).await;
+
+        assert_eq!(stored_signatures(&generic_repo), stored_signatures(&dyn_repo));
+        assert_eq!(generic_repo.stored.transactions().len(), dyn_repo.stored.transactions().len());
+        assert_eq!(generic.metrics().events_persisted(), dynamic.metrics().events_persisted());
+        assert_eq!(stored_signatures(&generic_repo).len(), 3);
+    }
+
+    /// Time to parse `rounds` transactions on the hot path of `pipeline`.
+    async fn time_to_process<R, P>(pipeline: &mut IngestionPipeline<R, P>, rounds: usize) -> Duration
+    where
+        R: TransactionRepository + ?Sized + 'static,
+        P: TransactionParser + ?Sized,
+    {
+        let transactions: Vec<_> = (0..rounds).map(|i| transaction(&format!("known-{}", i))).collect();
+        let started = Instant::now();
+        for txn in &transactions {
+            let _ = pipeline.process_transaction(txn).await;
+        }
+        started.elapsed()
+    }
+
+    #[tokio::test]
+    #[ignore = "benchmark; run with --ignored --nocapture"]
+    async fn generic_and_dyn_pipelines_parse_at_comparable_speed() {
+        const ROUNDS: usize = 200_000;
+        let (_tx, rx) = mpsc::channel(1);
+        let mut generic =
+            IngestionPipeline::<FlakyRepo, KnownProgramParser>::new(rx, Arc::new(FlakyRepo::default()), vec![Box::new(KnownProgramParser)], None);
+        let (_tx, rx) = mpsc::channel(1);
+        let repo: Arc<dyn TransactionRepository> = Arc::new(FlakyRepo::default());
+        let parsers: Vec<Box<dyn TransactionParser>> = vec![Box::new(KnownProgramParser)];
+        let mut dynamic = IngestionPipeline::new(rx, repo, parsers, None);
+
+        let generic_time = time_to_process(&mut generic, ROUNDS).await;
+        let dyn_time = time_to_process(&mut dynamic, ROUNDS).await;
+
+        // Dispatch is a small part of parsing; the generic form must not be meaningfully slower
+        assert!(generic_time < dyn_time * 2, "{} transactions: generic {:?} vs dyn {:?}", ROUNDS, generic_time, dyn_time);
+    }
+
+    /// Fails on every transaction whose signature starts with `bad`.
+    struct RejectingParser;
+
+    impl TransactionParser for RejectingParser {
+        fn parse(&self, txn: SolanaTransaction) -> ParserResult<Option<Vec<TransactionEvent>>> {
+            if txn.signature.starts_with("bad") {
+                return Err(ParserError::DecodeFailed("bad instruction".to_string()));
+            }
+            Ok(None)
+        }
+
+        fn name(&self) -> &str {
+            "rejecting"
+        }
+    }
+
+    #[tokio::test]
+    async fn dry_run_counts_per_parser_and_never_touches_the_repository() {
+        let repo = Arc::new(FlakyRepo::default());
+        let (tx, rx) = mpsc::channel(16);
+        let parsers: Vec<Box<dyn TransactionParser>> = vec![Box::new(KnownProgramParser), Box::new(RejectingParser)];
+        let mut pipeline = IngestionPipeline::<FlakyRepo>::new(rx, repo.clone(), parsers, None)
+            .with_config(PipelineConfig { dry_run: true, ..Default::default() });
+
+        run_pipeline(tx, &mut pipeline, &["known-1", "known-22", "bad-1", "other-1"]).await;
+
+        let coverage = pipeline.coverage.as_ref().unwrap();
+        assert_eq!(coverage.transactions(), 4);
+        assert_eq!(coverage.count("known", "sol_transfer"), 2);
+        assert_eq!(coverage.errors("rejecting"), 1);
+        assert_eq!(coverage.errors("known"), 0);
+        // Neither events, transaction records nor dead letters were written
+        assert!(repo.committed.lock().unwrap().is_empty());
+        assert!(repo.stored.events().is_empty());
+        assert!(repo.stored.transactions().is_empty());
+        assert!(repo.stored.dlq_entries().is_empty());
+    }
+
+    #[tokio::test]
+    async fn persist_latency_reflects_a_slow_repository() {
+        let repo = Arc::new(FlakyRepo { write_delay: Duration::from_millis(120), ..Default::default() });
+
+        let metrics = write(repo, PipelineConfig::default(), vec![transfer(1), transfer(2)]).await;
+
+        let latency = metrics.persist_latency();
+        assert_eq!(latency.count(), 1);
+        // 120ms lands in the 100–250ms bucket
+        assert_eq!(latency.quantile(0.5), Some(Duration::from_millis(250)));
+        assert_eq!(latency.quantile(0.99), Some(Duration::from_millis(250)));
+    }
+
+    #[tokio::test]
+    async fn disabled_parser_stops_while_the_others_continue() {
+        let (_tx, rx) = mpsc::channel(1);
+        let parsers: Vec<Box<dyn TransactionParser>> = vec![Box::new(KnownProgramParser), Box::new(RejectingParser)];
+        let mut pipeline = IngestionPipeline::<FlakyRepo>::new(rx, Arc::new(FlakyRepo::default()), parsers, None);
+        let control = pipeline.parser_control();
+
+        let (events, matched) = pipeline.process_transaction(&transaction("known-1")).await;
+        assert_eq!((events.len(), matched), (1, vec!["known".to_string()]));
+
+        assert!(control.set_enabled("known", false));
+        let (events, matched) = pipeline.process_transaction(&transaction("known-22")).await;
+        assert!(events.is_empty() && matched.is_empty());
+        // The other parser still runs and still dead-letters what it cannot parse
+        pipeline.process_transaction(&transaction("bad-1")).await;
+        assert_eq!(pipeline.repo.stored.dlq_entries().len(), 1);
+
+        assert!(control.set_enabled("known", true));
+        let (events, _) = pipeline.process_transaction(&transaction("known-333")).await;
+        assert_eq!(events.len(), 1);
+        assert!(!control.set_enabled("missing", false));
+    }
+
+    #[tokio::test]
+    async fn raw_frame_of_a_failed_parse_is_captured() {
+        let path = std::env::temp_dir().join(format!("indexer-parse-errors-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let (_tx, rx) = mpsc::channel(1);
+        let parsers: Vec<Box<dyn TransactionParser>> = vec![Box::new(RejectingParser)];
+        let mut pipeline = IngestionPipeline::<FlakyRepo>::new(rx, Arc::new(FlakyRepo::default()), parsers, None)
+            .with_parse_error_sink(Arc::new(FileParseErrorLog::open(&path).await.unwrap()));
+        // Not a valid SubscribeUpdate; the point is that it comes back byte for byte
+        let frame = vec![0x0a, 0x03, 0xde, 0xad, 0xbe];
+        let txn = SolanaTransaction { data: TxData::Grpc(frame.clone()), ..transaction("bad-frame") };
+
+        pipeline.process_transaction(&txn).await;
+
+        let failures = FileParseErrorLog::read_all(&path).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(failures.len(), 1);
+        assert_eq!((failures[0].signature.as_str(), failures[0].parser.as_str()), ("bad-frame", "rejecting"));
+        assert!(failures[0].error.contains("bad instruction"), "{}", failures[0].error);
+        let TxData::Grpc(raw) = failures[0].transaction().unwrap().data else { panic!("expected a gRPC frame") };
+        assert_eq!(raw, frame);
+    }
+
+    #[tokio::test]
+    async fn rate_limit_paces_the_pipeline() {
+        let repo = Arc::new(FlakyRepo::default());
+        let (tx, rx) = mpsc::channel(32);
+        let mut pipeline = IngestionPipeline::<FlakyRepo>::new(rx, repo, Vec::new(), None)
+            .with_config(PipelineConfig { max_events_per_sec: Some(10), ..Default::default() });
+        let signatures: Vec<String> = (0..15).map(|i| format!("other-{}", i)).collect();
+        let signatures: Vec<&str> = signatures.iter().map(String::as_str).collect();
+
+        let started = Instant::now();
+        run_pipeline(tx, &mut pipeline, &signatures).await;
+
+        // 10 from the full bucket, then 5 more at 10 per second
+        assert!(started.elapsed() >= Duration::from_millis(450), "took {:?}", started.elapsed());
+    }
+
+    #[tokio::test]
+    async fn only_allow_listed_transactions_reach_the_parsers() {
+        let repo = Arc::new(FlakyRepo::default());
+        let (tx, rx) = mpsc::channel(16);
+        let parsers: Vec<Box<dyn TransactionParser>> = vec![Box::new(KnownProgramParser)];
+        let mut pipeline = IngestionPipeline::<FlakyRepo>::new(rx, repo.clone(), parsers, None)
+            .with_signature_filter(SignatureFilter::new().with_allow(["known-1".to_string(), "known-333".to_string()]));
+
+        run_pipeline(tx, &mut pipeline, &["known-1", "known-22", "known-333", "known-4444"]).await;
+
+        // KnownProgramParser keys its event on the signature's length
+        let mut expected = vec![transfer(7).signature().to_string(), transfer(9).signature().to_string()];
+        expected.sort();
+        assert_eq!(stored_signatures(&repo), expected);
+    }
+
+    #[tokio::test]
+    async fn run_returns_only_after_queued_events_are_persisted() {
+        let repo = Arc::new(FlakyRepo { write_delay: Duration::from_millis(100), ..Default::default() });
+        let (tx, rx) = mpsc::channel(16);
+        let parsers: Vec<Box<dyn TransactionParser>> = vec![Box::new(KnownProgramParser)];
+        let mut pipeline = IngestionPipeline::<FlakyRepo>::new(rx, repo.clone(), parsers, None)
+            .with_config(PipelineConfig { min_batch_size: 2, max_batch_size: 2, ..Default::default() });
+
+        // Distinct lengths, so every transaction yields its own event
+        run_pipeline(tx, &mut pipeline, &["known-1", "known-22", "known-333", "known-4444", "known-55555"]).await;
+
+        assert_eq!(repo.stored.events().len(), 5);
+        assert_eq!(repo.committed.lock().unwrap().iter().sum::<usize>(), 5);
+    }
+
+    /// Echoes each transaction's slot and block time into one transfer event.
+    struct BlockTimeParser;
+
+    impl TransactionParser for BlockTimeParser {
+        fn parse(&self, txn: SolanaTransaction) -> ParserResult<Option<Vec<TransactionEvent>>> {
+            let event = SolTransfer { slot: txn.slot, block_time: txn.block_time, ..sol_transfer(txn.signature.len() as u8) };
+            Ok(Some(vec![TransactionEvent::SolTransfer(event)]))
+        }
+
+        fn name(&self) -> &str {
+            "block-time"
+        }
+    }
+
+    #[tokio::test]
+    async fn transaction_inherits_the_block_time_of_its_slot() {
+        let repo = Arc::new(FlakyRepo::default());
+        let (tx, rx) = mpsc::channel(16);
+        let parsers: Vec<Box<dyn TransactionParser>> = vec![Box::new(BlockTimeParser)];
+        let mut pipeline = IngestionPipeline::<FlakyRepo>::new(rx, repo.clone(), parsers, None);
+
+        tx.send(ChainEvent::BlockMeta {
+            slot: 10,
+            block_hash: "hash10".to_string(),
+            parent_block_hash: "hash9".to_string(),
+            block_time: Some(1_700_000_000),
+        })
+        .await
+        .unwrap();
+        tx.send(ChainEvent::Transaction(transaction("in-slot"))).await.unwrap();
+        // No block meta for slot 11 yet, so slot 10's time must not leak into it
+        let later = SolanaTransaction { slot: 11, ..transaction("next-slot") };
+        tx.send(ChainEvent::Transaction(later)).await.unwrap();
+        drop(tx);
+        pipeline.run().await;
+
+        let mut times: Vec<_> = repo.stored.events().iter().map(|ev| (ev.slot(), ev.block_time())).collect();
+        times.sort();
+        assert_eq!(times, vec![(10, Some(1_700_000_000)), (11, None)]);
+    }
+
+    /// Awaits a stub lookup, then emits a transfer in the slot it resolved, keyed
+    /// apart from `KnownProgramParser`'s events.
+    struct LookupParser;
+
+    #[async_trait]
+    impl AsyncTransactionParser for LookupParser {
+        async fn parse_async(&self, txn: SolanaTransaction) -> ParserResult<Option<Vec<TransactionEvent>>> {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            Ok(Some(vec![transfer_at(txn.signature.len() as u8 + 100, 99)]))
+        }
+
+        fn name(&self) -> &str {
+            "lookup"
+        }
+    }
+
+    #[tokio::test]
+    async fn async_parser_events_are_persisted_alongside_sync_ones() {
+        let repo = Arc::new(FlakyRepo::default());
+        let (tx, rx) = mpsc::channel(16);
+        let parsers: Vec<Box<dyn TransactionParser>> = vec![Box::new(KnownProgramParser)];
+        let mut pipeline =
+            IngestionPipeline::<FlakyRepo>::new(rx, repo.clone(), parsers, None).with_async_parser(Box::new(LookupParser));
+
+        run_pipeline(tx, &mut pipeline, &["known-1", "other-22"]).await;
+
+        let keyed = |events: &[TransactionEvent]| {
+            let mut keys: Vec<_> = events.iter().map(|ev| (ev.slot(), ev.signature().to_string())).collect();
+            keys.sort();
+            keys
+        };
+        assert_eq!(keyed(&repo.stored.events()), keyed(&[transfer(7), transfer_at(107, 99), transfer_at(108, 99)]));
+    }
+
+    #[tokio::test]
+    async fn null_repository_runs_the_same_path_without_storing() {
+        let repo = Arc::new(FlakyRepo::default());
+        let (tx, rx) = mpsc::channel(16);
+        let parsers: Vec<Box<dyn TransactionParser>> = vec![Box::new(KnownProgramParser)];
+        let mut stored = IngestionPipeline::<FlakyRepo>::new(rx, repo.clone(), parsers, None);
+        run_pipeline(tx, &mut stored, &MIXED_SIGNATURES).await;
+
+        let null = Arc::new(NullRepository::new(0));
+        let (tx, rx) = mpsc::channel(16);
+        let parsers: Vec<Box<dyn TransactionParser>> = vec![Box::new(KnownProgramParser)];
+        let mut counted = IngestionPipeline::<NullRepository>::new(rx, null.clone(), parsers, None);
+        run_pipeline(tx, &mut counted, &MIXED_SIGNATURES).await;
+
+        assert_eq!(repo.stored.events().len(), 3);
+        assert_eq!(null.events_saved(), 3);
+        assert_eq!(counted.metrics().events_persisted(), stored.metrics().events_persisted());
+    }
+
+    /// Emits each transfer twice, the copy under the next instruction index.
+    struct Duplicator;
+
+    impl EventTransformer for Duplicator {
+        fn transform(&self, event: TransactionEvent) -> Vec<TransactionEvent> {
+            let TransactionEvent::SolTransfer(mut copy) = event.clone() else { return vec![event] };
+            copy.instruction_index += 1;
+            vec![event, TransactionEvent::SolTransfer(copy)]
+        }
+
+        fn name(&self) -> &str {
+            "duplicator"
+        }
+    }
+
+    /// Drops every event.
+    struct DropAll;
+
+    impl EventTransformer for DropAll {
+        fn transform(&self, _event: TransactionEvent) -> Vec<TransactionEvent> {
+            Vec::new()
+        }
+
+        fn name(&self) -> &str {
+            "drop-all"
+        }
+    }
+
+    #[tokio::test]
+    async fn duplicating_transformer_enqueues_both_events() {
+        let repo = Arc::new(FlakyRepo::default());
+        let (tx, rx) = mpsc::channel(16);
+        let parsers: Vec<Box<dyn TransactionParser>> = vec![Box::new(KnownProgramParser)];
+        let mut pipeline =
+            IngestionPipeline::<FlakyRepo>::new(rx, repo.clone(), parsers, None).with_transformer(Box::new(Duplicator));
+
+        run_pipeline(tx, &mut pipeline, &["known-1"]).await;
+
+        let mut indices: Vec<_> = repo.stored.events().iter().map(|ev| ev.key().1).collect();
+        indices.sort();
+        assert_eq!(indices, vec![0, 1]);
+    }
+
+    #[test]
+    fn transformers_run_in_order_on_each_others_output() {
+        let (_tx, rx) = mpsc::channel(1);
+        let duplicate_then_drop = IngestionPipeline::<FlakyRepo>::new(rx, Arc::new(FlakyRepo::default()), Vec::new(), None)
+            .with_transformer(Box::new(Duplicator))
+            .with_transformer(Box::new(DropAll));
+        let (_tx, rx) = mpsc::channel(1);
+        let twice = IngestionPipeline::<FlakyRepo>::new(rx, Arc::new(FlakyRepo::default()), Vec::new(), None)
+            .with_transformer(Box::new(Duplicator))
+            .with_transformer(Box::new(Duplicator));
+
+        assert!(duplicate_then_drop.transform(vec![transfer(1)]).is_empty());
+        // 1 -> 2 -> 4; the second copy of index 1 collides with the first's copy
+        let indices: Vec<_> = twice.transform(vec![transfer(1)]).iter().map(|ev| ev.key().1).collect();
+        assert_eq!(indices, vec![0, 1, 1, 2]);
+    }
+
+    /// Emits every event twice under the same key, like two sources fanning in.
+    struct Repeat;
+
+    impl EventTransformer for Repeat {
+        fn transform(&self, event: TransactionEvent) -> Vec<TransactionEvent> {
+            vec![event.clone(), event]
+        }
+
+        fn name(&self) -> &str {
+            "repeat"
+        }
+    }
+
+    #[tokio::test]
+    async fn duplicates_within_a_batch_reach_the_repository_once() {
+        let repo = Arc::new(FlakyRepo::default());
+        let (tx, rx) = mpsc::channel(16);
+        let parsers: Vec<Box<dyn TransactionParser>> = vec![Box::new(KnownProgramParser)];
+        let mut pipeline = IngestionPipeline::<FlakyRepo>::new(rx, repo.clone(), parsers, None)
+            .with_config(PipelineConfig { min_batch_size: 100, max_batch_size: 100, ..Default::default() })
+            .with_transformer(Box::new(Repeat));
+
+        run_pipeline(tx, &mut pipeline, &["known-1", "known-22"]).await;
+
+        // Four events collected, two distinct keys, one commit
+        assert_eq!(*repo.committed.lock().unwrap(), vec![2]);
+        assert_eq!(repo.stored.events().len(), 2);
     }
 }
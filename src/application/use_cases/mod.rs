@@ -1,2 +1,4 @@
+pub mod fetch;
 pub mod ingest;
+pub use fetch::*;
 pub use ingest::*;
@@ -0,0 +1,162 @@
+use clap::{Parser, Subcommand, ValueEnum};
+
+/// Solana DEX indexer. Settings come from the environment (and `.env`); a
+/// subcommand's flags take precedence over the variables they stand in for.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// Print the stdout sink's lines without ANSI colors. Same as setting NO_COLOR.
+    #[arg(long, global = true)]
+    pub no_color: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Subcommand)]
+pub enum Command {
+    /// Index the configured source until it ends. The default.
+    Run,
+    /// Index an inclusive slot range, then exit.
+    Backfill {
+        #[arg(long)]
+        from_slot: u64,
+        #[arg(long)]
+        to_slot: u64,
+        /// Replay the range over gRPC (`from_slot`) or fetch its blocks over RPC.
+        #[arg(long, value_enum, default_value_t = BackfillSource::Grpc)]
+        source: BackfillSource,
+    },
+    /// Apply pending database migrations and exit.
+    Migrate,
+    /// Fill in mints left unknown in already-indexed rows, then exit. Resumes an
+    /// interrupted run.
+    BackfillMints {
+        #[arg(long, default_value_t = 100)]
+        batch_size: usize,
+        /// Cap on RPC account lookups per second.
+        #[arg(long, default_value_t = 5)]
+        requests_per_sec: u32,
+    },
+    /// Re-parse the transactions in a parse-error log and persist what now parses.
+    Replay {
+        #[arg(long)]
+        file: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum BackfillSource {
+    Grpc,
+    Rpc,
+}
+
+impl Cli {
+    pub fn command(&self) -> Command {
+        self.command.clone().unwrap_or(Command::Run)
+    }
+
+    /// The subcommand's overrides plus those of the global flags.
+    pub fn env_overrides(&self) -> Vec<(&'static str, String)> {
+        let mut overrides = self.command().env_overrides();
+        if self.no_color {
+            overrides.push(("NO_COLOR", "1".to_string()));
+        }
+        overrides
+    }
+}
+
+impl Command {
+    /// Environment variables the subcommand sets, to be read ahead of the
+    /// process environment. An empty value unsets the variable.
+    pub fn env_overrides(&self) -> Vec<(&'static str, String)> {
+        match self {
+            Command::Run | Command::Migrate | Command::BackfillMints { .. } => Vec::new(),
+            Command::Backfill { from_slot, to_slot, source } => {
+                let range = format!("{}-{}", from_slot, to_slot);
+                match source {
+                    BackfillSource::Grpc => vec![("SOURCE_TYPE", "grpc".to_string()), ("GRPC_SLOT_RANGE", range)],
+                    BackfillSource::Rpc => vec![("SOURCE_TYPE", "rpc".to_string()), ("RPC_SLOT_RANGE", range)],
+                }
+            }
+            Command::Replay { file } => vec![
+                ("SOURCE_TYPE", "replay".to_string()),
+                ("REPLAY_FILE", file.clone()),
+                // Failures during a replay must not append to the file being read
+                ("PARSE_ERROR_LOG", String::new()),
+            ],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        application::AppResult,
+        config::{AppConfig, SourceConfig},
+    };
+
+    /// The config a parsed command line resolves to, over a minimal environment.
+    fn resolve(args: &[&str]) -> AppResult<AppConfig> {
+        let cli = Cli::try_parse_from(args).unwrap();
+        let overrides = cli.env_overrides();
+        let base = [("SOURCE_TYPE", "file"), ("RPC_URL", "http://rpc.test"), ("GRPC_URL", "http://grpc.test")];
+        AppConfig::from_lookup(|name| match overrides.iter().find(|(key, _)| *key == name) {
+            Some((_, value)) => Some(value.clone()),
+            None => base.iter().find(|(key, _)| *key == name).map(|(_, value)| value.to_string()),
+        })
+    }
+
+    fn config(args: &[&str]) -> AppConfig {
+        resolve(args).unwrap()
+    }
+
+    #[test]
+    fn no_subcommand_runs() {
+        let cli = Cli::try_parse_from(["indexer"]).unwrap();
+        assert_eq!(cli.command(), Command::Run);
+        assert!(matches!(config(&["indexer"]).source, SourceConfig::File));
+    }
+
+    #[test]
+    fn backfill_defaults_to_a_grpc_slot_range() {
+        let cli = Cli::try_parse_from(["indexer", "backfill", "--from-slot", "95", "--to-slot", "105"]).unwrap();
+        assert_eq!(cli.command(), Command::Backfill { from_slot: 95, to_slot: 105, source: BackfillSource::Grpc });
+
+        match config(&["indexer", "backfill", "--from-slot", "95", "--to-slot", "105"]).source {
+            SourceConfig::Grpc(grpc) => assert_eq!(grpc.slot_range, Some((95, 105))),
+            other => panic!("expected a gRPC source, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn backfill_over_rpc_fetches_the_range() {
+        let args = ["indexer", "backfill", "--from-slot", "95", "--to-slot", "105", "--source", "rpc"];
+        assert!(matches!(config(&args).source, SourceConfig::Rpc { start: 95, end: 105 }));
+    }
+
+    #[test]
+    fn backfill_rejects_an_inverted_range() {
+        assert!(resolve(&["indexer", "backfill", "--from-slot", "105", "--to-slot", "95"]).is_err());
+    }
+
+    #[test]
+    fn backfill_requires_both_bounds() {
+        assert!(Cli::try_parse_from(["indexer", "backfill", "--from-slot", "95"]).is_err());
+    }
+
+    #[test]
+    fn replay_reads_the_file_and_stops_logging_parse_errors() {
+        let cli = Cli::try_parse_from(["indexer", "replay", "--file", "errors.jsonl"]).unwrap();
+        assert_eq!(cli.command(), Command::Replay { file: "errors.jsonl".to_string() });
+        assert!(matches!(config(&["indexer", "replay", "--file", "errors.jsonl"]).source, SourceConfig::Replay(ref f) if f == "errors.jsonl"));
+        assert!(cli.env_overrides().contains(&("PARSE_ERROR_LOG", String::new())));
+    }
+
+    #[test]
+    fn migrate_and_no_color_parse() {
+        let cli = Cli::try_parse_from(["indexer", "migrate", "--no-color"]).unwrap();
+        assert_eq!(cli.command(), Command::Migrate);
+        assert_eq!(cli.env_overrides(), vec![("NO_COLOR", "1".to_string())]);
+    }
+}
@@ -0,0 +1,522 @@
+use std::{net::SocketAddr, str::FromStr, time::Duration};
+
+use yellowstone_grpc_proto::geyser::CommitmentLevel;
+
+use crate::{
+    adapters::{
+        ChannelOptions, Compression, DEFAULT_ACQUIRE_TIMEOUT, DEFAULT_MAX_CONNECTIONS, DeliveryPolicy, RepositoryKind, SplTransferMode,
+        StdoutFormat, TransactionFilter, UpsertMode, WebhookConfig, parse_commitment,
+    },
+    application::{AppError, AppResult, FetcherConfig, MintDecimals, PipelineConfig, SuspiciousSwapAction, SwapValidator},
+    infrastructure::HealthConfig,
+};
+
+#[derive(Debug, Clone)]
+pub enum SourceConfig {
+    File,
+    Grpc(GrpcConfig),
+    /// Parse-error log to feed back through the parsers.
+    Replay(String),
+    /// Historical blocks over `RPC_URL` for an inclusive slot range.
+    Rpc { start: u64, end: u64 },
+    #[cfg(feature = "synthetic")]
+    Synthetic(crate::adapters::SyntheticConfig),
+}
+
+#[derive(Debug, Clone)]
+pub struct GrpcConfig {
+    pub url: String,
+    pub token: Option<String>,
+    pub commitment: CommitmentLevel,
+    /// One subscription per filter, merged into a single stream.
+    pub filters: Vec<TransactionFilter>,
+    /// Inclusive slot window for a bounded backfill.
+    pub slot_range: Option<(u64, u64)>,
+    pub channel: ChannelOptions,
+}
+
+#[derive(Debug, Clone)]
+pub struct DatabaseConfig {
+    pub url: String,
+    /// Backend picked from the URL's scheme.
+    pub kind: RepositoryKind,
+    pub max_connections: u32,
+    pub acquire_timeout: Duration,
+    pub upsert_mode: UpsertMode,
+    /// `None` sizes the writer pool from the connection pool.
+    pub writers: Option<usize>,
+    /// Hold events in `pending_events` until their slot is finalized.
+    pub two_phase: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct SeenSignaturesConfig {
+    pub path: String,
+    /// Signatures the filter is sized for; only used when creating a new file.
+    pub capacity: usize,
+    pub fp_rate: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct TelegramConfig {
+    pub bot_token: String,
+    pub chat_id: String,
+}
+
+/// Every setting `main` needs, read and validated from the environment in one place.
+#[derive(Debug, Clone)]
+pub struct AppConfig {
+    pub source: SourceConfig,
+    pub rpc_url: String,
+    /// `None` runs without persistence.
+    pub database: Option<DatabaseConfig>,
+    pub telegram: Option<TelegramConfig>,
+    pub key_cache_size: Option<usize>,
+    pub spl_transfer_mode: SplTransferMode,
+    /// `writers` is filled in by `main` once the repository is known.
+    pub pipeline: PipelineConfig,
+    pub fetcher: FetcherConfig,
+    pub disabled_parsers: Vec<String>,
+    pub health_addr: Option<SocketAddr>,
+    pub health: HealthConfig,
+    pub webhook: Option<WebhookConfig>,
+    /// Print every persisted event to stdout; `None` disables it.
+    pub stdout_sink: Option<StdoutFormat>,
+    /// ANSI colors in the stdout sink; off when `NO_COLOR` is set.
+    pub stdout_color: bool,
+    /// JSON-lines file receiving every persisted event; `None` disables it.
+    pub jsonl_sink: Option<String>,
+    pub jsonl_compression: Compression,
+    pub sink_policy: DeliveryPolicy,
+    /// Fraction of transactions kept; `None` keeps everything.
+    pub sample_rate: Option<f64>,
+    /// Swap sanity checks: what to do with failures (`None` skips the checks) and the ratio bound.
+    pub swap_validation: Option<SuspiciousSwapAction>,
+    pub swap_max_ratio: f64,
+    pub wal_path: Option<String>,
+    /// Spill file for events that do not fit the in-memory buffer. `None` makes
+    /// the source wait for room instead.
+    pub buffer_spill_path: Option<String>,
+    /// JSON-lines file receiving the raw transaction behind every parser failure.
+    pub parse_error_log: Option<String>,
+    /// JSON-lines file receiving one swap summary per completed slot.
+    pub slot_summary_log: Option<String>,
+    /// Count transactions per invoked program and log the most-unparsed ones.
+    pub program_coverage: bool,
+    /// Per-mint swap volume metric and the decimals used for it; `None` disables it.
+    pub volume_decimals: Option<MintDecimals>,
+    /// JSON file of extra instruction discriminators, keyed by parser name.
+    pub discriminators_path: Option<String>,
+    pub seen_signatures: Option<SeenSignaturesConfig>,
+    /// Only these signatures are indexed; empty means all.
+    pub signature_allow: Vec<String>,
+    pub signature_deny: Vec<String>,
+}
+
+impl AppConfig {
+    pub fn from_env() -> AppResult<Self> {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    /// Like `from_env`, but `overrides` are read ahead of the process environment.
+    pub fn from_env_with(overrides: &[(&str, String)]) -> AppResult<Self> {
+        Self::from_lookup(|name| match overrides.iter().find(|(key, _)| *key == name) {
+            Some((_, value)) => Some(value.clone()),
+            None => std::env::var(name).ok(),
+        })
+    }
+
+    /// Load from any key/value lookup; `from_env` passes the process environment.
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> AppResult<Self> {
+        let env = Env(&lookup);
+
+        let source = match env.get("SOURCE_TYPE").as_deref() {
+            Some("file") => SourceConfig::File,
+            Some("grpc") => SourceConfig::Grpc(grpc_config(&env)?),
+            Some("replay") => SourceConfig::Replay(env.require("REPLAY_FILE")?),
+            Some("rpc") => match slot_range(&env, "RPC_SLOT_RANGE")? {
+                Some((start, end)) => SourceConfig::Rpc { start, end },
+                None => return Err(invalid("SOURCE_TYPE=rpc requires RPC_SLOT_RANGE (e.g. 250000000-250001000)")),
+            },
+            #[cfg(feature = "synthetic")]
+            Some("synthetic") => SourceConfig::Synthetic(synthetic_config(&env)?),
+            Some(other) => return Err(AppError::InvalidSource(format!("unknown SOURCE_TYPE `{}`", other))),
+            None => return Err(AppError::InvalidSource("SOURCE_TYPE not set".to_string())),
+        };
+
+        let rpc_url = env.require("RPC_URL")?;
+
+        let database = match env.get("DATABASE_URL") {
+            Some(url) => Some(DatabaseConfig {
+                kind: RepositoryKind::from_url(&url)?,
+                url,
+                max_connections: env.parse("DB_MAX_CONNECTIONS")?.unwrap_or(DEFAULT_MAX_CONNECTIONS),
+                acquire_timeout: env.parse("DB_ACQUIRE_TIMEOUT_SECS")?.map(Duration::from_secs).unwrap_or(DEFAULT_ACQUIRE_TIMEOUT),
+                upsert_mode: env.parse("DB_UPSERT_MODE")?.unwrap_or_default(),
+                writers: env.parse("DB_WRITERS")?,
+                two_phase: env.get("TWO_PHASE_PERSISTENCE").as_deref() == Some("true"),
+            }),
+            None => None,
+        };
+        let two_phase = database.as_ref().is_some_and(|db| db.two_phase);
+        if two_phase && database.as_ref().is_some_and(|db| db.kind != RepositoryKind::Postgres) {
+            return Err(invalid("TWO_PHASE_PERSISTENCE needs a postgres:// DATABASE_URL"));
+        }
+        // Only a gRPC source below finalized commitment reports finalized slots
+        if two_phase && !matches!(&source, SourceConfig::Grpc(grpc) if grpc.commitment != CommitmentLevel::Finalized) {
+            return Err(invalid("TWO_PHASE_PERSISTENCE needs SOURCE_TYPE=grpc with GRPC_COMMITMENT processed or confirmed"));
+        }
+
+        let telegram = match (env.get("TELEGRAM_BOT_TOKEN"), env.get("TELEGRAM_CHAT_ID")) {
+            (Some(bot_token), Some(chat_id)) => Some(TelegramConfig { bot_token, chat_id }),
+            (None, None) => None,
+            _ => return Err(invalid("TELEGRAM_BOT_TOKEN and TELEGRAM_CHAT_ID must be set together")),
+        };
+
+        let min_batch_size = env.parse("MIN_BATCH_SIZE")?.unwrap_or(100);
+        let max_batch_size = env.parse("MAX_BATCH_SIZE")?.unwrap_or(min_batch_size.max(100));
+        if min_batch_size == 0 || min_batch_size > max_batch_size {
+            return Err(invalid(format!(
+                "MIN_BATCH_SIZE ({}) must be at least 1 and no more than MAX_BATCH_SIZE ({})",
+                min_batch_size, max_batch_size
+            )));
+        }
+        let pipeline = PipelineConfig {
+            dedup_capacity: env.parse("DEDUP_CACHE_SIZE")?.unwrap_or(100_000),
+            dedup_ttl: env.parse("DEDUP_TTL_SECS")?.map(Duration::from_secs),
+            skip_failed: env.get("INDEX_FAILED_TXS").as_deref() != Some("true"),
+            reorg_tolerance_slots: env.parse("REORG_TOLERANCE_SLOTS")?,
+            max_in_flight_batches: env.parse("MAX_IN_FLIGHT_BATCHES")?,
+            max_in_flight_events: env.parse("MAX_IN_FLIGHT_EVENTS")?,
+            dry_run: env.get("DRY_RUN").as_deref() == Some("true"),
+            best_effort_writes: env.get("BEST_EFFORT_WRITES").as_deref() == Some("true"),
+            max_events_per_sec: env.parse("MAX_EVENTS_PER_SEC")?,
+            max_rows_per_commit: env.parse::<usize>("MAX_ROWS_PER_COMMIT")?.filter(|&n| n > 0),
+            two_phase,
+            min_batch_size,
+            max_batch_size,
+            ..Default::default()
+        };
+
+        let health = HealthConfig {
+            max_event_age: Duration::from_secs(env.parse("HEALTH_MAX_EVENT_AGE_SECS")?.unwrap_or(60)),
+            ..Default::default()
+        };
+
+        let webhook = match env.get("WEBHOOK_URL") {
+            Some(url) => {
+                let mut webhook = WebhookConfig::new(url);
+                webhook.secret = env.get("WEBHOOK_SECRET");
+                if let Some(secs) = env.parse("WEBHOOK_TIMEOUT_SECS")? {
+                    webhook.timeout = Duration::from_secs(secs);
+                }
+                if let Some(retries) = env.parse("WEBHOOK_MAX_RETRIES")? {
+                    webhook.max_retries = retries;
+                }
+                if let Some(format) = env.parse("WEBHOOK_FORMAT")? {
+                    webhook.format = format;
+                }
+                if let Some(compression) = env.parse("WEBHOOK_COMPRESSION")? {
+                    webhook.compression = compression;
+                }
+                Some(webhook)
+            }
+            None => None,
+        };
+
+        let sink_policy = match env.get("SINK_POLICY").as_deref() {
+            None | Some("all") => DeliveryPolicy::All,
+            Some("any") => DeliveryPolicy::Any,
+            Some(other) => return Err(invalid(format!("Unknown SINK_POLICY: {} (expected all | any)", other))),
+        };
+
+        let sample_rate: Option<f64> = env.parse("SAMPLE_RATE")?;
+        if let Some(rate) = sample_rate.filter(|r| !(0.0..=1.0).contains(r)) {
+            return Err(invalid(format!("SAMPLE_RATE must be within 0.0..=1.0, got {}", rate)));
+        }
+
+        Ok(Self {
+            source,
+            rpc_url,
+            database,
+            telegram,
+            key_cache_size: env.parse("KEY_CACHE_SIZE")?,
+            spl_transfer_mode: env.parse("SPL_TRANSFER_MODE")?.unwrap_or_default(),
+            pipeline,
+            fetcher: FetcherConfig {
+                stall_timeout: env.parse("SOURCE_STALL_TIMEOUT_SECS")?.map(Duration::from_secs),
+                reconnect_on_stall: env.get("SOURCE_RECONNECT_ON_STALL").as_deref() == Some("true"),
+            },
+            disabled_parsers: env.list("DISABLED_PARSERS"),
+            health_addr: env.parse("HEALTH_ADDR")?,
+            health,
+            webhook,
+            stdout_sink: env.parse("STDOUT_SINK")?,
+            stdout_color: env.get("NO_COLOR").is_none(),
+            jsonl_sink: env.get("JSONL_SINK_PATH"),
+            jsonl_compression: env.parse("JSONL_COMPRESSION")?.unwrap_or_default(),
+            sink_policy,
+            sample_rate: sample_rate.filter(|&r| r < 1.0),
+            swap_validation: match env.get("SWAP_VALIDATION").as_deref() {
+                None | Some("off") => None,
+                Some(v) => Some(v.parse::<SuspiciousSwapAction>().map_err(|_| invalid(format!("Unknown SWAP_VALIDATION: {} (expected off | drop | flag)", v)))?),
+            },
+            swap_max_ratio: env.parse("SWAP_MAX_RATIO")?.unwrap_or(SwapValidator::DEFAULT_MAX_RATIO),
+            wal_path: env.get("WAL_PATH"),
+            buffer_spill_path: env.get("BUFFER_SPILL_PATH"),
+            parse_error_log: env.get("PARSE_ERROR_LOG"),
+            slot_summary_log: env.get("SLOT_SUMMARY_LOG"),
+            program_coverage: env.get("PROGRAM_COVERAGE").as_deref() == Some("true"),
+            volume_decimals: if env.get("VOLUME_METRICS").as_deref() == Some("true") {
+                Some(mint_decimals(&env)?)
+            } else {
+                None
+            },
+            discriminators_path: env.get("DISCRIMINATORS_PATH"),
+            seen_signatures: match env.get("SEEN_SIGNATURES_PATH") {
+                Some(path) => Some(SeenSignaturesConfig {
+                    path,
+                    capacity: env.parse("SEEN_SIGNATURES_CAPACITY")?.unwrap_or(10_000_000),
+                    fp_rate: env.parse("SEEN_SIGNATURES_FP_RATE")?.unwrap_or(0.001),
+                }),
+                None => None,
+            },
+            signature_allow: env.list("SIGNATURE_ALLOW"),
+            signature_deny: env.list("SIGNATURE_DENY"),
+        })
+    }
+}
+
+fn grpc_config(env: &Env) -> AppResult<GrpcConfig> {
+    let url = env
+        .get("GRPC_URL")
+        .ok_or_else(|| invalid("SOURCE_TYPE=grpc requires GRPC_URL (e.g. http://127.0.0.1:10000)"))?;
+
+    let mut filters = if env.get("GRPC_DEX_ONLY").as_deref() == Some("true") {
+        vec![TransactionFilter::dex_programs()]
+    } else if let Some(groups) = env.get("GRPC_SUBSCRIPTIONS") {
+        // `a,b;c` is two subscriptions: one including a or b, one including c
+        let exclude = env.list("GRPC_ACCOUNT_EXCLUDE");
+        let filters: Vec<_> = groups
+            .split(';')
+            .map(|group| group.split(',').map(str::trim).filter(|s| !s.is_empty()).map(str::to_string).collect::<Vec<_>>())
+            .filter(|include| !include.is_empty())
+            .map(|account_include| TransactionFilter { account_include, account_exclude: exclude.clone(), ..Default::default() })
+            .collect();
+        if filters.is_empty() {
+            return Err(invalid("GRPC_SUBSCRIPTIONS has no account groups"));
+        }
+        filters
+    } else {
+        vec![TransactionFilter {
+            account_include: env.list("GRPC_ACCOUNT_INCLUDE"),
+            account_required: env.list("GRPC_ACCOUNT_REQUIRED"),
+            account_exclude: env.list("GRPC_ACCOUNT_EXCLUDE"),
+            ..Default::default()
+        }]
+    };
+    // Failed transactions are filtered server-side unless the pipeline wants them
+    let include_failed = env.get("INDEX_FAILED_TXS").as_deref() == Some("true");
+    for filter in &mut filters {
+        filter.include_failed = include_failed;
+    }
+
+    let slot_range = slot_range(env, "GRPC_SLOT_RANGE")?;
+
+    let defaults = ChannelOptions::default();
+    let channel = ChannelOptions {
+        max_decoding_message_size: env.parse("GRPC_MAX_MESSAGE_BYTES")?.unwrap_or(defaults.max_decoding_message_size),
+        keepalive_interval: env.parse("GRPC_KEEPALIVE_SECS")?.map(Duration::from_secs).unwrap_or(defaults.keepalive_interval),
+        keepalive_timeout: env.parse("GRPC_KEEPALIVE_TIMEOUT_SECS")?.map(Duration::from_secs).unwrap_or(defaults.keepalive_timeout),
+        connect_timeout: env.parse("GRPC_CONNECT_TIMEOUT_SECS")?.map(Duration::from_secs).unwrap_or(defaults.connect_timeout),
+    };
+
+    Ok(GrpcConfig {
+        url,
+        token: env.get("GRPC_TOKEN"),
+        commitment: env.parse_with("GRPC_COMMITMENT", parse_commitment)?.unwrap_or(CommitmentLevel::Confirmed),
+        filters,
+        slot_range,
+        channel,
+    })
+}
+
+/// Inclusive `start-end` slot window, e.g. `250000000-250001000`.
+fn slot_range(env: &Env, name: &str) -> AppResult<Option<(u64, u64)>> {
+    env.parse_with(name, |range| {
+        let (start, end) = range.split_once('-')?;
+        let (start, end) = (start.trim().parse::<u64>().ok()?, end.trim().parse::<u64>().ok()?);
+        (start <= end).then_some((start, end))
+    })
+}
+
+/// Load-test source settings: SYNTHETIC_TPS, SYNTHETIC_COUNT and SYNTHETIC_MIX
+/// (`program:weight` pairs, e.g. `spl_token:3,raydium_amm:1`).
+#[cfg(feature = "synthetic")]
+fn synthetic_config(env: &Env) -> AppResult<crate::adapters::SyntheticConfig> {
+    use crate::adapters::{SyntheticConfig, SyntheticProgram};
+
+    let mut config = SyntheticConfig::default();
+    if let Some(tps) = env.parse("SYNTHETIC_TPS")? {
+        config.tps = tps;
+    }
+    config.max_count = env.parse("SYNTHETIC_COUNT")?;
+    let mix = env.list("SYNTHETIC_MIX");
+    if !mix.is_empty() {
+        config.mix = mix
+            .iter()
+            .map(|entry| {
+                let (name, weight) = entry.split_once(':').unwrap_or((entry, "1"));
+                let program = name
+                    .parse::<SyntheticProgram>()
+                    .map_err(|_| invalid(format!("Unknown SYNTHETIC_MIX program: {}", name)))?;
+                let weight = weight.parse().map_err(|_| invalid(format!("Invalid SYNTHETIC_MIX weight: {}", entry)))?;
+                Ok((program, weight))
+            })
+            .collect::<AppResult<_>>()?;
+    }
+    Ok(config)
+}
+
+/// The built-in mint decimals plus MINT_DECIMALS (`mint:decimals` pairs).
+fn mint_decimals(env: &Env) -> AppResult<MintDecimals> {
+    let mut registry = MintDecimals::new();
+    for entry in env.list("MINT_DECIMALS") {
+        let (mint, decimals) = entry
+            .split_once(':')
+            .ok_or_else(|| invalid(format!("MINT_DECIMALS entry needs mint:decimals, got {}", entry)))?;
+        let decimals = decimals.parse().map_err(|_| invalid(format!("Invalid MINT_DECIMALS decimals: {}", entry)))?;
+        registry.insert(mint, decimals);
+    }
+    Ok(registry)
+}
+
+fn invalid(reason: impl Into<String>) -> AppError {
+    AppError::ConfigError(reason.into())
+}
+
+/// Typed reads over a key/value lookup. Empty values count as unset.
+struct Env<'a>(&'a dyn Fn(&str) -> Option<String>);
+
+impl Env<'_> {
+    fn get(&self, name: &str) -> Option<String> {
+        (self.0)(name).filter(|v| !v.trim().is_empty())
+    }
+
+    fn require(&self, name: &str) -> AppResult<String> {
+        self.get(name).ok_or_else(|| invalid(format!("{} is required", name)))
+    }
+
+    fn parse<T: FromStr>(&self, name: &str) -> AppResult<Option<T>> {
+        self.parse_with(name, |v| v.trim().parse().ok())
+    }
+
+    fn parse_with<T>(&self, name: &str, parse: impl FnOnce(&str) -> Option<T>) -> AppResult<Option<T>> {
+        match self.get(name) {
+            Some(v) => parse(&v).map(Some).ok_or_else(|| invalid(format!("Invalid {}: {}", name, v))),
+            None => Ok(None),
+        }
+    }
+
+    /// Comma-separated list; unset or empty gives an empty list.
+    fn list(&self, name: &str) -> Vec<String> {
+        self.get(name)
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(vars: &[(&str, &str)]) -> AppResult<AppConfig> {
+        AppConfig::from_lookup(|name| vars.iter().find(|(key, _)| *key == name).map(|(_, v)| v.to_string()))
+    }
+
+    fn config_error(vars: &[(&str, &str)]) -> String {
+        match load(vars) {
+            Err(AppError::ConfigError(reason)) => reason,
+            other => panic!("expected a ConfigError, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn grpc_source_without_an_endpoint_is_a_config_error() {
+        let reason = config_error(&[("SOURCE_TYPE", "grpc"), ("RPC_URL", "http://rpc")]);
+
+        assert!(reason.contains("GRPC_URL"), "{}", reason);
+        assert!(reason.contains("SOURCE_TYPE=grpc"), "{}", reason);
+    }
+
+    #[test]
+    fn grpc_source_with_an_endpoint_loads_with_defaults() {
+        let config = load(&[("SOURCE_TYPE", "grpc"), ("GRPC_URL", "http://127.0.0.1:10000"), ("RPC_URL", "http://rpc")]).unwrap();
+
+        let SourceConfig::Grpc(grpc) = &config.source else { panic!("expected a gRPC source, got {:?}", config.source) };
+        assert_eq!(grpc.url, "http://127.0.0.1:10000");
+        assert_eq!(grpc.commitment, CommitmentLevel::Confirmed);
+        assert!(config.database.is_none());
+        assert_eq!((config.pipeline.min_batch_size, config.pipeline.max_batch_size), (100, 100));
+    }
+
+    #[test]
+    fn grpc_channel_limits_are_read_from_the_environment() {
+        let config = load(&[
+            ("SOURCE_TYPE", "grpc"),
+            ("GRPC_URL", "http://127.0.0.1:10000"),
+            ("RPC_URL", "http://rpc"),
+            ("GRPC_MAX_MESSAGE_BYTES", "134217728"),
+            ("GRPC_KEEPALIVE_SECS", "15"),
+            ("GRPC_KEEPALIVE_TIMEOUT_SECS", "5"),
+            ("GRPC_CONNECT_TIMEOUT_SECS", "3"),
+        ])
+        .unwrap();
+
+        let SourceConfig::Grpc(grpc) = &config.source else { panic!("expected a gRPC source, got {:?}", config.source) };
+        assert_eq!(grpc.channel, ChannelOptions {
+            max_decoding_message_size: 128 * 1024 * 1024,
+            keepalive_interval: Duration::from_secs(15),
+            keepalive_timeout: Duration::from_secs(5),
+            connect_timeout: Duration::from_secs(3),
+        });
+        assert!(config_error(&[
+            ("SOURCE_TYPE", "grpc"),
+            ("GRPC_URL", "http://127.0.0.1:10000"),
+            ("RPC_URL", "http://rpc"),
+            ("GRPC_KEEPALIVE_SECS", "soon"),
+        ])
+        .contains("GRPC_KEEPALIVE_SECS"));
+    }
+
+    #[test]
+    fn invalid_combinations_are_rejected() {
+        let base = [("SOURCE_TYPE", "file"), ("RPC_URL", "http://rpc")];
+        let with = |extra: &[(&'static str, &'static str)]| -> Vec<(&'static str, &'static str)> {
+            base.iter().chain(extra).copied().collect()
+        };
+
+        assert!(config_error(&with(&[("TELEGRAM_BOT_TOKEN", "token")])).contains("TELEGRAM_CHAT_ID"));
+        assert!(config_error(&with(&[("MIN_BATCH_SIZE", "500"), ("MAX_BATCH_SIZE", "100")])).contains("MIN_BATCH_SIZE"));
+        assert!(config_error(&with(&[("SAMPLE_RATE", "1.5")])).contains("SAMPLE_RATE"));
+        assert!(config_error(&with(&[("DB_MAX_CONNECTIONS", "many")])).contains("DB_MAX_CONNECTIONS"));
+        assert!(config_error(&with(&[("DATABASE_URL", "memory://"), ("TWO_PHASE_PERSISTENCE", "true")])).contains("postgres://"));
+    }
+
+    #[test]
+    fn missing_or_unknown_source_is_an_invalid_source() {
+        assert!(matches!(load(&[("RPC_URL", "http://rpc")]), Err(AppError::InvalidSource(_))));
+        assert!(matches!(load(&[("SOURCE_TYPE", "kafka"), ("RPC_URL", "http://rpc")]), Err(AppError::InvalidSource(_))));
+    }
+
+    #[test]
+    fn empty_values_count_as_unset() {
+        let reason = config_error(&[("SOURCE_TYPE", "grpc"), ("GRPC_URL", "  "), ("RPC_URL", "http://rpc")]);
+
+        assert!(reason.contains("GRPC_URL"), "{}", reason);
+    }
+}
@@ -0,0 +1,50 @@
+//! Events shared by the unit tests. Seeds fill the signature bytes, so events
+//! built from different seeds never share a key.
+
+use crate::domain::{Pubkey58, RaydiumPoolType, RaydiumSwapEvent, Signature58, SolTransfer, TransactionEvent};
+
+/// A 1 000-lamport transfer at slot 10, at top level of instruction 0.
+pub fn sol_transfer(seed: u8) -> SolTransfer {
+    SolTransfer {
+        from: Pubkey58::from([1u8; 32]),
+        to: Pubkey58::from([2u8; 32]),
+        slot: 10,
+        block_time: None,
+        lamports: 1_000,
+        signature: Signature58::from([seed; 64]),
+        instruction_index: 0,
+        inner_index: None,
+    }
+}
+
+pub fn transfer(seed: u8) -> TransactionEvent {
+    TransactionEvent::SolTransfer(sol_transfer(seed))
+}
+
+pub fn transfer_at(seed: u8, slot: u64) -> TransactionEvent {
+    TransactionEvent::SolTransfer(SolTransfer { slot, ..sol_transfer(seed) })
+}
+
+/// An AMM v4 swap of `MintA` for `MintB` at slot 10, at top level of instruction 0.
+pub fn raydium_swap(seed: u8, amount_in: u64, amount_received: u64) -> RaydiumSwapEvent {
+    RaydiumSwapEvent {
+        pool_type: RaydiumPoolType::AmmV4,
+        amm_pool: "pool".to_string(),
+        signer: "signer".to_string(),
+        amount_in,
+        min_amount_out: 0,
+        amount_received,
+        mint_source: "MintA".to_string(),
+        mint_destination: "MintB".to_string(),
+        slot: 10,
+        block_time: None,
+        signature: Signature58::from([seed; 64]),
+        instruction_index: 0,
+        inner_index: None,
+        cpi_depth: 0,
+    }
+}
+
+pub fn swap(seed: u8, amount_in: u64, amount_received: u64) -> TransactionEvent {
+    TransactionEvent::RaydiumSwap(raydium_swap(seed, amount_in, amount_received))
+}
@@ -0,0 +1,178 @@
+use std::{fmt, ops::Deref};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::domain::SIGNATURE_LEN;
+
+pub const PUBKEY_LEN: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("`{value}` is not a base58 {kind} ({expected} bytes)")]
+pub struct InvalidBase58 {
+    pub kind: &'static str,
+    pub expected: usize,
+    pub value: String,
+}
+
+/// Decodes `value` and checks it is exactly `N` bytes.
+fn decode<const N: usize>(value: &str, kind: &'static str) -> Result<[u8; N], InvalidBase58> {
+    let mut out = [0u8; N];
+    match bs58::decode(value).onto(&mut out) {
+        Ok(len) if len == N => Ok(out),
+        _ => Err(InvalidBase58 { kind, expected: N, value: value.to_string() }),
+    }
+}
+
+macro_rules! base58_newtype {
+    ($(#[$doc:meta])* $name:ident, $len:expr, $kind:literal) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+        #[serde(transparent)]
+        pub struct $name(String);
+
+        impl $name {
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+
+            pub fn into_string(self) -> String {
+                self.0
+            }
+
+            /// The raw bytes. Cannot fail: the constructors only accept valid values.
+            pub fn to_bytes(&self) -> [u8; $len] {
+                decode::<$len>(&self.0, $kind).expect(concat!("validated ", $kind))
+            }
+        }
+
+        impl TryFrom<&str> for $name {
+            type Error = InvalidBase58;
+
+            fn try_from(value: &str) -> Result<Self, Self::Error> {
+                decode::<$len>(value, $kind)?;
+                Ok(Self(value.to_string()))
+            }
+        }
+
+        impl TryFrom<String> for $name {
+            type Error = InvalidBase58;
+
+            fn try_from(value: String) -> Result<Self, Self::Error> {
+                decode::<$len>(&value, $kind)?;
+                Ok(Self(value))
+            }
+        }
+
+        impl TryFrom<&[u8]> for $name {
+            type Error = InvalidBase58;
+
+            fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+                <[u8; $len]>::try_from(bytes)
+                    .map(Self::from)
+                    .map_err(|_| InvalidBase58 { kind: $kind, expected: $len, value: bs58::encode(bytes).into_string() })
+            }
+        }
+
+        impl From<[u8; $len]> for $name {
+            fn from(bytes: [u8; $len]) -> Self {
+                Self(bs58::encode(bytes).into_string())
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl Deref for $name {
+            type Target = str;
+
+            fn deref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                self.0 == other
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self.0 == *other
+            }
+        }
+    };
+}
+
+base58_newtype!(
+    /// A base58 account address, checked to decode to 32 bytes. Serializes as the
+    /// plain string, so JSON and DB columns look the same as a `String` field.
+    Pubkey58, PUBKEY_LEN, "address"
+);
+
+base58_newtype!(
+    /// A base58 transaction signature, checked to decode to 64 bytes. Serializes
+    /// as the plain string.
+    Signature58, SIGNATURE_LEN, "signature"
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WSOL: &str = "So11111111111111111111111111111111111111112";
+
+    #[test]
+    fn invalid_base58_is_rejected() {
+        // 0, O, I and l are not in the base58 alphabet
+        let err = Pubkey58::try_from("0OIl1111111111111111111111111111111111111111").unwrap_err();
+        assert_eq!((err.kind, err.expected), ("address", PUBKEY_LEN));
+        assert!(Signature58::try_from("not a signature").is_err());
+    }
+
+    #[test]
+    fn wrong_length_is_rejected() {
+        // A valid address is not a valid signature, and vice versa
+        assert!(Signature58::try_from(WSOL).is_err());
+        let signature = Signature58::from([7u8; SIGNATURE_LEN]).to_string();
+        assert!(Pubkey58::try_from(signature.as_str()).is_err());
+        assert!(Pubkey58::try_from(&[1u8; 31][..]).is_err());
+    }
+
+    #[test]
+    fn valid_values_round_trip() {
+        let address = Pubkey58::try_from(WSOL).unwrap();
+        assert_eq!(address, WSOL);
+        assert_eq!(Pubkey58::from(address.to_bytes()), address);
+
+        let signature = Signature58::from([7u8; SIGNATURE_LEN]);
+        assert_eq!(Signature58::try_from(signature.to_string()).unwrap(), signature);
+        assert_eq!(signature.to_bytes(), [7u8; SIGNATURE_LEN]);
+    }
+
+    #[test]
+    fn serializes_as_the_plain_string() {
+        let address = Pubkey58::try_from(WSOL).unwrap();
+
+        let json = serde_json::to_string(&address).unwrap();
+
+        assert_eq!(json, format!("\"{}\"", WSOL));
+        assert_eq!(serde_json::from_str::<Pubkey58>(&json).unwrap(), address);
+    }
+}
@@ -1,7 +1,11 @@
+mod keys;
 mod models;
 mod tokenizer;
 pub mod constants;
+#[cfg(test)]
+pub mod fixtures;
 
+pub use keys::*;
 pub use models::*;
 pub use tokenizer::*;
 pub use constants::*;
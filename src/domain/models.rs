@@ -1,17 +1,26 @@
-use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, str::FromStr};
+
+use serde::{Deserialize, Deserializer, Serialize, de::Error as _};
 use solana_transaction_status::UiTransactionStatusMeta;
 use solana_sdk::transaction::VersionedTransaction;
 
-use crate::domain::TokenTransfer;
+use crate::domain::{Signature58, SolTransfer, TokenTransfer, USDC_MINT, WSOL_MINT};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ChainEvent {
     Transaction(SolanaTransaction),
     BlockMeta {
         slot: u64,
         block_hash: String,
         parent_block_hash: String,
+        /// Unix seconds, when the source knows it.
+        block_time: Option<i64>,
     },
+    /// The source saw `slot` dropped from the chain (a reorg under `Processed`
+    /// or `Confirmed` commitment); anything indexed at or above it is stale.
+    Rollback { slot: u64 },
+    /// `slot` reached finalized commitment; only sent by sources subscribed below it.
+    Finalized { slot: u64 },
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -20,6 +29,89 @@ pub enum TransactionEvent {
     RaydiumSwap(RaydiumSwapEvent),
     JupiterSwap(JupiterSwapEvent),
     PumpFunTrade(PumpFunTrade),
+    PumpAmmSwap(PumpAmmSwapEvent),
+    ComputeBudget(ComputeBudgetEvent),
+    SolTransfer(SolTransfer),
+}
+
+impl TransactionEvent {
+    pub fn signature(&self) -> &str {
+        match self {
+            Self::TokenTransfer(t) => t.signature.as_str(),
+            Self::RaydiumSwap(s) => s.signature.as_str(),
+            Self::JupiterSwap(s) => s.signature.as_str(),
+            Self::PumpFunTrade(t) => t.signature.as_str(),
+            Self::PumpAmmSwap(s) => s.signature.as_str(),
+            Self::ComputeBudget(c) => c.signature.as_str(),
+            Self::SolTransfer(t) => t.signature.as_str(),
+        }
+    }
+
+    pub fn instruction_index(&self) -> u32 {
+        match self {
+            Self::TokenTransfer(t) => t.instruction_index,
+            Self::RaydiumSwap(s) => s.instruction_index,
+            Self::JupiterSwap(s) => s.instruction_index,
+            Self::PumpFunTrade(t) => t.instruction_index,
+            Self::PumpAmmSwap(s) => s.instruction_index,
+            Self::ComputeBudget(c) => c.instruction_index,
+            Self::SolTransfer(t) => t.instruction_index,
+        }
+    }
+
+    pub fn inner_index(&self) -> Option<u32> {
+        match self {
+            Self::TokenTransfer(t) => t.inner_index,
+            Self::RaydiumSwap(s) => s.inner_index,
+            Self::JupiterSwap(s) => s.inner_index,
+            Self::PumpFunTrade(t) => t.inner_index,
+            Self::PumpAmmSwap(s) => s.inner_index,
+            Self::ComputeBudget(_) => None,
+            Self::SolTransfer(t) => t.inner_index,
+        }
+    }
+
+    pub fn slot(&self) -> u64 {
+        match self {
+            Self::TokenTransfer(t) => t.slot,
+            Self::RaydiumSwap(s) => s.slot,
+            Self::JupiterSwap(s) => s.slot,
+            Self::PumpFunTrade(t) => t.slot,
+            Self::PumpAmmSwap(s) => s.slot,
+            Self::ComputeBudget(c) => c.slot,
+            Self::SolTransfer(t) => t.slot,
+        }
+    }
+
+    pub fn block_time(&self) -> Option<i64> {
+        match self {
+            Self::TokenTransfer(t) => t.block_time,
+            Self::RaydiumSwap(s) => s.block_time,
+            Self::JupiterSwap(s) => s.block_time,
+            Self::PumpFunTrade(t) => t.block_time,
+            Self::PumpAmmSwap(s) => s.block_time,
+            Self::ComputeBudget(c) => c.block_time,
+            Self::SolTransfer(t) => t.block_time,
+        }
+    }
+
+    /// Position of the event within the chain: (signature, instruction_index, inner_index).
+    pub fn key(&self) -> (String, u32, Option<u32>) {
+        (self.signature().to_string(), self.instruction_index(), self.inner_index())
+    }
+
+    /// Short variant name, for counters and logs.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::TokenTransfer(_) => "token_transfer",
+            Self::RaydiumSwap(_) => "raydium_swap",
+            Self::JupiterSwap(_) => "jupiter_swap",
+            Self::PumpFunTrade(_) => "pump_fun_trade",
+            Self::PumpAmmSwap(_) => "pump_amm_swap",
+            Self::ComputeBudget(_) => "compute_budget",
+            Self::SolTransfer(_) => "sol_transfer",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,44 +119,315 @@ pub enum SwapEvent {
     Raydium(RaydiumSwapEvent),
     Jupiter(JupiterSwapEvent),
     PumpFun(PumpFunTrade),
+    PumpAmm(PumpAmmSwapEvent),
 }
 
 impl SwapEvent {
+    /// Pump.fun trades and PumpSwap swaps report their quote (SOL) side.
     pub fn amount_in(&self) -> u64 {
         match self {
             Self::Raydium(swap) => swap.amount_in,
             Self::Jupiter(swap) => swap.amount_in,
             Self::PumpFun(trade) => trade.sol_amount,
+            Self::PumpAmm(swap) => swap.quote_amount,
         }
     }
 
     pub fn signature(&self) -> &str {
         match self {
-            Self::Raydium(swap) => &swap.signature,
-            Self::Jupiter(swap) => &swap.signature,
-            Self::PumpFun(trade) => &trade.signature,
+            Self::Raydium(swap) => swap.signature.as_str(),
+            Self::Jupiter(swap) => swap.signature.as_str(),
+            Self::PumpFun(trade) => trade.signature.as_str(),
+            Self::PumpAmm(swap) => swap.signature.as_str(),
+        }
+    }
+
+    pub fn slot(&self) -> u64 {
+        match self {
+            Self::Raydium(swap) => swap.slot,
+            Self::Jupiter(swap) => swap.slot,
+            Self::PumpFun(trade) => trade.slot,
+            Self::PumpAmm(swap) => swap.slot,
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SwapProtocol {
+    Raydium,
+    Jupiter,
+    PumpFun,
+    PumpAmm,
+}
+
+/// Which way a swap went relative to its quote side: a buy pays the quote mint
+/// (normally SOL) for the other token, a sell the reverse. Stored as the
+/// `is_buy` column, and `From<bool>` reads that column back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SwapDirection {
+    Buy,
+    Sell,
+}
+
+impl SwapDirection {
+    pub fn is_buy(self) -> bool {
+        self == Self::Buy
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Buy => "buy",
+            Self::Sell => "sell",
+        }
+    }
+
+    /// Direction of a swap between two arbitrary mints, taking SOL and USDC as
+    /// quote mints. `None` when both or neither side is a quote mint.
+    pub fn from_mints(mint_in: &str, mint_out: &str) -> Option<Self> {
+        let is_quote = |mint: &str| mint == WSOL_MINT || mint == USDC_MINT;
+        match (is_quote(mint_in), is_quote(mint_out)) {
+            (true, false) => Some(Self::Buy),
+            (false, true) => Some(Self::Sell),
+            _ => None,
+        }
+    }
+}
+
+impl From<bool> for SwapDirection {
+    fn from(is_buy: bool) -> Self {
+        if is_buy { Self::Buy } else { Self::Sell }
+    }
+}
+
+impl From<SwapDirection> for bool {
+    fn from(direction: SwapDirection) -> Self {
+        direction.is_buy()
+    }
+}
+
+impl<'de> Deserialize<'de> for SwapDirection {
+    /// Also accepts the `is_buy` bool that events were serialized with before.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            IsBuy(bool),
+            Name(String),
+        }
+        match Repr::deserialize(deserializer)? {
+            Repr::IsBuy(is_buy) => Ok(is_buy.into()),
+            Repr::Name(name) => match name.as_str() {
+                "buy" => Ok(Self::Buy),
+                "sell" => Ok(Self::Sell),
+                other => Err(D::Error::unknown_variant(other, &["buy", "sell"])),
+            },
+        }
+    }
+}
+
+/// Protocol-agnostic view of a swap for cross-DEX analytics.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NormalizedSwap {
+    pub protocol: SwapProtocol,
+    pub signature: Signature58,
+    pub instruction_index: u32,
+    pub slot: u64,
+    pub signer: String,
+    pub pool: String,
+    pub mint_in: String,
+    pub mint_out: String,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    /// `None` for swaps with a quote mint on both sides or neither.
+    pub direction: Option<SwapDirection>,
+}
+
+impl From<&RaydiumSwapEvent> for NormalizedSwap {
+    fn from(s: &RaydiumSwapEvent) -> Self {
+        Self {
+            protocol: SwapProtocol::Raydium,
+            signature: s.signature.clone(),
+            instruction_index: s.instruction_index,
+            slot: s.slot,
+            signer: s.signer.clone(),
+            pool: s.amm_pool.clone(),
+            mint_in: s.mint_source.clone(),
+            mint_out: s.mint_destination.clone(),
+            amount_in: s.amount_in,
+            amount_out: s.amount_received,
+            direction: SwapDirection::from_mints(&s.mint_source, &s.mint_destination),
+        }
+    }
+}
+
+impl From<&JupiterSwapEvent> for NormalizedSwap {
+    fn from(s: &JupiterSwapEvent) -> Self {
+        Self {
+            protocol: SwapProtocol::Jupiter,
+            signature: s.signature.clone(),
+            instruction_index: s.instruction_index,
+            slot: s.slot,
+            signer: s.signer.clone(),
+            pool: s.amm_pool.clone(),
+            mint_in: s.mint_in.clone(),
+            mint_out: s.mint_out.clone(),
+            amount_in: s.amount_in,
+            amount_out: s.amount_out,
+            direction: SwapDirection::from_mints(&s.mint_in, &s.mint_out),
+        }
+    }
+}
+
+impl From<&PumpFunTrade> for NormalizedSwap {
+    /// Buys pay SOL for the token, sells pay the token for SOL.
+    fn from(t: &PumpFunTrade) -> Self {
+        let (mint_in, mint_out, amount_in, amount_out) = if t.direction.is_buy() {
+            (WSOL_MINT.to_string(), t.mint.clone(), t.sol_amount, t.token_amount)
+        } else {
+            (t.mint.clone(), WSOL_MINT.to_string(), t.token_amount, t.sol_amount)
+        };
+        Self {
+            protocol: SwapProtocol::PumpFun,
+            signature: t.signature.clone(),
+            instruction_index: t.instruction_index,
+            slot: t.slot,
+            signer: t.user.clone(),
+            // Each bonding curve is unique per mint, so the mint identifies the pool
+            pool: t.mint.clone(),
+            mint_in,
+            mint_out,
+            amount_in,
+            amount_out,
+            direction: Some(t.direction),
+        }
+    }
+}
+
+impl From<&PumpAmmSwapEvent> for NormalizedSwap {
+    /// Buys pay the quote mint for the base mint, sells the reverse.
+    fn from(s: &PumpAmmSwapEvent) -> Self {
+        let (mint_in, mint_out, amount_in, amount_out) = if s.direction.is_buy() {
+            (s.quote_mint.clone(), s.base_mint.clone(), s.quote_amount, s.base_amount)
+        } else {
+            (s.base_mint.clone(), s.quote_mint.clone(), s.base_amount, s.quote_amount)
+        };
+        Self {
+            protocol: SwapProtocol::PumpAmm,
+            signature: s.signature.clone(),
+            instruction_index: s.instruction_index,
+            slot: s.slot,
+            signer: s.user.clone(),
+            pool: s.pool.clone(),
+            mint_in,
+            mint_out,
+            amount_in,
+            amount_out,
+            direction: Some(s.direction),
+        }
+    }
+}
+
+impl From<&SwapEvent> for NormalizedSwap {
+    fn from(swap: &SwapEvent) -> Self {
+        match swap {
+            SwapEvent::Raydium(s) => s.into(),
+            SwapEvent::Jupiter(s) => s.into(),
+            SwapEvent::PumpFun(t) => t.into(),
+            SwapEvent::PumpAmm(s) => s.into(),
+        }
+    }
+}
+
+impl TransactionEvent {
+    /// Normalized swap view, or None for non-swap events.
+    pub fn as_normalized_swap(&self) -> Option<NormalizedSwap> {
+        match self {
+            Self::RaydiumSwap(s) => Some(s.into()),
+            Self::JupiterSwap(s) => Some(s.into()),
+            Self::PumpFunTrade(t) => Some(t.into()),
+            Self::PumpAmmSwap(s) => Some(s.into()),
+            Self::TokenTransfer(_) | Self::ComputeBudget(_) | Self::SolTransfer(_) => None,
+        }
+    }
+}
+
+/// Swap activity of one slot. Every swap adds its input amount to `mint_in` and
+/// its output amount to `mint_out`, so a mint's total is all of it that changed
+/// hands in the slot, in raw units.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlotSummary {
+    pub slot: u64,
+    pub swap_count: u64,
+    pub total_volume_per_mint: BTreeMap<String, u128>,
+}
+
+/// Compute-budget settings of one transaction, merged from its ComputeBudget
+/// program instructions. `instruction_index` is the first such instruction.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComputeBudgetEvent {
+    pub signature: Signature58,
+    pub instruction_index: u32,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub unit_limit: Option<u32>,
+    /// Priority fee per compute unit.
+    pub unit_price_micro_lamports: Option<u64>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PumpFunTrade {
-    pub signature: String,
+    pub signature: Signature58,
+    pub instruction_index: u32,
+    pub inner_index: Option<u32>,
+    /// 0 when invoked from a top-level instruction, 1+ when reached via CPI.
+    #[serde(default)]
+    pub cpi_depth: u32,
     pub slot: u64,
     pub mint: String,
-    pub is_buy: bool,
+    #[serde(alias = "is_buy")]
+    pub direction: SwapDirection,
     pub user: String,
-    pub timestamp: i64,
+    pub timestamp: Option<i64>,
     pub token_amount: u64,
     pub sol_amount: u64,
-    pub block_time: i64,
+    pub block_time: Option<i64>,
+}
+
+/// A buy or sell on a PumpSwap AMM pool, i.e. a Pump.fun token after graduation.
+/// `base_amount` is the token side and `quote_amount` the side the pool is priced
+/// in (normally WSOL). Reserves are the pool vault balances after the swap, when
+/// the transaction carries token balances for them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PumpAmmSwapEvent {
+    pub signature: Signature58,
+    pub instruction_index: u32,
+    pub inner_index: Option<u32>,
+    #[serde(default)]
+    pub cpi_depth: u32,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub pool: String,
+    pub user: String,
+    pub base_mint: String,
+    pub quote_mint: String,
+    #[serde(alias = "is_buy")]
+    pub direction: SwapDirection,
+    pub base_amount: u64,
+    pub quote_amount: u64,
+    pub pool_base_reserve: Option<u64>,
+    pub pool_quote_reserve: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JupiterSwapEvent {
-    pub signature: String,
+    pub signature: Signature58,
+    pub instruction_index: u32,
+    pub inner_index: Option<u32>,
+    #[serde(default)]
+    pub cpi_depth: u32,
     pub slot: u64,
-    pub block_time: i64,
+    pub block_time: Option<i64>,
     pub signer: String,
     pub amm_pool: String,
     pub mint_in: String,
@@ -84,8 +447,36 @@ pub struct RouteStep {
     pub output_index: u8,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RaydiumPoolType {
+    AmmV4,
+    Clmm,
+}
+
+impl RaydiumPoolType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::AmmV4 => "amm_v4",
+            Self::Clmm => "clmm",
+        }
+    }
+}
+
+impl FromStr for RaydiumPoolType {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "amm_v4" => Ok(Self::AmmV4),
+            "clmm" => Ok(Self::Clmm),
+            _ => Err(format!("unknown pool_type: {}", value)),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RaydiumSwapEvent {
+    pub pool_type: RaydiumPoolType,
     pub amm_pool: String,
     pub signer: String,
     pub amount_in: u64,
@@ -94,8 +485,27 @@ pub struct RaydiumSwapEvent {
     pub mint_source: String,
     pub mint_destination: String,
     pub slot: u64,
-    pub block_time: i64,
-    pub signature: String,
+    pub block_time: Option<i64>,
+    pub signature: Signature58,
+    pub instruction_index: u32,
+    pub inner_index: Option<u32>,
+    #[serde(default)]
+    pub cpi_depth: u32,
+}
+
+impl RaydiumSwapEvent {
+    /// How far the output landed above the swap's floor, in bps of `min_amount_out`:
+    /// 0 means the swap filled exactly at its slippage limit. `None` when either
+    /// amount is 0, i.e. no floor was set or the received amount is unknown.
+    /// Rounds toward zero, like the `realized_slippage_bps` column.
+    pub fn realized_slippage_bps(&self) -> Option<i32> {
+        if self.min_amount_out == 0 || self.amount_received == 0 {
+            return None;
+        }
+        let diff = self.amount_received as i128 - self.min_amount_out as i128;
+        let bps = diff * 10_000 / self.min_amount_out as i128;
+        Some(bps.clamp(i32::MIN as i128, i32::MAX as i128) as i32)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -104,7 +514,16 @@ pub struct SolanaTransaction {
     pub success: bool,
     pub data: TxData,
     pub slot: u64,
-    pub block_time: i64,
+    pub block_time: Option<i64>,
+    /// Lamports charged, when the source provides status meta.
+    pub fee: Option<u64>,
+    pub compute_units_consumed: Option<u64>,
+    /// First account key, the signer that paid the fee. Empty when the source has no message.
+    #[serde(default)]
+    pub fee_payer: String,
+    /// Base58 blockhash the transaction was signed against. Empty when unknown.
+    #[serde(default)]
+    pub recent_blockhash: String,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -116,8 +535,195 @@ pub enum TxData {
     },
 }
 
+/// What the `transactions` parent table records about a transaction that
+/// produced events, beyond the events themselves.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexedTransaction {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    /// Empty when the source had no message.
+    pub fee_payer: String,
+    pub success: bool,
+    /// Parsers whose events were kept, in registration order.
+    pub parsers: Vec<String>,
+}
+
+impl IndexedTransaction {
+    pub fn new(txn: &SolanaTransaction, parsers: Vec<String>) -> Self {
+        Self {
+            signature: txn.signature.clone(),
+            slot: txn.slot,
+            block_time: txn.block_time,
+            fee_payer: txn.fee_payer.clone(),
+            success: txn.success,
+            parsers,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct IndexerState {
     pub last_slot: u64,
     pub last_block_hash: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::fixtures;
+
+    fn raydium_swap() -> RaydiumSwapEvent {
+        RaydiumSwapEvent {
+            min_amount_out: 900,
+            mint_source: WSOL_MINT.to_string(),
+            mint_destination: "Token".to_string(),
+            instruction_index: 2,
+            ..fixtures::raydium_swap(1, 1_000, 950)
+        }
+    }
+
+    fn jupiter_swap() -> JupiterSwapEvent {
+        JupiterSwapEvent {
+            signature: Signature58::from([2u8; 64]),
+            instruction_index: 1,
+            inner_index: None,
+            cpi_depth: 0,
+            slot: 11,
+            block_time: None,
+            signer: "signer".to_string(),
+            amm_pool: "route".to_string(),
+            mint_in: "Token".to_string(),
+            mint_out: USDC_MINT.to_string(),
+            amount_in: 5_000,
+            amount_out: 40,
+            slippage_bps: 50,
+            platform_fee_bps: 0,
+            route_plan: Vec::new(),
+        }
+    }
+
+    fn pump_fun_trade(direction: SwapDirection) -> PumpFunTrade {
+        PumpFunTrade {
+            signature: Signature58::from([3u8; 64]),
+            instruction_index: 0,
+            inner_index: None,
+            cpi_depth: 0,
+            slot: 12,
+            mint: "Token".to_string(),
+            direction,
+            user: "user".to_string(),
+            timestamp: None,
+            token_amount: 1_000_000,
+            sol_amount: 2_000,
+            block_time: None,
+        }
+    }
+
+    #[test]
+    fn raydium_swap_maps_source_to_input() {
+        let swap = NormalizedSwap::from(&raydium_swap());
+
+        assert_eq!(swap.protocol, SwapProtocol::Raydium);
+        assert_eq!((swap.signer.as_str(), swap.pool.as_str()), ("signer", "pool"));
+        assert_eq!((swap.mint_in.as_str(), swap.mint_out.as_str()), (WSOL_MINT, "Token"));
+        assert_eq!((swap.amount_in, swap.amount_out), (1_000, 950));
+        assert_eq!((swap.slot, swap.instruction_index), (10, 2));
+        assert_eq!(swap.direction, Some(SwapDirection::Buy));
+    }
+
+    #[test]
+    fn jupiter_swap_keeps_its_mints_and_amounts() {
+        let swap = NormalizedSwap::from(&jupiter_swap());
+
+        assert_eq!(swap.protocol, SwapProtocol::Jupiter);
+        assert_eq!((swap.mint_in.as_str(), swap.mint_out.as_str()), ("Token", USDC_MINT));
+        assert_eq!((swap.amount_in, swap.amount_out), (5_000, 40));
+        assert_eq!(swap.direction, Some(SwapDirection::Sell));
+    }
+
+    #[test]
+    fn pump_fun_buy_pays_sol_for_the_token() {
+        let swap = NormalizedSwap::from(&pump_fun_trade(SwapDirection::Buy));
+
+        assert_eq!(swap.protocol, SwapProtocol::PumpFun);
+        assert_eq!((swap.mint_in.as_str(), swap.mint_out.as_str()), (WSOL_MINT, "Token"));
+        assert_eq!((swap.amount_in, swap.amount_out), (2_000, 1_000_000));
+        assert_eq!((swap.signer.as_str(), swap.pool.as_str()), ("user", "Token"));
+    }
+
+    #[test]
+    fn pump_fun_sell_pays_the_token_for_sol() {
+        let swap = NormalizedSwap::from(&pump_fun_trade(SwapDirection::Sell));
+
+        assert_eq!((swap.mint_in.as_str(), swap.mint_out.as_str()), ("Token", WSOL_MINT));
+        assert_eq!((swap.amount_in, swap.amount_out), (1_000_000, 2_000));
+        assert_eq!(swap.direction, Some(SwapDirection::Sell));
+    }
+
+    #[test]
+    fn only_swap_events_have_a_normalized_view() {
+        assert!(TransactionEvent::RaydiumSwap(raydium_swap()).as_normalized_swap().is_some());
+        let budget = TransactionEvent::ComputeBudget(ComputeBudgetEvent {
+            signature: Signature58::from([4u8; 64]),
+            instruction_index: 0,
+            slot: 13,
+            block_time: None,
+            unit_limit: Some(200_000),
+            unit_price_micro_lamports: None,
+        });
+        assert!(budget.as_normalized_swap().is_none());
+    }
+
+    fn slippage(min_amount_out: u64, amount_received: u64) -> Option<i32> {
+        RaydiumSwapEvent { min_amount_out, amount_received, ..raydium_swap() }.realized_slippage_bps()
+    }
+
+    #[test]
+    fn receiving_more_than_the_floor_is_positive_slippage() {
+        // 950 against a 900 floor is 5.55…%, rounded toward zero
+        assert_eq!(slippage(900, 950), Some(555));
+    }
+
+    #[test]
+    fn filling_exactly_at_the_floor_is_zero_slippage() {
+        assert_eq!(slippage(900, 900), Some(0));
+    }
+
+    #[test]
+    fn missing_floor_or_amount_has_no_slippage() {
+        assert_eq!(slippage(0, 950), None);
+        assert_eq!(slippage(900, 0), None);
+    }
+
+    #[test]
+    fn is_buy_maps_to_a_direction_and_back() {
+        assert_eq!(SwapDirection::from(true), SwapDirection::Buy);
+        assert_eq!(SwapDirection::from(false), SwapDirection::Sell);
+        assert!(bool::from(SwapDirection::Buy));
+        assert!(!bool::from(SwapDirection::Sell));
+    }
+
+    #[test]
+    fn direction_round_trips_through_serde() {
+        for direction in [SwapDirection::Buy, SwapDirection::Sell] {
+            let json = serde_json::to_string(&direction).unwrap();
+
+            assert_eq!(json, format!("\"{}\"", direction.as_str()));
+            assert_eq!(serde_json::from_str::<SwapDirection>(&json).unwrap(), direction);
+        }
+    }
+
+    #[test]
+    fn events_stored_with_is_buy_still_deserialize() {
+        let mut json = serde_json::to_value(pump_fun_trade(SwapDirection::Buy)).unwrap();
+        let fields = json.as_object_mut().unwrap();
+        fields.remove("direction");
+        fields.insert("is_buy".to_string(), serde_json::Value::Bool(true));
+
+        let trade: PumpFunTrade = serde_json::from_value(json).unwrap();
+
+        assert_eq!(trade.direction, SwapDirection::Buy);
+        assert!(serde_json::from_str::<SwapDirection>("\"sideways\"").is_err());
+    }
+}
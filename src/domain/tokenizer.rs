@@ -1,11 +1,29 @@
 use serde::{Deserialize, Serialize};
 
+use crate::domain::{Pubkey58, Signature58};
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TokenTransfer {
-    pub from: String,
-    pub to: String,
+    pub from: Pubkey58,
+    pub to: Pubkey58,
     pub slot: u64,
+    pub block_time: Option<i64>,
     pub amount: u64,
-    pub signature: String,
+    pub signature: Signature58,
     pub mint: Option<String>,
+    pub instruction_index: u32,
+    pub inner_index: Option<u32>,
+}
+
+/// Native SOL moved by the System program (`Transfer` or `TransferWithSeed`).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SolTransfer {
+    pub from: Pubkey58,
+    pub to: Pubkey58,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub lamports: u64,
+    pub signature: Signature58,
+    pub instruction_index: u32,
+    pub inner_index: Option<u32>,
 }
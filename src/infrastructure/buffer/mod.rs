@@ -1,2 +1,4 @@
 mod memory;
+mod spillover;
 pub use memory::*;
+pub use spillover::*;
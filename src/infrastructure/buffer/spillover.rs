@@ -0,0 +1,217 @@
+use std::{
+    io::SeekFrom,
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use tokio::{
+    fs::{File, OpenOptions},
+    io::{AsyncBufReadExt, AsyncSeekExt, AsyncWriteExt, BufReader},
+    sync::{Mutex, Notify, mpsc::{self, error::TrySendError}},
+};
+
+use crate::{
+    application::{AppError, AppResult, EventBuffer},
+    domain::ChainEvent,
+};
+
+/// Overflow file plus how many events in it have not been drained yet.
+struct Spill {
+    file: File,
+    queued: u64,
+}
+
+/// Channel buffer that never blocks the source and never drops: when the channel
+/// is full, events are appended to a JSON-lines spill file instead, and a
+/// background task feeds them back in order as the pipeline frees up room. Once
+/// anything is spilled, later events queue behind it on disk so order is kept.
+/// The file is truncated each time it is fully drained.
+///
+/// Memory stays bounded by the channel capacity; disk is bounded only by how long
+/// the pipeline stays behind. Spilled events do not survive a restart.
+pub struct SpilloverBuffer {
+    tx: mpsc::Sender<ChainEvent>,
+    capacity: usize,
+    spill: Arc<Mutex<Spill>>,
+    wake: Arc<Notify>,
+    closed: Arc<AtomicBool>,
+}
+
+impl SpilloverBuffer {
+    pub async fn new(capacity: usize, path: impl Into<PathBuf>) -> Result<(Self, mpsc::Receiver<ChainEvent>)> {
+        let path = path.into();
+        let (tx, rx) = mpsc::channel::<ChainEvent>(capacity);
+        let file = OpenOptions::new().create(true).read(true).append(true).open(&path).await?;
+        file.set_len(0).await?;
+        let reader = BufReader::new(File::open(&path).await?);
+
+        let spill = Arc::new(Mutex::new(Spill { file, queued: 0 }));
+        let wake = Arc::new(Notify::new());
+        let closed = Arc::new(AtomicBool::new(false));
+        tokio::spawn(Self::drain(reader, tx.clone(), spill.clone(), wake.clone(), closed.clone()));
+
+        Ok((Self { tx, capacity, spill, wake, closed }, rx))
+    }
+
+    /// Moves spilled events back into the channel, waiting for room as needed.
+    /// Exits once the buffer is dropped and the file is empty, releasing its sender.
+    async fn drain(
+        mut reader: BufReader<File>,
+        tx: mpsc::Sender<ChainEvent>,
+        spill: Arc<Mutex<Spill>>,
+        wake: Arc<Notify>,
+        closed: Arc<AtomicBool>,
+    ) {
+        let mut line = String::new();
+        loop {
+            if spill.lock().await.queued == 0 {
+                if closed.load(Ordering::Acquire) {
+                    return;
+                }
+                wake.notified().await;
+                continue;
+            }
+
+            line.clear();
+            match reader.read_line(&mut line).await {
+                Ok(0) => {
+                    tracing::error!("Spill file ended with events still counted as queued");
+                    return;
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    tracing::error!("Spill file read failed, spilled events are lost: {}", e);
+                    return;
+                }
+            }
+            match serde_json::from_str::<ChainEvent>(&line) {
+                Ok(event) => {
+                    if tx.send(event).await.is_err() {
+                        return;
+                    }
+                }
+                Err(e) => tracing::error!("Skipping unreadable spilled event: {}", e),
+            }
+
+            let mut spill = spill.lock().await;
+            spill.queued -= 1;
+            if spill.queued == 0 {
+                // Everything on disk is back in the channel; start the file over
+                if let Err(e) = spill.file.set_len(0).await {
+                    tracing::warn!("Spill file truncate failed: {}", e);
+                } else if let Err(e) = reader.seek(SeekFrom::Start(0)).await {
+                    tracing::error!("Spill file rewind failed: {}", e);
+                    return;
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl EventBuffer for SpilloverBuffer {
+    async fn produce(&self, event: ChainEvent) -> AppResult<()> {
+        let mut spill = self.spill.lock().await;
+        let event = if spill.queued == 0 {
+            match self.tx.try_send(event) {
+                Ok(()) => return Ok(()),
+                Err(TrySendError::Closed(_)) => return Err(AppError::ErrorSendingMessageViaBuffer),
+                Err(TrySendError::Full(event)) => event,
+            }
+        } else {
+            event
+        };
+
+        let mut line = serde_json::to_vec(&event).map_err(|e| AppError::WriteError(e.to_string()))?;
+        line.push(b'\n');
+        spill.file.write_all(&line).await.map_err(|e| AppError::WriteError(e.to_string()))?;
+        spill.file.flush().await.map_err(|e| AppError::WriteError(e.to_string()))?;
+        if spill.queued == 0 {
+            tracing::warn!("Event buffer full, spilling to disk");
+        }
+        spill.queued += 1;
+        self.wake.notify_one();
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        self.capacity - self.tx.capacity()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.tx.capacity() == self.capacity
+    }
+}
+
+impl Drop for SpilloverBuffer {
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::Release);
+        self.wake.notify_one();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn spill_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("indexer-spill-{}-{}.jsonl", std::process::id(), name))
+    }
+
+    fn slot(event: ChainEvent) -> u64 {
+        match event {
+            ChainEvent::Finalized { slot } => slot,
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn overflow_is_delivered_from_disk_in_order() {
+        let path = spill_path("order");
+        let (buffer, mut rx) = SpilloverBuffer::new(2, &path).await.unwrap();
+
+        // Nobody is receiving yet, so all but the first two go to the spill file
+        for slot in 0..20 {
+            buffer.produce(ChainEvent::Finalized { slot }).await.unwrap();
+        }
+        assert_eq!(buffer.len(), 2);
+        drop(buffer);
+
+        let mut slots = Vec::new();
+        while let Some(event) = tokio::time::timeout(Duration::from_secs(5), rx.recv()).await.unwrap() {
+            slots.push(slot(event));
+        }
+        assert_eq!(slots, (0..20).collect::<Vec<_>>());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn spill_file_is_reused_once_drained() {
+        let path = spill_path("reuse");
+        let (buffer, mut rx) = SpilloverBuffer::new(1, &path).await.unwrap();
+
+        for round in 0..3u64 {
+            for i in 0..5 {
+                buffer.produce(ChainEvent::Finalized { slot: round * 10 + i }).await.unwrap();
+            }
+            let mut slots = Vec::new();
+            for _ in 0..5 {
+                slots.push(slot(tokio::time::timeout(Duration::from_secs(5), rx.recv()).await.unwrap().unwrap()));
+            }
+            assert_eq!(slots, (round * 10..round * 10 + 5).collect::<Vec<_>>());
+        }
+
+        // Truncated after the last drain
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), 0);
+        drop(buffer);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,86 @@
+use sentry::ClientInitGuard;
+use sentry_tracing::EventFilter;
+use tracing::Level;
+use tracing_subscriber::{Layer, registry::LookupSpan};
+
+/// Keeps the Sentry client alive; dropping it flushes queued events.
+pub struct ErrorReportingGuard {
+    _client: ClientInitGuard,
+}
+
+/// Start the Sentry client for `dsn`. Panics are reported by the client's panic
+/// integration; `tracing` events reach Sentry through [`sentry_layer`].
+pub fn init(dsn: &str) -> ErrorReportingGuard {
+    let client = sentry::init((
+        dsn,
+        sentry::ClientOptions {
+            release: sentry::release_name!(),
+            attach_stacktrace: true,
+            ..Default::default()
+        },
+    ));
+    ErrorReportingGuard { _client: client }
+}
+
+/// Tracing layer that sends `error!` events to Sentry and keeps `warn!` and
+/// `info!` as breadcrumbs on the next one. Lower levels are ignored.
+pub fn sentry_layer<S>() -> impl Layer<S>
+where
+    S: tracing::Subscriber + for<'span> LookupSpan<'span>,
+{
+    sentry_tracing::layer().event_filter(|metadata| match *metadata.level() {
+        Level::ERROR => EventFilter::Event,
+        Level::WARN | Level::INFO => EventFilter::Breadcrumb,
+        _ => EventFilter::Ignore,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use sentry::{Client, ClientOptions, Envelope, Hub, Level as SentryLevel, Scope, Transport};
+    use tracing_subscriber::{Registry, layer::SubscriberExt};
+
+    use super::*;
+
+    /// Keeps every envelope the client would have sent.
+    #[derive(Default)]
+    struct CapturingTransport {
+        envelopes: Mutex<Vec<Envelope>>,
+    }
+
+    impl Transport for CapturingTransport {
+        fn send_envelope(&self, envelope: Envelope) {
+            self.envelopes.lock().unwrap().push(envelope);
+        }
+    }
+
+    #[test]
+    fn only_error_events_are_forwarded() {
+        let transport = Arc::new(CapturingTransport::default());
+        let options = ClientOptions {
+            dsn: Some("https://public@sentry.invalid/1".parse().unwrap()),
+            transport: Some(Arc::new(transport.clone())),
+            ..Default::default()
+        };
+        let hub = Arc::new(Hub::new(Some(Arc::new(Client::from(options))), Arc::new(Scope::default())));
+        let subscriber = Registry::default().with(sentry_layer());
+
+        Hub::run(hub, || {
+            tracing::subscriber::with_default(subscriber, || {
+                tracing::info!("connected");
+                tracing::warn!("retrying batch");
+                tracing::error!("batch dead-lettered");
+            });
+        });
+
+        let envelopes = transport.envelopes.lock().unwrap();
+        let events: Vec<_> = envelopes.iter().filter_map(|envelope| envelope.event()).collect();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].level, SentryLevel::Error);
+        assert_eq!(events[0].message.as_deref(), Some("batch dead-lettered"));
+        // The lower levels ride along as breadcrumbs
+        assert_eq!(events[0].breadcrumbs.values.len(), 2);
+    }
+}
@@ -0,0 +1,2 @@
+mod server;
+pub use server::*;
@@ -0,0 +1,213 @@
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{get, post},
+};
+use serde::Serialize;
+
+use crate::application::{MintVolume, ParserControl, PipelineMetrics, TransactionRepository};
+
+#[derive(Debug, Clone)]
+pub struct HealthConfig {
+    /// `/readyz` fails once no event has arrived for this long.
+    pub max_event_age: Duration,
+    /// `/readyz` fails when the ingest buffer is fuller than this fraction.
+    pub max_queue_fill: f64,
+    /// Bound on the DB ping so a hung pool cannot hang the probe.
+    pub db_timeout: Duration,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            max_event_age: Duration::from_secs(60),
+            max_queue_fill: 0.9,
+            db_timeout: Duration::from_secs(2),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Readiness {
+    pub ready: bool,
+    pub db_reachable: bool,
+    pub last_event_age_secs: Option<i64>,
+    pub queue_depth: usize,
+    pub queue_capacity: usize,
+    pub pool_size: u32,
+    pub pool_idle: usize,
+    pub events_received: u64,
+    pub events_persisted: u64,
+    pub write_errors: u64,
+    pub slots_gapped: u64,
+    pub source_stalls: u64,
+    pub suspicious_swaps: u64,
+    pub persist_latency_p50_ms: Option<u64>,
+    pub persist_latency_p99_ms: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ParserState {
+    pub name: String,
+    pub enabled: bool,
+}
+
+/// Serves `/healthz` (process is up) and `/readyz` (DB reachable, events
+/// flowing, buffer not saturated). With a parser control it also serves
+/// `GET /parsers` and `POST /parsers/{name}/enable|disable`. With volume tracking
+/// enabled on the metrics, `GET /volume` lists swap volume per mint.
+#[derive(Clone)]
+pub struct HealthServer {
+    metrics: Arc<PipelineMetrics>,
+    repo: Arc<dyn TransactionRepository>,
+    config: HealthConfig,
+    parsers: Option<ParserControl>,
+}
+
+impl HealthServer {
+    pub fn new(metrics: Arc<PipelineMetrics>, repo: Arc<dyn TransactionRepository>) -> Self {
+        Self { metrics, repo, config: HealthConfig::default(), parsers: None }
+    }
+
+    pub fn with_config(mut self, config: HealthConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn with_parser_control(mut self, parsers: ParserControl) -> Self {
+        self.parsers = Some(parsers);
+        self
+    }
+
+    pub fn router(self) -> Router {
+        Router::new()
+            .route("/healthz", get(|| async { StatusCode::OK }))
+            .route("/readyz", get(readyz))
+            .route("/volume", get(volume))
+            .route("/parsers", get(list_parsers))
+            .route("/parsers/{name}/enable", post(enable_parser))
+            .route("/parsers/{name}/disable", post(disable_parser))
+            .with_state(self)
+    }
+
+    pub async fn serve(self, addr: SocketAddr) -> anyhow::Result<()> {
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        tracing::info!("Health endpoints listening on {}", addr);
+        axum::serve(listener, self.router()).await?;
+        Ok(())
+    }
+
+    pub async fn readiness(&self, now: i64) -> Readiness {
+        let db_reachable = matches!(
+            tokio::time::timeout(self.config.db_timeout, self.repo.ping()).await,
+            Ok(Ok(()))
+        );
+
+        let last_event_age_secs = self.metrics.last_event_at().map(|at| now.saturating_sub(at));
+        let fresh = last_event_age_secs
+            .is_some_and(|age| age <= self.config.max_event_age.as_secs() as i64);
+
+        let queue_depth = self.metrics.queue_depth();
+        let queue_capacity = self.metrics.queue_capacity();
+        let saturated = queue_capacity > 0
+            && queue_depth as f64 > queue_capacity as f64 * self.config.max_queue_fill;
+
+        let latency = self.metrics.persist_latency();
+        Readiness {
+            ready: db_reachable && fresh && !saturated,
+            db_reachable,
+            last_event_age_secs,
+            queue_depth,
+            queue_capacity,
+            pool_size: self.metrics.pool_size(),
+            pool_idle: self.metrics.pool_idle(),
+            events_received: self.metrics.events_received(),
+            events_persisted: self.metrics.events_persisted(),
+            write_errors: self.metrics.write_errors(),
+            slots_gapped: self.metrics.slots_gapped(),
+            source_stalls: self.metrics.source_stalls(),
+            suspicious_swaps: self.metrics.suspicious_swaps(),
+            persist_latency_p50_ms: latency.quantile(0.50).map(|d| d.as_millis() as u64),
+            persist_latency_p99_ms: latency.quantile(0.99).map(|d| d.as_millis() as u64),
+        }
+    }
+}
+
+async fn readyz(State(server): State<HealthServer>) -> (StatusCode, Json<Readiness>) {
+    let readiness = server.readiness(chrono::Utc::now().timestamp()).await;
+    let status = if readiness.ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(readiness))
+}
+
+async fn volume(State(server): State<HealthServer>) -> Result<Json<Vec<MintVolume>>, StatusCode> {
+    let volume = server.metrics.volume().ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(volume.snapshot()))
+}
+
+async fn list_parsers(State(server): State<HealthServer>) -> Result<Json<Vec<ParserState>>, StatusCode> {
+    let parsers = server.parsers.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    let states = parsers.states().into_iter().map(|(name, enabled)| ParserState { name, enabled }).collect();
+    Ok(Json(states))
+}
+
+async fn enable_parser(State(server): State<HealthServer>, Path(name): Path<String>) -> StatusCode {
+    set_parser(&server, &name, true)
+}
+
+async fn disable_parser(State(server): State<HealthServer>, Path(name): Path<String>) -> StatusCode {
+    set_parser(&server, &name, false)
+}
+
+fn set_parser(server: &HealthServer, name: &str, enabled: bool) -> StatusCode {
+    match &server.parsers {
+        Some(parsers) if parsers.set_enabled(name, enabled) => StatusCode::NO_CONTENT,
+        _ => StatusCode::NOT_FOUND,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::NullRepository;
+
+    fn server() -> (HealthServer, Arc<PipelineMetrics>) {
+        let metrics = Arc::new(PipelineMetrics::new());
+        (HealthServer::new(metrics.clone(), Arc::new(NullRepository::new(0))), metrics)
+    }
+
+    #[tokio::test]
+    async fn readyz_is_ok_while_events_keep_arriving() {
+        let (server, metrics) = server();
+        metrics.record_event(chrono::Utc::now().timestamp());
+
+        let (status, Json(readiness)) = readyz(State(server)).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert!(readiness.db_reachable);
+    }
+
+    #[tokio::test]
+    async fn readyz_is_unavailable_once_the_last_event_is_stale() {
+        let (server, metrics) = server();
+        metrics.record_event(chrono::Utc::now().timestamp() - 600);
+
+        let (status, Json(readiness)) = readyz(State(server)).await;
+
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert!(readiness.last_event_age_secs.is_some_and(|age| age >= 600));
+    }
+
+    #[tokio::test]
+    async fn saturated_queue_is_not_ready() {
+        let (server, metrics) = server();
+        metrics.record_event(1_000);
+        metrics.set_queue(95, 100);
+
+        assert!(!server.readiness(1_000).await.ready);
+        metrics.set_queue(50, 100);
+        assert!(server.readiness(1_000).await.ready);
+    }
+}
@@ -0,0 +1,202 @@
+use std::str::FromStr;
+
+use tracing_subscriber::{EnvFilter, Layer, Registry, fmt::MakeWriter, layer::SubscriberExt, util::SubscriberInitExt};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human-readable single-line output (tracing's default).
+    Compact,
+    /// Multi-line, indented output for local debugging.
+    Pretty,
+    /// One JSON object per line, for log aggregators.
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "compact" | "text" => Ok(Self::Compact),
+            "pretty" => Ok(Self::Pretty),
+            "json" => Ok(Self::Json),
+            _ => Err(format!("unknown log format `{}`", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LogConfig {
+    pub format: LogFormat,
+    /// Colour codes in output. Ignored for JSON.
+    pub ansi: bool,
+    /// Filter used when `RUST_LOG` is unset.
+    pub default_filter: String,
+    /// OTLP collector to export spans to. Only honoured with the `otel` feature.
+    pub otlp_endpoint: Option<String>,
+    /// Sentry DSN to report errors and panics to. Only honoured with the `sentry` feature.
+    pub sentry_dsn: Option<String>,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            format: LogFormat::Compact,
+            ansi: true,
+            default_filter: "info".to_string(),
+            otlp_endpoint: None,
+            sentry_dsn: None,
+        }
+    }
+}
+
+impl LogConfig {
+    /// Reads `LOG_FORMAT` (compact | pretty | json), `LOG_ANSI` (true | false)
+    /// `OTEL_EXPORTER_OTLP_ENDPOINT` and `SENTRY_DSN`.
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            format: std::env::var("LOG_FORMAT")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.format),
+            ansi: std::env::var("LOG_ANSI").map(|v| v != "false").unwrap_or(defaults.ansi),
+            otlp_endpoint: std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT").ok(),
+            sentry_dsn: std::env::var("SENTRY_DSN").ok().filter(|v| !v.is_empty()),
+            ..defaults
+        }
+    }
+}
+
+/// Held for the life of the process; dropping it flushes any exporters.
+pub struct LoggingGuard {
+    #[cfg(feature = "otel")]
+    _telemetry: Option<super::telemetry::TelemetryGuard>,
+    #[cfg(feature = "sentry")]
+    _error_reporting: Option<super::error_reporting::ErrorReportingGuard>,
+}
+
+/// The formatting layer for `config`, writing to `writer`.
+fn fmt_layer<W>(config: &LogConfig, writer: W) -> Box<dyn Layer<Registry> + Send + Sync>
+where
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let layer = tracing_subscriber::fmt::layer().with_writer(writer);
+    match config.format {
+        LogFormat::Compact => layer.with_ansi(config.ansi).boxed(),
+        LogFormat::Pretty => layer.pretty().with_ansi(config.ansi).boxed(),
+        LogFormat::Json => layer.json().with_current_span(true).with_ansi(false).boxed(),
+    }
+}
+
+/// Install the global tracing subscriber. Level filtering always comes from
+/// `RUST_LOG`, falling back to `config.default_filter`.
+pub fn init(config: &LogConfig) -> LoggingGuard {
+    let filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new(&config.default_filter));
+
+    let fmt_layer = fmt_layer(config, std::io::stdout);
+
+    #[cfg(feature = "sentry")]
+    let (sentry_layer, error_reporting) = match config.sentry_dsn.as_deref() {
+        Some(dsn) => (Some(super::error_reporting::sentry_layer()), Some(super::error_reporting::init(dsn))),
+        None => (None, None),
+    };
+    #[cfg(not(feature = "sentry"))]
+    let sentry_layer: Option<tracing_subscriber::layer::Identity> = {
+        if config.sentry_dsn.is_some() {
+            eprintln!("SENTRY_DSN is set but the `sentry` feature is not enabled");
+        }
+        None
+    };
+
+    #[cfg(feature = "otel")]
+    {
+        let (otel_layer, telemetry) = match config.otlp_endpoint.as_deref() {
+            Some(endpoint) => match super::telemetry::otlp_layer(endpoint, env!("CARGO_PKG_NAME")) {
+                Ok((layer, guard)) => (Some(layer), Some(guard)),
+                Err(e) => {
+                    eprintln!("OTLP exporter setup failed, continuing without it: {e}");
+                    (None, None)
+                }
+            },
+            None => (None, None),
+        };
+
+        tracing_subscriber::registry().with(fmt_layer).with(otel_layer).with(sentry_layer).with(filter).init();
+        LoggingGuard {
+            _telemetry: telemetry,
+            #[cfg(feature = "sentry")]
+            _error_reporting: error_reporting,
+        }
+    }
+
+    #[cfg(not(feature = "otel"))]
+    {
+        if config.otlp_endpoint.is_some() {
+            eprintln!("OTEL_EXPORTER_OTLP_ENDPOINT is set but the `otel` feature is not enabled");
+        }
+        tracing_subscriber::registry().with(fmt_layer).with(sentry_layer).with(filter).init();
+        LoggingGuard {
+            #[cfg(feature = "sentry")]
+            _error_reporting: error_reporting,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    /// Collects everything the layer writes.
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for Capture {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'w> MakeWriter<'w> for Capture {
+        type Writer = Capture;
+
+        fn make_writer(&'w self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    #[test]
+    fn json_format_writes_one_object_per_line() {
+        let capture = Capture::default();
+        let config = LogConfig { format: LogFormat::Json, ..Default::default() };
+        let subscriber = Registry::default().with(fmt_layer(&config, capture.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(slot = 42, "batch written");
+        });
+
+        let output = String::from_utf8(capture.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(lines.len(), 1);
+        let line: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(line["level"], "INFO");
+        assert_eq!(line["fields"]["message"], "batch written");
+        assert_eq!(line["fields"]["slot"], 42);
+        assert!(line["timestamp"].is_string());
+    }
+
+    #[test]
+    fn log_format_names_are_case_insensitive() {
+        assert_eq!("JSON".parse(), Ok(LogFormat::Json));
+        assert_eq!("text".parse(), Ok(LogFormat::Compact));
+        assert!("xml".parse::<LogFormat>().is_err());
+    }
+}
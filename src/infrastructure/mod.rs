@@ -1,2 +1,17 @@
 mod buffer;
+mod health;
+mod parse_errors;
+mod seen;
+mod summaries;
+mod wal;
+pub mod logging;
+#[cfg(feature = "otel")]
+pub mod telemetry;
+#[cfg(feature = "sentry")]
+pub mod error_reporting;
 pub use buffer::*;
+pub use health::*;
+pub use parse_errors::*;
+pub use seen::*;
+pub use summaries::*;
+pub use wal::*;
@@ -0,0 +1,114 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    fs::{File, OpenOptions},
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    sync::Mutex,
+};
+
+use crate::{
+    application::ParseErrorSink,
+    domain::{SolanaTransaction, TxData},
+};
+
+/// How `ParseFailure::raw` is encoded before base64.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RawEncoding {
+    /// The protobuf `SubscribeUpdate` frame exactly as received over gRPC.
+    SubscribeUpdate,
+    /// The whole `SolanaTransaction` as JSON, for RPC-sourced transactions.
+    RpcJson,
+}
+
+/// One line of the parse-error log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParseFailure {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub parser: String,
+    pub error: String,
+    pub recorded_at: i64,
+    pub encoding: RawEncoding,
+    /// Base64 of the raw transaction.
+    pub raw: String,
+}
+
+impl ParseFailure {
+    fn new(txn: &SolanaTransaction, parser: &str, error: &str) -> Result<Self> {
+        let (encoding, raw) = match &txn.data {
+            TxData::Grpc(bytes) => (RawEncoding::SubscribeUpdate, BASE64.encode(bytes)),
+            TxData::Rpc { .. } => (RawEncoding::RpcJson, BASE64.encode(serde_json::to_vec(txn)?)),
+        };
+        Ok(Self {
+            signature: txn.signature.clone(),
+            slot: txn.slot,
+            block_time: txn.block_time,
+            parser: parser.to_string(),
+            error: error.to_string(),
+            recorded_at: chrono::Utc::now().timestamp(),
+            encoding,
+            raw,
+        })
+    }
+
+    /// Rebuild the transaction to feed back through a parser.
+    pub fn transaction(&self) -> Result<SolanaTransaction> {
+        let raw = BASE64.decode(&self.raw)?;
+        Ok(match self.encoding {
+            RawEncoding::RpcJson => serde_json::from_slice(&raw)?,
+            RawEncoding::SubscribeUpdate => SolanaTransaction {
+                signature: self.signature.clone(),
+                success: true,
+                data: TxData::Grpc(raw),
+                slot: self.slot,
+                block_time: self.block_time,
+                fee: None,
+                compute_units_consumed: None,
+                fee_payer: String::new(),
+                recent_blockhash: String::new(),
+            },
+        })
+    }
+}
+
+/// Appends every parser failure to a JSON-lines file. Frames are kept whole, so
+/// enable it only while investigating; each line is one full transaction.
+pub struct FileParseErrorLog {
+    file: Mutex<File>,
+}
+
+impl FileParseErrorLog {
+    pub async fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path.into()).await?;
+        Ok(Self { file: Mutex::new(file) })
+    }
+
+    /// Every failure in a log file, oldest first. Unreadable lines are skipped.
+    pub async fn read_all(path: impl AsRef<Path>) -> Result<Vec<ParseFailure>> {
+        let mut lines = BufReader::new(File::open(path).await?).lines();
+        let mut failures = Vec::new();
+        while let Some(line) = lines.next_line().await? {
+            match serde_json::from_str(&line) {
+                Ok(failure) => failures.push(failure),
+                Err(e) => tracing::warn!("Skipping unreadable parse-error record: {}", e),
+            }
+        }
+        Ok(failures)
+    }
+}
+
+#[async_trait]
+impl ParseErrorSink for FileParseErrorLog {
+    async fn record(&self, txn: &SolanaTransaction, parser_name: &str, error: &str) -> Result<()> {
+        let mut line = serde_json::to_vec(&ParseFailure::new(txn, parser_name, error)?)?;
+        line.push(b'\n');
+        self.file.lock().await.write_all(&line).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,4 @@
+mod file;
+mod replay;
+pub use file::*;
+pub use replay::*;
@@ -0,0 +1,52 @@
+use std::path::Path;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use tokio::{
+    fs::File,
+    io::{AsyncBufReadExt, BufReader, Lines},
+};
+
+use crate::{
+    application::{AppError, AppResult, TransactionSource},
+    domain::ChainEvent,
+    infrastructure::ParseFailure,
+};
+
+/// Feeds the transactions recorded in a parse-error log back through the
+/// pipeline, oldest first, then ends. Used after a parser fix to index what it
+/// used to reject. Unreadable lines are skipped.
+pub struct ParseErrorReplaySource {
+    lines: Lines<BufReader<File>>,
+    replayed: u64,
+}
+
+impl ParseErrorReplaySource {
+    pub async fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let lines = BufReader::new(File::open(path).await?).lines();
+        Ok(Self { lines, replayed: 0 })
+    }
+}
+
+#[async_trait]
+impl TransactionSource for ParseErrorReplaySource {
+    async fn next_event(&mut self) -> AppResult<Option<ChainEvent>> {
+        loop {
+            let line = self.lines.next_line().await.map_err(|e| AppError::InvalidSource(format!("replay file: {}", e)))?;
+            let Some(line) = line else {
+                tracing::info!("Replay finished after {} transactions", self.replayed);
+                return Ok(None);
+            };
+            let txn = serde_json::from_str::<ParseFailure>(&line)
+                .map_err(anyhow::Error::from)
+                .and_then(|failure| failure.transaction());
+            match txn {
+                Ok(txn) => {
+                    self.replayed += 1;
+                    return Ok(Some(ChainEvent::Transaction(txn)));
+                }
+                Err(e) => tracing::warn!("Skipping unreadable parse-error record: {}", e),
+            }
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use crate::application::BloomFilter;
+
+/// Keeps the seen-signatures filter in a single file between runs. It is written on
+/// clean shutdown only; signatures seen by a run that crashed are simply not
+/// remembered, and the DB's unique keys absorb the re-delivery.
+pub struct SeenSignatureStore {
+    path: PathBuf,
+}
+
+impl SeenSignatureStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// The saved filter, or an empty one sized for `capacity` / `fp_rate` when there
+    /// is no file yet. A file that does not decode is an error rather than silently
+    /// starting over.
+    pub async fn load(&self, capacity: usize, fp_rate: f64) -> Result<BloomFilter> {
+        match tokio::fs::read(&self.path).await {
+            Ok(bytes) => BloomFilter::from_bytes(&bytes)
+                .ok_or_else(|| anyhow::anyhow!("{} is not a seen-signatures filter", self.path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BloomFilter::new(capacity, fp_rate)),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Write to a sibling temp file and rename over the old one, so a crash
+    /// mid-write leaves the previous filter intact.
+    pub async fn save(&self, filter: &BloomFilter) -> Result<()> {
+        let tmp = self.path.with_extension("tmp");
+        tokio::fs::write(&tmp, filter.to_bytes()).await?;
+        tokio::fs::rename(&tmp, &self.path).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("indexer-seen-{}-{}.bloom", std::process::id(), name));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[tokio::test]
+    async fn saved_signatures_are_seen_after_reload() {
+        let path = temp_path("round-trip");
+        let store = SeenSignatureStore::new(&path);
+
+        let mut filter = store.load(1_000, 0.01).await.unwrap();
+        assert!(!filter.contains(b"sig-a"));
+        filter.insert(b"sig-a");
+        store.save(&filter).await.unwrap();
+
+        let reloaded = SeenSignatureStore::new(&path).load(1_000, 0.01).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(reloaded.contains(b"sig-a"));
+        assert!(!reloaded.contains(b"sig-b"));
+    }
+
+    #[tokio::test]
+    async fn corrupt_file_is_an_error() {
+        let path = temp_path("corrupt");
+        std::fs::write(&path, b"not a filter").unwrap();
+
+        let result = SeenSignatureStore::new(&path).load(1_000, 0.01).await;
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(result.is_err());
+    }
+}
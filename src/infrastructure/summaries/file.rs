@@ -0,0 +1,33 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use tokio::{
+    fs::{File, OpenOptions},
+    io::AsyncWriteExt,
+    sync::Mutex,
+};
+
+use crate::{application::SlotSummarySink, domain::SlotSummary};
+
+/// Appends each slot summary to a JSON-lines file, one slot per line.
+pub struct FileSlotSummaryLog {
+    file: Mutex<File>,
+}
+
+impl FileSlotSummaryLog {
+    pub async fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path.into()).await?;
+        Ok(Self { file: Mutex::new(file) })
+    }
+}
+
+#[async_trait]
+impl SlotSummarySink for FileSlotSummaryLog {
+    async fn emit_summary(&self, summary: &SlotSummary) -> Result<()> {
+        let mut line = serde_json::to_vec(summary)?;
+        line.push(b'\n');
+        self.file.lock().await.write_all(&line).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,82 @@
+use anyhow::Result;
+use opentelemetry::{KeyValue, trace::TracerProvider as _};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{Resource, trace::SdkTracerProvider};
+use tracing_subscriber::{Layer, registry::LookupSpan};
+
+/// Keeps the tracer provider alive; dropping it flushes pending spans.
+pub struct TelemetryGuard {
+    provider: SdkTracerProvider,
+}
+
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        if let Err(e) = self.provider.shutdown() {
+            eprintln!("OpenTelemetry shutdown failed: {e}");
+        }
+    }
+}
+
+/// Tracing layer that batches spans to an OTLP/gRPC collector at `endpoint`.
+pub fn otlp_layer<S>(endpoint: &str, service_name: &str) -> Result<(impl Layer<S>, TelemetryGuard)>
+where
+    S: tracing::Subscriber + for<'span> LookupSpan<'span>,
+{
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_tonic()
+        .with_endpoint(endpoint)
+        .build()?;
+
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(resource(service_name))
+        .build();
+
+    Ok((span_layer(&provider, service_name), TelemetryGuard { provider }))
+}
+
+fn resource(service_name: &str) -> Resource {
+    Resource::builder()
+        .with_service_name(service_name.to_string())
+        .with_attribute(KeyValue::new("service.version", env!("CARGO_PKG_VERSION")))
+        .build()
+}
+
+/// Tracing layer that hands every span to `provider`.
+fn span_layer<S>(provider: &SdkTracerProvider, service_name: &str) -> impl Layer<S>
+where
+    S: tracing::Subscriber + for<'span> LookupSpan<'span>,
+{
+    tracing_opentelemetry::layer().with_tracer(provider.tracer(service_name.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use opentelemetry_sdk::trace::InMemorySpanExporter;
+    use tracing_subscriber::{Registry, layer::SubscriberExt};
+
+    use super::*;
+
+    #[test]
+    fn transaction_span_is_exported() {
+        let exporter = InMemorySpanExporter::default();
+        let provider = SdkTracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .with_resource(resource("indexer-test"))
+            .build();
+        let subscriber = Registry::default().with(span_layer(&provider, "indexer-test"));
+
+        tracing::subscriber::with_default(subscriber, || {
+            // Same name and fields as the pipeline's per-transaction span
+            tracing::info_span!("process_transaction", signature = "sig", slot = 10u64).in_scope(|| {
+                tracing::info!("parsed");
+            });
+        });
+        provider.force_flush().unwrap();
+
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].name, "process_transaction");
+        assert!(spans[0].attributes.iter().any(|kv| kv.key.as_str() == "signature" && kv.value.as_str() == "sig"));
+    }
+}
@@ -0,0 +1,184 @@
+use std::{collections::HashSet, path::PathBuf};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio::{
+    fs::{File, OpenOptions},
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    sync::Mutex,
+};
+
+use crate::{
+    application::{WalEntry, WriteAheadLog},
+    domain::TransactionEvent,
+};
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum Record {
+    Batch { id: u64, slot: u64, events: Vec<TransactionEvent> },
+    Commit { id: u64 },
+}
+
+struct State {
+    file: File,
+    next_id: u64,
+    /// Batches appended but not yet committed, in this run or a previous one.
+    outstanding: HashSet<u64>,
+}
+
+/// Append-only JSON-lines log. Each batch is fsynced before `append` returns and a
+/// commit record is written once the DB accepts it. The file is truncated whenever
+/// nothing is outstanding, so it only grows while writes are in flight.
+pub struct FileWal {
+    path: PathBuf,
+    state: Mutex<State>,
+}
+
+impl FileWal {
+    pub async fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let (next_id, outstanding) = match File::open(&path).await {
+            Ok(file) => {
+                let entries = Self::scan(file).await?;
+                let next_id = entries.iter().map(|e| e.id + 1).max().unwrap_or(0);
+                (next_id, entries.iter().map(|e| e.id).collect())
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => (0, HashSet::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let file = OpenOptions::new().create(true).append(true).open(&path).await?;
+        if !outstanding.is_empty() {
+            tracing::warn!("WAL {} has {} uncommitted batch(es)", path.display(), outstanding.len());
+        }
+
+        Ok(Self { path, state: Mutex::new(State { file, next_id, outstanding }) })
+    }
+
+    /// Replay the log and return batches with no matching commit, in append order.
+    /// A torn final line from a crash mid-write is ignored.
+    async fn scan(file: File) -> Result<Vec<WalEntry>> {
+        let mut lines = BufReader::new(file).lines();
+        let mut entries: Vec<WalEntry> = Vec::new();
+
+        while let Some(line) = lines.next_line().await? {
+            match serde_json::from_str::<Record>(&line) {
+                Ok(Record::Batch { id, slot, events }) => entries.push(WalEntry { id, slot, events }),
+                Ok(Record::Commit { id }) => entries.retain(|e| e.id != id),
+                Err(e) => tracing::warn!("Skipping unreadable WAL record: {}", e),
+            }
+        }
+
+        Ok(entries)
+    }
+
+    async fn write(file: &mut File, record: &Record) -> Result<()> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        file.write_all(&line).await?;
+        file.sync_data().await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl WriteAheadLog for FileWal {
+    async fn append(&self, events: &[TransactionEvent], slot: u64) -> Result<u64> {
+        let mut state = self.state.lock().await;
+        let id = state.next_id;
+        Self::write(&mut state.file, &Record::Batch { id, slot, events: events.to_vec() }).await?;
+        state.next_id += 1;
+        state.outstanding.insert(id);
+        Ok(id)
+    }
+
+    async fn commit(&self, id: u64) -> Result<()> {
+        let mut state = self.state.lock().await;
+        state.outstanding.remove(&id);
+
+        if state.outstanding.is_empty() {
+            state.file.set_len(0).await?;
+            state.file.sync_data().await?;
+        } else {
+            Self::write(&mut state.file, &Record::Commit { id }).await?;
+        }
+        Ok(())
+    }
+
+    async fn uncommitted(&self) -> Result<Vec<WalEntry>> {
+        // Hold the lock so no append interleaves with the read
+        let _state = self.state.lock().await;
+        Self::scan(File::open(&self.path).await?).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::fixtures::transfer;
+
+    fn wal_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("indexer-wal-{}-{}.jsonl", std::process::id(), name));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    fn signatures(entry: &WalEntry) -> Vec<&str> {
+        entry.events.iter().map(|ev| ev.signature()).collect()
+    }
+
+    #[tokio::test]
+    async fn batches_left_uncommitted_by_a_crash_survive_a_reopen() {
+        let path = wal_path("crash");
+        let wal = FileWal::open(&path).await.unwrap();
+        let committed = wal.append(&[transfer(1)], 10).await.unwrap();
+        wal.append(&[transfer(2), transfer(3)], 11).await.unwrap();
+        wal.commit(committed).await.unwrap();
+        // The process dies before the second batch commits
+        drop(wal);
+
+        let reopened = FileWal::open(&path).await.unwrap();
+        let entries = reopened.uncommitted().await.unwrap();
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].slot, 11);
+        assert_eq!(signatures(&entries[0]), vec![transfer(2).signature(), transfer(3).signature()]);
+        // New batches do not reuse the surviving id
+        assert!(reopened.append(&[transfer(4)], 12).await.unwrap() > entries[0].id);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn torn_final_record_is_ignored() {
+        let path = wal_path("torn");
+        let wal = FileWal::open(&path).await.unwrap();
+        wal.append(&[transfer(1)], 10).await.unwrap();
+        drop(wal);
+        let mut file = OpenOptions::new().append(true).open(&path).await.unwrap();
+        file.write_all(br#"{"op":"batch","id":1,"slot":11,"ev"#).await.unwrap();
+        drop(file);
+
+        let entries = FileWal::open(&path).await.unwrap().uncommitted().await.unwrap();
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(signatures(&entries[0]), vec![transfer(1).signature()]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn log_is_emptied_once_everything_commits() {
+        let path = wal_path("empty");
+        let wal = FileWal::open(&path).await.unwrap();
+        let first = wal.append(&[transfer(1)], 10).await.unwrap();
+        let second = wal.append(&[transfer(2)], 11).await.unwrap();
+
+        wal.commit(first).await.unwrap();
+        wal.commit(second).await.unwrap();
+
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), 0);
+        assert!(wal.uncommitted().await.unwrap().is_empty());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
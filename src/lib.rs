@@ -1,4 +1,6 @@
 pub mod adapters;
 pub mod application;
+pub mod cli;
+pub mod config;
 pub mod domain;
 pub mod infrastructure;
@@ -2,44 +2,34 @@ mod domain;
 mod application;
 mod adapters;
 mod infrastructure;
+mod config;
+mod cli;
 
 use std::sync::Arc;
 
+use clap::Parser;
 use solana_client::rpc_client::RpcClient;
 use tokio::sync::Mutex;
 
 use crate::{
     adapters::{
-        FileSourceAdaptor, GrpcSourceAdaptor,
-        JupiterVixenParser, PostgresRepository, PumpFunParser,
-        RaydiumAmmParser, SplTokenTransfer, TelegramNotifier,
-        run_backfill_producer,
+        Base58Cache, DiscriminatorTable, FileSourceAdaptor, InvokedPrograms, GrpcSourceAdaptor,
+        ComputeBudgetParser, InMemoryRepository, JupiterVixenParser, NullRepository, PostgresRepository, RepositoryKind, PumpAmmParser, PumpFunParser,
+        RaydiumAmmParser, RaydiumClmmParser, SplTokenTransfer, SystemTransferParser, TelegramNotifier, WebhookSink,
+        MintBackfillConfig, MultiSink, RpcBlockSource, RpcMintResolver, StdoutSink, JsonlFileSink,
     },
     application::{
-        EventBuffer, IngestionPipeline, NotificationService,
-        TransactionParser, TransactionRepository, TransactionSource,
+        AppError, EventBuffer, EventSink, IngestionPipeline, NotificationService, PipelineConfig, PipelineMetrics,
+        SamplingFilter, SignatureFilter, SwapValidator, TransactionParser, TransactionRepository, TransactionSource, run_fetcher,
+    },
+    cli::{Cli, Command},
+    config::{AppConfig, DatabaseConfig, SourceConfig},
+    infrastructure::{
+        FileParseErrorLog, FileSlotSummaryLog, FileWal, HealthServer, MemoryBuffer, ParseErrorReplaySource, SeenSignatureStore,
+        SpilloverBuffer, logging,
     },
-    domain::{ChainEvent, IndexerState},
-    infrastructure::MemoryBuffer,
 };
 
-#[derive(Debug, PartialEq)]
-enum SourceMode {
-    File,
-    Grpc,
-}
-
-impl SourceMode {
-    fn from_env() -> Result<Self, String> {
-        match std::env::var("SOURCE_TYPE").as_deref() {
-            Ok("file") => Ok(Self::File),
-            Ok("grpc") => Ok(Self::Grpc),
-            Ok(other) => Err(format!("Unknown SOURCE_TYPE: {}", other)),
-            Err(_) => Err("SOURCE_TYPE not set".to_string()),
-        }
-    }
-}
-
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     rustls::crypto::ring::default_provider()
@@ -47,97 +37,296 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .expect("Failed to install rustls crypto provider");
 
     dotenv::dotenv().ok();
+    let cli = Cli::parse();
+    let command = cli.command();
+
+    let _logging = logging::init(&logging::LogConfig::from_env());
+
+    if command == Command::Migrate {
+        // Connecting applies the embedded migrations
+        let url = std::env::var("DATABASE_URL").map_err(|_| "DATABASE_URL is required to migrate")?;
+        PostgresRepository::new(&url).await?;
+        return Ok(());
+    }
 
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "info".into()),
-        )
-        .init();
+    if let Command::BackfillMints { batch_size, requests_per_sec } = command {
+        let url = std::env::var("DATABASE_URL").map_err(|_| "DATABASE_URL is required to backfill mints")?;
+        let rpc_url = std::env::var("RPC_URL").map_err(|_| "RPC_URL is required to backfill mints")?;
+        let repo = PostgresRepository::new(&url).await?;
+        let config = MintBackfillConfig { batch_size: batch_size.max(1), requests_per_sec };
+        repo.backfill_mints(&RpcMintResolver::new(rpc_url), config).await?;
+        return Ok(());
+    }
 
-    let source_mode = SourceMode::from_env()?;
-    let db_url  = std::env::var("DATABASE_URL").expect("DATABASE_URL required");
-    let rpc_url = std::env::var("RPC_URL").expect("RPC_URL required");
+    let config = AppConfig::from_env_with(&cli.env_overrides())?;
 
     // Optional Telegram alerts
-    let notifier_service = match (
-        std::env::var("TELEGRAM_BOT_TOKEN").ok(),
-        std::env::var("TELEGRAM_CHAT_ID").ok(),
-    ) {
-        (Some(token), Some(chat_id)) => {
+    let notifier_service = match config.telegram {
+        Some(telegram) => {
             tracing::info!("Telegram notifications enabled");
-            let adapter = Arc::new(TelegramNotifier::new(token, chat_id));
+            let adapter = Arc::new(TelegramNotifier::new(telegram.bot_token, telegram.chat_id));
             Some(Arc::new(NotificationService::new(adapter, 1_000_000_000)))
         }
-        _ => {
+        None => {
             tracing::warn!("Telegram credentials not set — notifications disabled");
             None
         }
     };
 
-    tracing::info!("Connecting to database...");
-    let repo = Arc::new(
-        PostgresRepository::new(&db_url)
-            .await
-            .expect("Failed to connect to PostgreSQL"),
-    );
-
-    let source: Arc<Mutex<dyn TransactionSource>> = if source_mode == SourceMode::File {
-        Arc::new(Mutex::new(FileSourceAdaptor::new(50_000)))
-    } else {
-        let grpc_url   = std::env::var("GRPC_URL").unwrap_or_else(|_| "http://127.0.0.1:10000".to_string());
-        let grpc_token = std::env::var("GRPC_TOKEN").ok();
-        tracing::info!("Connecting to gRPC at {}", grpc_url);
-        let adaptor = GrpcSourceAdaptor::connect(grpc_url, grpc_token)
-            .await
-            .expect("Failed to connect to gRPC endpoint");
-        Arc::new(Mutex::new(adaptor))
+    let (repo, writers) = open_repository(config.database).await;
+
+    let source: Arc<Mutex<dyn TransactionSource>> = match config.source {
+        SourceConfig::File => Arc::new(Mutex::new(FileSourceAdaptor::new(50_000))),
+        SourceConfig::Replay(path) => {
+            tracing::info!("Replaying parse failures from {}", path);
+            let replay = ParseErrorReplaySource::open(&path)
+                .await
+                .map_err(|e| format!("Failed to open replay file {}: {}", path, e))?;
+            Arc::new(Mutex::new(replay))
+        }
+        SourceConfig::Rpc { start, end } => {
+            tracing::info!("Fetching blocks {}–{} over RPC", start, end);
+            Arc::new(Mutex::new(RpcBlockSource::new(config.rpc_url.clone(), start, end)))
+        }
+        #[cfg(feature = "synthetic")]
+        SourceConfig::Synthetic(synthetic) => {
+            tracing::info!("Synthetic source: {} tps, mix {:?}", synthetic.tps, synthetic.mix);
+            Arc::new(Mutex::new(adapters::SyntheticSource::new(synthetic)))
+        }
+        SourceConfig::Grpc(grpc) => {
+            tracing::info!("Connecting to gRPC at {} ({:?})", grpc.url, grpc.commitment);
+            let mut adaptor = GrpcSourceAdaptor::connect_with_options(grpc.url, grpc.token, grpc.commitment, grpc.filters, grpc.channel)
+                .await
+                .expect("Failed to connect to gRPC endpoint");
+            if let Some((start, end)) = grpc.slot_range {
+                tracing::info!("Bounded backfill: slots {}–{}", start, end);
+                adaptor = adaptor.with_slot_range(start, end).await.map_err(|e| {
+                    AppError::ConnectionError(format!("Failed to subscribe for backfill slots {}–{}: {}", start, end, e))
+                })?;
+            }
+            Arc::new(Mutex::new(adaptor))
+        }
     };
 
-    let (buffer, rx) = MemoryBuffer::new(50_000);
-    let buffer = Arc::new(buffer);
+    let (buffer, rx): (Arc<dyn EventBuffer>, _) = match &config.buffer_spill_path {
+        Some(path) => {
+            let (buffer, rx) = SpilloverBuffer::new(50_000, path)
+                .await
+                .map_err(|e| format!("Failed to open spill file {}: {}", path, e))?;
+            tracing::info!("Buffer overflow spills to {}", path);
+            (Arc::new(buffer), rx)
+        }
+        None => {
+            let (buffer, rx) = MemoryBuffer::new(50_000);
+            (Arc::new(buffer), rx)
+        }
+    };
 
     let last_slot = repo.get_last_slot().await.unwrap_or(0);
-    let network_slot = RpcClient::new(&rpc_url).get_slot().unwrap_or(0);
+    let network_slot = RpcClient::new(&config.rpc_url).get_slot().unwrap_or(0);
     tracing::info!("Resuming from slot {} (network tip: {})", last_slot, network_slot);
 
+    if let Some(capacity) = config.key_cache_size {
+        Base58Cache::init_global(capacity);
+    }
+
+    let mut discriminators = match &config.discriminators_path {
+        Some(path) => DiscriminatorTable::load(path).map_err(|e| format!("Failed to load discriminators {}: {}", path, e))?,
+        None => Default::default(),
+    };
+    let mut clmm = RaydiumClmmParser::new();
+    if let Some(table) = discriminators.remove("raydium_clmm") {
+        clmm = clmm.with_discriminators(table);
+    }
+    let mut jupiter = JupiterVixenParser::new();
+    if let Some(table) = discriminators.remove("jupiter") {
+        jupiter = jupiter.with_discriminators(table);
+    }
+    let mut pump_fun = PumpFunParser::new();
+    if let Some(table) = discriminators.remove("pump_fun") {
+        pump_fun = pump_fun.with_discriminators(table);
+    }
+    for name in discriminators.keys() {
+        tracing::warn!("DISCRIMINATORS_PATH has entries for {}, which does not use a discriminator table", name);
+    }
+
     let parsers: Vec<Box<dyn TransactionParser>> = vec![
-        Box::new(SplTokenTransfer::new()),
+        Box::new(SplTokenTransfer::new().with_mode(config.spl_transfer_mode)),
         Box::new(RaydiumAmmParser::new()),
-        Box::new(JupiterVixenParser::new()),
-        Box::new(PumpFunParser::new()),
+        Box::new(clmm),
+        Box::new(jupiter),
+        Box::new(pump_fun),
+        Box::new(PumpAmmParser::new()),
+        Box::new(ComputeBudgetParser::new()),
+        Box::new(SystemTransferParser::new()),
     ];
 
-    // Producer: fetch events from source and push into the shared buffer
+    // Start the readiness clock at boot so /readyz has a grace period before the first event
+    let mut metrics = PipelineMetrics::new();
+    if let Some(decimals) = config.volume_decimals.clone() {
+        tracing::info!("Per-mint swap volume exposed on /volume");
+        metrics = metrics.with_volume(decimals);
+    }
+    let metrics = Arc::new(metrics);
+    metrics.set_last_event_at(chrono::Utc::now().timestamp());
+
+    // Producer: fetch events from source and push into the shared buffer. The task
+    // owns the only producer handle, so the channel closes when the source ends.
     let source_clone = source.clone();
-    let buffer_clone = buffer.clone();
+    let fetcher_metrics = metrics.clone();
+    let fetcher_config = config.fetcher.clone();
     tokio::spawn(async move {
         tracing::info!("Fetcher task started");
-        loop {
-            let event = source_clone.lock().await.next_event().await;
-            match event {
-                Ok(Some(ev)) => {
-                    if buffer_clone.produce(ev).await.is_err() {
-                        tracing::error!("Buffer closed — stopping fetcher");
-                        break;
-                    }
-                }
-                Ok(None) => {
-                    tracing::info!("Source stream exhausted");
-                    break;
-                }
-                Err(e) => {
-                    tracing::error!("Source error: {:?}", e);
-                    tokio::time::sleep(std::time::Duration::from_secs(1)).await;
-                }
-            }
+        if let Err(e) = run_fetcher(source_clone, buffer, fetcher_config, fetcher_metrics).await {
+            tracing::error!("Fetcher stopped: {}", e);
         }
     });
 
     // Consumer: parse events and persist in batches
-    let mut pipeline = IngestionPipeline::new(rx, repo, parsers, notifier_service);
+    let pipeline_config = PipelineConfig { writers, ..config.pipeline };
+
+    let health_repo = repo.clone();
+    let mut pipeline = IngestionPipeline::new(rx, repo, parsers, notifier_service)
+        .with_config(pipeline_config)
+        .with_metrics(metrics);
+
+    let parser_control = pipeline.parser_control();
+    for name in &config.disabled_parsers {
+        if !parser_control.set_enabled(name, false) {
+            tracing::warn!("DISABLED_PARSERS names unknown parser {}", name);
+        }
+    }
+
+    if let Some(addr) = config.health_addr {
+        let health = HealthServer::new(pipeline.metrics(), health_repo)
+            .with_config(config.health)
+            .with_parser_control(parser_control);
+        tokio::spawn(async move {
+            if let Err(e) = health.serve(addr).await {
+                tracing::error!("Health server stopped: {}", e);
+            }
+        });
+    }
+
+    let mut sinks: Vec<Arc<dyn EventSink>> = Vec::new();
+
+    if let Some(webhook) = config.webhook {
+        tracing::info!("Webhook sink enabled for {}", webhook.url);
+        sinks.push(Arc::new(WebhookSink::new(webhook)?));
+    }
+
+    if let Some(format) = config.stdout_sink {
+        tracing::info!("Stdout sink enabled ({:?})", format);
+        let mut stdout = StdoutSink::new().with_format(format).with_color(config.stdout_color);
+        if let Some(decimals) = config.volume_decimals.clone() {
+            stdout = stdout.with_decimals(decimals);
+        }
+        sinks.push(Arc::new(stdout));
+    }
+
+    if let Some(path) = &config.jsonl_sink {
+        tracing::info!("JSONL sink enabled for {} ({:?})", path, config.jsonl_compression);
+        sinks.push(Arc::new(JsonlFileSink::open(path, config.jsonl_compression)?));
+    }
+
+    if sinks.len() > 1 {
+        pipeline = pipeline.with_sink(Arc::new(MultiSink::new(sinks, config.sink_policy)));
+    } else if let Some(sink) = sinks.pop() {
+        pipeline = pipeline.with_sink(sink);
+    }
+
+    if !config.signature_allow.is_empty() || !config.signature_deny.is_empty() {
+        tracing::info!("Signature filter: {} allowed, {} denied", config.signature_allow.len(), config.signature_deny.len());
+        let filter = SignatureFilter::new().with_allow(config.signature_allow).with_deny(config.signature_deny);
+        pipeline = pipeline.with_signature_filter(filter);
+    }
+
+    if let Some(rate) = config.sample_rate {
+        tracing::info!("Sampling {:.1}% of transactions", rate * 100.0);
+        pipeline = pipeline.with_filter(Box::new(SamplingFilter::new(rate)));
+    }
+
+    if let Some(action) = config.swap_validation {
+        let validator = SwapValidator::new(config.swap_max_ratio, action).with_metrics(pipeline.metrics());
+        pipeline = pipeline.with_filter(Box::new(validator));
+    }
+
+    if let Some(path) = config.wal_path {
+        let wal = FileWal::open(&path).await.map_err(|e| format!("Failed to open WAL {}: {}", path, e))?;
+        tracing::info!("Write-ahead log enabled at {}", path);
+        pipeline = pipeline.with_wal(Arc::new(wal));
+    }
+
+    if let Some(path) = &config.parse_error_log {
+        let log = FileParseErrorLog::open(path).await.map_err(|e| format!("Failed to open parse-error log {}: {}", path, e))?;
+        tracing::info!("Parse failures will be logged with raw bytes to {}", path);
+        pipeline = pipeline.with_parse_error_sink(Arc::new(log));
+    }
+
+    if let Some(path) = &config.slot_summary_log {
+        let log = FileSlotSummaryLog::open(path).await.map_err(|e| format!("Failed to open slot summary log {}: {}", path, e))?;
+        tracing::info!("Per-slot swap summaries will be written to {}", path);
+        pipeline = pipeline.with_slot_summary_sink(Arc::new(log));
+    }
+
+    if config.program_coverage {
+        tracing::info!("Program coverage reporting enabled");
+        pipeline = pipeline.with_program_coverage(Arc::new(InvokedPrograms::new()));
+    }
+
+    let seen_store = match &config.seen_signatures {
+        Some(seen) => {
+            let store = SeenSignatureStore::new(&seen.path);
+            let filter = store.load(seen.capacity, seen.fp_rate).await
+                .map_err(|e| format!("Failed to load seen signatures {}: {}", seen.path, e))?;
+            pipeline = pipeline.with_seen_signatures(filter);
+            Some(store)
+        }
+        None => None,
+    };
+
     tracing::info!("Ingestion pipeline running");
     pipeline.run().await;
 
+    if let (Some(store), Some(filter)) = (seen_store, pipeline.seen_signatures()) {
+        match store.save(&filter).await {
+            Ok(()) => tracing::info!("Seen-signature filter saved"),
+            Err(e) => tracing::error!("Failed to save seen-signature filter: {}", e),
+        }
+    }
+
     Ok(())
 }
+
+/// Build the repository `DATABASE_URL` selects, with the number of writers to run against it.
+async fn open_repository(database: Option<DatabaseConfig>) -> (Arc<dyn TransactionRepository>, usize) {
+    let Some(db) = database else {
+        tracing::warn!("DATABASE_URL not set — events will be counted but not persisted");
+        return (Arc::new(NullRepository::new(100)), 1);
+    };
+
+    match db.kind {
+        RepositoryKind::Postgres => {
+            tracing::info!("Connecting to database...");
+            let pg = PostgresRepository::connect(&db.url, db.max_connections, db.acquire_timeout)
+                .await
+                .expect("Failed to connect to PostgreSQL")
+                .with_upsert_mode(db.upsert_mode)
+                .with_two_phase(db.two_phase);
+            if db.two_phase {
+                tracing::info!("Two-phase persistence: events stay pending until their slot is finalized");
+            }
+            // Leave one connection free for DLQ writes and cursor reads
+            let default_writers = (pg.max_connections() as usize).saturating_sub(1).max(1);
+            (Arc::new(pg), db.writers.unwrap_or(default_writers))
+        }
+        RepositoryKind::Memory => {
+            tracing::warn!("In-memory repository — events are lost when the process exits");
+            (Arc::new(InMemoryRepository::new()), db.writers.unwrap_or(1))
+        }
+        RepositoryKind::Null => {
+            tracing::warn!("Null repository — events will be counted but not persisted");
+            (Arc::new(NullRepository::new(100)), db.writers.unwrap_or(1))
+        }
+    }
+}